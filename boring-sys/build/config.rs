@@ -20,6 +20,7 @@ pub(crate) struct Features {
     pub(crate) rpk: bool,
     pub(crate) underscore_wildcards: bool,
     pub(crate) prefix_symbols: bool,
+    pub(crate) unsafe_deterministic_mode: bool,
 }
 
 pub(crate) struct Env {
@@ -92,7 +93,9 @@ impl Config {
             );
         }
 
-        let features_with_patches_enabled = self.features.rpk || self.features.underscore_wildcards;
+        let features_with_patches_enabled = self.features.rpk
+            || self.features.underscore_wildcards
+            || self.features.unsafe_deterministic_mode;
 
         let patches_required = features_with_patches_enabled && !self.env.assume_patched;
 
@@ -101,6 +104,13 @@ impl Config {
                 "cargo:warning=precompiled BoringSSL was provided, so patches will be ignored"
             );
         }
+
+        if is_precompiled_native_lib && self.features.unsafe_deterministic_mode {
+            println!(
+                "cargo:warning=precompiled BoringSSL was provided, so it must have been built \
+                with BORINGSSL_UNSAFE_DETERMINISTIC_MODE and patches/current-time-ssl.patch"
+            );
+        }
     }
 }
 
@@ -110,12 +120,15 @@ impl Features {
         let rpk = env::var_os("CARGO_FEATURE_RPK").is_some();
         let underscore_wildcards = env::var_os("CARGO_FEATURE_UNDERSCORE_WILDCARDS").is_some();
         let prefix_symbols = env::var_os("CARGO_FEATURE_PREFIX_SYMBOLS").is_some();
+        let unsafe_deterministic_mode =
+            env::var_os("CARGO_FEATURE_UNSAFE_DETERMINISTIC_MODE").is_some();
 
         Self {
             fips,
             rpk,
             underscore_wildcards,
             prefix_symbols,
+            unsafe_deterministic_mode,
         }
     }
 
@@ -28,6 +28,9 @@ fn should_use_cmake_cross_compilation(config: &Config) -> bool {
     }
 }
 
+// What BoringSSL's `FUZZ` CMake option defines, without also requiring a fuzzing toolchain.
+const DETERMINISTIC_MODE_CFLAG: &str = "-DBORINGSSL_UNSAFE_DETERMINISTIC_MODE";
+
 // Android NDK >= 19.
 const CMAKE_PARAMS_ANDROID_NDK: &[(&str, &[(&str, &str)])] = &[
     ("aarch64", &[("ANDROID_ABI", "arm64-v8a")]),
@@ -416,6 +419,11 @@ fn get_extra_clang_args_for_bindgen(config: &Config) -> Vec<String> {
         _ => {}
     }
 
+    // Declares the functions only available in this build configuration.
+    if config.features.unsafe_deterministic_mode {
+        params.push(DETERMINISTIC_MODE_CFLAG.to_string());
+    }
+
     params
 }
 
@@ -427,12 +435,14 @@ fn ensure_patches_applied(config: &Config) -> io::Result<()> {
         );
         return Ok(());
     } else if config.env.source_path.is_some()
-        && (config.features.rpk || config.features.underscore_wildcards)
+        && (config.features.rpk
+            || config.features.underscore_wildcards
+            || config.features.unsafe_deterministic_mode)
     {
         panic!(
             "BORING_BSSL_ASSUME_PATCHED must be set when setting
                BORING_BSSL_SOURCE_PATH and using any of the following
-               features: rpk, underscore-wildcards"
+               features: rpk, underscore-wildcards, unsafe-deterministic-mode"
         );
     }
 
@@ -460,6 +470,11 @@ fn ensure_patches_applied(config: &Config) -> io::Result<()> {
         apply_patch(config, "underscore-wildcards.patch")?;
     }
 
+    if config.features.unsafe_deterministic_mode {
+        println!("cargo:warning=applying current time patch to boringssl");
+        apply_patch(config, "current-time-ssl.patch")?;
+    }
+
     Ok(())
 }
 
@@ -543,6 +558,11 @@ fn built_boring_source_path(config: &Config) -> &PathBuf {
             cfg.define("CMAKE_POSITION_INDEPENDENT_CODE", "ON");
         }
 
+        if config.features.unsafe_deterministic_mode {
+            cfg.cflag(DETERMINISTIC_MODE_CFLAG)
+                .cxxflag(DETERMINISTIC_MODE_CFLAG);
+        }
+
         cfg.build_target("ssl").build();
         cfg.build_target("crypto").build()
     })
@@ -699,6 +719,7 @@ fn generate_bindings(config: &Config) {
         "hpke.h",
         "hmac.h",
         "hrss.h",
+        "kyber.h",
         "md4.h",
         "md5.h",
         "obj_mac.h",
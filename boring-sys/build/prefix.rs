@@ -3,6 +3,8 @@ use std::{fs, io::Write, path::PathBuf, process::Command};
 
 /// Prefix applied to all BoringSSL symbols so they don't collide with OpenSSL.
 /// Uses the crate version to generate a dynamic prefix.
+///
+/// Keep in sync with `SYMBOL_PREFIX` in `src/lib.rs`.
 const SYMBOL_PREFIX: &str = concat!(
     "BSSL_",
     env!("CARGO_PKG_VERSION_MAJOR"),
@@ -28,6 +28,25 @@ mod generated {
 }
 pub use generated::*;
 
+/// The prefix of the link names of BoringSSL's symbols, such as `BSSL_4_19_0_` in
+/// `BSSL_4_19_0_SSL_new`, or `None` unless the `prefix-symbols` feature is enabled.
+///
+/// The bindings of this crate already use the prefixed names. Code declaring its own `extern`
+/// blocks for BoringSSL functions must use them too.
+pub const SYMBOL_PREFIX: Option<&str> = if cfg!(feature = "prefix-symbols") {
+    Some(concat!(
+        "BSSL_",
+        env!("CARGO_PKG_VERSION_MAJOR"),
+        "_",
+        env!("CARGO_PKG_VERSION_MINOR"),
+        "_",
+        env!("CARGO_PKG_VERSION_PATCH"),
+        "_",
+    ))
+} else {
+    None
+};
+
 #[cfg(target_pointer_width = "64")]
 pub type BN_ULONG = u64;
 #[cfg(target_pointer_width = "32")]
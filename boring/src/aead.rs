@@ -0,0 +1,546 @@
+//! Authenticated encryption with associated data.
+//!
+//! This module wraps BoringSSL's `EVP_AEAD` interface, which is simpler and faster than using
+//! AEADs through [`symm::Cipher`](crate::symm::Cipher). An [`AeadCtx`] holds an expanded key and
+//! may be shared between threads.
+//!
+//! Every message sealed under the same key must use a distinct nonce. If nonces can't be reliably
+//! made unique, for instance because they are chosen at random, prefer
+//! [`Aead::xchacha20_poly1305`], whose nonces are long enough to be picked at random, or one of the
+//! AES-GCM-SIV variants, which only leak whether two messages are equal if a nonce is repeated.
+//! [`AeadCtxRef::seal_random_nonce`] picks the nonce itself and prepends it to the sealed message.
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::aead::{Aead, AeadCtx};
+//!
+//! let aead = Aead::aes_256_gcm();
+//! let ctx = AeadCtx::new(aead, &[0; 32]).unwrap();
+//! let nonce = [0; 12];
+//!
+//! let ciphertext = ctx.seal(&nonce, b"some plaintext", b"some ad").unwrap();
+//! let plaintext = ctx.open(&nonce, &ciphertext, b"some ad").unwrap();
+//! assert_eq!(plaintext, b"some plaintext");
+//! ```
+use crate::error::ErrorStack;
+use crate::rand::rand_bytes;
+use crate::{cvt_0i, cvt_p, ffi};
+
+use foreign_types::ForeignTypeRef;
+use openssl_macros::corresponds;
+use std::ptr;
+
+/// An AEAD algorithm.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Aead(*const ffi::EVP_AEAD);
+
+impl Aead {
+    /// AES-128 in Galois Counter Mode, with 96-bit nonces.
+    #[corresponds(EVP_aead_aes_128_gcm)]
+    #[must_use]
+    pub fn aes_128_gcm() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_aes_128_gcm()) }
+    }
+
+    /// AES-256 in Galois Counter Mode, with 96-bit nonces.
+    #[corresponds(EVP_aead_aes_256_gcm)]
+    #[must_use]
+    pub fn aes_256_gcm() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_aes_256_gcm()) }
+    }
+
+    /// AES-128 in GCM-SIV mode, as described in RFC 8452.
+    #[corresponds(EVP_aead_aes_128_gcm_siv)]
+    #[must_use]
+    pub fn aes_128_gcm_siv() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_aes_128_gcm_siv()) }
+    }
+
+    /// AES-256 in GCM-SIV mode, as described in RFC 8452.
+    #[corresponds(EVP_aead_aes_256_gcm_siv)]
+    #[must_use]
+    pub fn aes_256_gcm_siv() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_aes_256_gcm_siv()) }
+    }
+
+    /// ChaCha20-Poly1305, as described in RFC 8439.
+    #[corresponds(EVP_aead_chacha20_poly1305)]
+    #[must_use]
+    pub fn chacha20_poly1305() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_chacha20_poly1305()) }
+    }
+
+    /// ChaCha20-Poly1305 with a 192-bit nonce, which may be chosen at random.
+    #[corresponds(EVP_aead_xchacha20_poly1305)]
+    #[must_use]
+    pub fn xchacha20_poly1305() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_xchacha20_poly1305()) }
+    }
+
+    /// Returns the length of the algorithm's keys.
+    #[corresponds(EVP_AEAD_key_length)]
+    #[must_use]
+    pub fn key_len(&self) -> usize {
+        unsafe { ffi::EVP_AEAD_key_length(self.0) }
+    }
+
+    /// Returns the length of the algorithm's nonces.
+    #[corresponds(EVP_AEAD_nonce_length)]
+    #[must_use]
+    pub fn nonce_len(&self) -> usize {
+        unsafe { ffi::EVP_AEAD_nonce_length(self.0) }
+    }
+
+    /// Returns the maximum number of bytes sealing adds to a plaintext.
+    #[corresponds(EVP_AEAD_max_overhead)]
+    #[must_use]
+    pub fn max_overhead(&self) -> usize {
+        unsafe { ffi::EVP_AEAD_max_overhead(self.0) }
+    }
+
+    /// Returns the maximum, and default, tag length.
+    #[corresponds(EVP_AEAD_max_tag_len)]
+    #[must_use]
+    pub fn max_tag_len(&self) -> usize {
+        unsafe { ffi::EVP_AEAD_max_tag_len(self.0) }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    #[must_use]
+    pub fn as_ptr(&self) -> *const ffi::EVP_AEAD {
+        self.0
+    }
+}
+
+unsafe impl Sync for Aead {}
+unsafe impl Send for Aead {}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::EVP_AEAD_CTX;
+    fn drop = ffi::EVP_AEAD_CTX_free;
+
+    /// A key for use with an [`Aead`].
+    pub struct AeadCtx;
+}
+
+impl AeadCtx {
+    /// Creates a context for `aead` with the given key and the default tag length.
+    #[corresponds(EVP_AEAD_CTX_new)]
+    pub fn new(aead: Aead, key: &[u8]) -> Result<AeadCtx, ErrorStack> {
+        AeadCtx::with_tag_len(aead, key, ffi::EVP_AEAD_DEFAULT_TAG_LENGTH as usize)
+    }
+
+    /// Creates a context for `aead` with the given key, truncating tags to `tag_len` bytes.
+    #[corresponds(EVP_AEAD_CTX_new)]
+    pub fn with_tag_len(aead: Aead, key: &[u8], tag_len: usize) -> Result<AeadCtx, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::EVP_AEAD_CTX_new(
+                aead.as_ptr(),
+                key.as_ptr(),
+                key.len(),
+                tag_len,
+            ))
+            .map(|p| AeadCtx::from_ptr(p))
+        }
+    }
+}
+
+impl AeadCtxRef {
+    /// Returns the context's algorithm.
+    #[corresponds(EVP_AEAD_CTX_aead)]
+    #[must_use]
+    pub fn aead(&self) -> Aead {
+        unsafe { Aead(ffi::EVP_AEAD_CTX_aead(self.as_ptr())) }
+    }
+
+    /// Encrypts and authenticates `plaintext`, and authenticates `ad`.
+    ///
+    /// Returns the ciphertext followed by the tag. `nonce` must be exactly
+    /// [`Aead::nonce_len`] bytes long.
+    #[corresponds(EVP_AEAD_CTX_seal)]
+    pub fn seal(&self, nonce: &[u8], plaintext: &[u8], ad: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = vec![0; plaintext.len() + self.aead().max_overhead()];
+        let mut len = 0;
+        unsafe {
+            cvt_0i(ffi::EVP_AEAD_CTX_seal(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                &mut len,
+                out.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                plaintext.as_ptr(),
+                plaintext.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+        }
+        out.truncate(len);
+        Ok(out)
+    }
+
+    /// Decrypts and authenticates `ciphertext`, which includes the tag, and authenticates `ad`.
+    #[corresponds(EVP_AEAD_CTX_open)]
+    pub fn open(&self, nonce: &[u8], ciphertext: &[u8], ad: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = vec![0; ciphertext.len()];
+        let mut len = 0;
+        unsafe {
+            cvt_0i(ffi::EVP_AEAD_CTX_open(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                &mut len,
+                out.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+        }
+        out.truncate(len);
+        Ok(out)
+    }
+
+    /// Like [`seal`](Self::seal), but returns the ciphertext and the tag separately.
+    #[corresponds(EVP_AEAD_CTX_seal_scatter)]
+    pub fn seal_detached(
+        &self,
+        nonce: &[u8],
+        plaintext: &[u8],
+        ad: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), ErrorStack> {
+        let mut out = vec![0; plaintext.len()];
+        let mut tag = vec![0; self.aead().max_overhead()];
+        let mut tag_len = 0;
+        unsafe {
+            cvt_0i(ffi::EVP_AEAD_CTX_seal_scatter(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                tag.as_mut_ptr(),
+                &mut tag_len,
+                tag.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                plaintext.as_ptr(),
+                plaintext.len(),
+                ptr::null(),
+                0,
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+        }
+        tag.truncate(tag_len);
+        Ok((out, tag))
+    }
+
+    /// Like [`open`](Self::open), but takes the ciphertext and the tag separately.
+    #[corresponds(EVP_AEAD_CTX_open_gather)]
+    pub fn open_detached(
+        &self,
+        nonce: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+        ad: &[u8],
+    ) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = vec![0; ciphertext.len()];
+        unsafe {
+            cvt_0i(ffi::EVP_AEAD_CTX_open_gather(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                nonce.as_ptr(),
+                nonce.len(),
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                tag.as_ptr(),
+                tag.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+        }
+        Ok(out)
+    }
+
+    /// Like [`seal`](Self::seal), but encrypts `in_out` in place and appends the tag to it.
+    ///
+    /// This does not allocate if `in_out` has spare capacity for the tag, which is at most
+    /// [`Aead::max_overhead`] bytes.
+    #[corresponds(EVP_AEAD_CTX_seal_scatter)]
+    pub fn seal_in_place_append_tag(
+        &self,
+        nonce: &[u8],
+        in_out: &mut Vec<u8>,
+        ad: &[u8],
+    ) -> Result<(), ErrorStack> {
+        let mut tag = [0; ffi::EVP_AEAD_MAX_OVERHEAD as usize];
+        let tag_len = self.seal_in_place_detached(nonce, in_out, &mut tag, ad)?;
+        in_out.extend_from_slice(&tag[..tag_len]);
+        Ok(())
+    }
+
+    /// Like [`seal_detached`](Self::seal_detached), but encrypts `in_out` in place and writes
+    /// the tag to `tag`.
+    ///
+    /// Returns the length of the tag.
+    #[corresponds(EVP_AEAD_CTX_seal_scatter)]
+    pub fn seal_in_place_detached(
+        &self,
+        nonce: &[u8],
+        in_out: &mut [u8],
+        tag: &mut [u8],
+        ad: &[u8],
+    ) -> Result<usize, ErrorStack> {
+        let mut tag_len = 0;
+        let buf = in_out.as_mut_ptr();
+        unsafe {
+            cvt_0i(ffi::EVP_AEAD_CTX_seal_scatter(
+                self.as_ptr(),
+                buf,
+                tag.as_mut_ptr(),
+                &mut tag_len,
+                tag.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                buf,
+                in_out.len(),
+                ptr::null(),
+                0,
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+        }
+        Ok(tag_len)
+    }
+
+    /// Like [`open`](Self::open), but decrypts `in_out`, which includes the tag, in place.
+    ///
+    /// On success, `in_out` is truncated to the plaintext. On failure, its contents are
+    /// unspecified.
+    #[corresponds(EVP_AEAD_CTX_open)]
+    pub fn open_in_place(
+        &self,
+        nonce: &[u8],
+        in_out: &mut Vec<u8>,
+        ad: &[u8],
+    ) -> Result<(), ErrorStack> {
+        let mut len = 0;
+        let buf = in_out.as_mut_ptr();
+        unsafe {
+            cvt_0i(ffi::EVP_AEAD_CTX_open(
+                self.as_ptr(),
+                buf,
+                &mut len,
+                in_out.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                buf,
+                in_out.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+        }
+        in_out.truncate(len);
+        Ok(())
+    }
+
+    /// Like [`open_detached`](Self::open_detached), but decrypts `in_out` in place.
+    ///
+    /// On failure, the contents of `in_out` are unspecified.
+    #[corresponds(EVP_AEAD_CTX_open_gather)]
+    pub fn open_in_place_detached(
+        &self,
+        nonce: &[u8],
+        in_out: &mut [u8],
+        tag: &[u8],
+        ad: &[u8],
+    ) -> Result<(), ErrorStack> {
+        let buf = in_out.as_mut_ptr();
+        unsafe {
+            cvt_0i(ffi::EVP_AEAD_CTX_open_gather(
+                self.as_ptr(),
+                buf,
+                nonce.as_ptr(),
+                nonce.len(),
+                buf,
+                in_out.len(),
+                tag.as_ptr(),
+                tag.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Like [`seal`](Self::seal), but picks a random nonce and returns it followed by the
+    /// ciphertext and the tag.
+    ///
+    /// This should only be used with algorithms whose nonces may be chosen at random, such as
+    /// [`Aead::xchacha20_poly1305`] and the AES-GCM-SIV variants. With the 96-bit nonces of
+    /// AES-GCM and ChaCha20-Poly1305, the chance of repeating a nonce becomes significant
+    /// after about 2^32 messages sealed under the same key.
+    #[corresponds(EVP_AEAD_CTX_seal)]
+    pub fn seal_random_nonce(&self, plaintext: &[u8], ad: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let aead = self.aead();
+        let nonce_len = aead.nonce_len();
+        let mut out = vec![0; nonce_len + plaintext.len() + aead.max_overhead()];
+        let (nonce, sealed) = out.split_at_mut(nonce_len);
+        rand_bytes(nonce)?;
+
+        let mut len = 0;
+        unsafe {
+            cvt_0i(ffi::EVP_AEAD_CTX_seal(
+                self.as_ptr(),
+                sealed.as_mut_ptr(),
+                &mut len,
+                sealed.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                plaintext.as_ptr(),
+                plaintext.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+        }
+        out.truncate(nonce_len + len);
+        Ok(out)
+    }
+
+    /// Decrypts and authenticates a message sealed with
+    /// [`seal_random_nonce`](Self::seal_random_nonce), and authenticates `ad`.
+    #[corresponds(EVP_AEAD_CTX_open)]
+    pub fn open_random_nonce(&self, sealed: &[u8], ad: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let nonce_len = self.aead().nonce_len();
+        if sealed.len() < nonce_len {
+            return Err(ErrorStack::internal_error_str(
+                "sealed message shorter than the nonce",
+            ));
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(nonce_len);
+        self.open(nonce, ciphertext, ad)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_aes_128_gcm_vector() {
+        // Test case 2 from the original GCM specification.
+        let ctx = AeadCtx::new(Aead::aes_128_gcm(), &[0; 16]).unwrap();
+        let (ciphertext, tag) = ctx.seal_detached(&[0; 12], &[0; 16], b"").unwrap();
+        assert_eq!(hex::encode(&ciphertext), "0388dace60b6a392f328c2b971b2fe78");
+        assert_eq!(hex::encode(&tag), "ab6e47d42cec13bdf53a67b21257bddf");
+
+        let plaintext = ctx.open_detached(&[0; 12], &ciphertext, &tag, b"").unwrap();
+        assert_eq!(plaintext, [0; 16]);
+
+        let sealed = ctx.seal(&[0; 12], &[0; 16], b"").unwrap();
+        assert_eq!(sealed, [ciphertext, tag].concat());
+    }
+
+    #[test]
+    fn test_aes_128_gcm_siv_vector() {
+        // The first AEAD_AES_128_GCM_SIV test vector of RFC 8452.
+        let mut key = [0; 16];
+        key[0] = 1;
+        let mut nonce = [0; 12];
+        nonce[0] = 3;
+        let ctx = AeadCtx::new(Aead::aes_128_gcm_siv(), &key).unwrap();
+        let sealed = ctx.seal(&nonce, b"", b"").unwrap();
+        assert_eq!(hex::encode(&sealed), "dc20e2d83f25705bb49e439eca56de25");
+    }
+
+    #[test]
+    fn test_random_nonce() {
+        for aead in [Aead::aes_256_gcm_siv(), Aead::xchacha20_poly1305()] {
+            let ctx = AeadCtx::new(aead, &vec![1; aead.key_len()]).unwrap();
+
+            let sealed = ctx.seal_random_nonce(b"plaintext", b"ad").unwrap();
+            assert_eq!(sealed.len(), aead.nonce_len() + 9 + aead.max_overhead());
+            let (nonce, ciphertext) = sealed.split_at(aead.nonce_len());
+            assert_eq!(ctx.open(nonce, ciphertext, b"ad").unwrap(), b"plaintext");
+            assert_eq!(ctx.open_random_nonce(&sealed, b"ad").unwrap(), b"plaintext");
+            assert!(ctx.open_random_nonce(&sealed, b"other ad").is_err());
+
+            let other = ctx.seal_random_nonce(b"plaintext", b"ad").unwrap();
+            assert_ne!(sealed[..aead.nonce_len()], other[..aead.nonce_len()]);
+            assert_ne!(sealed, other);
+
+            assert!(ctx
+                .open_random_nonce(&sealed[..aead.nonce_len() - 1], b"ad")
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for aead in [
+            Aead::aes_128_gcm(),
+            Aead::aes_256_gcm(),
+            Aead::aes_128_gcm_siv(),
+            Aead::aes_256_gcm_siv(),
+            Aead::chacha20_poly1305(),
+            Aead::xchacha20_poly1305(),
+        ] {
+            let ctx = AeadCtx::new(aead, &vec![1; aead.key_len()]).unwrap();
+            assert!(ctx.aead() == aead);
+            let nonce = vec![2; aead.nonce_len()];
+
+            let ciphertext = ctx.seal(&nonce, b"plaintext", b"ad").unwrap();
+            assert_eq!(ciphertext.len(), 9 + aead.max_overhead());
+            assert_eq!(ctx.open(&nonce, &ciphertext, b"ad").unwrap(), b"plaintext");
+            assert!(ctx.open(&nonce, &ciphertext, b"other ad").is_err());
+            let other_nonce = vec![3; aead.nonce_len()];
+            assert!(ctx.open(&other_nonce, &ciphertext, b"ad").is_err());
+        }
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let aead = Aead::chacha20_poly1305();
+        assert!(AeadCtx::new(aead, &[0; 16]).is_err());
+
+        let ctx = AeadCtx::new(aead, &[0; 32]).unwrap();
+        assert!(ctx.seal(&[0; 8], b"plaintext", b"").is_err());
+
+        let ctx = AeadCtx::with_tag_len(aead, &[0; 32], 8).unwrap();
+        let (_, tag) = ctx.seal_detached(&[0; 12], b"plaintext", b"").unwrap();
+        assert_eq!(tag.len(), 8);
+    }
+
+    #[test]
+    fn test_in_place() {
+        let aead = Aead::aes_256_gcm();
+        let ctx = AeadCtx::new(aead, &[1; 32]).unwrap();
+        let nonce = [2; 12];
+        let sealed = ctx.seal(&nonce, b"plaintext", b"ad").unwrap();
+
+        let mut buf = Vec::with_capacity(9 + aead.max_overhead());
+        buf.extend_from_slice(b"plaintext");
+        ctx.seal_in_place_append_tag(&nonce, &mut buf, b"ad")
+            .unwrap();
+        assert_eq!(buf, sealed);
+
+        ctx.open_in_place(&nonce, &mut buf, b"ad").unwrap();
+        assert_eq!(buf, b"plaintext");
+
+        let mut buf = *b"plaintext";
+        let mut tag = [0; 16];
+        let tag_len = ctx
+            .seal_in_place_detached(&nonce, &mut buf, &mut tag, b"ad")
+            .unwrap();
+        assert_eq!([&buf[..], &tag[..tag_len]].concat(), sealed);
+
+        assert!(ctx
+            .open_in_place_detached(&nonce, &mut buf.clone(), &tag, b"other ad")
+            .is_err());
+        ctx.open_in_place_detached(&nonce, &mut buf, &tag, b"ad")
+            .unwrap();
+        assert_eq!(&buf, b"plaintext");
+    }
+}
@@ -40,7 +40,7 @@
 use crate::ffi;
 use libc::{c_int, c_uint, size_t};
 use openssl_macros::corresponds;
-use std::mem::MaybeUninit;
+use std::mem::{self, MaybeUninit};
 use std::ptr;
 
 /// Provides Error handling for parsing keys.
@@ -99,8 +99,38 @@ impl AesKey {
             }
         }
     }
+
+    pub(crate) fn as_ptr(&self) -> *const ffi::AES_KEY {
+        &self.0
+    }
+
+    fn cleanse(&mut self) {
+        unsafe {
+            ffi::OPENSSL_cleanse(
+                (&mut self.0 as *mut ffi::AES_KEY).cast(),
+                mem::size_of::<ffi::AES_KEY>(),
+            );
+        }
+    }
 }
 
+impl Drop for AesKey {
+    fn drop(&mut self) {
+        self.cleanse();
+    }
+}
+
+/// Zeroizing the key leaves it unusable until it is dropped.
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for AesKey {
+    fn zeroize(&mut self) {
+        self.cleanse();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for AesKey {}
+
 /// Wrap a key, according to [RFC 3394](https://tools.ietf.org/html/rfc3394)
 ///
 /// * `key`: The key-encrypting-key to use. Must be a encrypting key
@@ -180,6 +210,105 @@ pub fn unwrap_key(
     }
 }
 
+/// Wrap a key with padding, according to [RFC 5649](https://tools.ietf.org/html/rfc5649)
+///
+/// * `key`: The key-encrypting-key to use. Must be a encrypting key
+/// * `out`: The output buffer to store the ciphertext
+/// * `in_`: The input buffer, storing the key to be wrapped. It may have any non-zero length
+///
+/// Returns the number of bytes written into `out`, which should be at least 15 bytes longer than
+/// `in_`.
+#[corresponds(AES_wrap_key_padded)]
+pub fn wrap_key_padded(key: &AesKey, out: &mut [u8], in_: &[u8]) -> Result<usize, KeyError> {
+    unsafe {
+        let mut written = 0;
+        let r = ffi::AES_wrap_key_padded(
+            &key.0,
+            out.as_mut_ptr(),
+            &mut written,
+            out.len(),
+            in_.as_ptr(),
+            in_.len(),
+        );
+        if r == 1 {
+            Ok(written)
+        } else {
+            Err(KeyError(()))
+        }
+    }
+}
+
+/// Unwrap a key wrapped with padding, according to [RFC 5649](https://tools.ietf.org/html/rfc5649)
+///
+/// * `key`: The key-encrypting-key to decrypt the wrapped key. Must be a decrypting key
+/// * `out`: The buffer to write the unwrapped key to, which should be as long as `in_`
+/// * `in_`: The input ciphertext
+///
+/// Returns the number of bytes written into `out`
+#[corresponds(AES_unwrap_key_padded)]
+pub fn unwrap_key_padded(key: &AesKey, out: &mut [u8], in_: &[u8]) -> Result<usize, KeyError> {
+    unsafe {
+        let mut written = 0;
+        let r = ffi::AES_unwrap_key_padded(
+            &key.0,
+            out.as_mut_ptr(),
+            &mut written,
+            out.len(),
+            in_.as_ptr(),
+            in_.len(),
+        );
+        if r == 1 {
+            Ok(written)
+        } else {
+            Err(KeyError(()))
+        }
+    }
+}
+
+/// Wraps `key_data` under the key-encrypting-key `kek` with AES-KW (RFC 3394) and the default IV.
+///
+/// `key_data` must be a multiple of 8 bytes, and at least 16 bytes, long.
+pub fn kw_wrap(kek: &[u8], key_data: &[u8]) -> Result<Vec<u8>, KeyError> {
+    if key_data.len() < 16 || key_data.len() % 8 != 0 {
+        return Err(KeyError(()));
+    }
+    let key = AesKey::new_encrypt(kek)?;
+    let mut out = vec![0; key_data.len() + 8];
+    let len = wrap_key(&key, None, &mut out, key_data)?;
+    out.truncate(len);
+    Ok(out)
+}
+
+/// Unwraps a key wrapped with [`kw_wrap`], checking its integrity.
+pub fn kw_unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, KeyError> {
+    if wrapped.len() < 24 || wrapped.len() % 8 != 0 {
+        return Err(KeyError(()));
+    }
+    let key = AesKey::new_decrypt(kek)?;
+    let mut out = vec![0; wrapped.len() - 8];
+    let len = unwrap_key(&key, None, &mut out, wrapped)?;
+    out.truncate(len);
+    Ok(out)
+}
+
+/// Wraps `key_data`, which may have any non-zero length, under `kek` with AES-KWP (RFC 5649).
+pub fn kwp_wrap(kek: &[u8], key_data: &[u8]) -> Result<Vec<u8>, KeyError> {
+    let key = AesKey::new_encrypt(kek)?;
+    let mut out = vec![0; key_data.len() + 15];
+    let len = wrap_key_padded(&key, &mut out, key_data)?;
+    out.truncate(len);
+    Ok(out)
+}
+
+/// Unwraps a key wrapped with [`kwp_wrap`], checking its integrity.
+pub fn kwp_unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, KeyError> {
+    let key = AesKey::new_decrypt(kek)?;
+    let mut out = vec![0; wrapped.len()];
+    let len = unwrap_key_padded(&key, &mut out, wrapped)?;
+    out.truncate(len);
+    Ok(out)
+}
+
 #[cfg(test)]
 mod test {
     use hex::FromHex;
@@ -210,4 +339,43 @@ mod test {
         );
         assert_eq!(&unwrapped[..], &key_data[..]);
     }
+
+    // from the RFC https://tools.ietf.org/html/rfc5649#section-6
+    #[test]
+    fn test_wrap_unwrap_padded() {
+        let kek = Vec::from_hex("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8").unwrap();
+
+        let key_data = Vec::from_hex("c37b7e6492584340bed12207808941155068f738").unwrap();
+        let wrapped = kwp_wrap(&kek, &key_data).unwrap();
+        assert_eq!(
+            hex::encode(&wrapped),
+            "138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a"
+        );
+        assert_eq!(kwp_unwrap(&kek, &wrapped).unwrap(), key_data);
+
+        let key_data = Vec::from_hex("466f7250617369").unwrap();
+        let wrapped = kwp_wrap(&kek, &key_data).unwrap();
+        assert_eq!(hex::encode(&wrapped), "afbeb0f07dfbf5419200f2ccb50bb24f");
+        assert_eq!(kwp_unwrap(&kek, &wrapped).unwrap(), key_data);
+
+        let mut corrupted = wrapped;
+        corrupted[0] ^= 1;
+        assert!(kwp_unwrap(&kek, &corrupted).is_err());
+    }
+
+    #[test]
+    fn test_kw_wrap_unwrap() {
+        let kek = Vec::from_hex("000102030405060708090A0B0C0D0E0F").unwrap();
+        let key_data = Vec::from_hex("00112233445566778899AABBCCDDEEFF").unwrap();
+
+        let wrapped = kw_wrap(&kek, &key_data).unwrap();
+        assert_eq!(
+            hex::encode_upper(&wrapped),
+            "1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5"
+        );
+        assert_eq!(kw_unwrap(&kek, &wrapped).unwrap(), key_data);
+
+        assert!(kw_wrap(&kek, &key_data[..15]).is_err());
+        assert!(kw_unwrap(&[0; 16], &wrapped).is_err());
+    }
 }
@@ -33,6 +33,7 @@ use std::fmt;
 use std::ptr;
 use std::slice;
 use std::str;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::bio::MemBio;
 use crate::bn::{BigNum, BigNumRef};
@@ -203,6 +204,34 @@ impl Asn1TimeRef {
         }
     }
 
+    /// Returns the time as the number of seconds since the Unix epoch.
+    #[corresponds(ASN1_TIME_to_posix)]
+    pub fn to_unix(&self) -> Result<i64, ErrorStack> {
+        let mut out = 0;
+        unsafe { cvt(ffi::ASN1_TIME_to_posix(self.as_ptr(), &mut out))? };
+        Ok(out)
+    }
+
+    /// Converts the time to a `SystemTime`.
+    pub fn to_system_time(&self) -> Result<SystemTime, ErrorStack> {
+        let secs = self.to_unix()?;
+        let offset = Duration::from_secs(secs.unsigned_abs());
+        let time = if secs >= 0 {
+            UNIX_EPOCH.checked_add(offset)
+        } else {
+            UNIX_EPOCH.checked_sub(offset)
+        };
+        time.ok_or_else(|| ErrorStack::internal_error_str("time out of range for SystemTime"))
+    }
+
+    /// Returns the number of seconds from now until this time.
+    ///
+    /// The result is negative if the time is in the past, which makes this convenient for
+    /// checking how long a certificate has left before it expires.
+    pub fn seconds_from_now(&self) -> Result<i64, ErrorStack> {
+        Ok(self.to_unix()? - system_time_to_unix(SystemTime::now())?)
+    }
+
     /// Compare two times
     #[corresponds(ASN1_TIME_compare)]
     pub fn compare(&self, other: &Self) -> Result<Ordering, ErrorStack> {
@@ -323,6 +352,11 @@ impl Asn1Time {
         }
     }
 
+    /// Creates a new time from a `SystemTime`, truncated to whole seconds.
+    pub fn from_system_time(time: SystemTime) -> Result<Asn1Time, ErrorStack> {
+        Self::from_unix(unix_to_time_t(system_time_to_unix(time)?)?)
+    }
+
     /// Creates a new time corresponding to the specified ASN1 time string.
     #[corresponds(ASN1_TIME_set_string)]
     #[allow(clippy::should_implement_trait)]
@@ -380,6 +414,74 @@ impl<'a> PartialOrd<&'a Asn1TimeRef> for Asn1Time {
     }
 }
 
+impl TryFrom<SystemTime> for Asn1Time {
+    type Error = ErrorStack;
+
+    fn try_from(time: SystemTime) -> Result<Asn1Time, ErrorStack> {
+        Asn1Time::from_system_time(time)
+    }
+}
+
+impl TryFrom<&Asn1TimeRef> for SystemTime {
+    type Error = ErrorStack;
+
+    fn try_from(time: &Asn1TimeRef) -> Result<SystemTime, ErrorStack> {
+        time.to_system_time()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for Asn1Time {
+    type Error = ErrorStack;
+
+    fn try_from(time: chrono::DateTime<chrono::Utc>) -> Result<Asn1Time, ErrorStack> {
+        Asn1Time::from_unix(unix_to_time_t(time.timestamp())?)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<&Asn1TimeRef> for chrono::DateTime<chrono::Utc> {
+    type Error = ErrorStack;
+
+    fn try_from(time: &Asn1TimeRef) -> Result<chrono::DateTime<chrono::Utc>, ErrorStack> {
+        chrono::DateTime::from_timestamp(time.to_unix()?, 0)
+            .ok_or_else(|| ErrorStack::internal_error_str("time out of range for chrono"))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for Asn1Time {
+    type Error = ErrorStack;
+
+    fn try_from(time: time::OffsetDateTime) -> Result<Asn1Time, ErrorStack> {
+        Asn1Time::from_unix(unix_to_time_t(time.unix_timestamp())?)
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<&Asn1TimeRef> for time::OffsetDateTime {
+    type Error = ErrorStack;
+
+    fn try_from(time: &Asn1TimeRef) -> Result<time::OffsetDateTime, ErrorStack> {
+        time::OffsetDateTime::from_unix_timestamp(time.to_unix()?)
+            .map_err(ErrorStack::internal_error)
+    }
+}
+
+fn system_time_to_unix(time: SystemTime) -> Result<i64, ErrorStack> {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => i64::try_from(d.as_secs()),
+        Err(e) => i64::try_from(e.duration().as_secs()).map(|s| -s),
+    };
+    secs.map_err(ErrorStack::internal_error)
+}
+
+// `time_t` is `i64` on most platforms
+#[allow(clippy::useless_conversion)]
+fn unix_to_time_t(time: i64) -> Result<time_t, ErrorStack> {
+    time_t::try_from(time).map_err(ErrorStack::internal_error)
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::ASN1_STRING;
     fn drop = ffi::ASN1_STRING_free;
@@ -659,6 +761,51 @@ mod tests {
         Asn1Time::from_str("99991231235959Z").unwrap();
     }
 
+    #[test]
+    fn time_system_time_roundtrip() {
+        let time = Asn1Time::from_str("20300101000000Z").unwrap();
+        assert_eq!(time.to_unix().unwrap(), 1893456000);
+
+        let system_time = time.to_system_time().unwrap();
+        assert_eq!(system_time, UNIX_EPOCH + Duration::from_secs(1893456000));
+        assert!(Asn1Time::try_from(system_time).unwrap() == time);
+
+        let before_epoch = Asn1Time::from_str("19600101000000Z").unwrap();
+        let system_time = SystemTime::try_from(&*before_epoch).unwrap();
+        assert!(Asn1Time::from_system_time(system_time).unwrap() == before_epoch);
+
+        let far_future = Asn1Time::from_unix(253402300799).unwrap();
+        assert!(far_future == Asn1Time::from_str("99991231235959Z").unwrap());
+    }
+
+    #[test]
+    fn time_seconds_from_now() {
+        let tomorrow = Asn1Time::days_from_now(1).unwrap();
+        let secs = tomorrow.seconds_from_now().unwrap();
+        assert!((86400 - 5..=86400).contains(&secs));
+
+        let past = Asn1Time::from_unix(0).unwrap();
+        assert!(past.seconds_from_now().unwrap() < 0);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn time_chrono() {
+        let time = Asn1Time::from_str("20300101000000Z").unwrap();
+        let date = chrono::DateTime::<chrono::Utc>::try_from(&*time).unwrap();
+        assert_eq!(date.timestamp(), 1893456000);
+        assert!(Asn1Time::try_from(date).unwrap() == time);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_time() {
+        let time = Asn1Time::from_str("20300101000000Z").unwrap();
+        let date = time::OffsetDateTime::try_from(&*time).unwrap();
+        assert_eq!(date.unix_timestamp(), 1893456000);
+        assert!(Asn1Time::try_from(date).unwrap() == time);
+    }
+
     #[test]
     fn time_from_unix() {
         let t = Asn1Time::from_unix(0).unwrap();
@@ -25,9 +25,12 @@
 use crate::ffi;
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::{c_int, size_t};
+use std::any::Any;
 use std::cmp::Ordering;
 use std::ffi::CString;
-use std::ops::{Add, Deref, Div, Mul, Neg, Rem, Shl, Shr, Sub};
+use std::mem::ManuallyDrop;
+use std::ops::{Add, Deref, DerefMut, Div, Mul, Neg, Rem, Shl, Shr, Sub};
+use std::panic::{self, AssertUnwindSafe};
 use std::{fmt, ptr};
 
 use crate::asn1::Asn1Integer;
@@ -54,6 +57,18 @@ impl MsbOption {
     pub const TWO_ONES: MsbOption = MsbOption(1);
 }
 
+/// Progress reported by [`BigNumRef::generate_prime_with_callback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PrimeProgress {
+    /// The given candidate, counting from zero, has been generated.
+    Generated(i32),
+    /// Trial division of the current candidate has finished.
+    TrialDivision,
+    /// The given round of primality testing, counting from zero, has finished.
+    PrimeTest(i32),
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::BN_CTX;
     fn drop = ffi::BN_CTX_free;
@@ -79,6 +94,49 @@ impl BigNumContext {
     }
 }
 
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::BN_MONT_CTX;
+    fn drop = ffi::BN_MONT_CTX_free;
+
+    /// Precomputed values for Montgomery arithmetic modulo a fixed odd modulus.
+    pub struct MontgomeryContext;
+}
+
+impl MontgomeryContext {
+    /// Returns a new `MontgomeryContext` for the odd modulus `modulus`.
+    #[corresponds(BN_MONT_CTX_new_for_modulus)]
+    pub fn new(
+        modulus: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<MontgomeryContext, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::BN_MONT_CTX_new_for_modulus(
+                modulus.as_ptr(),
+                ctx.as_ptr(),
+            ))
+            .map(|p| MontgomeryContext::from_ptr(p))
+        }
+    }
+
+    /// Like [`new`](Self::new), but does not leak the value of `modulus` through timing. Use this
+    /// when the modulus is secret, such as an RSA prime.
+    #[corresponds(BN_MONT_CTX_new_consttime)]
+    pub fn new_consttime(
+        modulus: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<MontgomeryContext, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::BN_MONT_CTX_new_consttime(
+                modulus.as_ptr(),
+                ctx.as_ptr(),
+            ))
+            .map(|p| MontgomeryContext::from_ptr(p))
+        }
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::BIGNUM;
     fn drop = ffi::BN_free;
@@ -402,6 +460,46 @@ impl BigNumRef {
         }
     }
 
+    /// Like [`generate_prime`](Self::generate_prime), but reports progress to `callback`.
+    ///
+    /// Generating large safe primes can take a long time. Returning `false` from `callback`
+    /// aborts the generation, and this method then returns an error.
+    #[corresponds(BN_generate_prime_ex)]
+    pub fn generate_prime_with_callback<F>(
+        &mut self,
+        bits: i32,
+        safe: bool,
+        add: Option<&BigNumRef>,
+        rem: Option<&BigNumRef>,
+        callback: F,
+    ) -> Result<(), ErrorStack>
+    where
+        F: FnMut(PrimeProgress) -> bool,
+    {
+        let mut state = GenCbState {
+            callback,
+            panic: None,
+        };
+        let res = unsafe {
+            let cb = cvt_p(ffi::BN_GENCB_new())?;
+            ffi::BN_GENCB_set(cb, Some(invoke_gencb::<F>), &mut state as *mut _ as *mut _);
+            let res = cvt(ffi::BN_generate_prime_ex(
+                self.as_ptr(),
+                bits as c_int,
+                c_int::from(safe),
+                add.map(|n| n.as_ptr()).unwrap_or(ptr::null_mut()),
+                rem.map(|n| n.as_ptr()).unwrap_or(ptr::null_mut()),
+                cb,
+            ));
+            ffi::BN_GENCB_free(cb);
+            res
+        };
+        if let Some(panic) = state.panic {
+            panic::resume_unwind(panic);
+        }
+        res.map(|_| ())
+    }
+
     /// Places the result of `a * b` in `self`.
     /// [`core::ops::Mul`] is also implemented for `BigNumRef`.
     ///
@@ -636,6 +734,93 @@ impl BigNumRef {
         }
     }
 
+    /// Places the result of `a^p mod m` in `self`, without leaking `a` or `p` through timing.
+    ///
+    /// BoringSSL does not support OpenSSL's `BN_FLG_CONSTTIME`, so secret exponents must go
+    /// through this method rather than [`mod_exp`](Self::mod_exp). `m` must be odd. If `mont` is
+    /// given, it must have been created for `m`.
+    #[corresponds(BN_mod_exp_mont_consttime)]
+    pub fn mod_exp_consttime(
+        &mut self,
+        a: &BigNumRef,
+        p: &BigNumRef,
+        m: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+        mont: Option<&MontgomeryContextRef>,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::BN_mod_exp_mont_consttime(
+                self.as_ptr(),
+                a.as_ptr(),
+                p.as_ptr(),
+                m.as_ptr(),
+                ctx.as_ptr(),
+                mont.map_or(ptr::null(), |m| m.as_ptr()),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Places `a` converted into the Montgomery domain of `mont` in `self`.
+    #[corresponds(BN_to_montgomery)]
+    pub fn to_montgomery(
+        &mut self,
+        a: &BigNumRef,
+        mont: &MontgomeryContextRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::BN_to_montgomery(
+                self.as_ptr(),
+                a.as_ptr(),
+                mont.as_ptr(),
+                ctx.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Places `a` converted out of the Montgomery domain of `mont` in `self`.
+    #[corresponds(BN_from_montgomery)]
+    pub fn from_montgomery(
+        &mut self,
+        a: &BigNumRef,
+        mont: &MontgomeryContextRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::BN_from_montgomery(
+                self.as_ptr(),
+                a.as_ptr(),
+                mont.as_ptr(),
+                ctx.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Places the Montgomery product of `a` and `b` in `self`. Both inputs must already be in the
+    /// Montgomery domain of `mont`, and the result is too.
+    #[corresponds(BN_mod_mul_montgomery)]
+    pub fn mod_mul_montgomery(
+        &mut self,
+        a: &BigNumRef,
+        b: &BigNumRef,
+        mont: &MontgomeryContextRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::BN_mod_mul_montgomery(
+                self.as_ptr(),
+                a.as_ptr(),
+                b.as_ptr(),
+                mont.as_ptr(),
+                ctx.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Places the inverse of `a` modulo `n` in `self`.
     #[corresponds(BN_mod_inverse)]
     pub fn mod_inverse(
@@ -890,6 +1075,87 @@ impl BigNum {
     }
 }
 
+/// A [`BigNum`] for secret values, which is erased when dropped.
+///
+/// BoringSSL has no secure heap, so the memory is not locked; only the zeroization on drop
+/// differs from a plain `BigNum`.
+pub struct SecureBigNum(ManuallyDrop<BigNum>);
+
+impl SecureBigNum {
+    /// Creates a new `SecureBigNum` with the value 0.
+    #[corresponds(BN_secure_new)]
+    pub fn new() -> Result<SecureBigNum, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let v = cvt_p(ffi::BN_secure_new())?;
+            Ok(SecureBigNum(ManuallyDrop::new(BigNum::from_ptr(v))))
+        }
+    }
+}
+
+impl From<BigNum> for SecureBigNum {
+    fn from(bn: BigNum) -> SecureBigNum {
+        SecureBigNum(ManuallyDrop::new(bn))
+    }
+}
+
+impl Drop for SecureBigNum {
+    fn drop(&mut self) {
+        unsafe { ffi::BN_clear_free(self.0.as_ptr()) }
+    }
+}
+
+impl Deref for SecureBigNum {
+    type Target = BigNumRef;
+
+    fn deref(&self) -> &BigNumRef {
+        &self.0
+    }
+}
+
+impl DerefMut for SecureBigNum {
+    fn deref_mut(&mut self) -> &mut BigNumRef {
+        &mut self.0
+    }
+}
+
+impl fmt::Debug for SecureBigNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecureBigNum")
+    }
+}
+
+struct GenCbState<F> {
+    callback: F,
+    panic: Option<Box<dyn Any + Send + 'static>>,
+}
+
+unsafe extern "C" fn invoke_gencb<F>(event: c_int, n: c_int, cb: *mut ffi::BN_GENCB) -> c_int
+where
+    F: FnMut(PrimeProgress) -> bool,
+{
+    let state = &mut *(ffi::BN_GENCB_get_arg(cb) as *mut GenCbState<F>);
+    if state.panic.is_some() {
+        return 0;
+    }
+
+    let progress = match (event, n) {
+        (ffi::BN_GENCB_GENERATED, n) => PrimeProgress::Generated(n),
+        (ffi::BN_GENCB_PRIME_TEST, -1) => PrimeProgress::TrialDivision,
+        (ffi::BN_GENCB_PRIME_TEST, n) => PrimeProgress::PrimeTest(n),
+        _ => return 1,
+    };
+
+    match panic::catch_unwind(AssertUnwindSafe(|| (state.callback)(progress))) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(err) => {
+            state.panic = Some(err);
+            0
+        }
+    }
+}
+
 impl fmt::Debug for BigNumRef {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.to_dec_str() {
@@ -1145,7 +1411,7 @@ impl Neg for BigNum {
 
 #[cfg(test)]
 mod tests {
-    use crate::bn::{BigNum, BigNumContext};
+    use crate::bn::{BigNum, BigNumContext, MontgomeryContext, PrimeProgress, SecureBigNum};
 
     #[test]
     fn test_to_from_slice() {
@@ -1197,4 +1463,74 @@ mod tests {
         assert!(p.is_prime(100, &mut ctx).unwrap());
         assert!(p.is_prime_fasttest(100, &mut ctx, true).unwrap());
     }
+
+    #[test]
+    fn test_prime_callback() {
+        let mut events = vec![];
+        let mut p = BigNum::new().unwrap();
+        p.generate_prime_with_callback(128, true, None, None, |progress| {
+            events.push(progress);
+            true
+        })
+        .unwrap();
+        assert!(matches!(events[0], PrimeProgress::Generated(0)));
+        assert!(events.contains(&PrimeProgress::TrialDivision));
+
+        let mut ctx = BigNumContext::new().unwrap();
+        assert!(p.is_prime(100, &mut ctx).unwrap());
+
+        let mut p = BigNum::new().unwrap();
+        p.generate_prime_with_callback(128, true, None, None, |_| false)
+            .expect_err("generation should be aborted");
+    }
+
+    #[test]
+    fn test_mod_exp_consttime() {
+        let mut ctx = BigNumContext::new().unwrap();
+        let a = BigNum::from_u32(4).unwrap();
+        let p = BigNum::from_u32(13).unwrap();
+        let m = BigNum::from_u32(497).unwrap();
+
+        let mut r = BigNum::new().unwrap();
+        r.mod_exp_consttime(&a, &p, &m, &mut ctx, None).unwrap();
+        assert_eq!(r, BigNum::from_u32(445).unwrap());
+
+        let mont = MontgomeryContext::new_consttime(&m, &mut ctx).unwrap();
+        let mut r = BigNum::new().unwrap();
+        r.mod_exp_consttime(&a, &p, &m, &mut ctx, Some(&mont))
+            .unwrap();
+        assert_eq!(r, BigNum::from_u32(445).unwrap());
+    }
+
+    #[test]
+    fn test_montgomery() {
+        let mut ctx = BigNumContext::new().unwrap();
+        let m = BigNum::from_u32(497).unwrap();
+        let mont = MontgomeryContext::new(&m, &mut ctx).unwrap();
+
+        let a = BigNum::from_u32(100).unwrap();
+        let b = BigNum::from_u32(200).unwrap();
+        let mut a_mont = BigNum::new().unwrap();
+        let mut b_mont = BigNum::new().unwrap();
+        a_mont.to_montgomery(&a, &mont, &mut ctx).unwrap();
+        b_mont.to_montgomery(&b, &mont, &mut ctx).unwrap();
+
+        let mut product = BigNum::new().unwrap();
+        product
+            .mod_mul_montgomery(&a_mont, &b_mont, &mont, &mut ctx)
+            .unwrap();
+        let mut r = BigNum::new().unwrap();
+        r.from_montgomery(&product, &mont, &mut ctx).unwrap();
+        assert_eq!(r, BigNum::from_u32(100 * 200 % 497).unwrap());
+    }
+
+    #[test]
+    fn test_secure_bignum() {
+        let mut secret = SecureBigNum::new().unwrap();
+        secret.add_word(42).unwrap();
+        assert_eq!(&*secret, &BigNum::from_u32(42).unwrap());
+
+        let secret = SecureBigNum::from(BigNum::from_u32(7).unwrap());
+        assert_eq!(secret.num_bits(), 3);
+    }
 }
@@ -0,0 +1,190 @@
+//! Reading DER and TLS encodings with BoringSSL's `CBS` API.
+
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr;
+
+use crate::ffi;
+use crate::nid::Nid;
+
+/// The tag of `[0] EXPLICIT` and other constructed context-specific elements with number 0.
+pub(crate) const CONTEXT_0: ffi::CBS_ASN1_TAG =
+    ffi::CBS_ASN1_CONTEXT_SPECIFIC | ffi::CBS_ASN1_CONSTRUCTED;
+/// The tag of constructed context-specific elements with number 1.
+pub(crate) const CONTEXT_1: ffi::CBS_ASN1_TAG = CONTEXT_0 | 1;
+
+/// Why a DER encoding could not be read.
+#[derive(Debug)]
+pub(crate) struct Malformed(pub(crate) &'static str);
+
+const INVALID: Malformed = Malformed("invalid DER");
+#[cfg(feature = "ct")]
+const TRUNCATED: Malformed = Malformed("truncated data");
+
+/// A `CBS` reading a borrowed DER or TLS encoding.
+pub(crate) struct Cbs<'a> {
+    cbs: ffi::CBS,
+    _p: PhantomData<&'a [u8]>,
+}
+
+impl<'a> Cbs<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Cbs<'a> {
+        let mut cbs = MaybeUninit::uninit();
+        unsafe {
+            ffi::CBS_init(cbs.as_mut_ptr(), data.as_ptr(), data.len());
+            Cbs::from_raw(cbs.assume_init())
+        }
+    }
+
+    fn from_raw(cbs: ffi::CBS) -> Cbs<'a> {
+        Cbs {
+            cbs,
+            _p: PhantomData,
+        }
+    }
+
+    /// Returns the bytes that have not been read.
+    pub(crate) fn as_slice(&self) -> &'a [u8] {
+        unsafe {
+            let len = ffi::CBS_len(&self.cbs);
+            if len == 0 {
+                return &[];
+            }
+            std::slice::from_raw_parts(ffi::CBS_data(&self.cbs), len)
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        unsafe { ffi::CBS_len(&self.cbs) == 0 }
+    }
+
+    /// Reads the contents of the next element, which must have tag `tag`.
+    pub(crate) fn get_asn1(&mut self, tag: ffi::CBS_ASN1_TAG) -> Result<Cbs<'a>, Malformed> {
+        let mut out = MaybeUninit::uninit();
+        unsafe {
+            if ffi::CBS_get_asn1(&mut self.cbs, out.as_mut_ptr(), tag) != 1 {
+                return Err(INVALID);
+            }
+            Ok(Cbs::from_raw(out.assume_init()))
+        }
+    }
+
+    /// Reads the whole encoding of the next element, which must have tag `tag`.
+    pub(crate) fn get_asn1_element(
+        &mut self,
+        tag: ffi::CBS_ASN1_TAG,
+    ) -> Result<Cbs<'a>, Malformed> {
+        let mut out = MaybeUninit::uninit();
+        unsafe {
+            if ffi::CBS_get_asn1_element(&mut self.cbs, out.as_mut_ptr(), tag) != 1 {
+                return Err(INVALID);
+            }
+            Ok(Cbs::from_raw(out.assume_init()))
+        }
+    }
+
+    /// Reads the contents of the next element if it has tag `tag`.
+    pub(crate) fn get_optional_asn1(
+        &mut self,
+        tag: ffi::CBS_ASN1_TAG,
+    ) -> Result<Option<Cbs<'a>>, Malformed> {
+        let mut out = MaybeUninit::uninit();
+        let mut present = 0;
+        unsafe {
+            if ffi::CBS_get_optional_asn1(&mut self.cbs, out.as_mut_ptr(), &mut present, tag) != 1 {
+                return Err(INVALID);
+            }
+            Ok((present == 1).then(|| Cbs::from_raw(out.assume_init())))
+        }
+    }
+
+    /// Reads the next element, returning its tag and contents.
+    pub(crate) fn get_any_asn1(&mut self) -> Result<(ffi::CBS_ASN1_TAG, Cbs<'a>), Malformed> {
+        let mut out = MaybeUninit::uninit();
+        let mut tag = 0;
+        unsafe {
+            if ffi::CBS_get_any_asn1(&mut self.cbs, out.as_mut_ptr(), &mut tag) != 1 {
+                return Err(INVALID);
+            }
+            Ok((tag, Cbs::from_raw(out.assume_init())))
+        }
+    }
+
+    /// Reads an OBJECT IDENTIFIER, returning its NID, which is [`Nid::UNDEF`] if it is unknown.
+    pub(crate) fn get_oid_nid(&mut self) -> Result<Nid, Malformed> {
+        let oid = self.get_asn1(ffi::CBS_ASN1_OBJECT)?;
+        Ok(Nid::from_raw(unsafe { ffi::OBJ_cbs2nid(&oid.cbs) }))
+    }
+
+    /// Reads a `u8`.
+    #[cfg(feature = "ct")]
+    pub(crate) fn get_u8(&mut self) -> Result<u8, Malformed> {
+        let mut out = 0;
+        unsafe {
+            if ffi::CBS_get_u8(&mut self.cbs, &mut out) != 1 {
+                return Err(TRUNCATED);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Reads a big-endian `u64`.
+    #[cfg(feature = "ct")]
+    pub(crate) fn get_u64(&mut self) -> Result<u64, Malformed> {
+        let mut out = 0;
+        unsafe {
+            if ffi::CBS_get_u64(&mut self.cbs, &mut out) != 1 {
+                return Err(TRUNCATED);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Reads the next `len` bytes.
+    #[cfg(feature = "ct")]
+    pub(crate) fn get_bytes(&mut self, len: usize) -> Result<&'a [u8], Malformed> {
+        let mut out = MaybeUninit::uninit();
+        unsafe {
+            if ffi::CBS_get_bytes(&mut self.cbs, out.as_mut_ptr(), len) != 1 {
+                return Err(TRUNCATED);
+            }
+            Ok(Cbs::from_raw(out.assume_init()).as_slice())
+        }
+    }
+
+    /// Reads a value prefixed with its 16-bit length.
+    #[cfg(feature = "ct")]
+    pub(crate) fn get_u16_length_prefixed(&mut self) -> Result<Cbs<'a>, Malformed> {
+        let mut out = MaybeUninit::uninit();
+        unsafe {
+            if ffi::CBS_get_u16_length_prefixed(&mut self.cbs, out.as_mut_ptr()) != 1 {
+                return Err(TRUNCATED);
+            }
+            Ok(Cbs::from_raw(out.assume_init()))
+        }
+    }
+
+    /// Skips the next element.
+    pub(crate) fn skip_any_asn1(&mut self) -> Result<(), Malformed> {
+        unsafe {
+            if ffi::CBS_get_any_asn1(&mut self.cbs, ptr::null_mut(), ptr::null_mut()) != 1 {
+                return Err(INVALID);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// DER tags for building test encodings.
+#[cfg(test)]
+pub(crate) mod test {
+    pub(crate) const DER_INTEGER: u8 = 0x02;
+    pub(crate) const DER_BIT_STRING: u8 = 0x03;
+    pub(crate) const DER_OCTET_STRING: u8 = 0x04;
+    pub(crate) const DER_OID: u8 = 0x06;
+    pub(crate) const DER_ENUMERATED: u8 = 0x0a;
+    pub(crate) const DER_GENERALIZED_TIME: u8 = 0x18;
+    pub(crate) const DER_SEQUENCE: u8 = 0x30;
+    pub(crate) const DER_CONTEXT_0: u8 = 0xa0;
+    pub(crate) const DER_CONTEXT_1: u8 = 0xa1;
+}
@@ -0,0 +1,125 @@
+//! The ChaCha20 stream cipher, as described in [RFC 8439].
+//!
+//! This is the bare cipher, without authentication. Most applications want
+//! [`Aead::chacha20_poly1305`](crate::aead::Aead::chacha20_poly1305) instead.
+//!
+//! [RFC 8439]: https://www.rfc-editor.org/rfc/rfc8439
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::chacha::chacha20_in_place;
+//!
+//! let key = [0x42; 32];
+//! let nonce = [0x24; 12];
+//!
+//! let mut buf = *b"some plaintext";
+//! chacha20_in_place(&key, &nonce, 1, &mut buf);
+//! chacha20_in_place(&key, &nonce, 1, &mut buf);
+//! assert_eq!(&buf, b"some plaintext");
+//! ```
+use crate::ffi;
+use openssl_macros::corresponds;
+
+/// The length of a ChaCha20 key, in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// The length of a ChaCha20 nonce, in bytes.
+pub const NONCE_LEN: usize = 12;
+
+/// Encrypts or decrypts `input` into `output`, starting at block `counter`.
+///
+/// The caller is responsible for never reusing a key, nonce and counter combination, and for
+/// keeping the 32-bit block counter from wrapping around.
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+#[corresponds(CRYPTO_chacha_20)]
+pub fn chacha20(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    counter: u32,
+    input: &[u8],
+    output: &mut [u8],
+) {
+    assert_eq!(input.len(), output.len());
+    unsafe {
+        ffi::CRYPTO_chacha_20(
+            output.as_mut_ptr(),
+            input.as_ptr(),
+            input.len(),
+            key.as_ptr(),
+            nonce.as_ptr(),
+            counter,
+        );
+    }
+}
+
+/// Encrypts or decrypts `buf` in place, starting at block `counter`.
+#[corresponds(CRYPTO_chacha_20)]
+pub fn chacha20_in_place(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    counter: u32,
+    buf: &mut [u8],
+) {
+    let ptr = buf.as_mut_ptr();
+    unsafe {
+        ffi::CRYPTO_chacha_20(ptr, ptr, buf.len(), key.as_ptr(), nonce.as_ptr(), counter);
+    }
+}
+
+/// Fills `out` with keystream, starting at block `counter`.
+pub fn keystream(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], counter: u32, out: &mut [u8]) {
+    out.fill(0);
+    chacha20_in_place(key, nonce, counter, out);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex::FromHex;
+
+    // Test vector from RFC 8439, section 2.4.2.
+    const PLAINTEXT: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+                               only one tip for the future, sunscreen would be it.";
+    const CIPHERTEXT: &str = "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b\
+                              f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8\
+                              07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736\
+                              5af90bbf74a35be6b40b8eedf2785e42874d";
+
+    fn key_and_nonce() -> ([u8; KEY_LEN], [u8; NONCE_LEN]) {
+        let mut key = [0; KEY_LEN];
+        key.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8);
+        let nonce = <[u8; NONCE_LEN]>::from_hex("000000000000004a00000000").unwrap();
+        (key, nonce)
+    }
+
+    #[test]
+    fn test_rfc8439_vector() {
+        let (key, nonce) = key_and_nonce();
+
+        let mut ciphertext = vec![0; PLAINTEXT.len()];
+        chacha20(&key, &nonce, 1, PLAINTEXT, &mut ciphertext);
+        assert_eq!(hex::encode(&ciphertext), CIPHERTEXT);
+
+        chacha20_in_place(&key, &nonce, 1, &mut ciphertext);
+        assert_eq!(ciphertext, PLAINTEXT);
+    }
+
+    #[test]
+    fn test_keystream() {
+        let (key, nonce) = key_and_nonce();
+
+        let mut stream = vec![0; PLAINTEXT.len()];
+        keystream(&key, &nonce, 1, &mut stream);
+        let ciphertext: Vec<u8> = PLAINTEXT.iter().zip(&stream).map(|(p, k)| p ^ k).collect();
+        assert_eq!(hex::encode(ciphertext), CIPHERTEXT);
+
+        // The counter selects the 64-byte block the keystream starts at.
+        let mut later = vec![0; 64];
+        keystream(&key, &nonce, 2, &mut later);
+        assert_eq!(&later[..50], &stream[64..]);
+    }
+}
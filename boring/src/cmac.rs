@@ -0,0 +1,178 @@
+//! Cipher-based message authentication codes, as described in [RFC 4493].
+//!
+//! [RFC 4493]: https://www.rfc-editor.org/rfc/rfc4493
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::cmac::{aes_cmac, Cmac};
+//! use boring::symm::Cipher;
+//!
+//! let key = [0x42; 16];
+//!
+//! let mut cmac = Cmac::new(Cipher::aes_128_cbc(), &key).unwrap();
+//! cmac.update(b"hello, ").unwrap();
+//! cmac.update(b"world").unwrap();
+//! let tag = cmac.finish().unwrap();
+//!
+//! assert_eq!(tag, aes_cmac(&key, b"hello, world").unwrap());
+//! ```
+use crate::error::ErrorStack;
+use crate::symm::Cipher;
+use crate::{cvt, cvt_p, ffi};
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl_macros::corresponds;
+use std::io::{self, Write};
+use std::ptr;
+
+/// The length of a CMAC tag, in bytes.
+pub const TAG_LEN: usize = 16;
+
+/// Computes the AES-CMAC of `data` in one shot.
+///
+/// `key` must be 16 or 32 bytes long, selecting AES-128 or AES-256.
+#[corresponds(AES_CMAC)]
+pub fn aes_cmac(key: &[u8], data: &[u8]) -> Result<[u8; TAG_LEN], ErrorStack> {
+    let mut out = [0; TAG_LEN];
+    unsafe {
+        ffi::init();
+        cvt(ffi::AES_CMAC(
+            out.as_mut_ptr(),
+            key.as_ptr(),
+            key.len(),
+            data.as_ptr(),
+            data.len(),
+        ))?;
+    }
+    Ok(out)
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::CMAC_CTX;
+    fn drop = ffi::CMAC_CTX_free;
+
+    /// An incremental CMAC computation.
+    pub struct Cmac;
+}
+
+impl Cmac {
+    /// Creates a CMAC context keyed with `key`.
+    ///
+    /// `cipher` is the CBC mode of the underlying block cipher, such as
+    /// [`Cipher::aes_128_cbc`] or [`Cipher::aes_256_cbc`].
+    #[corresponds(CMAC_Init)]
+    pub fn new(cipher: Cipher, key: &[u8]) -> Result<Cmac, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let cmac = Cmac::from_ptr(cvt_p(ffi::CMAC_CTX_new())?);
+            cvt(ffi::CMAC_Init(
+                cmac.as_ptr(),
+                key.as_ptr().cast(),
+                key.len(),
+                cipher.as_ptr(),
+                ptr::null_mut(),
+            ))?;
+            Ok(cmac)
+        }
+    }
+}
+
+impl Clone for Cmac {
+    fn clone(&self) -> Cmac {
+        unsafe {
+            let cmac = Cmac::from_ptr(cvt_p(ffi::CMAC_CTX_new()).unwrap());
+            cvt(ffi::CMAC_CTX_copy(cmac.as_ptr(), self.as_ptr())).unwrap();
+            cmac
+        }
+    }
+}
+
+impl CmacRef {
+    /// Feeds more data into the computation.
+    #[corresponds(CMAC_Update)]
+    pub fn update(&mut self, data: &[u8]) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::CMAC_Update(self.as_ptr(), data.as_ptr(), data.len())).map(|_| ()) }
+    }
+
+    /// Returns the tag over all data fed in so far.
+    ///
+    /// The context must be [`reset`](Self::reset) before it is used again.
+    #[corresponds(CMAC_Final)]
+    pub fn finish(&mut self) -> Result<[u8; TAG_LEN], ErrorStack> {
+        let mut out = [0; TAG_LEN];
+        let mut len = 0;
+        unsafe {
+            cvt(ffi::CMAC_Final(self.as_ptr(), out.as_mut_ptr(), &mut len))?;
+        }
+        debug_assert_eq!(len, TAG_LEN);
+        Ok(out)
+    }
+
+    /// Discards all data fed in so far, keeping the key.
+    #[corresponds(CMAC_Reset)]
+    pub fn reset(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::CMAC_Reset(self.as_ptr())).map(|_| ()) }
+    }
+}
+
+impl Write for Cmac {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex::FromHex;
+
+    // Test vectors from RFC 4493, section 4.
+    const KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+
+    #[test]
+    fn test_aes_cmac() {
+        let key = Vec::from_hex(KEY).unwrap();
+        assert_eq!(
+            hex::encode(aes_cmac(&key, b"").unwrap()),
+            "bb1d6929e95937287fa37d129b756746"
+        );
+
+        let data = Vec::from_hex("6bc1bee22e409f96e93d7e117393172a").unwrap();
+        assert_eq!(
+            hex::encode(aes_cmac(&key, &data).unwrap()),
+            "070a16b46b4d4144f79bdd9dd04a287c"
+        );
+    }
+
+    #[test]
+    fn test_cmac_incremental() {
+        let key = Vec::from_hex(KEY).unwrap();
+        let data = Vec::from_hex("6bc1bee22e409f96e93d7e117393172a").unwrap();
+
+        let mut cmac = Cmac::new(Cipher::aes_128_cbc(), &key).unwrap();
+        cmac.update(&data[..5]).unwrap();
+        let mut copy = cmac.clone();
+        cmac.update(&data[5..]).unwrap();
+        assert_eq!(
+            hex::encode(cmac.finish().unwrap()),
+            "070a16b46b4d4144f79bdd9dd04a287c"
+        );
+
+        copy.write_all(&data[5..]).unwrap();
+        assert_eq!(
+            hex::encode(copy.finish().unwrap()),
+            "070a16b46b4d4144f79bdd9dd04a287c"
+        );
+
+        cmac.reset().unwrap();
+        assert_eq!(
+            hex::encode(cmac.finish().unwrap()),
+            "bb1d6929e95937287fa37d129b756746"
+        );
+    }
+}
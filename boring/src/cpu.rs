@@ -0,0 +1,136 @@
+//! CPU features used to accelerate cryptography.
+//!
+//! BoringSSL picks assembly implementations at runtime based on what the CPU supports. Only the
+//! AES hardware check is exposed by BoringSSL itself; the remaining features are detected with the
+//! standard library, which queries the same CPUID and HWCAP bits.
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::cpu::CpuFeatures;
+//!
+//! let features = CpuFeatures::detect();
+//! println!("crypto acceleration: {features:?}");
+//! ```
+use crate::ffi;
+use openssl_macros::corresponds;
+
+/// Returns whether BoringSSL was built with assembly implementations.
+///
+/// If this returns `false`, all algorithms use portable C code regardless of the CPU.
+#[corresponds(CRYPTO_has_asm)]
+#[must_use]
+pub fn has_asm() -> bool {
+    unsafe { ffi::CRYPTO_has_asm() == 1 }
+}
+
+/// Returns whether BoringSSL uses hardware AES and carry-less multiplication, making AES-GCM
+/// fast and constant-time.
+///
+/// When this returns `false`, ChaCha20-Poly1305 is usually the faster AEAD.
+#[corresponds(EVP_has_aes_hardware)]
+#[must_use]
+pub fn has_aes_hardware() -> bool {
+    unsafe {
+        ffi::init();
+        ffi::EVP_has_aes_hardware() == 1
+    }
+}
+
+/// Returns whether the CPU supports AVX2.
+///
+/// Always `false` on non-x86 targets.
+#[must_use]
+pub fn has_avx2() -> bool {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        std::arch::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        false
+    }
+}
+
+/// Returns whether the CPU has SHA-256 instructions: the SHA extensions on x86, or the SHA2
+/// extension on AArch64.
+///
+/// Always `false` on other targets.
+#[must_use]
+pub fn has_sha_extensions() -> bool {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        std::arch::is_x86_feature_detected!("sha")
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("sha2")
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
+/// Returns whether the CPU supports NEON.
+///
+/// NEON is mandatory on AArch64. This is always `false` on other targets, including 32-bit ARM,
+/// where runtime detection is not available in stable Rust.
+#[must_use]
+pub fn has_neon() -> bool {
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("neon")
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    {
+        false
+    }
+}
+
+/// A snapshot of the acceleration paths available to BoringSSL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CpuFeatures {
+    /// See [`has_asm`].
+    pub asm: bool,
+    /// See [`has_aes_hardware`].
+    pub aes_hardware: bool,
+    /// See [`has_avx2`].
+    pub avx2: bool,
+    /// See [`has_sha_extensions`].
+    pub sha_extensions: bool,
+    /// See [`has_neon`].
+    pub neon: bool,
+}
+
+impl CpuFeatures {
+    /// Detects the features of the current CPU.
+    #[must_use]
+    pub fn detect() -> CpuFeatures {
+        CpuFeatures {
+            asm: has_asm(),
+            aes_hardware: has_aes_hardware(),
+            avx2: has_avx2(),
+            sha_extensions: has_sha_extensions(),
+            neon: has_neon(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let features = CpuFeatures::detect();
+        assert_eq!(features, CpuFeatures::detect());
+        assert_eq!(features.aes_hardware, has_aes_hardware());
+
+        #[cfg(target_arch = "aarch64")]
+        assert!(features.neon);
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        assert!(!features.avx2);
+    }
+}
@@ -0,0 +1,1001 @@
+//! Certificate Transparency.
+//!
+//! This module verifies Signed Certificate Timestamps (SCTs), as described in [RFC 6962], and
+//! checks them against a policy. SCTs may be delivered in the TLS extension, in a stapled OCSP
+//! response, or embedded in the certificate itself.
+//!
+//! Logs are loaded from a log list in the JSON format published by Chrome at
+//! <https://www.gstatic.com/ct/log_list/v3/log_list.json>.
+//!
+//! A [`CtVerifier`] can be installed on an [`SslConnector`] with
+//! [`SslConnectorBuilder::set_ct_verifier`], in which case the policy is enforced during
+//! certificate verification.
+//!
+//! [RFC 6962]: https://www.rfc-editor.org/rfc/rfc6962
+//! [`SslConnector`]: crate::ssl::SslConnector
+//! [`SslConnectorBuilder::set_ct_verifier`]: crate::ssl::SslConnectorBuilder::set_ct_verifier
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use boring::ct::{CtPolicy, CtVerifier, LogList};
+//! use boring::ssl::{SslConnector, SslMethod};
+//!
+//! let logs = LogList::from_json(&std::fs::read("log_list.json").unwrap()).unwrap();
+//! let verifier = CtVerifier::new(logs, CtPolicy::new().min_scts(2).min_distinct_operators(2));
+//!
+//! let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+//! connector.set_ct_verifier(verifier);
+//! let connector = connector.build();
+//! ```
+use foreign_types::ForeignTypeRef;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::ptr;
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::asn1::{Asn1Object, Asn1StringRef, Asn1Time};
+use crate::base64;
+use crate::cbs::{Cbs, Malformed, CONTEXT_0, CONTEXT_1};
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::pkey::{Id, PKey, Public};
+use crate::sha::sha256;
+use crate::sign::Verifier;
+use crate::stack::StackRef;
+use crate::x509::{X509Ref, X509};
+use crate::{cvt, ffi};
+
+static EMBEDDED_SCT_OID: LazyLock<Asn1Object> =
+    LazyLock::new(|| Asn1Object::from_str("1.3.6.1.4.1.11129.2.4.2").unwrap());
+
+// DER encoding of 1.3.6.1.4.1.11129.2.4.5, the OCSP SingleResponse extension carrying SCTs.
+const OCSP_SCT_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x05];
+
+/// An error verifying Certificate Transparency information.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CtError {
+    /// The log list could not be parsed.
+    InvalidLogList(String),
+    /// An SCT, SCT list or OCSP response was malformed.
+    Malformed(&'static str),
+    /// The SCT was issued by a log which is not in the log list.
+    UnknownLog,
+    /// The SCT uses a version or signature algorithm which is not supported.
+    UnsupportedAlgorithm,
+    /// An embedded SCT was provided without the certificate's issuer.
+    MissingIssuer,
+    /// The SCT signature did not verify.
+    BadSignature,
+    /// Not enough valid SCTs were provided to satisfy the policy.
+    PolicyNotMet {
+        /// The number of logs which issued a valid SCT.
+        valid_scts: usize,
+        /// The number of distinct operators which issued a valid SCT.
+        distinct_operators: usize,
+    },
+    /// An error in BoringSSL.
+    Internal(ErrorStack),
+}
+
+impl fmt::Display for CtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CtError::InvalidLogList(e) => write!(f, "invalid CT log list: {e}"),
+            CtError::Malformed(e) => write!(f, "malformed CT data: {e}"),
+            CtError::UnknownLog => f.write_str("SCT issued by an unknown log"),
+            CtError::UnsupportedAlgorithm => f.write_str("unsupported SCT version or algorithm"),
+            CtError::MissingIssuer => f.write_str("issuer required to verify embedded SCT"),
+            CtError::BadSignature => f.write_str("invalid SCT signature"),
+            CtError::PolicyNotMet {
+                valid_scts,
+                distinct_operators,
+            } => write!(
+                f,
+                "CT policy not met: {valid_scts} valid SCTs from {distinct_operators} operators"
+            ),
+            CtError::Internal(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for CtError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CtError::Internal(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorStack> for CtError {
+    fn from(e: ErrorStack) -> CtError {
+        CtError::Internal(e)
+    }
+}
+
+impl From<Malformed> for CtError {
+    fn from(e: Malformed) -> CtError {
+        CtError::Malformed(e.0)
+    }
+}
+
+/// The state of a log in the log list.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LogState {
+    /// The log has applied for inclusion, or its state is not listed.
+    Pending,
+    /// The log has been qualified but is not yet usable.
+    Qualified,
+    /// The log is fully trusted.
+    Usable,
+    /// The log no longer accepts new certificates.
+    ReadOnly,
+    /// The log has been retired.
+    ///
+    /// Only SCTs issued before the log's retirement, given by [`Log::retired_at`], count.
+    Retired,
+    /// The log was rejected and is not trusted.
+    Rejected,
+}
+
+impl LogState {
+    /// Returns `true` if SCTs from logs in this state count towards a policy.
+    #[must_use]
+    pub fn is_trusted(self) -> bool {
+        matches!(
+            self,
+            LogState::Qualified | LogState::Usable | LogState::ReadOnly | LogState::Retired
+        )
+    }
+}
+
+/// A Certificate Transparency log.
+#[derive(Debug, Clone)]
+pub struct Log {
+    id: [u8; 32],
+    key: PKey<Public>,
+    description: String,
+    operator: String,
+    state: LogState,
+    retired_at: Option<u64>,
+}
+
+impl Log {
+    /// Returns the log ID, the SHA-256 hash of the log's public key.
+    #[must_use]
+    pub fn id(&self) -> &[u8; 32] {
+        &self.id
+    }
+
+    /// Returns the log's public key.
+    #[must_use]
+    pub fn key(&self) -> &PKey<Public> {
+        &self.key
+    }
+
+    /// Returns the log's description.
+    #[must_use]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the name of the log's operator.
+    #[must_use]
+    pub fn operator(&self) -> &str {
+        &self.operator
+    }
+
+    /// Returns the log's state.
+    #[must_use]
+    pub fn state(&self) -> LogState {
+        self.state
+    }
+
+    /// Returns the time the log was retired, in milliseconds since the Unix epoch.
+    ///
+    /// This is `None` unless the log's state is [`LogState::Retired`].
+    #[must_use]
+    pub fn retired_at(&self) -> Option<u64> {
+        self.retired_at
+    }
+
+    /// Returns `true` if an SCT issued by the log at `timestamp` counts towards a policy.
+    fn accepts(&self, timestamp: u64) -> bool {
+        self.state.is_trusted() && self.retired_at.map_or(true, |at| timestamp < at)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonLogList {
+    operators: Vec<JsonOperator>,
+}
+
+#[derive(Deserialize)]
+struct JsonOperator {
+    name: String,
+    #[serde(default)]
+    logs: Vec<JsonLog>,
+    #[serde(default)]
+    tiled_logs: Vec<JsonLog>,
+}
+
+#[derive(Deserialize)]
+struct JsonLog {
+    #[serde(default)]
+    description: String,
+    log_id: String,
+    key: String,
+    #[serde(default)]
+    state: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// A list of Certificate Transparency logs.
+#[derive(Debug, Clone, Default)]
+pub struct LogList {
+    logs: Vec<Log>,
+}
+
+impl LogList {
+    /// Parses a log list in Chrome's v3 JSON format.
+    ///
+    /// Both `logs` and `tiled_logs` are loaded.
+    pub fn from_json(json: &[u8]) -> Result<LogList, CtError> {
+        let list: JsonLogList =
+            serde_json::from_slice(json).map_err(|e| CtError::InvalidLogList(e.to_string()))?;
+
+        let mut logs = Vec::new();
+        for operator in list.operators {
+            for log in operator.logs.into_iter().chain(operator.tiled_logs) {
+                let id = base64::decode_block(&log.log_id)
+                    .ok()
+                    .and_then(|id| id.try_into().ok())
+                    .ok_or_else(|| CtError::InvalidLogList("invalid log_id".into()))?;
+                let key = base64::decode_block(&log.key)
+                    .and_then(|key| PKey::public_key_from_der(&key))
+                    .map_err(|_| CtError::InvalidLogList("invalid key".into()))?;
+                let state = match log.state.as_ref().and_then(|s| s.keys().next()) {
+                    Some(s) if s == "qualified" => LogState::Qualified,
+                    Some(s) if s == "usable" => LogState::Usable,
+                    Some(s) if s == "readonly" => LogState::ReadOnly,
+                    Some(s) if s == "retired" => LogState::Retired,
+                    Some(s) if s == "rejected" => LogState::Rejected,
+                    _ => LogState::Pending,
+                };
+                let retired_at = match state {
+                    LogState::Retired => log
+                        .state
+                        .as_ref()
+                        .and_then(|s| s.get("retired")?.get("timestamp")?.as_str())
+                        .and_then(parse_timestamp),
+                    _ => None,
+                };
+                if state == LogState::Retired && retired_at.is_none() {
+                    return Err(CtError::InvalidLogList(
+                        "invalid retirement timestamp".into(),
+                    ));
+                }
+
+                logs.push(Log {
+                    id,
+                    key,
+                    description: log.description,
+                    operator: operator.name.clone(),
+                    state,
+                    retired_at,
+                });
+            }
+        }
+
+        Ok(LogList { logs })
+    }
+
+    /// Returns the logs in the list.
+    #[must_use]
+    pub fn logs(&self) -> &[Log] {
+        &self.logs
+    }
+
+    /// Returns the log with the given ID.
+    #[must_use]
+    pub fn find(&self, id: &[u8; 32]) -> Option<&Log> {
+        self.logs.iter().find(|log| &log.id == id)
+    }
+}
+
+/// Where an SCT was obtained from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SctSource {
+    /// The `signed_certificate_timestamp` TLS extension.
+    TlsExtension,
+    /// A stapled OCSP response.
+    Ocsp,
+    /// The certificate's SCT list extension.
+    Embedded,
+}
+
+/// A Signed Certificate Timestamp.
+#[derive(Debug, Clone)]
+pub struct Sct {
+    log_id: [u8; 32],
+    timestamp: u64,
+    extensions: Vec<u8>,
+    hash_algorithm: u8,
+    signature_algorithm: u8,
+    signature: Vec<u8>,
+    source: SctSource,
+}
+
+impl Sct {
+    /// Parses a TLS-encoded `SignedCertificateTimestampList`.
+    ///
+    /// SCTs with a version other than v1 are skipped.
+    pub fn parse_list(list: &[u8], source: SctSource) -> Result<Vec<Sct>, CtError> {
+        let mut list = Cbs::new(list);
+        let mut entries = list.get_u16_length_prefixed()?;
+        if !list.is_empty() {
+            return Err(CtError::Malformed("trailing data after SCT list"));
+        }
+
+        let mut scts = Vec::new();
+        while !entries.is_empty() {
+            let mut sct = entries.get_u16_length_prefixed()?;
+            // RFC 6962 only defines v1, which is encoded as zero.
+            if sct.get_u8()? != 0 {
+                continue;
+            }
+            let log_id = sct.get_bytes(32)?.try_into().unwrap();
+            let timestamp = sct.get_u64()?;
+            let extensions = sct.get_u16_length_prefixed()?.as_slice().to_vec();
+            let hash_algorithm = sct.get_u8()?;
+            let signature_algorithm = sct.get_u8()?;
+            let signature = sct.get_u16_length_prefixed()?.as_slice().to_vec();
+            if !sct.is_empty() {
+                return Err(CtError::Malformed("trailing data after SCT"));
+            }
+
+            scts.push(Sct {
+                log_id,
+                timestamp,
+                extensions,
+                hash_algorithm,
+                signature_algorithm,
+                signature,
+                source,
+            });
+        }
+
+        Ok(scts)
+    }
+
+    /// Returns the ID of the log which issued the SCT.
+    #[must_use]
+    pub fn log_id(&self) -> &[u8; 32] {
+        &self.log_id
+    }
+
+    /// Returns the SCT's timestamp, in milliseconds since the Unix epoch.
+    #[must_use]
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Returns where the SCT was obtained from.
+    #[must_use]
+    pub fn source(&self) -> SctSource {
+        self.source
+    }
+
+    /// Verifies the SCT's signature over `cert` with the log's key.
+    ///
+    /// Embedded SCTs are signed over the precertificate, which depends on the certificate's
+    /// issuer, so `issuer` must be provided for them.
+    pub fn verify(
+        &self,
+        log: &Log,
+        cert: &X509Ref,
+        issuer: Option<&X509Ref>,
+    ) -> Result<(), CtError> {
+        if log.id != self.log_id {
+            return Err(CtError::UnknownLog);
+        }
+
+        // Only SHA-256 with ECDSA or RSA is permitted by RFC 6962.
+        let key_id = match self.signature_algorithm {
+            1 => Id::RSA,
+            3 => Id::EC,
+            _ => return Err(CtError::UnsupportedAlgorithm),
+        };
+        if self.hash_algorithm != 4 || log.key.id() != key_id {
+            return Err(CtError::UnsupportedAlgorithm);
+        }
+
+        let mut data = vec![0, 0];
+        data.extend_from_slice(&self.timestamp.to_be_bytes());
+        match self.source {
+            SctSource::Embedded => {
+                let issuer = issuer.ok_or(CtError::MissingIssuer)?;
+                data.extend_from_slice(&[0, 1]);
+                data.extend_from_slice(&sha256(&issuer.public_key_info_to_der()?));
+                push_vec24(&mut data, &precert_tbs(cert)?)?;
+            }
+            SctSource::TlsExtension | SctSource::Ocsp => {
+                data.extend_from_slice(&[0, 0]);
+                push_vec24(&mut data, &cert.to_der()?)?;
+            }
+        }
+        data.extend_from_slice(&(self.extensions.len() as u16).to_be_bytes());
+        data.extend_from_slice(&self.extensions);
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &log.key)?;
+        verifier.update(&data)?;
+        match verifier.verify(&self.signature) {
+            Ok(true) => Ok(()),
+            _ => Err(CtError::BadSignature),
+        }
+    }
+}
+
+/// Returns the SCTs embedded in the certificate.
+pub fn embedded_scts(cert: &X509Ref) -> Result<Vec<Sct>, CtError> {
+    unsafe {
+        let loc = ffi::X509_get_ext_by_OBJ(cert.as_ptr(), EMBEDDED_SCT_OID.as_ptr(), -1);
+        if loc < 0 {
+            return Ok(vec![]);
+        }
+        let ext = ffi::X509_get_ext(cert.as_ptr(), loc);
+        let data = Asn1StringRef::from_ptr(ffi::X509_EXTENSION_get_data(ext) as *mut _);
+        let list = Cbs::new(data.as_slice()).get_asn1(ffi::CBS_ASN1_OCTETSTRING)?;
+        Sct::parse_list(list.as_slice(), SctSource::Embedded)
+    }
+}
+
+/// Returns the SCTs in a DER-encoded OCSP response.
+///
+/// The response's signature and status are not checked. SCTs are self-authenticating, as each
+/// one is signed over the certificate it refers to.
+pub fn ocsp_scts(response: &[u8]) -> Result<Vec<Sct>, CtError> {
+    let mut response = Cbs::new(response).get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    response.get_asn1(ffi::CBS_ASN1_ENUMERATED)?;
+    let Some(mut bytes) = response.get_optional_asn1(CONTEXT_0)? else {
+        return Ok(vec![]);
+    };
+    let mut bytes = bytes.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    bytes.get_asn1(ffi::CBS_ASN1_OBJECT)?;
+    let mut basic = bytes
+        .get_asn1(ffi::CBS_ASN1_OCTETSTRING)?
+        .get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    let mut data = basic.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    data.get_optional_asn1(CONTEXT_0)?;
+    data.skip_any_asn1()?;
+    data.get_asn1(ffi::CBS_ASN1_GENERALIZEDTIME)?;
+
+    let mut scts = Vec::new();
+    let mut responses = data.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    while !responses.is_empty() {
+        let mut single = responses.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+        single.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+        single.skip_any_asn1()?;
+        single.get_asn1(ffi::CBS_ASN1_GENERALIZEDTIME)?;
+        single.get_optional_asn1(CONTEXT_0)?;
+        let Some(mut extensions) = single.get_optional_asn1(CONTEXT_1)? else {
+            continue;
+        };
+
+        let mut extensions = extensions.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+        while !extensions.is_empty() {
+            let mut extension = extensions.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+            let oid = extension.get_asn1(ffi::CBS_ASN1_OBJECT)?;
+            extension.get_optional_asn1(ffi::CBS_ASN1_BOOLEAN)?;
+            let mut value = extension.get_asn1(ffi::CBS_ASN1_OCTETSTRING)?;
+            if oid.as_slice() == OCSP_SCT_OID {
+                let list = value.get_asn1(ffi::CBS_ASN1_OCTETSTRING)?;
+                scts.extend(Sct::parse_list(list.as_slice(), SctSource::Ocsp)?);
+            }
+        }
+    }
+
+    Ok(scts)
+}
+
+/// A Certificate Transparency policy.
+#[derive(Debug, Clone)]
+pub struct CtPolicy {
+    min_scts: usize,
+    min_distinct_operators: usize,
+}
+
+impl CtPolicy {
+    /// Returns a policy requiring valid SCTs from two logs run by distinct operators.
+    #[must_use]
+    pub fn new() -> CtPolicy {
+        CtPolicy {
+            min_scts: 2,
+            min_distinct_operators: 2,
+        }
+    }
+
+    /// Sets the number of distinct logs which must have issued a valid SCT.
+    #[must_use]
+    pub fn min_scts(mut self, min_scts: usize) -> CtPolicy {
+        self.min_scts = min_scts;
+        self
+    }
+
+    /// Sets the number of distinct log operators which must have issued a valid SCT.
+    #[must_use]
+    pub fn min_distinct_operators(mut self, min_distinct_operators: usize) -> CtPolicy {
+        self.min_distinct_operators = min_distinct_operators;
+        self
+    }
+}
+
+impl Default for CtPolicy {
+    fn default() -> CtPolicy {
+        CtPolicy::new()
+    }
+}
+
+/// Verifies SCTs against a log list and enforces a [`CtPolicy`].
+#[derive(Debug, Clone)]
+pub struct CtVerifier {
+    logs: LogList,
+    policy: CtPolicy,
+}
+
+impl CtVerifier {
+    /// Creates a verifier from a log list and a policy.
+    #[must_use]
+    pub fn new(logs: LogList, policy: CtPolicy) -> CtVerifier {
+        CtVerifier { logs, policy }
+    }
+
+    /// Returns the log list.
+    #[must_use]
+    pub fn logs(&self) -> &LogList {
+        &self.logs
+    }
+
+    /// Returns the policy.
+    #[must_use]
+    pub fn policy(&self) -> &CtPolicy {
+        &self.policy
+    }
+
+    /// Checks the SCTs for the leaf of a verified `chain` against the policy.
+    ///
+    /// `tls_scts` is the TLS-encoded SCT list received in the TLS extension and `ocsp_response`
+    /// is a stapled OCSP response. SCTs embedded in the leaf are always considered. SCTs from
+    /// unknown logs, logs which are not trusted, with timestamps in the future or after their
+    /// log was retired, or with invalid signatures are ignored.
+    pub fn verify(
+        &self,
+        chain: &StackRef<X509>,
+        tls_scts: Option<&[u8]>,
+        ocsp_response: Option<&[u8]>,
+    ) -> Result<(), CtError> {
+        let cert = chain.get(0).ok_or(CtError::Malformed("empty chain"))?;
+        let issuer = chain.get(1);
+
+        let mut scts = embedded_scts(cert)?;
+        if let Some(list) = tls_scts {
+            scts.extend(Sct::parse_list(list, SctSource::TlsExtension)?);
+        }
+        if let Some(response) = ocsp_response {
+            scts.extend(ocsp_scts(response)?);
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+
+        let mut logs = HashSet::new();
+        let mut operators = HashSet::new();
+        for sct in &scts {
+            let Some(log) = self.logs.find(sct.log_id()) else {
+                continue;
+            };
+            if sct.timestamp <= now
+                && log.accepts(sct.timestamp)
+                && sct.verify(log, cert, issuer).is_ok()
+            {
+                logs.insert(log.id);
+                operators.insert(log.operator.as_str());
+            }
+        }
+
+        if logs.len() < self.policy.min_scts || operators.len() < self.policy.min_distinct_operators
+        {
+            return Err(CtError::PolicyNotMet {
+                valid_scts: logs.len(),
+                distinct_operators: operators.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses an RFC 3339 timestamp from the log list, such as `2024-01-01T00:00:00Z`, into
+/// milliseconds since the Unix epoch. Fractional seconds are truncated.
+fn parse_timestamp(s: &str) -> Option<u64> {
+    let (datetime, rest) = s.split_at_checked(19)?;
+    let fraction = rest.strip_suffix('Z')?;
+    if !(fraction.is_empty()
+        || fraction.len() > 1
+            && fraction.starts_with('.')
+            && fraction[1..].bytes().all(|b| b.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    let mut generalized = String::with_capacity(15);
+    for (i, c) in datetime.chars().enumerate() {
+        match (i, c) {
+            (4 | 7, '-') | (10, 'T') | (13 | 16, ':') => {}
+            (_, '0'..='9') if ![4, 7, 10, 13, 16].contains(&i) => generalized.push(c),
+            _ => return None,
+        }
+    }
+    generalized.push('Z');
+
+    let secs = Asn1Time::from_str(&generalized).ok()?.to_unix().ok()?;
+    u64::try_from(secs).ok()?.checked_mul(1000)
+}
+
+fn precert_tbs(cert: &X509Ref) -> Result<Vec<u8>, ErrorStack> {
+    let cert = X509::from_der(&cert.to_der()?)?;
+    unsafe {
+        let loc = ffi::X509_get_ext_by_OBJ(cert.as_ptr(), EMBEDDED_SCT_OID.as_ptr(), -1);
+        if loc >= 0 {
+            ffi::X509_EXTENSION_free(ffi::X509_delete_ext(cert.as_ptr(), loc));
+        }
+        let len = cvt(ffi::i2d_re_X509_tbs(cert.as_ptr(), ptr::null_mut()))?;
+        let mut buf = vec![0; len as usize];
+        cvt(ffi::i2d_re_X509_tbs(cert.as_ptr(), &mut buf.as_mut_ptr()))?;
+        Ok(buf)
+    }
+}
+
+fn push_vec24(out: &mut Vec<u8>, data: &[u8]) -> Result<(), CtError> {
+    if data.len() >= 1 << 24 {
+        return Err(CtError::Malformed("certificate too large"));
+    }
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::cbs::test::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::pkey::Private;
+    use crate::sign::Signer;
+    use crate::stack::Stack;
+
+    pub(crate) fn log_key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    pub(crate) fn log_list(keys: &[(&str, &PKey<Private>)]) -> LogList {
+        log_list_with_state(keys, r#"{"usable":{"timestamp":"2024-01-01T00:00:00Z"}}"#).unwrap()
+    }
+
+    fn log_list_with_state(
+        keys: &[(&str, &PKey<Private>)],
+        state: &str,
+    ) -> Result<LogList, CtError> {
+        let operators = keys
+            .iter()
+            .map(|(operator, key)| {
+                let spki = key.public_key_to_der().unwrap();
+                format!(
+                    r#"{{"name":"{operator}","logs":[{{"description":"{operator} log","log_id":"{}","key":"{}","state":{state}}}]}}"#,
+                    base64::encode_block(&sha256(&spki)),
+                    base64::encode_block(&spki),
+                )
+            })
+            .collect::<Vec<_>>();
+        let json = format!(r#"{{"version":"1","operators":[{}]}}"#, operators.join(","));
+        LogList::from_json(json.as_bytes())
+    }
+
+    pub(crate) fn sign_sct(key: &PKey<Private>, cert: &X509Ref) -> Vec<u8> {
+        sign_sct_at(key, cert, 1_700_000_000_000)
+    }
+
+    fn sign_sct_at(key: &PKey<Private>, cert: &X509Ref, timestamp: u64) -> Vec<u8> {
+        let mut entry = vec![0, 0];
+        push_vec24(&mut entry, &cert.to_der().unwrap()).unwrap();
+        sign_entry(key, timestamp, &entry)
+    }
+
+    fn sign_precert_sct(key: &PKey<Private>, cert: &X509Ref, issuer: &X509Ref) -> Vec<u8> {
+        let mut entry = vec![0, 1];
+        entry.extend_from_slice(&sha256(&issuer.public_key_info_to_der().unwrap()));
+        push_vec24(&mut entry, &precert_tbs(cert).unwrap()).unwrap();
+        sign_entry(key, 1_700_000_000_000, &entry)
+    }
+
+    fn sign_entry(key: &PKey<Private>, timestamp: u64, entry: &[u8]) -> Vec<u8> {
+        let log_id = sha256(&key.public_key_to_der().unwrap());
+        let timestamp = timestamp.to_be_bytes();
+
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        signer.update(&[0, 0]).unwrap();
+        signer.update(&timestamp).unwrap();
+        signer.update(entry).unwrap();
+        signer.update(&[0, 0]).unwrap();
+        let signature = signer.sign_to_vec().unwrap();
+
+        let mut sct = vec![0];
+        sct.extend_from_slice(&log_id);
+        sct.extend_from_slice(&timestamp);
+        sct.extend_from_slice(&[0, 0, 4, 3]);
+        sct.extend_from_slice(&(signature.len() as u16).to_be_bytes());
+        sct.extend_from_slice(&signature);
+        sct
+    }
+
+    pub(crate) fn sct_list(scts: &[Vec<u8>]) -> Vec<u8> {
+        let mut entries = Vec::new();
+        for sct in scts {
+            entries.extend_from_slice(&(sct.len() as u16).to_be_bytes());
+            entries.extend_from_slice(sct);
+        }
+        let mut list = (entries.len() as u16).to_be_bytes().to_vec();
+        list.extend_from_slice(&entries);
+        list
+    }
+
+    fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        match contents.len() {
+            len @ 0..=0x7f => out.push(len as u8),
+            len @ 0x80..=0xff => out.extend_from_slice(&[0x81, len as u8]),
+            len => {
+                out.push(0x82);
+                out.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+        }
+        out.extend_from_slice(contents);
+        out
+    }
+
+    /// Returns a minimal OCSP response with `list` in its single response's extensions.
+    fn ocsp_response(list: &[u8]) -> Vec<u8> {
+        const OCSP_BASIC_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01];
+        let time = der(DER_GENERALIZED_TIME, b"20240101000000Z");
+
+        let extension = [
+            der(DER_OID, OCSP_SCT_OID),
+            der(DER_OCTET_STRING, &der(DER_OCTET_STRING, list)),
+        ];
+        let extensions = der(DER_SEQUENCE, &der(DER_SEQUENCE, &extension.concat()));
+        let single = [
+            der(DER_SEQUENCE, &[]),
+            // certStatus good, an implicitly tagged NULL.
+            vec![0x80, 0x00],
+            time.clone(),
+            der(DER_CONTEXT_1, &extensions),
+        ];
+        let data = [
+            // responderID byKey.
+            der(0xa2, &der(DER_OCTET_STRING, &[0; 20])),
+            time,
+            der(DER_SEQUENCE, &der(DER_SEQUENCE, &single.concat())),
+        ];
+        let basic = der(DER_SEQUENCE, &der(DER_SEQUENCE, &data.concat()));
+        let bytes = [der(DER_OID, OCSP_BASIC_OID), der(DER_OCTET_STRING, &basic)];
+        let response = [
+            der(DER_ENUMERATED, &[0]),
+            der(DER_CONTEXT_0, &der(DER_SEQUENCE, &bytes.concat())),
+        ];
+        der(DER_SEQUENCE, &response.concat())
+    }
+
+    /// Returns a copy of `cert` with `list` in its SCT list extension.
+    fn embed_scts(cert: &X509Ref, list: &[u8]) -> X509 {
+        const EMBEDDED_SCT_OID_DER: &[u8] =
+            &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x02];
+        let extension = der(
+            DER_SEQUENCE,
+            &[
+                der(DER_OID, EMBEDDED_SCT_OID_DER),
+                der(DER_OCTET_STRING, &der(DER_OCTET_STRING, list)),
+            ]
+            .concat(),
+        );
+
+        let cert = cert.to_owned();
+        unsafe {
+            let mut p = extension.as_ptr();
+            let ext = ffi::d2i_X509_EXTENSION(ptr::null_mut(), &mut p, extension.len() as _);
+            assert!(!ext.is_null());
+            assert_eq!(ffi::X509_add_ext(cert.as_ptr(), ext, -1), 1);
+            ffi::X509_EXTENSION_free(ext);
+        }
+        cert
+    }
+
+    fn chain() -> Stack<X509> {
+        let mut chain = Stack::new().unwrap();
+        chain
+            .push(X509::from_pem(include_bytes!("../test/cert.pem")).unwrap())
+            .unwrap();
+        chain
+            .push(X509::from_pem(include_bytes!("../test/root-ca.pem")).unwrap())
+            .unwrap();
+        chain
+    }
+
+    #[test]
+    fn test_log_list() {
+        let key = log_key();
+        let logs = log_list(&[("Example", &key)]);
+
+        assert_eq!(logs.logs().len(), 1);
+        let log = &logs.logs()[0];
+        assert_eq!(log.operator(), "Example");
+        assert_eq!(log.description(), "Example log");
+        assert_eq!(log.state(), LogState::Usable);
+        assert_eq!(log.id(), &sha256(&key.public_key_to_der().unwrap()));
+        assert!(logs.find(log.id()).is_some());
+        assert!(logs.find(&[0; 32]).is_none());
+
+        assert!(LogList::from_json(b"{}").is_err());
+    }
+
+    #[test]
+    fn test_sct_verify() {
+        let key = log_key();
+        let logs = log_list(&[("Example", &key)]);
+        let chain = chain();
+
+        let list = sct_list(&[sign_sct(&key, &chain[0])]);
+        let scts = Sct::parse_list(&list, SctSource::TlsExtension).unwrap();
+        assert_eq!(scts.len(), 1);
+        assert_eq!(scts[0].timestamp(), 1_700_000_000_000);
+        assert_eq!(scts[0].source(), SctSource::TlsExtension);
+        scts[0].verify(&logs.logs()[0], &chain[0], None).unwrap();
+
+        // The SCT was not issued for the root.
+        assert!(matches!(
+            scts[0].verify(&logs.logs()[0], &chain[1], None),
+            Err(CtError::BadSignature)
+        ));
+    }
+
+    #[test]
+    fn test_ct_policy() {
+        let google = log_key();
+        let google2 = log_key();
+        let other = log_key();
+        let logs = log_list(&[("Google", &google), ("Google", &google2), ("Other", &other)]);
+        let chain = chain();
+
+        let distinct = sct_list(&[sign_sct(&google, &chain[0]), sign_sct(&other, &chain[0])]);
+        let same = sct_list(&[sign_sct(&google, &chain[0]), sign_sct(&google2, &chain[0])]);
+
+        let verifier = CtVerifier::new(logs.clone(), CtPolicy::new());
+        verifier.verify(&chain, Some(&distinct), None).unwrap();
+        assert!(matches!(
+            verifier.verify(&chain, Some(&same), None),
+            Err(CtError::PolicyNotMet {
+                valid_scts: 2,
+                distinct_operators: 1
+            })
+        ));
+        assert!(matches!(
+            verifier.verify(&chain, None, None),
+            Err(CtError::PolicyNotMet { valid_scts: 0, .. })
+        ));
+
+        let verifier = CtVerifier::new(logs, CtPolicy::new().min_distinct_operators(1));
+        verifier.verify(&chain, Some(&same), None).unwrap();
+    }
+
+    #[test]
+    fn test_retired_log() {
+        let key = log_key();
+        let logs = log_list_with_state(
+            &[("Example", &key)],
+            r#"{"retired":{"timestamp":"2023-01-01T00:00:00Z"}}"#,
+        )
+        .unwrap();
+        assert_eq!(logs.logs()[0].state(), LogState::Retired);
+        assert_eq!(logs.logs()[0].retired_at(), Some(1_672_531_200_000));
+
+        let chain = chain();
+        let verifier = CtVerifier::new(logs, CtPolicy::new().min_scts(1).min_distinct_operators(1));
+
+        let before = sct_list(&[sign_sct_at(&key, &chain[0], 1_600_000_000_000)]);
+        verifier.verify(&chain, Some(&before), None).unwrap();
+
+        let after = sct_list(&[sign_sct(&key, &chain[0])]);
+        assert!(matches!(
+            verifier.verify(&chain, Some(&after), None),
+            Err(CtError::PolicyNotMet { valid_scts: 0, .. })
+        ));
+
+        let missing = log_list_with_state(&[("Example", &key)], r#"{"retired":{}}"#);
+        assert!(matches!(missing, Err(CtError::InvalidLogList(_))));
+    }
+
+    #[test]
+    fn test_future_sct() {
+        let key = log_key();
+        let logs = log_list(&[("Example", &key)]);
+        let chain = chain();
+        let verifier = CtVerifier::new(logs, CtPolicy::new().min_scts(1).min_distinct_operators(1));
+
+        let tomorrow = SystemTime::now() + std::time::Duration::from_secs(24 * 60 * 60);
+        let tomorrow = tomorrow.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let list = sct_list(&[sign_sct_at(&key, &chain[0], tomorrow)]);
+        assert!(matches!(
+            verifier.verify(&chain, Some(&list), None),
+            Err(CtError::PolicyNotMet { valid_scts: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_embedded_scts() {
+        let google = log_key();
+        let other = log_key();
+        let logs = log_list(&[("Google", &google), ("Other", &other)]);
+        let chain = chain();
+
+        let list = sct_list(&[
+            sign_precert_sct(&google, &chain[0], &chain[1]),
+            sign_precert_sct(&other, &chain[0], &chain[1]),
+        ]);
+        let cert = embed_scts(&chain[0], &list);
+
+        let scts = embedded_scts(&cert).unwrap();
+        assert_eq!(scts.len(), 2);
+        assert_eq!(scts[0].source(), SctSource::Embedded);
+        scts[0]
+            .verify(&logs.logs()[0], &cert, Some(&chain[1]))
+            .unwrap();
+        assert!(matches!(
+            scts[0].verify(&logs.logs()[0], &cert, None),
+            Err(CtError::MissingIssuer)
+        ));
+
+        let mut embedded = Stack::new().unwrap();
+        embedded.push(cert).unwrap();
+        embedded.push(chain[1].to_owned()).unwrap();
+        CtVerifier::new(logs, CtPolicy::new())
+            .verify(&embedded, None, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_ocsp_scts() {
+        let google = log_key();
+        let other = log_key();
+        let logs = log_list(&[("Google", &google), ("Other", &other)]);
+        let chain = chain();
+
+        let list = sct_list(&[sign_sct(&google, &chain[0]), sign_sct(&other, &chain[0])]);
+        let response = ocsp_response(&list);
+
+        let scts = ocsp_scts(&response).unwrap();
+        assert_eq!(scts.len(), 2);
+        assert_eq!(scts[0].source(), SctSource::Ocsp);
+        scts[0].verify(&logs.logs()[0], &chain[0], None).unwrap();
+
+        CtVerifier::new(logs, CtPolicy::new())
+            .verify(&chain, None, Some(&response))
+            .unwrap();
+    }
+}
@@ -87,6 +87,12 @@ impl<'a> Deriver<'a> {
         buf.truncate(len);
         Ok(buf)
     }
+
+    /// Like [`Deriver::derive_to_vec`], but the shared secret is wiped when dropped.
+    #[cfg(feature = "zeroize")]
+    pub fn derive_to_zeroizing(&mut self) -> Result<zeroize::Zeroizing<Vec<u8>>, ErrorStack> {
+        self.derive_to_vec().map(zeroize::Zeroizing::new)
+    }
 }
 
 #[cfg(test)]
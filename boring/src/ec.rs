@@ -330,6 +330,25 @@ impl EcPointRef {
         }
     }
 
+    /// Computes `a + a`, storing the result in `self`.
+    #[corresponds(EC_POINT_dbl)]
+    pub fn dbl(
+        &mut self,
+        group: &EcGroupRef,
+        a: &EcPointRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EC_POINT_dbl(
+                group.as_ptr(),
+                self.as_ptr(),
+                a.as_ptr(),
+                ctx.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Inverts `self`.
     #[corresponds(EC_POINT_invert)]
     pub fn invert(&mut self, group: &EcGroupRef, ctx: &BigNumContextRef) -> Result<(), ErrorStack> {
@@ -410,6 +429,33 @@ impl EcPointRef {
         }
     }
 
+    /// Returns whether this point is the point at infinity.
+    #[corresponds(EC_POINT_is_at_infinity)]
+    #[must_use]
+    pub fn is_infinity(&self, group: &EcGroupRef) -> bool {
+        unsafe { ffi::EC_POINT_is_at_infinity(group.as_ptr(), self.as_ptr()) == 1 }
+    }
+
+    /// Checks whether this point lies on the curve.
+    ///
+    /// Points built through this module are always on the curve, so this is mostly useful as a
+    /// sanity check before handing a point to code that does not validate its inputs.
+    #[corresponds(EC_POINT_is_on_curve)]
+    pub fn is_on_curve(
+        &self,
+        group: &EcGroupRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<bool, ErrorStack> {
+        unsafe {
+            let res = cvt_n(ffi::EC_POINT_is_on_curve(
+                group.as_ptr(),
+                self.as_ptr(),
+                ctx.as_ptr(),
+            ))?;
+            Ok(res == 1)
+        }
+    }
+
     /// Place affine coordinates of a curve over a prime field in the provided
     /// `x` and `y` `BigNum`s
     #[corresponds(EC_POINT_get_affine_coordinates_GFp)]
@@ -440,7 +486,33 @@ impl EcPoint {
         unsafe { cvt_p(ffi::EC_POINT_new(group.as_ptr())).map(|p| EcPoint::from_ptr(p)) }
     }
 
+    /// Creates a point from its affine coordinates over a prime field.
+    ///
+    /// Fails if `(x, y)` is not on the curve.
+    #[corresponds(EC_POINT_set_affine_coordinates_GFp)]
+    pub fn from_affine_coordinates_gfp(
+        group: &EcGroupRef,
+        x: &BigNumRef,
+        y: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<EcPoint, ErrorStack> {
+        let point = EcPoint::new(group)?;
+        unsafe {
+            cvt(ffi::EC_POINT_set_affine_coordinates_GFp(
+                group.as_ptr(),
+                point.as_ptr(),
+                x.as_ptr(),
+                y.as_ptr(),
+                ctx.as_ptr(),
+            ))?;
+        }
+        Ok(point)
+    }
+
     /// Creates point from a binary representation
+    ///
+    /// Both the compressed and uncompressed forms are accepted, and the point is checked to be on
+    /// the curve. Combined with [`EcPointRef::to_bytes`], this converts between encodings.
     #[corresponds(EC_POINT_oct2point)]
     pub fn from_bytes(
         group: &EcGroupRef,
@@ -929,4 +1001,70 @@ mod test {
         assert_eq!(xbn2, xbn);
         assert_eq!(ybn2, ybn);
     }
+
+    #[test]
+    fn point_arithmetic() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        let two = BigNum::from_u32(2).unwrap();
+        let three = BigNum::from_u32(3).unwrap();
+
+        let mut doubled = EcPoint::new(&group).unwrap();
+        doubled.dbl(&group, group.generator(), &mut ctx).unwrap();
+        let mut expected = EcPoint::new(&group).unwrap();
+        expected.mul_generator(&group, &two, &ctx).unwrap();
+        assert!(doubled.eq(&group, &expected, &mut ctx).unwrap());
+
+        let mut sum = EcPoint::new(&group).unwrap();
+        sum.add(&group, &doubled, group.generator(), &mut ctx)
+            .unwrap();
+        expected
+            .mul(&group, group.generator(), &three, &ctx)
+            .unwrap();
+        assert!(sum.eq(&group, &expected, &mut ctx).unwrap());
+        assert!(sum.is_on_curve(&group, &mut ctx).unwrap());
+        assert!(!sum.is_infinity(&group));
+
+        let mut order = BigNum::new().unwrap();
+        group.order(&mut order, &mut ctx).unwrap();
+        let mut infinity = EcPoint::new(&group).unwrap();
+        infinity.mul_generator(&group, &order, &ctx).unwrap();
+        assert!(infinity.is_infinity(&group));
+    }
+
+    #[test]
+    fn point_encodings() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        let x = Vec::from_hex("30a0424cd21c2944838a2d75c92b37e76ea20d9f00893a3b4eee8a3c0aafec3e")
+            .unwrap();
+        let y = Vec::from_hex("e04b65e92456d9888b52b379bdfbd51ee869ef1f0fc65b6659695b6cce081723")
+            .unwrap();
+        let xbn = BigNum::from_slice(&x).unwrap();
+        let ybn = BigNum::from_slice(&y).unwrap();
+
+        let point = EcPoint::from_affine_coordinates_gfp(&group, &xbn, &ybn, &mut ctx).unwrap();
+        let uncompressed = point
+            .to_bytes(&group, PointConversionForm::UNCOMPRESSED, &mut ctx)
+            .unwrap();
+        assert_eq!(uncompressed, [&[0x04][..], &x, &y].concat());
+
+        let compressed = point
+            .to_bytes(&group, PointConversionForm::COMPRESSED, &mut ctx)
+            .unwrap();
+        // `y` is odd.
+        assert_eq!(compressed, [&[0x03][..], &x].concat());
+
+        let decompressed = EcPoint::from_bytes(&group, &compressed, &mut ctx).unwrap();
+        assert_eq!(
+            decompressed
+                .to_bytes(&group, PointConversionForm::UNCOMPRESSED, &mut ctx)
+                .unwrap(),
+            uncompressed
+        );
+
+        let bad_y = BigNum::from_u32(1).unwrap();
+        EcPoint::from_affine_coordinates_gfp(&group, &xbn, &bad_y, &mut ctx)
+            .expect_err("point is not on the curve");
+    }
 }
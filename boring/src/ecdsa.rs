@@ -8,7 +8,7 @@ use std::mem;
 use std::ptr;
 
 use crate::bn::{BigNum, BigNumRef};
-use crate::ec::EcKeyRef;
+use crate::ec::{EcGroupRef, EcKeyRef};
 use crate::error::ErrorStack;
 use crate::pkey::{HasPrivate, HasPublic};
 use crate::{cvt_n, cvt_p};
@@ -55,6 +55,20 @@ impl EcdsaSig {
         }
     }
 
+    /// Decodes a signature in the fixed-width `r || s` form of IEEE P1363.
+    ///
+    /// This is the encoding used by JOSE, COSE and WebCrypto. `bytes` must consist of two halves
+    /// of equal length.
+    pub fn from_p1363(bytes: &[u8]) -> Result<EcdsaSig, ErrorStack> {
+        if bytes.is_empty() || bytes.len() % 2 != 0 {
+            return Err(ErrorStack::internal_error_str(
+                "invalid P1363 ECDSA signature length",
+            ));
+        }
+        let (r, s) = bytes.split_at(bytes.len() / 2);
+        EcdsaSig::from_private_components(BigNum::from_slice(r)?, BigNum::from_slice(s)?)
+    }
+
     from_der! {
         /// Decodes a DER-encoded ECDSA signature.
         #[corresponds(d2i_ECDSA_SIG)]
@@ -112,6 +126,56 @@ impl EcdsaSigRef {
             BigNumRef::from_ptr(s as *mut _)
         }
     }
+
+    /// Returns `r` as a big-endian byte string padded to the length of `group`'s order.
+    pub fn r_bytes(&self, group: &EcGroupRef) -> Result<Vec<u8>, ErrorStack> {
+        self.r().to_vec_padded(scalar_len(group))
+    }
+
+    /// Returns `s` as a big-endian byte string padded to the length of `group`'s order.
+    pub fn s_bytes(&self, group: &EcGroupRef) -> Result<Vec<u8>, ErrorStack> {
+        self.s().to_vec_padded(scalar_len(group))
+    }
+
+    /// Serializes the signature into the fixed-width `r || s` form of IEEE P1363.
+    ///
+    /// Each half is as long as `group`'s order, so a P-256 signature is 64 bytes.
+    pub fn to_p1363(&self, group: &EcGroupRef) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = self.r_bytes(group)?;
+        out.extend_from_slice(&self.s_bytes(group)?);
+        Ok(out)
+    }
+}
+
+fn scalar_len(group: &EcGroupRef) -> usize {
+    group.order_bits().div_ceil(8) as usize
 }
 
 use crate::ffi::{ECDSA_SIG_get0, ECDSA_SIG_set0};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+
+    #[test]
+    fn test_p1363_round_trip() {
+        let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let digest = [7; 48];
+
+        let sig = EcdsaSig::sign(&digest, &key).unwrap();
+        let p1363 = sig.to_p1363(&group).unwrap();
+        assert_eq!(p1363.len(), 96);
+        assert_eq!(&p1363[..48], &sig.r_bytes(&group).unwrap()[..]);
+        assert_eq!(&p1363[48..], &sig.s_bytes(&group).unwrap()[..]);
+
+        let decoded = EcdsaSig::from_p1363(&p1363).unwrap();
+        assert!(decoded.verify(&digest, &key).unwrap());
+        assert_eq!(decoded.to_der().unwrap(), sig.to_der().unwrap());
+
+        assert!(EcdsaSig::from_p1363(&p1363[..95]).is_err());
+        assert!(EcdsaSig::from_p1363(&[]).is_err());
+    }
+}
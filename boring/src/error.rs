@@ -86,6 +86,41 @@ impl ErrorStack {
     pub fn errors(&self) -> &[Error] {
         &self.0
     }
+
+    /// Returns the [`io::ErrorKind`] best describing the errors in the stack.
+    pub(crate) fn io_error_kind(&self) -> io::ErrorKind {
+        for e in &self.0 {
+            match e.reason_kind() {
+                // Alerts other than close_notify are fatal, and end the connection.
+                Some(ErrorReason::Ssl(SslReason::AlertCloseNotify)) => {
+                    return io::ErrorKind::ConnectionAborted
+                }
+                _ if e.library_code() == ffi::ERR_LIB_SSL.0 as c_int
+                    && e.reason_code() > ffi::SSL_AD_REASON_OFFSET =>
+                {
+                    return io::ErrorKind::ConnectionReset
+                }
+                Some(ErrorReason::Ssl(
+                    SslReason::WrongVersionNumber
+                    | SslReason::HttpRequest
+                    | SslReason::DecryptionFailedOrBadRecordMac
+                    | SslReason::ExcessiveMessageSize
+                    | SslReason::UnexpectedMessage
+                    | SslReason::UnexpectedRecord,
+                )) => return io::ErrorKind::InvalidData,
+                _ => {}
+            }
+        }
+
+        io::ErrorKind::Other
+    }
+
+    /// Returns `true` if any error in the stack has the given reason.
+    #[must_use]
+    pub fn contains(&self, reason: impl Into<ErrorReason>) -> bool {
+        let reason = reason.into();
+        self.0.iter().any(|e| e.reason_kind() == Some(reason))
+    }
 }
 
 impl fmt::Display for ErrorStack {
@@ -116,7 +151,7 @@ impl error::Error for ErrorStack {}
 
 impl From<ErrorStack> for io::Error {
     fn from(e: ErrorStack) -> io::Error {
-        io::Error::other(e)
+        io::Error::new(e.io_error_kind(), e)
     }
 }
 
@@ -277,6 +312,34 @@ impl Error {
         ffi::ERR_GET_REASON(self.code)
     }
 
+    /// Returns the reason for the error as an enum, if its library and reason are known to this
+    /// crate.
+    ///
+    /// Unlike [`Error::reason`], the result does not change between BoringSSL versions.
+    #[must_use]
+    pub fn reason_kind(&self) -> Option<ErrorReason> {
+        if self.is_internal() {
+            return None;
+        }
+
+        let reason = self.reason_code();
+        match self.library_code() {
+            lib if lib == ffi::ERR_LIB_SSL.0 as c_int => {
+                SslReason::from_raw(reason).map(Into::into)
+            }
+            lib if lib == ffi::ERR_LIB_PEM.0 as c_int => {
+                PemReason::from_raw(reason).map(Into::into)
+            }
+            lib if lib == ffi::ERR_LIB_X509.0 as c_int => {
+                X509Reason::from_raw(reason).map(Into::into)
+            }
+            lib if lib == ffi::ERR_LIB_CIPHER.0 as c_int => {
+                CipherReason::from_raw(reason).map(Into::into)
+            }
+            _ => None,
+        }
+    }
+
     /// Returns the name of the source file which encountered the error.
     #[must_use]
     pub fn file(&self) -> &'static str {
@@ -373,6 +436,186 @@ impl fmt::Display for Error {
 
 impl error::Error for Error {}
 
+/// The reason for an [`Error`], by library.
+///
+/// See [`Error::reason_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorReason {
+    /// A reason reported by the TLS library.
+    Ssl(SslReason),
+    /// A reason reported by the PEM library.
+    Pem(PemReason),
+    /// A reason reported by the X.509 library.
+    X509(X509Reason),
+    /// A reason reported by the cipher library.
+    Cipher(CipherReason),
+}
+
+macro_rules! reasons {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident => $wrapper:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $code:ident,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+        }
+
+        impl $name {
+            fn from_raw(reason: c_int) -> Option<$name> {
+                match reason {
+                    $(ffi::$code => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the raw reason code.
+            #[must_use]
+            pub fn as_raw(&self) -> c_int {
+                match self {
+                    $($name::$variant => ffi::$code,)*
+                }
+            }
+        }
+
+        impl From<$name> for ErrorReason {
+            fn from(reason: $name) -> ErrorReason {
+                ErrorReason::$wrapper(reason)
+            }
+        }
+    };
+}
+
+reasons! {
+    /// Reasons reported by the TLS library (`SSL_R_*`).
+    pub enum SslReason => Ssl {
+        /// The peer's certificate could not be verified.
+        CertificateVerifyFailed = SSL_R_CERTIFICATE_VERIFY_FAILED,
+        /// The certificate and private key of the context do not match.
+        CertificateAndPrivateKeyMismatch = SSL_R_CERTIFICATE_AND_PRIVATE_KEY_MISMATCH,
+        /// The connection was rejected by a callback.
+        ConnectionRejected = SSL_R_CONNECTION_REJECTED,
+        /// A record could not be decrypted or authenticated.
+        DecryptionFailedOrBadRecordMac = SSL_R_DECRYPTION_FAILED_OR_BAD_RECORD_MAC,
+        /// A handshake message was too large.
+        ExcessiveMessageSize = SSL_R_EXCESSIVE_MESSAGE_SIZE,
+        /// The server refused the ClientHello.
+        HandshakeFailureOnClientHello = SSL_R_HANDSHAKE_FAILURE_ON_CLIENT_HELLO,
+        /// The peer sent an HTTP request instead of a TLS handshake.
+        HttpRequest = SSL_R_HTTP_REQUEST,
+        /// No ALPN protocol is supported by both peers.
+        NoApplicationProtocol = SSL_R_NO_APPLICATION_PROTOCOL,
+        /// The peer sent no certificate.
+        NoCertificatesReturned = SSL_R_NO_CERTIFICATES_RETURNED,
+        /// No certificate was configured.
+        NoCertificateSet = SSL_R_NO_CERTIFICATE_SET,
+        /// No cipher is supported by both peers.
+        NoSharedCipher = SSL_R_NO_SHARED_CIPHER,
+        /// The client did not send a required certificate.
+        PeerDidNotReturnACertificate = SSL_R_PEER_DID_NOT_RETURN_A_CERTIFICATE,
+        /// The connection was already shut down.
+        ProtocolIsShutdown = SSL_R_PROTOCOL_IS_SHUTDOWN,
+        /// A DTLS read timed out.
+        ReadTimeoutExpired = SSL_R_READ_TIMEOUT_EXPIRED,
+        /// An unexpected handshake message was received.
+        UnexpectedMessage = SSL_R_UNEXPECTED_MESSAGE,
+        /// An unexpected record was received.
+        UnexpectedRecord = SSL_R_UNEXPECTED_RECORD,
+        /// No protocol version is supported by both peers.
+        UnsupportedProtocol = SSL_R_UNSUPPORTED_PROTOCOL,
+        /// The peer used a curve that was not offered.
+        WrongCurve = SSL_R_WRONG_CURVE,
+        /// The peer sent a record with an unexpected version, often because it does not speak
+        /// TLS.
+        WrongVersionNumber = SSL_R_WRONG_VERSION_NUMBER,
+        /// The peer sent a `close_notify` alert.
+        AlertCloseNotify = SSL_R_SSLV3_ALERT_CLOSE_NOTIFY,
+        /// The peer sent a `handshake_failure` alert.
+        AlertHandshakeFailure = SSL_R_SSLV3_ALERT_HANDSHAKE_FAILURE,
+        /// The peer sent a `bad_certificate` alert.
+        AlertBadCertificate = SSL_R_SSLV3_ALERT_BAD_CERTIFICATE,
+        /// The peer sent a `certificate_revoked` alert.
+        AlertCertificateRevoked = SSL_R_SSLV3_ALERT_CERTIFICATE_REVOKED,
+        /// The peer sent a `certificate_expired` alert.
+        AlertCertificateExpired = SSL_R_SSLV3_ALERT_CERTIFICATE_EXPIRED,
+        /// The peer sent an `unknown_ca` alert.
+        AlertUnknownCa = SSL_R_TLSV1_ALERT_UNKNOWN_CA,
+        /// The peer sent an `access_denied` alert.
+        AlertAccessDenied = SSL_R_TLSV1_ALERT_ACCESS_DENIED,
+        /// The peer sent a `decrypt_error` alert.
+        AlertDecryptError = SSL_R_TLSV1_ALERT_DECRYPT_ERROR,
+        /// The peer sent a `protocol_version` alert.
+        AlertProtocolVersion = SSL_R_TLSV1_ALERT_PROTOCOL_VERSION,
+        /// The peer sent an `internal_error` alert.
+        AlertInternalError = SSL_R_TLSV1_ALERT_INTERNAL_ERROR,
+        /// The peer sent a `certificate_required` alert.
+        AlertCertificateRequired = SSL_R_TLSV1_ALERT_CERTIFICATE_REQUIRED,
+        /// The peer sent a `no_application_protocol` alert.
+        AlertNoApplicationProtocol = SSL_R_TLSV1_ALERT_NO_APPLICATION_PROTOCOL,
+    }
+}
+
+reasons! {
+    /// Reasons reported by the PEM library (`PEM_R_*`).
+    pub enum PemReason => Pem {
+        /// The base64 encoding is invalid.
+        BadBase64Decode = PEM_R_BAD_BASE64_DECODE,
+        /// An encrypted key could not be decrypted, usually because of a wrong password.
+        BadDecrypt = PEM_R_BAD_DECRYPT,
+        /// The end line is missing or malformed.
+        BadEndLine = PEM_R_BAD_END_LINE,
+        /// The password could not be read.
+        BadPasswordRead = PEM_R_BAD_PASSWORD_READ,
+        /// No PEM block of the expected type was found.
+        NoStartLine = PEM_R_NO_START_LINE,
+        /// The key is encrypted with an unsupported cipher.
+        UnsupportedCipher = PEM_R_UNSUPPORTED_CIPHER,
+        /// The key uses an unsupported encryption scheme.
+        UnsupportedEncryption = PEM_R_UNSUPPORTED_ENCRYPTION,
+    }
+}
+
+reasons! {
+    /// Reasons reported by the X.509 library (`X509_R_*`).
+    pub enum X509Reason => X509 {
+        /// The certificate is already in the store.
+        CertAlreadyInHashTable = X509_R_CERT_ALREADY_IN_HASH_TABLE,
+        /// The public and private keys do not match.
+        KeyValuesMismatch = X509_R_KEY_VALUES_MISMATCH,
+        /// No certificate was given to verify.
+        NoCertSetForUsToVerify = X509_R_NO_CERT_SET_FOR_US_TO_VERIFY,
+        /// The signature algorithm does not match the key.
+        SignatureAlgorithmMismatch = X509_R_SIGNATURE_ALGORITHM_MISMATCH,
+        /// The key type is not supported.
+        UnknownKeyType = X509_R_UNKNOWN_KEY_TYPE,
+        /// The certificate version is invalid.
+        InvalidVersion = X509_R_INVALID_VERSION,
+    }
+}
+
+reasons! {
+    /// Reasons reported by the cipher library (`CIPHER_R_*`).
+    pub enum CipherReason => Cipher {
+        /// Decryption or authentication failed.
+        BadDecrypt = CIPHER_R_BAD_DECRYPT,
+        /// The key has the wrong length.
+        BadKeyLength = CIPHER_R_BAD_KEY_LENGTH,
+        /// The input is not a multiple of the block size.
+        DataNotMultipleOfBlockLength = CIPHER_R_DATA_NOT_MULTIPLE_OF_BLOCK_LENGTH,
+        /// The key length is invalid.
+        InvalidKeyLength = CIPHER_R_INVALID_KEY_LENGTH,
+        /// The key size is not supported.
+        UnsupportedKeySize = CIPHER_R_UNSUPPORTED_KEY_SIZE,
+        /// The final block has the wrong length.
+        WrongFinalBlockLength = CIPHER_R_WRONG_FINAL_BLOCK_LENGTH,
+    }
+}
+
 #[test]
 fn internal_err() {
     let e = ErrorStack::internal_error(io::Error::other("hello, boring"));
@@ -383,3 +626,16 @@ fn internal_err() {
     let e = ErrorStack::get();
     assert!(e.to_string().contains("hello, boring"), "{e} {e:?}");
 }
+
+#[test]
+fn reason_kind() {
+    let e = crate::x509::X509::from_pem(b"not a certificate").unwrap_err();
+    assert!(e.contains(PemReason::NoStartLine), "{e:?}");
+    assert_eq!(
+        e.errors()[0].reason_kind(),
+        Some(ErrorReason::Pem(PemReason::NoStartLine))
+    );
+
+    let e = ErrorStack::internal_error(io::Error::other("hello, boring"));
+    assert_eq!(e.errors()[0].reason_kind(), None);
+}
@@ -5,6 +5,7 @@
 //! [OpenSSL's documentation]: https://www.openssl.org/docs/fips/UserGuide-2.0.pdf
 use crate::ffi;
 use openssl_macros::corresponds;
+use std::ffi::{CStr, CString};
 
 /// Determines if the library is running in the FIPS 140-2 mode of operation.
 #[corresponds(FIPS_mode)]
@@ -13,6 +14,61 @@ pub fn enabled() -> bool {
     unsafe { ffi::FIPS_mode() != 0 }
 }
 
+/// Returns the name of the FIPS module.
+#[corresponds(FIPS_module_name)]
+#[must_use]
+pub fn module_name() -> &'static str {
+    unsafe {
+        CStr::from_ptr(ffi::FIPS_module_name())
+            .to_str()
+            .expect("module name is not UTF-8")
+    }
+}
+
+/// Returns the version of the FIPS module, as a date of the form `yyyymmddXX`.
+///
+/// Returns `None` if the library isn't built at exactly a validated version.
+#[corresponds(FIPS_version)]
+#[must_use]
+pub fn version() -> Option<u32> {
+    match unsafe { ffi::FIPS_version() } {
+        0 => None,
+        version => Some(version),
+    }
+}
+
+/// Determines if `algorithm`, such as `"AES-GCM"`, is validated in this build.
+#[corresponds(FIPS_query_algorithm_status)]
+#[must_use]
+pub fn is_algorithm_approved(algorithm: &str) -> bool {
+    let Ok(algorithm) = CString::new(algorithm) else {
+        return false;
+    };
+    unsafe { ffi::FIPS_query_algorithm_status(algorithm.as_ptr()) == 1 }
+}
+
+/// Runs the known-answer self tests, returning whether they passed.
+///
+/// The module already runs them at power-on; this re-runs them on demand, for example as a
+/// startup check.
+#[corresponds(BORINGSSL_self_test)]
+#[must_use]
+pub fn self_test() -> bool {
+    unsafe {
+        ffi::init();
+        ffi::BORINGSSL_self_test() == 1
+    }
+}
+
+/// Runs the module's integrity test, which checks the module's code and data against the hash
+/// embedded at build time.
+#[cfg(feature = "fips")]
+#[corresponds(BORINGSSL_integrity_test)]
+#[must_use]
+pub fn integrity_test() -> bool {
+    unsafe { ffi::BORINGSSL_integrity_test() == 1 }
+}
+
 #[test]
 fn is_enabled() {
     #[cfg(feature = "fips")]
@@ -20,3 +76,13 @@ fn is_enabled() {
     #[cfg(not(feature = "fips"))]
     assert!(!enabled());
 }
+
+#[test]
+fn module_info() {
+    assert!(!module_name().is_empty());
+    // MSVC builds of BoringSSL don't include the self tests.
+    #[cfg(not(target_env = "msvc"))]
+    assert!(self_test());
+    assert_eq!(is_algorithm_approved("AES-GCM"), enabled());
+    assert!(!is_algorithm_approved("AES-GCM\0"));
+}
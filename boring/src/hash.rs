@@ -7,6 +7,13 @@ use std::io;
 use std::io::prelude::*;
 use std::ops::{Deref, DerefMut};
 use std::ptr;
+#[cfg(feature = "tokio")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 use crate::error::ErrorStack;
 use crate::ffi::{EVP_MD_CTX_free, EVP_MD_CTX_new};
@@ -76,6 +83,15 @@ impl MessageDigest {
         unsafe { MessageDigest(ffi::EVP_sha512_256()) }
     }
 
+    /// BLAKE2b with a 256-bit output.
+    ///
+    /// BoringSSL implements neither keyed BLAKE2b nor other output sizes.
+    #[corresponds(EVP_blake2b256)]
+    #[must_use]
+    pub fn blake2b256() -> MessageDigest {
+        unsafe { MessageDigest(ffi::EVP_blake2b256()) }
+    }
+
     #[allow(clippy::trivially_copy_pass_by_ref)]
     #[must_use]
     pub fn as_ptr(&self) -> *const ffi::EVP_MD {
@@ -330,6 +346,168 @@ impl fmt::Debug for DigestBytes {
     }
 }
 
+/// A writer that hashes all data written through it to an inner writer.
+///
+/// With the `tokio` feature, this also implements [`AsyncWrite`] when the inner writer does.
+///
+/// # Examples
+///
+/// ```
+/// use boring::hash::{hash, HasherWriter, MessageDigest};
+/// use std::io::Write;
+///
+/// let mut writer = HasherWriter::new(Vec::new(), MessageDigest::sha256()).unwrap();
+/// writer.write_all(b"hello, world").unwrap();
+/// let digest = writer.finish().unwrap();
+/// assert_eq!(&*digest, &*hash(MessageDigest::sha256(), b"hello, world").unwrap());
+/// assert_eq!(writer.get_ref(), b"hello, world");
+/// ```
+pub struct HasherWriter<W> {
+    inner: W,
+    hasher: Hasher,
+}
+
+impl<W> HasherWriter<W> {
+    /// Wraps `inner`, hashing written data with `ty`.
+    pub fn new(inner: W, ty: MessageDigest) -> Result<HasherWriter<W>, ErrorStack> {
+        Ok(HasherWriter {
+            inner,
+            hasher: Hasher::new(ty)?,
+        })
+    }
+
+    /// Returns the hash of the data written so far and resets the hasher.
+    pub fn finish(&mut self) -> Result<DigestBytes, ErrorStack> {
+        self.hasher.finish()
+    }
+
+    /// Returns a shared reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Data written directly to the inner writer is not hashed.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the inner writer, discarding the hasher.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for HasherWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n])?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W: AsyncWrite + Unpin> AsyncWrite for HasherWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = std::task::ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+        this.hasher.update(&buf[..n])?;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+/// A reader that hashes all data read through it from an inner reader.
+///
+/// With the `tokio` feature, this also implements [`AsyncRead`] when the inner reader does.
+///
+/// # Examples
+///
+/// ```
+/// use boring::hash::{hash, HasherReader, MessageDigest};
+/// use std::io;
+///
+/// let mut reader = HasherReader::new(&b"hello, world"[..], MessageDigest::sha256()).unwrap();
+/// io::copy(&mut reader, &mut io::sink()).unwrap();
+/// let digest = reader.finish().unwrap();
+/// assert_eq!(&*digest, &*hash(MessageDigest::sha256(), b"hello, world").unwrap());
+/// ```
+pub struct HasherReader<R> {
+    inner: R,
+    hasher: Hasher,
+}
+
+impl<R> HasherReader<R> {
+    /// Wraps `inner`, hashing read data with `ty`.
+    pub fn new(inner: R, ty: MessageDigest) -> Result<HasherReader<R>, ErrorStack> {
+        Ok(HasherReader {
+            inner,
+            hasher: Hasher::new(ty)?,
+        })
+    }
+
+    /// Returns the hash of the data read so far and resets the hasher.
+    pub fn finish(&mut self) -> Result<DigestBytes, ErrorStack> {
+        self.hasher.finish()
+    }
+
+    /// Returns a shared reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    ///
+    /// Data read directly from the inner reader is not hashed.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the inner reader, discarding the hasher.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for HasherReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n])?;
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R: AsyncRead + Unpin> AsyncRead for HasherReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let start = buf.filled().len();
+        std::task::ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        this.hasher.update(&buf.filled()[start..])?;
+        Poll::Ready(Ok(()))
+    }
+}
+
 /// Computes the hash of the `data` with the non-XOF hasher `t`.
 pub fn hash(t: MessageDigest, data: &[u8]) -> Result<DigestBytes, ErrorStack> {
     let mut h = Hasher::new(t)?;
@@ -580,6 +758,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_blake2b256() {
+        let tests = [
+            (
+                "",
+                "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8",
+            ),
+            (
+                "616263",
+                "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+            ),
+        ];
+
+        for test in &tests {
+            hash_test(MessageDigest::blake2b256(), test);
+        }
+
+        let mut h = Hasher::new(MessageDigest::blake2b256()).unwrap();
+        for test in &tests {
+            hash_recycle_test(&mut h, test);
+        }
+        assert_eq!(MessageDigest::blake2b256().size(), 32);
+    }
+
     #[test]
     fn from_nid() {
         assert_eq!(
@@ -587,4 +789,56 @@ mod tests {
             MessageDigest::sha256().as_ptr()
         );
     }
+
+    #[test]
+    fn test_hasher_writer() {
+        let mut writer = HasherWriter::new(Vec::new(), MessageDigest::md5()).unwrap();
+        for test in MD5_TESTS.iter() {
+            writer.write_all(&Vec::from_hex(test.0).unwrap()).unwrap();
+            assert_eq!(hex::encode(writer.finish().unwrap()), test.1);
+        }
+        let written = MD5_TESTS
+            .iter()
+            .flat_map(|test| Vec::from_hex(test.0).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(writer.into_inner(), written);
+    }
+
+    #[test]
+    fn test_hasher_reader() {
+        let data = Vec::from_hex(MD5_TESTS[12].0).unwrap();
+        let mut reader = HasherReader::new(&data[..], MessageDigest::md5()).unwrap();
+        let mut buf = [0; 5];
+        let mut read = vec![];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            read.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(read, data);
+        assert_eq!(hex::encode(reader.finish().unwrap()), MD5_TESTS[12].1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_async_adapters() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let data = Vec::from_hex(MD5_TESTS[12].0).unwrap();
+
+        // The adapters also implement `std::io`, so the tokio methods are called by path.
+        let mut writer = HasherWriter::new(Vec::new(), MessageDigest::md5()).unwrap();
+        AsyncWriteExt::write_all(&mut writer, &data).await.unwrap();
+        assert_eq!(hex::encode(writer.finish().unwrap()), MD5_TESTS[12].1);
+
+        let mut reader = HasherReader::new(&data[..], MessageDigest::md5()).unwrap();
+        let mut read = vec![];
+        AsyncReadExt::read_to_end(&mut reader, &mut read)
+            .await
+            .unwrap();
+        assert_eq!(read, data);
+        assert_eq!(hex::encode(reader.finish().unwrap()), MD5_TESTS[12].1);
+    }
 }
@@ -1,7 +1,158 @@
+//! Hybrid Public Key Encryption, as described in [RFC 9180].
+//!
+//! A cipher suite is selected with an [`HpkeKem`], an [`HpkeKdf`] and an [`HpkeAead`]. The sender
+//! encapsulates a shared secret to the recipient's public key with [`HpkeSender::new`], and sends
+//! the resulting encapsulated key along with any ciphertexts. The recipient recovers the context
+//! with [`HpkeRecipient::new`].
+//!
+//! [RFC 9180]: https://www.rfc-editor.org/rfc/rfc9180
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::hpke::{HpkeAead, HpkeKdf, HpkeKem, HpkeKey, HpkeRecipient, HpkeSender};
+//!
+//! let kem = HpkeKem::x25519_hkdf_sha256();
+//! let kdf = HpkeKdf::hkdf_sha256();
+//! let aead = HpkeAead::aes_128_gcm();
+//!
+//! let key = HpkeKey::generate(kem).unwrap();
+//! let public_key = key.public_key().unwrap();
+//!
+//! let (mut sender, enc) = HpkeSender::new(kem, kdf, aead, &public_key, b"info").unwrap();
+//! let ciphertext = sender.seal(b"hello", b"").unwrap();
+//!
+//! let mut recipient = HpkeRecipient::new(&key, kdf, aead, &enc, b"info").unwrap();
+//! assert_eq!(recipient.open(&ciphertext, b"").unwrap(), b"hello");
+//! ```
 use crate::error::ErrorStack;
 use crate::{cvt_0i, cvt_p, ffi};
 
-use foreign_types::ForeignType;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl_macros::corresponds;
+use std::ptr;
+
+/// An HPKE key encapsulation mechanism.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct HpkeKem(*const ffi::EVP_HPKE_KEM);
+
+impl HpkeKem {
+    /// DHKEM(X25519, HKDF-SHA256).
+    #[corresponds(EVP_hpke_x25519_hkdf_sha256)]
+    #[must_use]
+    pub fn x25519_hkdf_sha256() -> HpkeKem {
+        unsafe { HpkeKem(ffi::EVP_hpke_x25519_hkdf_sha256()) }
+    }
+
+    /// Returns the KEM's IANA identifier.
+    #[corresponds(EVP_HPKE_KEM_id)]
+    #[must_use]
+    pub fn id(&self) -> u16 {
+        unsafe { ffi::EVP_HPKE_KEM_id(self.0) }
+    }
+
+    /// Returns the length of the KEM's public keys.
+    #[corresponds(EVP_HPKE_KEM_public_key_len)]
+    #[must_use]
+    pub fn public_key_len(&self) -> usize {
+        unsafe { ffi::EVP_HPKE_KEM_public_key_len(self.0) }
+    }
+
+    /// Returns the length of the KEM's private keys.
+    #[corresponds(EVP_HPKE_KEM_private_key_len)]
+    #[must_use]
+    pub fn private_key_len(&self) -> usize {
+        unsafe { ffi::EVP_HPKE_KEM_private_key_len(self.0) }
+    }
+
+    /// Returns the length of the KEM's encapsulated keys.
+    #[corresponds(EVP_HPKE_KEM_enc_len)]
+    #[must_use]
+    pub fn enc_len(&self) -> usize {
+        unsafe { ffi::EVP_HPKE_KEM_enc_len(self.0) }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    #[must_use]
+    pub fn as_ptr(&self) -> *const ffi::EVP_HPKE_KEM {
+        self.0
+    }
+}
+
+unsafe impl Sync for HpkeKem {}
+unsafe impl Send for HpkeKem {}
+
+/// An HPKE key derivation function.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct HpkeKdf(*const ffi::EVP_HPKE_KDF);
+
+impl HpkeKdf {
+    /// HKDF-SHA256.
+    #[corresponds(EVP_hpke_hkdf_sha256)]
+    #[must_use]
+    pub fn hkdf_sha256() -> HpkeKdf {
+        unsafe { HpkeKdf(ffi::EVP_hpke_hkdf_sha256()) }
+    }
+
+    /// Returns the KDF's IANA identifier.
+    #[corresponds(EVP_HPKE_KDF_id)]
+    #[must_use]
+    pub fn id(&self) -> u16 {
+        unsafe { ffi::EVP_HPKE_KDF_id(self.0) }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    #[must_use]
+    pub fn as_ptr(&self) -> *const ffi::EVP_HPKE_KDF {
+        self.0
+    }
+}
+
+unsafe impl Sync for HpkeKdf {}
+unsafe impl Send for HpkeKdf {}
+
+/// An HPKE AEAD.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct HpkeAead(*const ffi::EVP_HPKE_AEAD);
+
+impl HpkeAead {
+    /// AES-128-GCM.
+    #[corresponds(EVP_hpke_aes_128_gcm)]
+    #[must_use]
+    pub fn aes_128_gcm() -> HpkeAead {
+        unsafe { HpkeAead(ffi::EVP_hpke_aes_128_gcm()) }
+    }
+
+    /// AES-256-GCM.
+    #[corresponds(EVP_hpke_aes_256_gcm)]
+    #[must_use]
+    pub fn aes_256_gcm() -> HpkeAead {
+        unsafe { HpkeAead(ffi::EVP_hpke_aes_256_gcm()) }
+    }
+
+    /// ChaCha20-Poly1305.
+    #[corresponds(EVP_hpke_chacha20_poly1305)]
+    #[must_use]
+    pub fn chacha20_poly1305() -> HpkeAead {
+        unsafe { HpkeAead(ffi::EVP_hpke_chacha20_poly1305()) }
+    }
+
+    /// Returns the AEAD's IANA identifier.
+    #[corresponds(EVP_HPKE_AEAD_id)]
+    #[must_use]
+    pub fn id(&self) -> u16 {
+        unsafe { ffi::EVP_HPKE_AEAD_id(self.0) }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    #[must_use]
+    pub fn as_ptr(&self) -> *const ffi::EVP_HPKE_AEAD {
+        self.0
+    }
+}
+
+unsafe impl Sync for HpkeAead {}
+unsafe impl Send for HpkeAead {}
 
 foreign_type_and_impl_send_sync! {
     type CType = ffi::EVP_HPKE_KEY;
@@ -14,18 +165,417 @@ impl HpkeKey {
     /// Allocates and initializes a key with the `EVP_HPKE_KEY` type using the
     /// `EVP_hpke_x25519_hkdf_sha256` KEM algorithm.
     pub fn dhkem_p256_sha256(pkey: &[u8]) -> Result<HpkeKey, ErrorStack> {
+        HpkeKey::new(HpkeKem::x25519_hkdf_sha256(), pkey)
+    }
+
+    /// Creates a key for `kem` from a serialized private key.
+    #[corresponds(EVP_HPKE_KEY_init)]
+    pub fn new(kem: HpkeKem, private_key: &[u8]) -> Result<HpkeKey, ErrorStack> {
         unsafe {
             ffi::init();
             let hpke = cvt_p(ffi::EVP_HPKE_KEY_new()).map(|p| HpkeKey::from_ptr(p))?;
 
             cvt_0i(ffi::EVP_HPKE_KEY_init(
                 hpke.as_ptr(),
-                ffi::EVP_hpke_x25519_hkdf_sha256(),
-                pkey.as_ptr(),
-                pkey.len(),
+                kem.as_ptr(),
+                private_key.as_ptr(),
+                private_key.len(),
             ))?;
 
             Ok(hpke)
         }
     }
+
+    /// Generates a random key for `kem`.
+    #[corresponds(EVP_HPKE_KEY_generate)]
+    pub fn generate(kem: HpkeKem) -> Result<HpkeKey, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let hpke = cvt_p(ffi::EVP_HPKE_KEY_new()).map(|p| HpkeKey::from_ptr(p))?;
+
+            cvt_0i(ffi::EVP_HPKE_KEY_generate(hpke.as_ptr(), kem.as_ptr()))?;
+
+            Ok(hpke)
+        }
+    }
+}
+
+impl HpkeKeyRef {
+    /// Returns the key's KEM.
+    #[corresponds(EVP_HPKE_KEY_kem)]
+    #[must_use]
+    pub fn kem(&self) -> HpkeKem {
+        unsafe { HpkeKem(ffi::EVP_HPKE_KEY_kem(self.as_ptr())) }
+    }
+
+    /// Returns the serialized public key.
+    #[corresponds(EVP_HPKE_KEY_public_key)]
+    pub fn public_key(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = vec![0; ffi::EVP_HPKE_MAX_PUBLIC_KEY_LENGTH as usize];
+        let mut len = 0;
+        unsafe {
+            cvt_0i(ffi::EVP_HPKE_KEY_public_key(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                &mut len,
+                out.len(),
+            ))?;
+        }
+        out.truncate(len);
+        Ok(out)
+    }
+
+    /// Returns the serialized private key.
+    #[corresponds(EVP_HPKE_KEY_private_key)]
+    pub fn private_key(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = vec![0; ffi::EVP_HPKE_MAX_PRIVATE_KEY_LENGTH as usize];
+        let mut len = 0;
+        unsafe {
+            cvt_0i(ffi::EVP_HPKE_KEY_private_key(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                &mut len,
+                out.len(),
+            ))?;
+        }
+        out.truncate(len);
+        Ok(out)
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::EVP_HPKE_CTX;
+    fn drop = ffi::EVP_HPKE_CTX_free;
+
+    /// The sender's side of an HPKE context.
+    pub struct HpkeSender;
+}
+
+impl HpkeSender {
+    /// Sets up a sender context in base mode, encapsulating to `peer_public_key`.
+    ///
+    /// Returns the context and the encapsulated key, which must be sent to the recipient.
+    #[corresponds(EVP_HPKE_CTX_setup_sender)]
+    pub fn new(
+        kem: HpkeKem,
+        kdf: HpkeKdf,
+        aead: HpkeAead,
+        peer_public_key: &[u8],
+        info: &[u8],
+    ) -> Result<(HpkeSender, Vec<u8>), ErrorStack> {
+        unsafe {
+            ffi::init();
+            let ctx = cvt_p(ffi::EVP_HPKE_CTX_new()).map(|p| HpkeSender::from_ptr(p))?;
+            let mut enc = vec![0; ffi::EVP_HPKE_MAX_ENC_LENGTH as usize];
+            let mut enc_len = 0;
+
+            cvt_0i(ffi::EVP_HPKE_CTX_setup_sender(
+                ctx.as_ptr(),
+                enc.as_mut_ptr(),
+                &mut enc_len,
+                enc.len(),
+                kem.as_ptr(),
+                kdf.as_ptr(),
+                aead.as_ptr(),
+                peer_public_key.as_ptr(),
+                peer_public_key.len(),
+                info.as_ptr(),
+                info.len(),
+            ))?;
+
+            enc.truncate(enc_len);
+            Ok((ctx, enc))
+        }
+    }
+
+    /// Sets up a sender context in auth mode, authenticating the sender with `key`.
+    #[corresponds(EVP_HPKE_CTX_setup_auth_sender)]
+    pub fn new_auth(
+        key: &HpkeKeyRef,
+        kdf: HpkeKdf,
+        aead: HpkeAead,
+        peer_public_key: &[u8],
+        info: &[u8],
+    ) -> Result<(HpkeSender, Vec<u8>), ErrorStack> {
+        unsafe {
+            ffi::init();
+            let ctx = cvt_p(ffi::EVP_HPKE_CTX_new()).map(|p| HpkeSender::from_ptr(p))?;
+            let mut enc = vec![0; ffi::EVP_HPKE_MAX_ENC_LENGTH as usize];
+            let mut enc_len = 0;
+
+            cvt_0i(ffi::EVP_HPKE_CTX_setup_auth_sender(
+                ctx.as_ptr(),
+                enc.as_mut_ptr(),
+                &mut enc_len,
+                enc.len(),
+                key.as_ptr(),
+                kdf.as_ptr(),
+                aead.as_ptr(),
+                peer_public_key.as_ptr(),
+                peer_public_key.len(),
+                info.as_ptr(),
+                info.len(),
+            ))?;
+
+            enc.truncate(enc_len);
+            Ok((ctx, enc))
+        }
+    }
+}
+
+impl HpkeSenderRef {
+    /// Encrypts and authenticates `plaintext` with the associated data `ad`.
+    ///
+    /// Each call uses the next nonce in the context's sequence, so messages must be opened in the
+    /// same order.
+    #[corresponds(EVP_HPKE_CTX_seal)]
+    pub fn seal(&mut self, plaintext: &[u8], ad: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let mut out = vec![0; plaintext.len() + ffi::EVP_HPKE_CTX_max_overhead(self.as_ptr())];
+            let mut len = 0;
+
+            cvt_0i(ffi::EVP_HPKE_CTX_seal(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                &mut len,
+                out.len(),
+                plaintext.as_ptr(),
+                plaintext.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+
+            out.truncate(len);
+            Ok(out)
+        }
+    }
+
+    /// Derives a secret of length `len` from the context, as described in RFC 9180 section 5.3.
+    #[corresponds(EVP_HPKE_CTX_export)]
+    pub fn export(&self, context: &[u8], len: usize) -> Result<Vec<u8>, ErrorStack> {
+        unsafe { export(self.as_ptr(), context, len) }
+    }
+
+    /// Returns the maximum number of bytes [`seal`](Self::seal) adds to a plaintext.
+    #[corresponds(EVP_HPKE_CTX_max_overhead)]
+    #[must_use]
+    pub fn max_overhead(&self) -> usize {
+        unsafe { ffi::EVP_HPKE_CTX_max_overhead(self.as_ptr()) }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::EVP_HPKE_CTX;
+    fn drop = ffi::EVP_HPKE_CTX_free;
+
+    /// The recipient's side of an HPKE context.
+    pub struct HpkeRecipient;
+}
+
+impl HpkeRecipient {
+    /// Sets up a recipient context in base mode from the sender's encapsulated key.
+    #[corresponds(EVP_HPKE_CTX_setup_recipient)]
+    pub fn new(
+        key: &HpkeKeyRef,
+        kdf: HpkeKdf,
+        aead: HpkeAead,
+        enc: &[u8],
+        info: &[u8],
+    ) -> Result<HpkeRecipient, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let ctx = cvt_p(ffi::EVP_HPKE_CTX_new()).map(|p| HpkeRecipient::from_ptr(p))?;
+
+            cvt_0i(ffi::EVP_HPKE_CTX_setup_recipient(
+                ctx.as_ptr(),
+                key.as_ptr(),
+                kdf.as_ptr(),
+                aead.as_ptr(),
+                enc.as_ptr(),
+                enc.len(),
+                info.as_ptr(),
+                info.len(),
+            ))?;
+
+            Ok(ctx)
+        }
+    }
+
+    /// Sets up a recipient context in auth mode, checking that the sender holds the private key
+    /// for `peer_public_key`.
+    #[corresponds(EVP_HPKE_CTX_setup_auth_recipient)]
+    pub fn new_auth(
+        key: &HpkeKeyRef,
+        kdf: HpkeKdf,
+        aead: HpkeAead,
+        enc: &[u8],
+        info: &[u8],
+        peer_public_key: &[u8],
+    ) -> Result<HpkeRecipient, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let ctx = cvt_p(ffi::EVP_HPKE_CTX_new()).map(|p| HpkeRecipient::from_ptr(p))?;
+
+            cvt_0i(ffi::EVP_HPKE_CTX_setup_auth_recipient(
+                ctx.as_ptr(),
+                key.as_ptr(),
+                kdf.as_ptr(),
+                aead.as_ptr(),
+                enc.as_ptr(),
+                enc.len(),
+                info.as_ptr(),
+                info.len(),
+                peer_public_key.as_ptr(),
+                peer_public_key.len(),
+            ))?;
+
+            Ok(ctx)
+        }
+    }
+}
+
+impl HpkeRecipientRef {
+    /// Decrypts and authenticates `ciphertext` with the associated data `ad`.
+    #[corresponds(EVP_HPKE_CTX_open)]
+    pub fn open(&mut self, ciphertext: &[u8], ad: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let mut out = vec![0; ciphertext.len()];
+            let mut len = 0;
+
+            cvt_0i(ffi::EVP_HPKE_CTX_open(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                &mut len,
+                out.len(),
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+
+            out.truncate(len);
+            Ok(out)
+        }
+    }
+
+    /// Derives a secret of length `len` from the context, as described in RFC 9180 section 5.3.
+    #[corresponds(EVP_HPKE_CTX_export)]
+    pub fn export(&self, context: &[u8], len: usize) -> Result<Vec<u8>, ErrorStack> {
+        unsafe { export(self.as_ptr(), context, len) }
+    }
+}
+
+unsafe fn export(
+    ctx: *const ffi::EVP_HPKE_CTX,
+    context: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut out = vec![0; len];
+    cvt_0i(ffi::EVP_HPKE_CTX_export(
+        ctx,
+        out.as_mut_ptr(),
+        len,
+        if context.is_empty() {
+            ptr::null()
+        } else {
+            context.as_ptr()
+        },
+        context.len(),
+    ))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn suites() -> Vec<(HpkeKdf, HpkeAead)> {
+        vec![
+            (HpkeKdf::hkdf_sha256(), HpkeAead::aes_128_gcm()),
+            (HpkeKdf::hkdf_sha256(), HpkeAead::aes_256_gcm()),
+            (HpkeKdf::hkdf_sha256(), HpkeAead::chacha20_poly1305()),
+        ]
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let kem = HpkeKem::x25519_hkdf_sha256();
+        assert_eq!(kem.id(), 0x0020);
+
+        let key = HpkeKey::generate(kem).unwrap();
+        let public_key = key.public_key().unwrap();
+        assert_eq!(public_key.len(), kem.public_key_len());
+
+        for (kdf, aead) in suites() {
+            let (mut sender, enc) = HpkeSender::new(kem, kdf, aead, &public_key, b"info").unwrap();
+            assert_eq!(enc.len(), kem.enc_len());
+            let mut recipient = HpkeRecipient::new(&key, kdf, aead, &enc, b"info").unwrap();
+
+            for msg in [&b"first"[..], b"second"] {
+                let ciphertext = sender.seal(msg, b"ad").unwrap();
+                assert_eq!(ciphertext.len(), msg.len() + sender.max_overhead());
+                assert_eq!(recipient.open(&ciphertext, b"ad").unwrap(), msg);
+            }
+
+            let ciphertext = sender.seal(b"third", b"ad").unwrap();
+            assert!(recipient.open(&ciphertext, b"wrong ad").is_err());
+
+            assert_eq!(
+                sender.export(b"context", 42).unwrap(),
+                recipient.export(b"context", 42).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_auth_mode() {
+        let kem = HpkeKem::x25519_hkdf_sha256();
+        let kdf = HpkeKdf::hkdf_sha256();
+        let aead = HpkeAead::chacha20_poly1305();
+
+        let sender_key = HpkeKey::generate(kem).unwrap();
+        let recipient_key = HpkeKey::generate(kem).unwrap();
+        let other_key = HpkeKey::generate(kem).unwrap();
+
+        let (mut sender, enc) = HpkeSender::new_auth(
+            &sender_key,
+            kdf,
+            aead,
+            &recipient_key.public_key().unwrap(),
+            b"",
+        )
+        .unwrap();
+        let ciphertext = sender.seal(b"hello", b"").unwrap();
+
+        let mut recipient = HpkeRecipient::new_auth(
+            &recipient_key,
+            kdf,
+            aead,
+            &enc,
+            b"",
+            &sender_key.public_key().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(recipient.open(&ciphertext, b"").unwrap(), b"hello");
+
+        let mut recipient = HpkeRecipient::new_auth(
+            &recipient_key,
+            kdf,
+            aead,
+            &enc,
+            b"",
+            &other_key.public_key().unwrap(),
+        )
+        .unwrap();
+        assert!(recipient.open(&ciphertext, b"").is_err());
+    }
+
+    #[test]
+    fn test_key_round_trip() {
+        let kem = HpkeKem::x25519_hkdf_sha256();
+        let key = HpkeKey::generate(kem).unwrap();
+        let copy = HpkeKey::new(kem, &key.private_key().unwrap()).unwrap();
+        assert_eq!(key.public_key().unwrap(), copy.public_key().unwrap());
+        assert!(copy.kem() == kem);
+    }
 }
@@ -0,0 +1,421 @@
+//! Key derivation functions.
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::hash::MessageDigest;
+//! use boring::kdf::Hkdf;
+//!
+//! let mut key = [0; 32];
+//! Hkdf::new(MessageDigest::sha256())
+//!     .salt(b"salt")
+//!     .info(b"protocol v1 ")
+//!     .info(b"client key")
+//!     .derive(b"input keying material", &mut key)
+//!     .unwrap();
+//! ```
+use crate::cvt;
+use crate::error::ErrorStack;
+use crate::ffi;
+use crate::hash::MessageDigest;
+use crate::memcmp;
+use crate::pkcs5;
+use openssl_macros::corresponds;
+use std::num::NonZeroU32;
+
+/// The shortest `expected` value accepted by [`pbkdf2_verify`] and [`scrypt_verify`].
+pub const MIN_VERIFY_LEN: usize = 16;
+
+fn check_verify_len(expected: &[u8]) -> Result<(), ErrorStack> {
+    if expected.len() < MIN_VERIFY_LEN {
+        return Err(ErrorStack::internal_error_str(
+            "expected key is shorter than MIN_VERIFY_LEN",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Derives `out.len()` bytes from `secret` using HKDF, as described in [RFC 5869].
+///
+/// This performs both the extract and the expand steps.
+///
+/// [RFC 5869]: https://www.rfc-editor.org/rfc/rfc5869
+#[corresponds(HKDF)]
+pub fn hkdf(
+    digest: MessageDigest,
+    secret: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    unsafe {
+        ffi::init();
+        cvt(ffi::HKDF(
+            out.as_mut_ptr(),
+            out.len(),
+            digest.as_ptr(),
+            secret.as_ptr(),
+            secret.len(),
+            salt.as_ptr(),
+            salt.len(),
+            info.as_ptr(),
+            info.len(),
+        ))
+        .map(|_| ())
+    }
+}
+
+/// Computes the HKDF pseudorandom key for `secret` and `salt`.
+///
+/// The returned key is [`MessageDigest::size`] bytes long.
+#[corresponds(HKDF_extract)]
+pub fn hkdf_extract(
+    digest: MessageDigest,
+    secret: &[u8],
+    salt: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut prk = vec![0; ffi::EVP_MAX_MD_SIZE as usize];
+    let mut len = 0;
+    unsafe {
+        ffi::init();
+        cvt(ffi::HKDF_extract(
+            prk.as_mut_ptr(),
+            &mut len,
+            digest.as_ptr(),
+            secret.as_ptr(),
+            secret.len(),
+            salt.as_ptr(),
+            salt.len(),
+        ))?;
+    }
+    prk.truncate(len);
+    Ok(prk)
+}
+
+/// Expands the pseudorandom key `prk` into `out.len()` bytes of output keying material.
+///
+/// At most 255 times [`MessageDigest::size`] bytes may be derived.
+#[corresponds(HKDF_expand)]
+pub fn hkdf_expand(
+    digest: MessageDigest,
+    prk: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    unsafe {
+        ffi::init();
+        cvt(ffi::HKDF_expand(
+            out.as_mut_ptr(),
+            out.len(),
+            digest.as_ptr(),
+            prk.as_ptr(),
+            prk.len(),
+            info.as_ptr(),
+            info.len(),
+        ))
+        .map(|_| ())
+    }
+}
+
+/// A builder for HKDF derivations.
+///
+/// The salt defaults to empty, which HKDF treats as a string of zero bytes. The info parameter is
+/// the concatenation of every slice passed to [`info`](Hkdf::info).
+#[derive(Clone)]
+pub struct Hkdf<'a> {
+    digest: MessageDigest,
+    salt: &'a [u8],
+    info: Vec<u8>,
+}
+
+impl<'a> Hkdf<'a> {
+    /// Creates a builder using `digest` as the underlying hash function.
+    #[must_use]
+    pub fn new(digest: MessageDigest) -> Hkdf<'a> {
+        Hkdf {
+            digest,
+            salt: &[],
+            info: Vec::new(),
+        }
+    }
+
+    /// Sets the salt.
+    #[must_use]
+    pub fn salt(mut self, salt: &'a [u8]) -> Hkdf<'a> {
+        self.salt = salt;
+        self
+    }
+
+    /// Appends `info` to the context and application specific information.
+    #[must_use]
+    pub fn info(mut self, info: &[u8]) -> Hkdf<'a> {
+        self.info.extend_from_slice(info);
+        self
+    }
+
+    /// Derives `out.len()` bytes from `secret`.
+    pub fn derive(&self, secret: &[u8], out: &mut [u8]) -> Result<(), ErrorStack> {
+        hkdf(self.digest, secret, self.salt, &self.info, out)
+    }
+
+    /// Performs only the extract step, returning the pseudorandom key.
+    pub fn extract(&self, secret: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        hkdf_extract(self.digest, secret, self.salt)
+    }
+
+    /// Performs only the expand step from a pseudorandom key, ignoring the salt.
+    pub fn expand(&self, prk: &[u8], out: &mut [u8]) -> Result<(), ErrorStack> {
+        hkdf_expand(self.digest, prk, &self.info, out)
+    }
+}
+
+/// Derives `out.len()` bytes from `password` and `salt` using PBKDF2 with HMAC over `digest`.
+///
+/// For password hashing, prefer [`scrypt`] where interoperability permits it.
+#[corresponds(PKCS5_PBKDF2_HMAC)]
+pub fn pbkdf2(
+    digest: MessageDigest,
+    password: &[u8],
+    salt: &[u8],
+    iterations: NonZeroU32,
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    unsafe {
+        ffi::init();
+        cvt(ffi::PKCS5_PBKDF2_HMAC(
+            password.as_ptr().cast(),
+            password.len(),
+            salt.as_ptr(),
+            salt.len(),
+            iterations.get(),
+            digest.as_ptr(),
+            out.len(),
+            out.as_mut_ptr(),
+        ))
+        .map(|_| ())
+    }
+}
+
+/// Returns `true` if `password` and `salt` derive `expected` with PBKDF2.
+///
+/// The comparison takes constant time. This fails if `expected` is shorter than
+/// [`MIN_VERIFY_LEN`] bytes, which would let almost any password verify.
+pub fn pbkdf2_verify(
+    digest: MessageDigest,
+    password: &[u8],
+    salt: &[u8],
+    iterations: NonZeroU32,
+    expected: &[u8],
+) -> Result<bool, ErrorStack> {
+    check_verify_len(expected)?;
+
+    let mut derived = vec![0; expected.len()];
+    pbkdf2(digest, password, salt, iterations, &mut derived)?;
+    Ok(memcmp::eq(&derived, expected))
+}
+
+/// Derives `out.len()` bytes from `password` and `salt` using scrypt, as described in
+/// [RFC 7914].
+///
+/// `n` is the CPU/memory cost and must be a power of two, `r` the block size and `p` the
+/// parallelization parameter. The derivation fails if it needs more than `maxmem` bytes of
+/// memory, roughly `128 * r * (n + p)`, which bounds the cost of parameters an attacker
+/// chooses. A `maxmem` of zero selects BoringSSL's default of 32 MiB.
+///
+/// [RFC 7914]: https://www.rfc-editor.org/rfc/rfc7914
+#[corresponds(EVP_PBE_scrypt)]
+pub fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    n: u64,
+    r: u64,
+    p: u64,
+    maxmem: u64,
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let maxmem = usize::try_from(maxmem)
+        .map_err(|_| ErrorStack::internal_error_str("scrypt memory limit too large"))?;
+
+    pkcs5::scrypt(password, salt, n, r, p, maxmem, out)
+}
+
+/// Returns `true` if `password` and `salt` derive `expected` with scrypt.
+///
+/// The comparison takes constant time. This fails if `expected` is shorter than
+/// [`MIN_VERIFY_LEN`] bytes, which would let almost any password verify.
+pub fn scrypt_verify(
+    password: &[u8],
+    salt: &[u8],
+    n: u64,
+    r: u64,
+    p: u64,
+    maxmem: u64,
+    expected: &[u8],
+) -> Result<bool, ErrorStack> {
+    check_verify_len(expected)?;
+
+    let mut derived = vec![0; expected.len()];
+    scrypt(password, salt, n, r, p, maxmem, &mut derived)?;
+    Ok(memcmp::eq(&derived, expected))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex::FromHex;
+
+    // Test case 1 from RFC 5869.
+    const IKM: &str = "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b";
+    const SALT: &str = "000102030405060708090a0b0c";
+    const INFO: &str = "f0f1f2f3f4f5f6f7f8f9";
+    const PRK: &str = "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5";
+    const OKM: &str =
+        "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865";
+
+    #[test]
+    fn test_hkdf_rfc5869() {
+        let ikm = Vec::from_hex(IKM).unwrap();
+        let salt = Vec::from_hex(SALT).unwrap();
+        let info = Vec::from_hex(INFO).unwrap();
+
+        let prk = hkdf_extract(MessageDigest::sha256(), &ikm, &salt).unwrap();
+        assert_eq!(hex::encode(&prk), PRK);
+
+        let mut okm = [0; 42];
+        hkdf_expand(MessageDigest::sha256(), &prk, &info, &mut okm).unwrap();
+        assert_eq!(hex::encode(okm), OKM);
+
+        let mut okm = [0; 42];
+        hkdf(MessageDigest::sha256(), &ikm, &salt, &info, &mut okm).unwrap();
+        assert_eq!(hex::encode(okm), OKM);
+    }
+
+    #[test]
+    fn test_hkdf_builder() {
+        let ikm = Vec::from_hex(IKM).unwrap();
+        let salt = Vec::from_hex(SALT).unwrap();
+        let info = Vec::from_hex(INFO).unwrap();
+
+        let hkdf = Hkdf::new(MessageDigest::sha256())
+            .salt(&salt)
+            .info(&info[..4])
+            .info(&info[4..]);
+
+        let mut okm = [0; 42];
+        hkdf.derive(&ikm, &mut okm).unwrap();
+        assert_eq!(hex::encode(okm), OKM);
+
+        let prk = hkdf.extract(&ikm).unwrap();
+        assert_eq!(hex::encode(&prk), PRK);
+
+        let mut okm = [0; 42];
+        hkdf.expand(&prk, &mut okm).unwrap();
+        assert_eq!(hex::encode(okm), OKM);
+    }
+
+    #[test]
+    fn test_hkdf_expand_too_long() {
+        let mut okm = vec![0; 255 * 32 + 1];
+        assert!(hkdf_expand(MessageDigest::sha256(), &[0; 32], b"", &mut okm).is_err());
+    }
+
+    #[test]
+    fn test_pbkdf2() {
+        // From RFC 7914, section 11.
+        let iterations = NonZeroU32::new(1).unwrap();
+        let mut out = [0; 64];
+        pbkdf2(
+            MessageDigest::sha256(),
+            b"passwd",
+            b"salt",
+            iterations,
+            &mut out,
+        )
+        .unwrap();
+        let expected = "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+                        49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783";
+        assert_eq!(hex::encode(out), expected);
+
+        assert!(pbkdf2_verify(
+            MessageDigest::sha256(),
+            b"passwd",
+            b"salt",
+            iterations,
+            &out
+        )
+        .unwrap());
+        assert!(!pbkdf2_verify(
+            MessageDigest::sha256(),
+            b"passwe",
+            b"salt",
+            iterations,
+            &out
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_pbkdf2_verify_short() {
+        let iterations = NonZeroU32::new(1).unwrap();
+        let mut out = [0; MIN_VERIFY_LEN];
+        pbkdf2(
+            MessageDigest::sha256(),
+            b"passwd",
+            b"salt",
+            iterations,
+            &mut out,
+        )
+        .unwrap();
+
+        for expected in [&[][..], &out[..MIN_VERIFY_LEN - 1]] {
+            assert!(pbkdf2_verify(
+                MessageDigest::sha256(),
+                b"passwe",
+                b"salt",
+                iterations,
+                expected
+            )
+            .is_err());
+        }
+        assert!(pbkdf2_verify(
+            MessageDigest::sha256(),
+            b"passwd",
+            b"salt",
+            iterations,
+            &out
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_scrypt() {
+        // From RFC 7914, section 12.
+        const MAXMEM: u64 = 32 * 1024 * 1024;
+
+        let mut out = [0; 64];
+        scrypt(b"password", b"NaCl", 1024, 8, 16, MAXMEM, &mut out).unwrap();
+        let expected = "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+                        2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640";
+        assert_eq!(hex::encode(out), expected);
+
+        assert!(scrypt_verify(b"password", b"NaCl", 1024, 8, 16, MAXMEM, &out).unwrap());
+        assert!(!scrypt_verify(b"password", b"NaCm", 1024, 8, 16, MAXMEM, &out).unwrap());
+        assert!(scrypt(b"password", b"NaCl", 1000, 8, 16, MAXMEM, &mut out).is_err());
+        // The parameters need about 1 MiB.
+        assert!(scrypt(b"password", b"NaCl", 1024, 8, 16, 1024 * 1024, &mut out).is_err());
+        assert!(scrypt_verify(b"password", b"NaCl", 1 << 40, 8, 16, MAXMEM, &out).is_err());
+    }
+
+    #[test]
+    fn test_scrypt_verify_short() {
+        const MAXMEM: u64 = 32 * 1024 * 1024;
+
+        let mut out = [0; MIN_VERIFY_LEN];
+        scrypt(b"password", b"NaCl", 16, 1, 1, MAXMEM, &mut out).unwrap();
+
+        assert!(scrypt_verify(b"passwore", b"NaCl", 16, 1, 1, MAXMEM, &[]).is_err());
+        assert!(scrypt_verify(b"passwore", b"NaCl", 16, 1, 1, MAXMEM, &out[..8]).is_err());
+        assert!(scrypt_verify(b"password", b"NaCl", 16, 1, 1, MAXMEM, &out).unwrap());
+    }
+}
@@ -0,0 +1,362 @@
+//! Post-quantum key encapsulation.
+//!
+//! This module exposes ML-KEM-768 (FIPS 203) and its predecessor Kyber768, the KEMs of the
+//! X25519MLKEM768 and X25519Kyber768Draft00 TLS key exchanges, for building hybrid schemes
+//! outside of TLS. Both use the same key and ciphertext formats, but derive different shared
+//! secrets, so both parties must agree on the variant. ML-KEM-1024 is not available in the
+//! BoringSSL version this crate builds against.
+//!
+//! These KEMs should only be used in combination with a classical key exchange such as
+//! [`x25519`](crate::x25519).
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::kem::MlKem768PrivateKey;
+//!
+//! let (private_key, public_key) = MlKem768PrivateKey::generate();
+//!
+//! let (ciphertext, sender_secret) = public_key.encapsulate().unwrap();
+//! let recipient_secret = private_key.decapsulate(&ciphertext);
+//! assert_eq!(sender_secret.as_bytes(), recipient_secret.as_bytes());
+//! ```
+use crate::error::ErrorStack;
+use crate::ffi;
+use libc::c_int;
+use openssl_macros::corresponds;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+
+/// The length of an encoded ML-KEM-768 or Kyber768 public key, in bytes.
+pub const KYBER768_PUBLIC_KEY_LEN: usize = ffi::KYBER768_PUBLIC_KEY_BYTES as usize;
+
+/// The length of an encoded ML-KEM-768 or Kyber768 private key, in bytes.
+pub const KYBER768_PRIVATE_KEY_LEN: usize = ffi::KYBER768_PRIVATE_KEY_BYTES as usize;
+
+/// The length of an ML-KEM-768 or Kyber768 ciphertext, in bytes.
+pub const KYBER768_CIPHERTEXT_LEN: usize = ffi::KYBER768_CIPHERTEXT_BYTES as usize;
+
+/// The length of a shared secret, in bytes.
+pub const SHARED_SECRET_LEN: usize = ffi::KYBER_KEY_BYTES as usize;
+
+/// The offset of the public key within an encoded private key.
+const PUBLIC_KEY_OFFSET: usize = 1152;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A variant of the 768-bit KEM, either [`MlKem768`] or [`Kyber768`].
+pub trait Kem768: private::Sealed {
+    #[doc(hidden)]
+    const MLKEM: c_int;
+}
+
+/// ML-KEM-768, as standardized in FIPS 203.
+pub enum MlKem768 {}
+
+impl private::Sealed for MlKem768 {}
+
+impl Kem768 for MlKem768 {
+    const MLKEM: c_int = 1;
+}
+
+/// Kyber768, the round 3 submission ML-KEM-768 was standardized from.
+pub enum Kyber768 {}
+
+impl private::Sealed for Kyber768 {}
+
+impl Kem768 for Kyber768 {
+    const MLKEM: c_int = 0;
+}
+
+/// An ML-KEM-768 private key.
+pub type MlKem768PrivateKey = Kem768PrivateKey<MlKem768>;
+
+/// An ML-KEM-768 public key.
+pub type MlKem768PublicKey = Kem768PublicKey<MlKem768>;
+
+/// A Kyber768 private key.
+pub type Kyber768PrivateKey = Kem768PrivateKey<Kyber768>;
+
+/// A Kyber768 public key.
+pub type Kyber768PublicKey = Kem768PublicKey<Kyber768>;
+
+/// A private key of the variant `K` of the 768-bit KEM.
+pub struct Kem768PrivateKey<K: Kem768> {
+    key: Box<ffi::KYBER768_private_key>,
+    _p: PhantomData<K>,
+}
+
+impl<K: Kem768> Kem768PrivateKey<K> {
+    /// Generates a new random key pair.
+    #[corresponds(KYBER768_generate_key)]
+    #[must_use]
+    pub fn generate() -> (Kem768PrivateKey<K>, Kem768PublicKey<K>) {
+        let mut seed = [0; ffi::KYBER_GENERATE_KEY_BYTES as usize];
+        unsafe {
+            ffi::init();
+            let mut private_key = Box::new(mem::zeroed::<ffi::KYBER768_private_key>());
+            let mut public_key = Box::new(mem::zeroed::<ffi::KYBER768_public_key>());
+            ffi::RAND_bytes(seed.as_mut_ptr(), seed.len());
+            ffi::KYBER768_generate_key(&mut *public_key, &mut *private_key, seed.as_ptr());
+            ffi::OPENSSL_cleanse(seed.as_mut_ptr().cast(), seed.len());
+            (
+                Kem768PrivateKey::from_raw(private_key),
+                Kem768PublicKey::from_raw(public_key),
+            )
+        }
+    }
+
+    /// Parses a private key in the standard encoding, which FIPS 203 calls the decapsulation
+    /// key.
+    ///
+    /// The encoding is not validated. Decapsulating with a malformed key yields secrets the
+    /// sender does not know.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; KYBER768_PRIVATE_KEY_LEN]) -> Kem768PrivateKey<K> {
+        Kem768PrivateKey::from_raw(Box::new(ffi::KYBER768_private_key { opaque: *bytes }))
+    }
+
+    fn from_raw(key: Box<ffi::KYBER768_private_key>) -> Kem768PrivateKey<K> {
+        Kem768PrivateKey {
+            key,
+            _p: PhantomData,
+        }
+    }
+
+    /// Returns the standard encoding of the private key.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; KYBER768_PRIVATE_KEY_LEN] {
+        self.key.opaque
+    }
+
+    /// Returns the public key corresponding to this private key.
+    #[must_use]
+    pub fn public_key(&self) -> Kem768PublicKey<K> {
+        let mut public_key = [0; KYBER768_PUBLIC_KEY_LEN];
+        public_key.copy_from_slice(
+            &self.key.opaque[PUBLIC_KEY_OFFSET..PUBLIC_KEY_OFFSET + KYBER768_PUBLIC_KEY_LEN],
+        );
+        Kem768PublicKey::from_bytes(&public_key)
+    }
+
+    /// Recovers the shared secret from `ciphertext`.
+    ///
+    /// An invalid ciphertext does not produce an error. Instead, the result is a secret that the
+    /// sender does not know, so the failure surfaces when the secret is first used with an
+    /// authenticated cipher.
+    #[corresponds(KYBER768_decap)]
+    #[must_use]
+    pub fn decapsulate(&self, ciphertext: &[u8; KYBER768_CIPHERTEXT_LEN]) -> SharedSecret {
+        let mut secret = SharedSecret([0; SHARED_SECRET_LEN]);
+        unsafe {
+            ffi::KYBER768_decap(
+                secret.0.as_mut_ptr(),
+                &*self.key,
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+                K::MLKEM,
+            );
+        }
+        secret
+    }
+
+    fn cleanse(&mut self) {
+        unsafe {
+            ffi::OPENSSL_cleanse(self.key.opaque.as_mut_ptr().cast(), self.key.opaque.len());
+        }
+    }
+}
+
+impl<K: Kem768> Drop for Kem768PrivateKey<K> {
+    fn drop(&mut self) {
+        self.cleanse();
+    }
+}
+
+/// Zeroizing the key leaves it unusable until it is dropped.
+#[cfg(feature = "zeroize")]
+impl<K: Kem768> zeroize::Zeroize for Kem768PrivateKey<K> {
+    fn zeroize(&mut self) {
+        self.cleanse();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<K: Kem768> zeroize::ZeroizeOnDrop for Kem768PrivateKey<K> {}
+
+impl<K: Kem768> fmt::Debug for Kem768PrivateKey<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Kem768PrivateKey")
+    }
+}
+
+/// A public key of the variant `K` of the 768-bit KEM.
+pub struct Kem768PublicKey<K: Kem768> {
+    key: Box<ffi::KYBER768_public_key>,
+    _p: PhantomData<K>,
+}
+
+impl<K: Kem768> Kem768PublicKey<K> {
+    /// Parses a public key in the standard encoding, which FIPS 203 calls the encapsulation key.
+    ///
+    /// The encoding is checked by [`Self::encapsulate`].
+    #[corresponds(KYBER768_parse_public_key)]
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; KYBER768_PUBLIC_KEY_LEN]) -> Kem768PublicKey<K> {
+        unsafe {
+            let mut public_key = Box::new(mem::zeroed::<ffi::KYBER768_public_key>());
+            ffi::KYBER768_parse_public_key(&mut *public_key, bytes.as_ptr());
+            Kem768PublicKey::from_raw(public_key)
+        }
+    }
+
+    fn from_raw(key: Box<ffi::KYBER768_public_key>) -> Kem768PublicKey<K> {
+        Kem768PublicKey {
+            key,
+            _p: PhantomData,
+        }
+    }
+
+    /// Returns the standard encoding of the public key.
+    #[corresponds(KYBER768_marshal_public_key)]
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; KYBER768_PUBLIC_KEY_LEN] {
+        let mut out = [0; KYBER768_PUBLIC_KEY_LEN];
+        unsafe { ffi::KYBER768_marshal_public_key(out.as_mut_ptr(), &*self.key) };
+        out
+    }
+
+    /// Generates a random shared secret and encapsulates it to this public key.
+    ///
+    /// Returns the ciphertext to send to the holder of the private key, and the shared secret.
+    /// Fails if BoringSSL rejects the encoding of the public key.
+    #[corresponds(KYBER768_encap)]
+    pub fn encapsulate(&self) -> Result<([u8; KYBER768_CIPHERTEXT_LEN], SharedSecret), ErrorStack> {
+        let mut ciphertext = [0; KYBER768_CIPHERTEXT_LEN];
+        let mut secret = SharedSecret([0; SHARED_SECRET_LEN]);
+        let mut seed = [0; ffi::KYBER_ENCAP_BYTES as usize];
+        let ok = unsafe {
+            ffi::init();
+            ffi::RAND_bytes(seed.as_mut_ptr(), seed.len());
+            let ok = ffi::KYBER768_encap(
+                ciphertext.as_mut_ptr(),
+                secret.0.as_mut_ptr(),
+                &*self.key,
+                seed.as_ptr(),
+                K::MLKEM,
+            );
+            ffi::OPENSSL_cleanse(seed.as_mut_ptr().cast(), seed.len());
+            ok
+        };
+
+        if ok != 1 {
+            return Err(ErrorStack::internal_error_str("invalid public key"));
+        }
+
+        Ok((ciphertext, secret))
+    }
+}
+
+impl<K: Kem768> Clone for Kem768PublicKey<K> {
+    fn clone(&self) -> Kem768PublicKey<K> {
+        Kem768PublicKey::from_raw(Box::new(ffi::KYBER768_public_key {
+            opaque: self.key.opaque,
+        }))
+    }
+}
+
+impl<K: Kem768> fmt::Debug for Kem768PublicKey<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Kem768PublicKey")
+    }
+}
+
+/// A shared secret established by a KEM.
+pub struct SharedSecret([u8; SHARED_SECRET_LEN]);
+
+impl SharedSecret {
+    /// Returns the raw bytes of the shared secret.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; SHARED_SECRET_LEN] {
+        &self.0
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        unsafe { ffi::OPENSSL_cleanse(self.0.as_mut_ptr().cast(), self.0.len()) }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SharedSecret {
+    fn zeroize(&mut self) {
+        unsafe { ffi::OPENSSL_cleanse(self.0.as_mut_ptr().cast(), self.0.len()) }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SharedSecret {}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SharedSecret")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn encapsulate_decapsulate<K: Kem768>() {
+        let (private_key, public_key) = Kem768PrivateKey::<K>::generate();
+        let (ciphertext, secret) = public_key.encapsulate().unwrap();
+        assert_eq!(
+            private_key.decapsulate(&ciphertext).as_bytes(),
+            secret.as_bytes()
+        );
+
+        let mut corrupted = ciphertext;
+        corrupted[0] ^= 1;
+        assert_ne!(
+            private_key.decapsulate(&corrupted).as_bytes(),
+            secret.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_encapsulate_decapsulate() {
+        encapsulate_decapsulate::<MlKem768>();
+        encapsulate_decapsulate::<Kyber768>();
+    }
+
+    #[test]
+    fn test_variants_differ() {
+        let (private_key, public_key) = MlKem768PrivateKey::generate();
+        let (ciphertext, secret) = public_key.encapsulate().unwrap();
+
+        let private_key = Kyber768PrivateKey::from_bytes(&private_key.to_bytes());
+        assert_ne!(
+            private_key.decapsulate(&ciphertext).as_bytes(),
+            secret.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_key_encoding() {
+        let (private_key, public_key) = MlKem768PrivateKey::generate();
+
+        let private_key = MlKem768PrivateKey::from_bytes(&private_key.to_bytes());
+        let public_key = MlKem768PublicKey::from_bytes(&public_key.to_bytes());
+        assert_eq!(public_key.to_bytes(), private_key.public_key().to_bytes());
+
+        let (ciphertext, secret) = public_key.clone().encapsulate().unwrap();
+        assert_eq!(
+            private_key.decapsulate(&ciphertext).as_bytes(),
+            secret.as_bytes()
+        );
+    }
+}
@@ -102,7 +102,21 @@
 extern crate bitflags;
 #[macro_use]
 extern crate foreign_types;
+#[cfg(not(feature = "raw-ffi"))]
 extern crate boring_sys as ffi;
+/// The raw bindings to BoringSSL this crate is built on, for calling functions it does not wrap
+/// yet.
+///
+/// This is the `boring-sys` crate of the same version, so code using it does not need to depend
+/// on `boring-sys` and keep its version in step with `boring`. The bindings are generated from
+/// the vendored BoringSSL revision, and revision bumps are released as major versions of
+/// `boring`, so items are only removed or change signature in major versions. Which items exist
+/// also depends on features such as `fips` and `rpk`.
+///
+/// With the `prefix-symbols` feature, the bindings link to names prefixed with
+/// [`ffi::SYMBOL_PREFIX`], which code declaring its own `extern` blocks must use as well.
+#[cfg(feature = "raw-ffi")]
+pub extern crate boring_sys as ffi;
 extern crate libc;
 
 #[cfg(test)]
@@ -121,13 +135,20 @@ use crate::error::ErrorStack;
 mod macros;
 
 mod bio;
+mod cbs;
 #[macro_use]
 mod util;
+pub mod aead;
 pub mod aes;
 pub mod asn1;
 pub mod base64;
 pub mod bn;
+pub mod chacha;
+pub mod cmac;
 pub mod conf;
+pub mod cpu;
+#[cfg(feature = "ct")]
+pub mod ct;
 pub mod derive;
 pub mod dh;
 pub mod dsa;
@@ -139,8 +160,15 @@ pub mod fips;
 pub mod hash;
 pub mod hmac;
 pub mod hpke;
+pub mod kdf;
+pub mod kem;
+#[cfg(feature = "mem-hooks")]
+pub mod mem;
 pub mod memcmp;
 pub mod nid;
+#[cfg(feature = "openssl-compat")]
+pub mod openssl_compat;
+pub mod pem;
 pub mod pkcs12;
 pub mod pkcs5;
 pub mod pkey;
@@ -148,12 +176,15 @@ pub mod rand;
 pub mod rsa;
 pub mod sha;
 pub mod sign;
+pub mod siphash;
 pub mod srtp;
 pub mod ssl;
 pub mod stack;
 pub mod string;
 pub mod symm;
+pub mod trust_token;
 pub mod version;
+pub mod x25519;
 pub mod x509;
 
 fn cvt_p<T>(r: *mut T) -> Result<*mut T, ErrorStack> {
@@ -0,0 +1,139 @@
+//! Routing of BoringSSL's heap allocations.
+//!
+//! BoringSSL allocates through `malloc` unless the process defines the `OPENSSL_memory_alloc`,
+//! `OPENSSL_memory_free` and `OPENSSL_memory_get_size` hooks, which this module does. Allocations
+//! are then served by the allocator registered with [`set_allocator`], or the system allocator if
+//! none is, and the bytes held by BoringSSL are counted by [`allocated_bytes`].
+//!
+//! BoringSSL only looks for the hooks on ELF targets such as Linux, Android and the BSDs. On other
+//! targets, allocations keep going through `malloc` and nothing is counted.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::alloc::System;
+//!
+//! boring::mem::set_allocator(&System).unwrap();
+//!
+//! let _key = boring::rsa::Rsa::generate(2048).unwrap();
+//! println!("BoringSSL holds {} bytes", boring::mem::allocated_bytes());
+//! ```
+use crate::ffi;
+use libc::c_void;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// The alignment `malloc` guarantees on 64-bit targets, which BoringSSL relies on.
+const ALIGN: usize = 16;
+
+/// The size of the prefix storing the size of an allocation, which preserves its alignment.
+const PREFIX: usize = ALIGN;
+
+static ALLOCATOR: OnceLock<&'static (dyn GlobalAlloc + Sync)> = OnceLock::new();
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Registers the allocator serving BoringSSL's allocations.
+///
+/// The allocator is called while BoringSSL holds its own locks, from any thread, including in
+/// thread destructors. It must not call into BoringSSL.
+///
+/// This must be called before BoringSSL first allocates, typically at the start of `main`, and
+/// can only be called once. Passing the `#[global_allocator]` of the program routes BoringSSL's
+/// allocations through it.
+pub fn set_allocator(
+    allocator: &'static (dyn GlobalAlloc + Sync),
+) -> Result<(), AllocatorAlreadySet> {
+    ALLOCATOR.set(allocator).map_err(|_| AllocatorAlreadySet)
+}
+
+/// Returns the number of bytes currently allocated by BoringSSL.
+///
+/// The prefixes the hooks add to allocations are not counted.
+#[must_use]
+pub fn allocated_bytes() -> usize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+/// The error returned by [`set_allocator`] when BoringSSL has already allocated or an allocator
+/// was already registered.
+#[derive(Debug)]
+pub struct AllocatorAlreadySet;
+
+impl fmt::Display for AllocatorAlreadySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an allocator is already in use by BoringSSL")
+    }
+}
+
+impl Error for AllocatorAlreadySet {}
+
+fn allocator() -> &'static (dyn GlobalAlloc + Sync) {
+    *ALLOCATOR.get_or_init(|| &System)
+}
+
+fn layout(size: usize) -> Option<Layout> {
+    Layout::from_size_align(size.checked_add(PREFIX)?, ALIGN).ok()
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+extern "C" fn OPENSSL_memory_alloc(size: usize) -> *mut c_void {
+    let Some(layout) = layout(size) else {
+        return std::ptr::null_mut();
+    };
+
+    unsafe {
+        let ptr = allocator().alloc(layout);
+        if ptr.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        ptr.cast::<usize>().write(size);
+        ALLOCATED.fetch_add(size, Ordering::Relaxed);
+
+        ptr.add(PREFIX).cast()
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+unsafe extern "C" fn OPENSSL_memory_free(ptr: *mut c_void) {
+    unsafe {
+        let ptr = ptr.cast::<u8>().sub(PREFIX);
+        let size = ptr.cast::<usize>().read();
+        let layout = Layout::from_size_align_unchecked(size + PREFIX, ALIGN);
+
+        // BoringSSL leaves zeroing freed memory to the hooks. `OPENSSL_cleanse` neither
+        // allocates nor locks, so it is safe to call here.
+        ffi::OPENSSL_cleanse(ptr.cast(), layout.size());
+        allocator().dealloc(ptr, layout);
+
+        ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+unsafe extern "C" fn OPENSSL_memory_get_size(ptr: *mut c_void) -> usize {
+    unsafe { ptr.cast::<u8>().sub(PREFIX).cast::<usize>().read() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn::BigNum;
+
+    #[test]
+    fn allocated_bytes() {
+        let _bn = BigNum::from_u32(1).unwrap();
+
+        if cfg!(target_os = "linux") {
+            assert!(super::allocated_bytes() > 0);
+            assert!(set_allocator(&System).is_err());
+        }
+    }
+}
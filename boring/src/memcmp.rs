@@ -1,4 +1,4 @@
-//! Utilities to safely compare cryptographic values.
+//! Utilities to safely compare and handle cryptographic values.
 //!
 //! Extra care must be taken when comparing values in
 //! cryptographic code. If done incorrectly, it can lead
@@ -31,6 +31,8 @@
 //! ```
 use crate::ffi;
 use libc::size_t;
+use openssl_macros::corresponds;
+use std::hint::black_box;
 
 /// Returns `true` iff `a` and `b` contain the same bytes.
 ///
@@ -74,9 +76,44 @@ pub fn eq(a: &[u8], b: &[u8]) -> bool {
     ret == 0
 }
 
+/// Writes `a` to `out` if `choice` is `true`, and `b` otherwise.
+///
+/// The selection is made without branching on `choice`, so it does not leak which input was
+/// chosen through timing.
+///
+/// # Panics
+///
+/// This function will panic the current task if `a`, `b` and `out` do not all have the same
+/// length.
+///
+/// # Examples
+///
+/// ```
+/// use boring::memcmp::select;
+///
+/// let mut out = [0; 3];
+/// select(false, &[1, 2, 3], &[4, 5, 6], &mut out);
+/// assert_eq!(out, [4, 5, 6]);
+/// ```
+pub fn select(choice: bool, a: &[u8], b: &[u8], out: &mut [u8]) {
+    assert!(a.len() == b.len() && a.len() == out.len());
+    let mask = black_box(0u8.wrapping_sub(choice as u8));
+    for ((out, a), b) in out.iter_mut().zip(a).zip(b) {
+        *out = (a & mask) | (b & !mask);
+    }
+}
+
+/// Overwrites `buf` with zeros in a way the compiler will not optimize away.
+///
+/// Use this to wipe keys and other secrets from memory once they are no longer needed.
+#[corresponds(OPENSSL_cleanse)]
+pub fn cleanse(buf: &mut [u8]) {
+    unsafe { ffi::OPENSSL_cleanse(buf.as_mut_ptr().cast(), buf.len()) }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::eq;
+    use super::{cleanse, eq, select};
 
     #[test]
     fn test_eq() {
@@ -90,4 +127,20 @@ mod tests {
     fn test_diff_lens() {
         let _ = eq(&[], &[1]);
     }
+
+    #[test]
+    fn test_select() {
+        let mut out = [0; 3];
+        select(true, &[1, 2, 3], &[4, 5, 6], &mut out);
+        assert_eq!(out, [1, 2, 3]);
+        select(false, &[1, 2, 3], &[4, 5, 6], &mut out);
+        assert_eq!(out, [4, 5, 6]);
+    }
+
+    #[test]
+    fn test_cleanse() {
+        let mut buf = [0xff; 8];
+        cleanse(&mut buf);
+        assert_eq!(buf, [0; 8]);
+    }
 }
@@ -0,0 +1,167 @@
+//! The module layout of the `openssl` crate, for migrating code written against it.
+//!
+//! `boring` started as a fork of the `openssl` crate, and most of its types and methods keep
+//! their original paths and names. This module re-exports them under the modules of the `openssl`
+//! crate, and adds the commonly used items whose names differ in `boring`. Items are only
+//! mirrored where they behave the same, so code depending on OpenSSL-specific behavior still
+//! fails to compile rather than silently changing meaning.
+//!
+//! Code can be migrated by importing this module as `openssl`, along with the extension traits
+//! of the [`prelude`]:
+//!
+//! ```
+//! use boring::openssl_compat as openssl;
+//! use boring::openssl_compat::prelude::*;
+//!
+//! use openssl::pkey::PKey;
+//! use openssl::ssl::{SslContext, SslMethod};
+//!
+//! let key = PKey::generate_ed25519().unwrap();
+//!
+//! let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+//! ctx.set_groups_list("X25519:P-256").unwrap();
+//! ctx.set_private_key(&key).unwrap();
+//! ```
+pub use crate::init;
+pub use crate::{
+    aes, asn1, base64, bn, conf, derive, dh, dsa, ec, ecdsa, error, ex_data, fips, hash, memcmp,
+    nid, pkcs12, pkcs5, rsa, sha, sign, srtp, stack, string, symm, version,
+};
+
+/// The extension traits adding the methods of the `openssl` crate.
+pub mod prelude {
+    pub use super::pkey::PKeyExt as _;
+    pub use super::ssl::SslContextBuilderExt as _;
+    pub use super::x509::X509RefExt as _;
+}
+
+/// The `openssl::pkey` module.
+pub mod pkey {
+    pub use crate::pkey::*;
+
+    use crate::error::ErrorStack;
+
+    /// Methods of `openssl::pkey::PKey` missing from [`PKey`].
+    pub trait PKeyExt: Sized {
+        /// Generates a new Ed25519 key.
+        fn generate_ed25519() -> Result<Self, ErrorStack>;
+
+        /// Generates a new X25519 key.
+        fn generate_x25519() -> Result<Self, ErrorStack>;
+    }
+
+    impl PKeyExt for PKey<Private> {
+        fn generate_ed25519() -> Result<Self, ErrorStack> {
+            generate_raw(Id::ED25519)
+        }
+
+        fn generate_x25519() -> Result<Self, ErrorStack> {
+            generate_raw(Id::X25519)
+        }
+    }
+
+    fn generate_raw(key_type: Id) -> Result<PKey<Private>, ErrorStack> {
+        let mut bytes = [0; 32];
+        crate::rand::rand_bytes(&mut bytes)?;
+        let key = PKey::private_key_from_raw_bytes(&bytes, key_type);
+        crate::memcmp::cleanse(&mut bytes);
+        key
+    }
+}
+
+/// The `openssl::rand` module.
+pub mod rand {
+    pub use crate::rand::*;
+
+    use crate::error::ErrorStack;
+
+    /// Fills `buf` with cryptographically strong pseudo-random bytes meant to stay private.
+    ///
+    /// BoringSSL uses the same generator for public and private bytes, so this is
+    /// [`rand_bytes`].
+    pub fn rand_priv_bytes(buf: &mut [u8]) -> Result<(), ErrorStack> {
+        rand_bytes(buf)
+    }
+}
+
+/// The `openssl::ssl` module.
+pub mod ssl {
+    pub use crate::ssl::*;
+
+    use crate::error::ErrorStack;
+    use crate::{cvt, ffi};
+
+    /// Methods of `openssl::ssl::SslContextBuilder` missing from [`SslContextBuilder`].
+    pub trait SslContextBuilderExt {
+        /// Sets the groups offered for key exchange, such as `X25519:P-256`.
+        ///
+        /// This is [`SslContextBuilder::set_curves_list`].
+        fn set_groups_list(&mut self, groups: &str) -> Result<(), ErrorStack>;
+
+        /// Sets the number of TLS 1.3 session tickets sent after a full handshake.
+        fn set_num_tickets(&mut self, num_tickets: usize) -> Result<(), ErrorStack>;
+    }
+
+    impl SslContextBuilderExt for SslContextBuilder {
+        fn set_groups_list(&mut self, groups: &str) -> Result<(), ErrorStack> {
+            self.set_curves_list(groups)
+        }
+
+        fn set_num_tickets(&mut self, num_tickets: usize) -> Result<(), ErrorStack> {
+            unsafe { cvt(ffi::SSL_CTX_set_num_tickets(self.as_ptr(), num_tickets)).map(|_| ()) }
+        }
+    }
+}
+
+/// The `openssl::x509` module.
+pub mod x509 {
+    pub use crate::x509::*;
+
+    use crate::bio::MemBio;
+    use crate::error::ErrorStack;
+    use crate::{cvt, ffi};
+    use foreign_types::ForeignTypeRef;
+
+    /// Methods of `openssl::x509::X509Ref` missing from [`X509Ref`].
+    pub trait X509RefExt {
+        /// Returns a human-readable description of the certificate.
+        fn to_text(&self) -> Result<Vec<u8>, ErrorStack>;
+    }
+
+    impl X509RefExt for X509Ref {
+        fn to_text(&self) -> Result<Vec<u8>, ErrorStack> {
+            let bio = MemBio::new()?;
+            unsafe {
+                cvt(ffi::X509_print(bio.as_ptr(), self.as_ptr()))?;
+            }
+
+            Ok(bio.get_buf().to_owned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::prelude::*;
+    use super::*;
+
+    #[test]
+    fn extensions() {
+        let key = pkey::PKey::generate_ed25519().unwrap();
+        assert_eq!(key.id(), pkey::Id::ED25519);
+        let key = pkey::PKey::generate_x25519().unwrap();
+        assert_eq!(key.id(), pkey::Id::X25519);
+
+        let mut buf = [0; 16];
+        rand::rand_priv_bytes(&mut buf).unwrap();
+
+        let mut ctx = ssl::SslContext::builder(ssl::SslMethod::tls()).unwrap();
+        ctx.set_groups_list("X25519:P-256").unwrap();
+        assert!(ctx.set_groups_list("bogus").is_err());
+        ctx.set_num_tickets(1).unwrap();
+
+        let cert = x509::X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let text = cert.to_text().unwrap();
+        assert!(String::from_utf8(text).unwrap().contains("foobar.com"));
+    }
+}
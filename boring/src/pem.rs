@@ -0,0 +1,230 @@
+//! Parsing of PEM bundles.
+//!
+//! A bundle holds several PEM blocks, such as a private key followed by its certificate chain.
+//! [`parse_bundle`] yields the blocks of a bundle in order, skipping any text around them.
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::pem::{self, PemItem};
+//!
+//! let bundle = [
+//!     include_bytes!("../test/key.pem").as_slice(),
+//!     include_bytes!("../test/cert.pem"),
+//! ]
+//! .concat();
+//!
+//! let mut key = None;
+//! let mut chain = vec![];
+//!
+//! for item in pem::parse_bundle(&bundle).unwrap() {
+//!     match item.unwrap() {
+//!         PemItem::PrivateKey(pkey) => key = Some(pkey),
+//!         PemItem::Certificate(cert) => chain.push(cert),
+//!         _ => {}
+//!     }
+//! }
+//!
+//! assert!(key.is_some());
+//! assert_eq!(chain.len(), 1);
+//! ```
+use crate::bio::MemBioSlice;
+use crate::ec::EcKey;
+use crate::error::ErrorStack;
+use crate::ffi;
+use crate::pkey::{PKey, Private};
+use crate::rsa::Rsa;
+use crate::x509::{X509Crl, X509Req, X509};
+use libc::{c_char, c_long};
+use openssl_macros::corresponds;
+use std::ffi::CStr;
+use std::fmt;
+use std::ptr;
+use std::slice;
+
+/// A block of a PEM bundle.
+#[non_exhaustive]
+pub enum PemItem {
+    /// A `CERTIFICATE` or `X509 CERTIFICATE` block.
+    Certificate(X509),
+    /// A `PRIVATE KEY`, `RSA PRIVATE KEY` or `EC PRIVATE KEY` block.
+    PrivateKey(PKey<Private>),
+    /// An `X509 CRL` block.
+    Crl(X509Crl),
+    /// A `CERTIFICATE REQUEST` or `NEW CERTIFICATE REQUEST` block.
+    CertificateRequest(X509Req),
+    /// Any other block, such as an `ENCRYPTED PRIVATE KEY`, or a block with headers.
+    Unknown {
+        /// The label of the block, such as `ENCRYPTED PRIVATE KEY`.
+        label: String,
+        /// The decoded contents of the block.
+        der: Vec<u8>,
+    },
+}
+
+impl fmt::Debug for PemItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PemItem::Certificate(cert) => f.debug_tuple("Certificate").field(cert).finish(),
+            PemItem::PrivateKey(pkey) => f.debug_tuple("PrivateKey").field(pkey).finish(),
+            PemItem::Crl(_) => f.write_str("Crl"),
+            PemItem::CertificateRequest(_) => f.write_str("CertificateRequest"),
+            PemItem::Unknown { label, .. } => {
+                f.debug_struct("Unknown").field("label", label).finish()
+            }
+        }
+    }
+}
+
+/// Returns an iterator over the blocks of the PEM bundle `pem`.
+///
+/// A block that fails to parse is yielded as an error, and the following blocks are still
+/// yielded. Reading stops at the first block whose base64 encoding is invalid.
+#[corresponds(PEM_read_bio)]
+pub fn parse_bundle(pem: &[u8]) -> Result<PemItems<'_>, ErrorStack> {
+    Ok(PemItems {
+        bio: MemBioSlice::new(pem)?,
+        done: false,
+    })
+}
+
+/// An iterator over the blocks of a PEM bundle.
+///
+/// See [`parse_bundle`].
+pub struct PemItems<'a> {
+    bio: MemBioSlice<'a>,
+    done: bool,
+}
+
+impl Iterator for PemItems<'_> {
+    type Item = Result<PemItem, ErrorStack>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let item = unsafe { read_item(&self.bio) };
+
+        match item {
+            Ok(Some(item)) => Some(item),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads the next block of `bio`, returning `None` once there are none left.
+unsafe fn read_item(
+    bio: &MemBioSlice<'_>,
+) -> Result<Option<Result<PemItem, ErrorStack>>, ErrorStack> {
+    let mut name: *mut c_char = ptr::null_mut();
+    let mut header: *mut c_char = ptr::null_mut();
+    let mut data: *mut u8 = ptr::null_mut();
+    let mut len: c_long = 0;
+
+    if ffi::PEM_read_bio(bio.as_ptr(), &mut name, &mut header, &mut data, &mut len) == 0 {
+        let err = ffi::ERR_peek_last_error();
+
+        if ffi::ERR_GET_LIB(err) == ffi::ERR_LIB_PEM.0.try_into().unwrap()
+            && ffi::ERR_GET_REASON(err) == ffi::PEM_R_NO_START_LINE
+        {
+            ErrorStack::clear();
+            return Ok(None);
+        }
+
+        return Err(ErrorStack::get());
+    }
+
+    let label = CStr::from_ptr(name).to_string_lossy();
+    let has_header = *header != 0;
+    let der = slice::from_raw_parts(data, len as usize);
+
+    let item = if has_header {
+        Ok(PemItem::Unknown {
+            label: label.into_owned(),
+            der: der.to_vec(),
+        })
+    } else {
+        parse_item(&label, der)
+    };
+
+    // `OPENSSL_free` zeroes the decoded data, which may be a private key.
+    ffi::OPENSSL_free(name.cast());
+    ffi::OPENSSL_free(header.cast());
+    ffi::OPENSSL_free(data.cast());
+
+    Ok(Some(item))
+}
+
+fn parse_item(label: &str, der: &[u8]) -> Result<PemItem, ErrorStack> {
+    let item = match label {
+        "CERTIFICATE" | "X509 CERTIFICATE" => PemItem::Certificate(X509::from_der(der)?),
+        "PRIVATE KEY" => PemItem::PrivateKey(PKey::private_key_from_pkcs8(der)?),
+        "RSA PRIVATE KEY" => PemItem::PrivateKey(PKey::from_rsa(Rsa::private_key_from_der(der)?)?),
+        "EC PRIVATE KEY" => {
+            PemItem::PrivateKey(PKey::from_ec_key(EcKey::private_key_from_der(der)?)?)
+        }
+        "X509 CRL" => PemItem::Crl(X509Crl::from_der(der)?),
+        "CERTIFICATE REQUEST" | "NEW CERTIFICATE REQUEST" => {
+            PemItem::CertificateRequest(X509Req::from_der(der)?)
+        }
+        _ => PemItem::Unknown {
+            label: label.to_owned(),
+            der: der.to_vec(),
+        },
+    };
+
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mixed_bundle() {
+        let bundle = [
+            b"# A server identity\n".as_slice(),
+            include_bytes!("../test/cert.pem"),
+            b"\nsome notes\n",
+            include_bytes!("../test/key.pem"),
+            include_bytes!("../test/crl-empty.pem"),
+            include_bytes!("../test/rsa-encrypted.pem"),
+            include_bytes!("../test/dhparams.pem"),
+        ]
+        .concat();
+
+        let items = parse_bundle(&bundle)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(items.len(), 5);
+        assert!(matches!(items[0], PemItem::Certificate(_)));
+        assert!(matches!(items[1], PemItem::PrivateKey(_)));
+        assert!(matches!(items[2], PemItem::Crl(_)));
+        assert!(matches!(&items[3], PemItem::Unknown { label, .. } if label == "RSA PRIVATE KEY"));
+        assert!(matches!(&items[4], PemItem::Unknown { label, .. } if label == "DH PARAMETERS"));
+    }
+
+    #[test]
+    fn invalid_block() {
+        let bundle = [
+            b"-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n".as_slice(),
+            include_bytes!("../test/cert.pem"),
+        ]
+        .concat();
+
+        let mut items = parse_bundle(&bundle).unwrap();
+        assert!(items.next().unwrap().is_err());
+        assert!(matches!(items.next(), Some(Ok(PemItem::Certificate(_)))));
+        assert!(items.next().is_none());
+    }
+}
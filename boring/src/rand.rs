@@ -12,6 +12,8 @@
 //! ```
 use crate::ffi;
 use libc::c_int;
+#[cfg(feature = "unsafe-deterministic-mode")]
+use openssl_macros::corresponds;
 
 use crate::cvt;
 use crate::error::ErrorStack;
@@ -40,6 +42,74 @@ pub fn rand_bytes(buf: &mut [u8]) -> Result<(), ErrorStack> {
     }
 }
 
+/// A [`rand_core`] RNG backed by [`rand_bytes`].
+///
+/// This lets code that is generic over `rand_core::RngCore` draw from BoringSSL's DRBG, which is
+/// the FIPS-validated one in FIPS builds.
+///
+/// ```
+/// use boring::rand::BoringRng;
+/// use rand_core::RngCore;
+///
+/// let mut rng = BoringRng;
+/// let mut buf = [0; 32];
+/// rng.fill_bytes(&mut buf);
+/// ```
+#[cfg(feature = "rand_core")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct BoringRng;
+
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for BoringRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0; 4];
+        self.fill_bytes(&mut buf);
+        u32::from_ne_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0; 8];
+        self.fill_bytes(&mut buf);
+        u64::from_ne_bytes(buf)
+    }
+
+    /// Fills `dest` with random bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `RAND_bytes` fails, which BoringSSL only does on allocation failure.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(c_int::MAX as usize) {
+            rand_bytes(chunk).expect("RAND_bytes failed");
+        }
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::CryptoRng for BoringRng {}
+
+/// Restarts the fixed sequence of seeds of the random number generator.
+///
+/// With the `unsafe-deterministic-mode` feature, BoringSSL seeds the generator of a thread with
+/// the next seed of a fixed sequence when the thread first draws random bytes. After this call,
+/// the first thread to seed its generator draws the same bytes, and so generates the same keys
+/// and handshakes, as the first one after any previous call. Threads which already drew random
+/// bytes keep their generator, so reproducible runs should each start on a new thread.
+///
+/// The generator has no seed to set. BoringSSL derives each seed from ChaCha20 with an all-zero
+/// key and the number of seeds drawn so far as the nonce, and this function only resets that
+/// count. To draw other bytes from the start of a run, draw and discard some first.
+///
+/// **Never** use this outside of tests and fuzzing: the bytes are predictable.
+#[cfg(feature = "unsafe-deterministic-mode")]
+#[corresponds(RAND_reset_for_fuzzing)]
+pub fn reset_deterministic_rng() {
+    unsafe {
+        ffi::init();
+        ffi::RAND_reset_for_fuzzing();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::rand_bytes;
@@ -49,4 +119,16 @@ mod tests {
         let mut buf = [0; 32];
         rand_bytes(&mut buf).unwrap();
     }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn test_boring_rng() {
+        use rand_core::RngCore;
+
+        let mut rng = super::BoringRng;
+        let mut buf = [0; 64];
+        rng.fill_bytes(&mut buf);
+        assert_ne!(buf, [0; 64]);
+        assert_ne!(rng.next_u64(), rng.next_u64());
+    }
 }
@@ -51,6 +51,7 @@ use crate::{cvt, cvt_p};
 use crate::ffi::{EVP_MD_CTX_free, EVP_MD_CTX_new};
 
 /// Salt lengths that must be used with `set_rsa_pss_saltlen`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct RsaPssSaltlen(c_int);
 
 impl RsaPssSaltlen {
@@ -71,6 +72,50 @@ impl RsaPssSaltlen {
     /// The salt length is set to the maximum permissible value.
     /// Corresponds to the special value `-2`.
     pub const MAXIMUM_LENGTH: RsaPssSaltlen = RsaPssSaltlen(-2);
+    /// When verifying, the salt length is recovered from the signature.
+    /// Corresponds to the special value `-2`, which is `MAXIMUM_LENGTH` when signing.
+    pub const AUTO: RsaPssSaltlen = RsaPssSaltlen(-2);
+}
+
+/// RSA-PSS parameters, applied with `Signer::set_rsa_pss` or `Verifier::set_rsa_pss`.
+///
+/// BoringSSL does not support RSA-PSS-only (`rsassaPss`) keys. PSS is instead selected per
+/// operation on an ordinary RSA key.
+#[derive(Copy, Clone)]
+pub struct RsaPssParams {
+    salt_len: RsaPssSaltlen,
+    mgf1_md: Option<MessageDigest>,
+}
+
+impl RsaPssParams {
+    /// Creates parameters with a salt as long as the digest and MGF1 over the signing digest.
+    #[must_use]
+    pub fn new() -> RsaPssParams {
+        RsaPssParams {
+            salt_len: RsaPssSaltlen::DIGEST_LENGTH,
+            mgf1_md: None,
+        }
+    }
+
+    /// Sets the salt length.
+    #[must_use]
+    pub fn salt_len(mut self, salt_len: RsaPssSaltlen) -> RsaPssParams {
+        self.salt_len = salt_len;
+        self
+    }
+
+    /// Sets the digest used by MGF1.
+    #[must_use]
+    pub fn mgf1_md(mut self, md: MessageDigest) -> RsaPssParams {
+        self.mgf1_md = Some(md);
+        self
+    }
+}
+
+impl Default for RsaPssParams {
+    fn default() -> RsaPssParams {
+        RsaPssParams::new()
+    }
 }
 
 /// A type which computes cryptographic signatures of data.
@@ -206,6 +251,42 @@ impl<'a> Signer<'a> {
         }
     }
 
+    /// Returns the RSA PSS salt length.
+    ///
+    /// This is only useful for RSA keys.
+    #[corresponds(EVP_PKEY_CTX_get_rsa_pss_saltlen)]
+    pub fn rsa_pss_saltlen(&self) -> Result<RsaPssSaltlen, ErrorStack> {
+        unsafe {
+            let mut len = 0;
+            cvt(ffi::EVP_PKEY_CTX_get_rsa_pss_saltlen(self.pctx, &mut len))
+                .map(|_| RsaPssSaltlen(len))
+        }
+    }
+
+    /// Returns the RSA MGF1 algorithm.
+    ///
+    /// This is only useful for RSA keys.
+    #[corresponds(EVP_PKEY_CTX_get_rsa_mgf1_md)]
+    pub fn rsa_mgf1_md(&self) -> Result<MessageDigest, ErrorStack> {
+        unsafe {
+            let mut md = ptr::null();
+            cvt(ffi::EVP_PKEY_CTX_get_rsa_mgf1_md(self.pctx, &mut md))
+                .map(|_| MessageDigest::from_ptr(md))
+        }
+    }
+
+    /// Configures RSA-PSS padding with the given salt length and MGF1 digest.
+    ///
+    /// This is only useful for RSA keys.
+    pub fn set_rsa_pss(&mut self, params: &RsaPssParams) -> Result<(), ErrorStack> {
+        self.set_rsa_padding(Padding::PKCS1_PSS)?;
+        self.set_rsa_pss_saltlen(params.salt_len)?;
+        if let Some(md) = params.mgf1_md {
+            self.set_rsa_mgf1_md(md)?;
+        }
+        Ok(())
+    }
+
     /// Feeds more data into the `Signer`.
     ///
     /// Please note that PureEdDSA (Ed25519 and Ed448 keys) do not support streaming.
@@ -453,6 +534,42 @@ impl<'a> Verifier<'a> {
         }
     }
 
+    /// Returns the RSA PSS salt length.
+    ///
+    /// This is only useful for RSA keys.
+    #[corresponds(EVP_PKEY_CTX_get_rsa_pss_saltlen)]
+    pub fn rsa_pss_saltlen(&self) -> Result<RsaPssSaltlen, ErrorStack> {
+        unsafe {
+            let mut len = 0;
+            cvt(ffi::EVP_PKEY_CTX_get_rsa_pss_saltlen(self.pctx, &mut len))
+                .map(|_| RsaPssSaltlen(len))
+        }
+    }
+
+    /// Returns the RSA MGF1 algorithm.
+    ///
+    /// This is only useful for RSA keys.
+    #[corresponds(EVP_PKEY_CTX_get_rsa_mgf1_md)]
+    pub fn rsa_mgf1_md(&self) -> Result<MessageDigest, ErrorStack> {
+        unsafe {
+            let mut md = ptr::null();
+            cvt(ffi::EVP_PKEY_CTX_get_rsa_mgf1_md(self.pctx, &mut md))
+                .map(|_| MessageDigest::from_ptr(md))
+        }
+    }
+
+    /// Configures RSA-PSS padding with the given salt length and MGF1 digest.
+    ///
+    /// This is only useful for RSA keys.
+    pub fn set_rsa_pss(&mut self, params: &RsaPssParams) -> Result<(), ErrorStack> {
+        self.set_rsa_padding(Padding::PKCS1_PSS)?;
+        self.set_rsa_pss_saltlen(params.salt_len)?;
+        if let Some(md) = params.mgf1_md {
+            self.set_rsa_mgf1_md(md)?;
+        }
+        Ok(())
+    }
+
     /// Feeds more data into the `Verifier`.
     ///
     /// Please note that PureEdDSA (Ed25519 and Ed448 keys) do not support streaming.
@@ -524,7 +641,7 @@ use crate::ffi::EVP_DigestVerifyFinal;
 
 #[cfg(test)]
 mod test {
-    use super::RsaPssSaltlen;
+    use super::{RsaPssParams, RsaPssSaltlen};
     use hex::{self, FromHex};
 
     use crate::ec::{EcGroup, EcKey};
@@ -626,4 +743,34 @@ mod test {
         verifier.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
         assert!(verifier.verify(&signature).unwrap());
     }
+
+    #[test]
+    fn rsa_pss_params() {
+        let key = include_bytes!("../test/rsa.pem");
+        let private_key = Rsa::private_key_from_pem(key).unwrap();
+        let pkey = PKey::from_rsa(private_key).unwrap();
+        let params = RsaPssParams::new()
+            .salt_len(RsaPssSaltlen::custom(20))
+            .mgf1_md(MessageDigest::sha1());
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+        signer.set_rsa_pss(&params).unwrap();
+        assert_eq!(signer.rsa_padding().unwrap(), Padding::PKCS1_PSS);
+        assert_eq!(signer.rsa_pss_saltlen().unwrap(), RsaPssSaltlen::custom(20));
+        assert!(signer.rsa_mgf1_md().unwrap() == MessageDigest::sha1());
+        signer.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
+        let signature = signer.sign_to_vec().unwrap();
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey).unwrap();
+        verifier
+            .set_rsa_pss(&params.salt_len(RsaPssSaltlen::AUTO))
+            .unwrap();
+        verifier.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
+        assert!(verifier.verify(&signature).unwrap());
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey).unwrap();
+        verifier.set_rsa_pss(&RsaPssParams::new()).unwrap();
+        verifier.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
+        assert!(!verifier.verify(&signature).unwrap());
+    }
 }
@@ -0,0 +1,144 @@
+//! SipHash-2-4, a fast keyed hash for short inputs.
+//!
+//! SipHash is a good fit for hash tables that must resist flooding by attacker-chosen keys, and
+//! for MACs over short messages where a 64-bit tag is enough.
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::siphash::{siphash24, SipHashBuilder};
+//! use std::collections::HashMap;
+//!
+//! let key = [0x42; 16];
+//! let tag = siphash24(&key, b"message");
+//!
+//! let mut map = HashMap::with_hasher(SipHashBuilder::random().unwrap());
+//! map.insert("key", "value");
+//! ```
+use crate::error::ErrorStack;
+use crate::ffi;
+use crate::rand::rand_bytes;
+use openssl_macros::corresponds;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+
+/// The length of a SipHash key, in bytes.
+pub const KEY_LEN: usize = 16;
+
+/// Computes the SipHash-2-4 of `input` under `key`.
+#[corresponds(SIPHASH_24)]
+#[must_use]
+pub fn siphash24(key: &[u8; KEY_LEN], input: &[u8]) -> u64 {
+    let key = split_key(key);
+    unsafe { ffi::SIPHASH_24(key.as_ptr(), input.as_ptr(), input.len()) }
+}
+
+fn split_key(key: &[u8; KEY_LEN]) -> [u64; 2] {
+    let (k0, k1) = key.split_at(8);
+    [
+        u64::from_le_bytes(k0.try_into().unwrap()),
+        u64::from_le_bytes(k1.try_into().unwrap()),
+    ]
+}
+
+/// A [`Hasher`] computing SipHash-2-4.
+///
+/// BoringSSL only provides a one-shot SipHash, so written bytes are buffered until
+/// [`finish`](Hasher::finish) is called.
+#[derive(Clone)]
+pub struct SipHasher24 {
+    key: [u64; 2],
+    buf: Vec<u8>,
+}
+
+impl SipHasher24 {
+    /// Creates a hasher keyed with `key`.
+    #[must_use]
+    pub fn new(key: &[u8; KEY_LEN]) -> SipHasher24 {
+        SipHasher24 {
+            key: split_key(key),
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl Hasher for SipHasher24 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        unsafe { ffi::SIPHASH_24(self.key.as_ptr(), self.buf.as_ptr(), self.buf.len()) }
+    }
+}
+
+impl fmt::Debug for SipHasher24 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SipHasher24")
+    }
+}
+
+/// A [`BuildHasher`] producing [`SipHasher24`]s that share a key.
+#[derive(Clone)]
+pub struct SipHashBuilder {
+    key: [u8; KEY_LEN],
+}
+
+impl SipHashBuilder {
+    /// Creates a builder keyed with `key`.
+    #[must_use]
+    pub fn new(key: &[u8; KEY_LEN]) -> SipHashBuilder {
+        SipHashBuilder { key: *key }
+    }
+
+    /// Creates a builder with a random key.
+    pub fn random() -> Result<SipHashBuilder, ErrorStack> {
+        let mut key = [0; KEY_LEN];
+        rand_bytes(&mut key)?;
+        Ok(SipHashBuilder { key })
+    }
+}
+
+impl BuildHasher for SipHashBuilder {
+    type Hasher = SipHasher24;
+
+    fn build_hasher(&self) -> SipHasher24 {
+        SipHasher24::new(&self.key)
+    }
+}
+
+impl fmt::Debug for SipHashBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SipHashBuilder")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key() -> [u8; KEY_LEN] {
+        let mut key = [0; KEY_LEN];
+        key.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8);
+        key
+    }
+
+    // Test vector from the SipHash paper, appendix A.
+    #[test]
+    fn test_siphash24() {
+        let input: Vec<u8> = (0..15).collect();
+        assert_eq!(siphash24(&key(), &input), 0xa129ca6149be45e5);
+        assert_eq!(siphash24(&key(), b""), 0x726fdb47dd0e0e31);
+    }
+
+    #[test]
+    fn test_hasher() {
+        let mut hasher = SipHasher24::new(&key());
+        hasher.write(&[0, 1, 2, 3, 4]);
+        hasher.write(&[5, 6, 7, 8, 9, 10, 11, 12, 13, 14]);
+        assert_eq!(hasher.finish(), 0xa129ca6149be45e5);
+
+        let builder = SipHashBuilder::new(&key());
+        assert_eq!(builder.hash_one("abc"), builder.hash_one("abc"));
+    }
+}
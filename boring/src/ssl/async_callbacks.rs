@@ -1,8 +1,8 @@
 use super::mut_only::MutOnly;
 use super::{
-    ClientHello, GetSessionPendingError, PrivateKeyMethod, PrivateKeyMethodError, SelectCertError,
-    Ssl, SslAlert, SslContextBuilder, SslRef, SslSession, SslSignatureAlgorithm, SslVerifyError,
-    SslVerifyMode,
+    CertCallbackError, ClientHello, GetSessionPendingError, PrivateKeyMethod,
+    PrivateKeyMethodError, SelectCertError, Ssl, SslAlert, SslContextBuilder, SslRef, SslSession,
+    SslSignatureAlgorithm, SslVerifyError, SslVerifyMode,
 };
 use crate::ex_data::Index;
 use std::convert::identity;
@@ -37,6 +37,12 @@ pub type BoxCustomVerifyFuture = ExDataFuture<Result<BoxCustomVerifyFinish, SslA
 /// The type of callbacks returned by [`BoxCustomVerifyFuture`] methods.
 pub type BoxCustomVerifyFinish = Box<dyn FnOnce(&mut SslRef) -> Result<(), SslAlert>>;
 
+/// The type of futures to pass to [`SslContextBuilder::set_async_cert_callback`].
+pub type BoxCertCallbackFuture = ExDataFuture<Result<BoxCertCallbackFinish, AsyncSelectCertError>>;
+
+/// The type of callbacks returned by [`BoxCertCallbackFuture`] methods.
+pub type BoxCertCallbackFinish = Box<dyn FnOnce(&mut SslRef) -> Result<(), AsyncSelectCertError>>;
+
 /// Convenience alias for futures stored in [`Ssl`] ex data by [`SslContextBuilder`] methods.
 ///
 /// Public for documentation purposes.
@@ -53,6 +59,9 @@ pub(crate) static SELECT_PRIVATE_KEY_METHOD_FUTURE_INDEX: LazyLock<
 pub(crate) static SELECT_GET_SESSION_FUTURE_INDEX: LazyLock<
     Index<Ssl, MutOnly<Option<BoxGetSessionFuture>>>,
 > = LazyLock::new(|| Ssl::new_ex_index().unwrap());
+pub(crate) static CERT_CALLBACK_FUTURE_INDEX: LazyLock<
+    Index<Ssl, MutOnly<Option<BoxCertCallbackFuture>>>,
+> = LazyLock::new(|| Ssl::new_ex_index().unwrap());
 pub(crate) static SELECT_CUSTOM_VERIFY_FUTURE_INDEX: LazyLock<
     Index<Ssl, MutOnly<Option<BoxCustomVerifyFuture>>>,
 > = LazyLock::new(|| Ssl::new_ex_index().unwrap());
@@ -98,6 +107,46 @@ impl SslContextBuilder {
         });
     }
 
+    /// Sets a callback that is called to configure the certificate and private key for a
+    /// connection, such as a client certificate once the server has requested one.
+    ///
+    /// This method uses a function that returns a future whose output is
+    /// itself a closure that will be passed the `Ssl` to install the
+    /// certificate and key obtained in the future, for example from a
+    /// secrets manager.
+    ///
+    /// A task waker must be set on `Ssl` values associated with the resulting
+    /// `SslContext` with [`SslRef::set_task_waker`].
+    ///
+    /// See [`SslContextBuilder::set_cert_callback`] for the sync setter of
+    /// this callback.
+    pub fn set_async_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<BoxCertCallbackFuture, AsyncSelectCertError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.set_cert_callback(move |ssl| {
+            let fut_poll_result = with_ex_data_future(
+                &mut *ssl,
+                *CERT_CALLBACK_FUTURE_INDEX,
+                |ssl| ssl,
+                &callback,
+                identity,
+            );
+
+            let fut_result = match fut_poll_result {
+                Poll::Ready(fut_result) => fut_result,
+                Poll::Pending => return Err(CertCallbackError::RETRY),
+            };
+
+            let finish = fut_result.or(Err(CertCallbackError::ERROR))?;
+
+            finish(ssl).or(Err(CertCallbackError::ERROR))
+        });
+    }
+
     /// Configures a custom private key method on the context.
     ///
     /// A task waker must be set on `Ssl` values associated with the resulting
@@ -1,10 +1,11 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
 use super::{
-    AlpnError, CertificateCompressor, ClientHello, GetSessionPendingError, PrivateKeyMethod,
-    PrivateKeyMethodError, SelectCertError, SniError, Ssl, SslAlert, SslContext, SslContextRef,
-    SslInfoCallbackAlert, SslInfoCallbackMode, SslInfoCallbackValue, SslRef, SslSession,
-    SslSessionRef, SslSignatureAlgorithm, SslVerifyError, SESSION_CTX_INDEX,
+    AlpnError, CertCallbackError, CertificateCompressor, ClientHello, GetSessionPendingError,
+    PrivateKeyMethod, PrivateKeyMethodError, SelectCertError, SniError, Ssl, SslAlert,
+    SslContentType, SslContext, SslContextRef, SslInfoCallbackAlert, SslInfoCallbackMode,
+    SslInfoCallbackValue, SslRef, SslSession, SslSessionRef, SslSignatureAlgorithm, SslVerifyError,
+    SESSION_CTX_INDEX,
 };
 use crate::error::ErrorStack;
 use crate::ffi;
@@ -21,6 +22,8 @@ use std::ptr;
 use std::slice;
 use std::str;
 use std::sync::Arc;
+#[cfg(feature = "unsafe-deterministic-mode")]
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub extern "C" fn raw_verify<F>(preverify_ok: c_int, x509_ctx: *mut ffi::X509_STORE_CTX) -> c_int
 where
@@ -387,6 +390,24 @@ where
     }
 }
 
+pub(super) unsafe extern "C" fn raw_cert_cb<F>(ssl: *mut ffi::SSL, _: *mut c_void) -> c_int
+where
+    F: Fn(&mut SslRef) -> Result<(), CertCallbackError> + Sync + Send + 'static,
+{
+    // SAFETY: boring provides valid inputs.
+    let ssl = unsafe { SslRef::from_ptr_mut(ssl) };
+
+    let ssl_context = ssl.ssl_context().to_owned();
+    let callback = ssl_context
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: cert callback missing");
+
+    match callback(ssl) {
+        Ok(()) => 1,
+        Err(e) => e.0,
+    }
+}
+
 pub(super) unsafe extern "C" fn raw_tlsext_status<F>(ssl: *mut ffi::SSL, _: *mut c_void) -> c_int
 where
     F: Fn(&mut SslRef) -> Result<bool, ErrorStack> + 'static + Sync + Send,
@@ -526,6 +547,54 @@ where
     callback(ssl, &line);
 }
 
+/// The time the clock of a context is frozen at, in microseconds since the Unix epoch.
+#[cfg(feature = "unsafe-deterministic-mode")]
+pub(super) struct FrozenTime(u64);
+
+#[cfg(feature = "unsafe-deterministic-mode")]
+impl FrozenTime {
+    pub(super) fn new(time: SystemTime) -> FrozenTime {
+        let micros = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros();
+
+        FrozenTime(micros.try_into().unwrap_or(u64::MAX))
+    }
+}
+
+/// The clock BoringSSL's deterministic mode freezes at without a callback.
+#[cfg(feature = "unsafe-deterministic-mode")]
+const DETERMINISTIC_TIME_MICROS: u64 = 1234 * 1_000_000 + 1234;
+
+/// Returns the time the clock of `ssl` is frozen at, in microseconds since the Unix epoch.
+#[cfg(feature = "unsafe-deterministic-mode")]
+pub(super) fn frozen_time_micros(ssl: &SslRef) -> u64 {
+    ssl.ssl_context()
+        .ex_data(SslContext::cached_ex_index::<FrozenTime>())
+        .map_or(DETERMINISTIC_TIME_MICROS, |time| time.0)
+}
+
+#[cfg(feature = "unsafe-deterministic-mode")]
+pub(super) extern "C" fn raw_frozen_time(ssl: *const ffi::SSL, out_clock: *mut ffi::timeval) {
+    // BoringSSL passes no connection when it rotates session ticket keys. Those times are only
+    // compared with each other, so they can keep the clock of deterministic mode.
+    let micros = if ssl.is_null() {
+        DETERMINISTIC_TIME_MICROS
+    } else {
+        // SAFETY: boring provides valid inputs.
+        frozen_time_micros(unsafe { SslRef::from_ptr(ssl as *mut _) })
+    };
+
+    // SAFETY: boring provides valid inputs.
+    let out_clock = unsafe { &mut *out_clock };
+
+    out_clock.tv_sec = (micros / 1_000_000)
+        .try_into()
+        .unwrap_or_else(|_| i32::MAX.into());
+    out_clock.tv_usec = (micros % 1_000_000) as _;
+}
+
 pub(super) unsafe extern "C" fn raw_sign<M>(
     ssl: *mut ffi::SSL,
     out: *mut u8,
@@ -616,6 +685,56 @@ where
     }
 }
 
+pub(super) unsafe extern "C" fn raw_msg_callback<F>(
+    write_p: c_int,
+    _version: c_int,
+    content_type: c_int,
+    buf: *const c_void,
+    len: usize,
+    ssl: *mut ffi::SSL,
+    _arg: *mut c_void,
+) where
+    F: Fn(&SslRef, bool, SslContentType, &[u8]) + Send + Sync + 'static,
+{
+    // SAFETY: boring provides valid inputs.
+    let ssl = unsafe { SslRef::from_ptr_mut(ssl) };
+    let buf = if len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(buf.cast::<u8>(), len) }
+    };
+
+    super::transcript::msg(ssl, SslContentType(content_type), buf);
+
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: msg callback missing");
+
+    callback(ssl, write_p != 0, SslContentType(content_type), buf);
+}
+
+/// The msg callback of contexts without one set by the user.
+pub(super) unsafe extern "C" fn raw_builtin_msg_callback(
+    _write_p: c_int,
+    _version: c_int,
+    content_type: c_int,
+    buf: *const c_void,
+    len: usize,
+    ssl: *mut ffi::SSL,
+    _arg: *mut c_void,
+) {
+    // SAFETY: boring provides valid inputs.
+    let ssl = unsafe { SslRef::from_ptr_mut(ssl) };
+    let buf = if len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(buf.cast::<u8>(), len) }
+    };
+
+    super::transcript::msg(ssl, SslContentType(content_type), buf);
+}
+
 pub(super) unsafe extern "C" fn raw_info_callback<F>(
     ssl: *const ffi::SSL,
     mode: c_int,
@@ -628,9 +747,11 @@ pub(super) unsafe extern "C" fn raw_info_callback<F>(
     let mut_ref = ssl as *mut ffi::SSL;
 
     // SAFETY: boring provides valid inputs.
-    let ssl = unsafe { SslRef::from_ptr(mut_ref) };
-    let ssl_context = ssl.ssl_context();
+    let ssl = unsafe { SslRef::from_ptr_mut(mut_ref) };
 
+    builtin_info(ssl, mode, value);
+
+    let ssl_context = ssl.ssl_context();
     let callback = ssl_context
         .ex_data(SslContext::cached_ex_index::<F>())
         .expect("BUG: info callback missing");
@@ -645,6 +766,30 @@ pub(super) unsafe extern "C" fn raw_info_callback<F>(
     callback(ssl, SslInfoCallbackMode(mode), value);
 }
 
+/// The info callback of contexts without one set by the user.
+pub(super) unsafe extern "C" fn raw_builtin_info_callback(
+    ssl: *const ffi::SSL,
+    mode: c_int,
+    value: c_int,
+) {
+    // SAFETY: boring provides valid inputs.
+    let ssl = unsafe { SslRef::from_ptr_mut(ssl as *mut ffi::SSL) };
+
+    builtin_info(ssl, mode, value);
+}
+
+/// Reports handshakes and alerts to the telemetry hook, the handshake timings and `tracing`,
+/// whatever the user's info callback.
+fn builtin_info(ssl: &mut SslRef, mode: c_int, value: c_int) {
+    #[cfg(feature = "tracing")]
+    super::trace::info(ssl, mode, value);
+    #[cfg(not(feature = "tracing"))]
+    let _ = value;
+
+    super::telemetry::info(ssl, mode);
+    super::timings::info(ssl, mode);
+}
+
 pub(super) unsafe extern "C" fn raw_ssl_cert_compress<C>(
     ssl: *mut ffi::SSL,
     out: *mut ffi::CBB,
@@ -0,0 +1,113 @@
+use crate::hash::MessageDigest;
+use crate::ssl::{NameType, SslRef};
+
+/// A summary of the parameters of a TLS connection, for structured logs.
+///
+/// See [`SslRef::connection_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    protocol: &'static str,
+    cipher: Option<&'static str>,
+    group: Option<&'static str>,
+    alpn_protocol: Option<Vec<u8>>,
+    server_name: Option<String>,
+    resumed: bool,
+    peer_certificate_sha256: Option<[u8; 32]>,
+}
+
+impl ConnectionInfo {
+    /// Returns the name of the protocol version, such as `TLSv1.3`.
+    #[must_use]
+    pub fn protocol(&self) -> &'static str {
+        self.protocol
+    }
+
+    /// Returns the name of the negotiated cipher.
+    #[must_use]
+    pub fn cipher(&self) -> Option<&'static str> {
+        self.cipher
+    }
+
+    /// Returns the name of the group used for key exchange.
+    #[must_use]
+    pub fn group(&self) -> Option<&'static str> {
+        self.group
+    }
+
+    /// Returns the protocol selected via ALPN.
+    #[must_use]
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Returns the server name indicated by the client.
+    #[must_use]
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    /// Returns `true` if the session was resumed.
+    #[must_use]
+    pub fn resumed(&self) -> bool {
+        self.resumed
+    }
+
+    /// Returns the SHA-256 digest of the DER encoding of the peer's certificate.
+    #[must_use]
+    pub fn peer_certificate_sha256(&self) -> Option<&[u8; 32]> {
+        self.peer_certificate_sha256.as_ref()
+    }
+}
+
+/// Serializes as a map with hexadecimal digests and ALPN protocols as (lossy) strings.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ConnectionInfo {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("ConnectionInfo", 7)?;
+        s.serialize_field("protocol", self.protocol)?;
+        s.serialize_field("cipher", &self.cipher)?;
+        s.serialize_field("group", &self.group)?;
+        s.serialize_field(
+            "alpn_protocol",
+            &self.alpn_protocol.as_deref().map(String::from_utf8_lossy),
+        )?;
+        s.serialize_field("server_name", &self.server_name)?;
+        s.serialize_field("resumed", &self.resumed)?;
+        s.serialize_field(
+            "peer_certificate_sha256",
+            &self.peer_certificate_sha256.map(|digest| {
+                digest
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect::<String>()
+            }),
+        )?;
+        s.end()
+    }
+}
+
+impl SslRef {
+    /// Returns a summary of the parameters of the connection.
+    ///
+    /// Computing it takes a SHA-256 digest of the peer's certificate, and copies the ALPN
+    /// protocol and server name.
+    #[must_use]
+    pub fn connection_info(&self) -> ConnectionInfo {
+        let peer_certificate_sha256 = self
+            .peer_certificate()
+            .and_then(|cert| cert.digest(MessageDigest::sha256()).ok())
+            .and_then(|digest| (*digest).try_into().ok());
+
+        ConnectionInfo {
+            protocol: self.version_str(),
+            cipher: self.current_cipher().map(|cipher| cipher.name()),
+            group: self.curve_name(),
+            alpn_protocol: self.selected_alpn_protocol().map(<[u8]>::to_vec),
+            server_name: self.servername(NameType::HOST_NAME).map(ToOwned::to_owned),
+            resumed: self.session_reused(),
+            peer_certificate_sha256,
+        }
+    }
+}
@@ -1,17 +1,41 @@
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
 use std::io::{Read, Write};
+use std::mem;
 use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, LazyLock};
+
+#[cfg(feature = "ct")]
+use crate::ct::CtVerifier;
 
 use crate::dh::Dh;
 use crate::error::ErrorStack;
+use crate::ex_data::Index;
+use crate::ffi;
+use crate::ssl::ocsp;
+#[cfg(feature = "platform-verifier")]
+use crate::ssl::platform_verifier;
 use crate::ssl::{
-    HandshakeError, Ssl, SslContext, SslContextBuilder, SslContextRef, SslMethod, SslMode,
-    SslOptions, SslRef, SslStream, SslVerifyMode,
+    AlpnError, HandshakeError, Ssl, SslContext, SslContextBuilder, SslContextRef, SslMethod,
+    SslMode, SslOptions, SslRef, SslStream, SslVerifyMode,
 };
+#[cfg(feature = "platform-verifier")]
+use crate::ssl::{SslAlert, SslVerifyError};
+use crate::stack::StackRef;
 use crate::version;
+use crate::x509::{X509StoreContext, X509StoreContextRef, X509VerifyError, X509VerifyResult, X509};
 use std::net::IpAddr;
 
 use super::MidHandshakeSslStream;
 
+#[cfg(feature = "ct")]
+static CT_VERIFIER_INDEX: LazyLock<Index<SslContext, Arc<CtVerifier>>> =
+    LazyLock::new(|| SslContext::new_ex_index().unwrap());
+static MUST_STAPLE_INDEX: LazyLock<Index<SslContext, bool>> =
+    LazyLock::new(|| SslContext::new_ex_index().unwrap());
+static REVOCATION_CHECKER_INDEX: LazyLock<Index<SslContext, Arc<dyn RevocationChecker>>> =
+    LazyLock::new(|| SslContext::new_ex_index().unwrap());
+
 const FFDHE_2048: &str = "
 -----BEGIN DH PARAMETERS-----
 MIIBCAKCAQEA//////////+t+FRYortKmq/cViAnPTzx2LnFg84tNpWp4TZBFGQz
@@ -133,9 +157,25 @@ impl SslConnector {
             ssl,
             sni: true,
             verify_hostname: true,
+            spki_pins: Vec::new(),
         })
     }
 
+    /// Returns an immutable configuration, shared by clones, to create the `Ssl` of many
+    /// connections from.
+    ///
+    /// Unlike [`Self::configure`], the per-connection settings are only set up once, which makes
+    /// it cheaper for clients making many connections with the same settings.
+    #[must_use]
+    pub fn shared_configuration(&self) -> SharedConnectConfiguration {
+        SharedConnectConfiguration {
+            ctx: self.0.clone(),
+            sni: true,
+            verify_hostname: true,
+            checks: LeafChecks::new(Vec::new(), &self.0),
+        }
+    }
+
     /// Consumes the `SslConnector`, returning the inner raw `SslContext`.
     #[must_use]
     pub fn into_context(self) -> SslContext {
@@ -158,6 +198,87 @@ impl SslConnectorBuilder {
     pub fn build(self) -> SslConnector {
         SslConnector(self.0.build())
     }
+
+    /// Enforces a Certificate Transparency policy on connections made with this connector.
+    ///
+    /// This enables SCT and OCSP stapling requests. The policy is checked once the certificate
+    /// chain has otherwise been verified successfully. If it is not met, the handshake fails and
+    /// [`SslRef::verify_result`] returns [`X509VerifyError::CT_POLICY_NOT_MET`].
+    ///
+    /// Like [`ConnectConfiguration::set_pin_spki_sha256`], this installs a verify callback on
+    /// each [`Ssl`], which calls the verify callback of the context, if any, before checking the
+    /// policy.
+    #[cfg(feature = "ct")]
+    pub fn set_ct_verifier(&mut self, verifier: CtVerifier) {
+        self.0.enable_signed_cert_timestamps();
+        self.0.enable_ocsp_stapling();
+        self.0.set_ex_data(*CT_VERIFIER_INDEX, Arc::new(verifier));
+    }
+
+    /// Requires servers whose certificate has the TLS feature extension for OCSP stapling, also
+    /// known as must-staple, to staple a valid OCSP response.
+    ///
+    /// This enables OCSP stapling requests. The stapled response must be successful, signed by
+    /// the issuer of the certificate or a responder it delegated to, current, and report the
+    /// certificate as good. This is checked once the certificate chain has otherwise been
+    /// verified successfully. If it is not met, the handshake fails and
+    /// [`SslRef::verify_result`] returns [`X509VerifyError::MUST_STAPLE_NOT_MET`], or
+    /// [`X509VerifyError::CERT_REVOKED`] if the response reports the certificate as revoked.
+    ///
+    /// Like [`ConnectConfiguration::set_pin_spki_sha256`], this installs a verify callback on
+    /// each [`Ssl`].
+    pub fn set_enforce_must_staple(&mut self, enforce: bool) {
+        if enforce {
+            self.0.enable_ocsp_stapling();
+        }
+        self.0.replace_ex_data(*MUST_STAPLE_INDEX, enforce);
+    }
+
+    /// Consults `checker` about the revocation status of the certificate chains of servers.
+    ///
+    /// This enables OCSP stapling requests, so that the checker may use stapled responses. The
+    /// checker is consulted once the certificate chain has otherwise been verified successfully.
+    /// If it returns an error, the handshake fails and [`SslRef::verify_result`] returns that
+    /// error.
+    ///
+    /// Like [`ConnectConfiguration::set_pin_spki_sha256`], this installs a verify callback on
+    /// each [`Ssl`].
+    pub fn set_revocation_checker<C>(&mut self, checker: C)
+    where
+        C: RevocationChecker,
+    {
+        self.0.enable_ocsp_stapling();
+        self.0
+            .replace_ex_data(*REVOCATION_CHECKER_INDEX, Arc::new(checker));
+    }
+
+    /// Verifies the certificate chains of servers with the verifier of the operating system
+    /// rather than with BoringSSL.
+    ///
+    /// Chains are then trusted according to the roots and revocation settings of the system,
+    /// including roots installed by enterprise policies. Security.framework is used on Apple
+    /// platforms and CryptoAPI on Windows. On other platforms, BoringSSL keeps verifying chains
+    /// against the system trust store, as with [`SslContextBuilder::set_default_verify_paths`].
+    ///
+    /// Hostnames, SPKI pins, revocation checkers and Certificate Transparency policies are still
+    /// checked as configured. The verify callbacks and certificate store of the context are not
+    /// used, and a failed verification is reported by the alert sent to the server rather than by
+    /// [`SslRef::verify_result`].
+    #[cfg(feature = "platform-verifier")]
+    pub fn set_platform_verifier(&mut self) -> Result<(), ErrorStack> {
+        if !platform_verifier::IS_NATIVE {
+            return self.0.set_default_verify_paths();
+        }
+
+        self.0.replace_ex_data(
+            SslContext::cached_ex_index::<PlatformVerifier>(),
+            PlatformVerifier,
+        );
+        self.0
+            .set_custom_verify_callback(SslVerifyMode::PEER, verify_with_platform);
+
+        Ok(())
+    }
 }
 
 impl Deref for SslConnectorBuilder {
@@ -179,6 +300,7 @@ pub struct ConnectConfiguration {
     ssl: Ssl,
     sni: bool,
     verify_hostname: bool,
+    spki_pins: Vec<[u8; 32]>,
 }
 
 impl ConnectConfiguration {
@@ -216,23 +338,44 @@ impl ConnectConfiguration {
         self.verify_hostname = verify_hostname;
     }
 
+    /// A builder-style version of `set_pin_spki_sha256`.
+    #[must_use]
+    pub fn pin_spki_sha256(mut self, pins: &[[u8; 32]]) -> ConnectConfiguration {
+        self.set_pin_spki_sha256(pins);
+        self
+    }
+
+    /// Requires some certificate in the verified chain to have one of the provided
+    /// SubjectPublicKeyInfo hashes, as returned by [`X509Ref::spki_sha256`].
+    ///
+    /// Pins are only checked once the chain has otherwise been verified successfully. If none of
+    /// them match, the handshake fails and [`SslRef::verify_result`] returns
+    /// [`X509VerifyError::SPKI_PIN_MISMATCH`]. An empty list disables pinning, which is the
+    /// default.
+    ///
+    /// Pinning is implemented with a verify callback on the [`Ssl`]. It first calls the verify
+    /// callback already set on the context or with [`SslRef::set_verify_callback`], if any, and
+    /// only checks the pins if that accepts the chain. A verify callback set on the `Ssl`
+    /// returned by [`Self::into_ssl`] replaces it. Pinning is combined with the connector's
+    /// must-staple enforcement, revocation checker and Certificate Transparency policy, if any.
+    ///
+    /// [`X509Ref::spki_sha256`]: crate::x509::X509Ref::spki_sha256
+    pub fn set_pin_spki_sha256(&mut self, pins: &[[u8; 32]]) {
+        self.spki_pins = pins.to_vec();
+    }
+
     /// Returns an [`Ssl`] configured to connect to the provided domain.
     ///
     /// The domain is used for SNI (if it is not an IP address) and hostname verification if enabled.
     pub fn into_ssl(mut self, domain: &str) -> Result<Ssl, ErrorStack> {
-        if self.sni && domain.parse::<IpAddr>().is_err() {
-            self.ssl.set_hostname(domain)?;
-        }
-
-        #[cfg(feature = "rpk")]
-        let verify_hostname = !self.ssl.ssl_context().is_rpk() && self.verify_hostname;
-
-        #[cfg(not(feature = "rpk"))]
-        let verify_hostname = self.verify_hostname;
-
-        if verify_hostname {
-            setup_verify_hostname(&mut self.ssl, domain)?;
-        }
+        let checks = LeafChecks::new(mem::take(&mut self.spki_pins), self.ssl.ssl_context());
+        setup_connect_ssl(
+            &mut self.ssl,
+            domain,
+            self.sni,
+            self.verify_hostname,
+            checks,
+        )?;
 
         Ok(self.ssl)
     }
@@ -284,6 +427,99 @@ impl DerefMut for ConnectConfiguration {
     }
 }
 
+/// An immutable client configuration, cheaply cloned and shared by many connections.
+///
+/// Unlike a [`ConnectConfiguration`], it is not tied to a single connection. Each call to
+/// [`Self::ssl`] creates the `Ssl` of a new connection from it.
+///
+/// See [`SslConnector::shared_configuration`].
+#[derive(Clone)]
+pub struct SharedConnectConfiguration {
+    ctx: SslContext,
+    sni: bool,
+    verify_hostname: bool,
+    checks: Option<Arc<LeafChecks>>,
+}
+
+impl SharedConnectConfiguration {
+    /// Configures the use of Server Name Indication (SNI) when connecting.
+    ///
+    /// See [`ConnectConfiguration::set_use_server_name_indication`].
+    #[must_use]
+    pub fn use_server_name_indication(mut self, use_sni: bool) -> SharedConnectConfiguration {
+        self.sni = use_sni;
+        self
+    }
+
+    /// Configures the use of hostname verification when connecting.
+    ///
+    /// See [`ConnectConfiguration::set_verify_hostname`].
+    #[must_use]
+    pub fn verify_hostname(mut self, verify_hostname: bool) -> SharedConnectConfiguration {
+        self.verify_hostname = verify_hostname;
+        self
+    }
+
+    /// Requires some certificate in the verified chain to have one of the provided
+    /// SubjectPublicKeyInfo hashes.
+    ///
+    /// See [`ConnectConfiguration::set_pin_spki_sha256`].
+    #[must_use]
+    pub fn pin_spki_sha256(mut self, pins: &[[u8; 32]]) -> SharedConnectConfiguration {
+        self.checks = LeafChecks::new(pins.to_vec(), &self.ctx);
+        self
+    }
+
+    /// Returns a shared reference to the context connections are created with.
+    #[must_use]
+    pub fn context(&self) -> &SslContextRef {
+        &self.ctx
+    }
+
+    /// Returns a new [`Ssl`] configured to connect to the provided domain.
+    ///
+    /// The domain is used for SNI (if it is not an IP address) and hostname verification if enabled.
+    pub fn ssl(&self, domain: &str) -> Result<Ssl, ErrorStack> {
+        let mut ssl = Ssl::new(&self.ctx)?;
+        setup_connect_ssl(
+            &mut ssl,
+            domain,
+            self.sni,
+            self.verify_hostname,
+            self.checks.clone(),
+        )?;
+
+        Ok(ssl)
+    }
+
+    /// Initiates a client-side TLS session on a stream.
+    ///
+    /// This is a convenience method which combines [`Self::ssl`] and [`Ssl::setup_connect`].
+    pub fn setup_connect<S>(
+        &self,
+        domain: &str,
+        stream: S,
+    ) -> Result<MidHandshakeSslStream<S>, ErrorStack>
+    where
+        S: Read + Write,
+    {
+        Ok(self.ssl(domain)?.setup_connect(stream))
+    }
+
+    /// Attempts a client-side TLS session on a stream.
+    ///
+    /// This is a convenience method which combines [`Self::setup_connect`] and
+    /// [`MidHandshakeSslStream::handshake`].
+    pub fn connect<S>(&self, domain: &str, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: Read + Write,
+    {
+        self.setup_connect(domain, stream)
+            .map_err(HandshakeError::SetupFailure)?
+            .handshake()
+    }
+}
+
 /// A type which wraps server-side streams in a TLS session.
 ///
 /// OpenSSL's default configuration is highly insecure. This connector manages the OpenSSL
@@ -417,6 +653,23 @@ impl SslAcceptor {
 pub struct SslAcceptorBuilder(SslContextBuilder);
 
 impl SslAcceptorBuilder {
+    /// Negotiates the first protocol of `protocols` which the client also supports with ALPN.
+    ///
+    /// `protocols` is in the ALPN wire format, as with [`SslContextBuilder::set_alpn_protos`],
+    /// and ordered by preference. If the client supports none of them, the handshake continues
+    /// without ALPN if `no_overlap` is [`AlpnError::NOACK`], and fails if it is
+    /// [`AlpnError::ALERT_FATAL`].
+    ///
+    /// This replaces the callback set with [`SslContextBuilder::set_alpn_select_callback`].
+    pub fn set_alpn_server_preference(&mut self, protocols: &[u8], no_overlap: AlpnError) {
+        let protocols = protocols.to_vec();
+        self.0.set_alpn_select_callback(move |_, client| {
+            alpn_protocols(&protocols)
+                .find_map(|protocol| alpn_protocols(client).find(|&p| p == protocol))
+                .ok_or(no_overlap)
+        });
+    }
+
     /// Consumes the builder, returning a `SslAcceptor`.
     #[must_use]
     pub fn build(self) -> SslAcceptor {
@@ -424,6 +677,16 @@ impl SslAcceptorBuilder {
     }
 }
 
+/// Returns the protocols of an ALPN protocol list in wire format.
+fn alpn_protocols(mut protocols: &[u8]) -> impl Iterator<Item = &[u8]> {
+    std::iter::from_fn(move || {
+        let (&len, rest) = protocols.split_first()?;
+        let (protocol, rest) = rest.split_at_checked(len.into())?;
+        protocols = rest;
+        Some(protocol)
+    })
+}
+
 impl Deref for SslAcceptorBuilder {
     type Target = SslContextBuilder;
 
@@ -452,3 +715,197 @@ fn setup_verify_hostname(ssl: &mut SslRef, domain: &str) -> Result<(), ErrorStac
         Err(_) => param.set_host(domain),
     }
 }
+
+fn setup_connect_ssl(
+    ssl: &mut SslRef,
+    domain: &str,
+    sni: bool,
+    verify_hostname: bool,
+    checks: Option<Arc<LeafChecks>>,
+) -> Result<(), ErrorStack> {
+    if sni && domain.parse::<IpAddr>().is_err() {
+        ssl.set_hostname(domain)?;
+    }
+
+    #[cfg(feature = "rpk")]
+    let verify_hostname = !ssl.ssl_context().is_rpk() && verify_hostname;
+
+    #[cfg(feature = "platform-verifier")]
+    if ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<PlatformVerifier>())
+        .is_some()
+    {
+        let host = verify_hostname.then(|| domain.to_owned());
+        ssl.replace_ex_data(
+            Ssl::cached_ex_index::<PlatformVerification>(),
+            PlatformVerification { host, checks },
+        );
+
+        return Ok(());
+    }
+
+    if verify_hostname {
+        setup_verify_hostname(ssl, domain)?;
+    }
+
+    if let Some(checks) = checks {
+        setup_leaf_checks(ssl, checks);
+    }
+
+    Ok(())
+}
+
+/// Marks contexts verifying chains with the platform verifier.
+#[cfg(feature = "platform-verifier")]
+struct PlatformVerifier;
+
+/// What the platform verifier checks on a connection.
+#[cfg(feature = "platform-verifier")]
+struct PlatformVerification {
+    host: Option<String>,
+    checks: Option<Arc<LeafChecks>>,
+}
+
+#[cfg(feature = "platform-verifier")]
+fn verify_with_platform(ssl: &mut SslRef) -> Result<(), SslVerifyError> {
+    let ssl = &*ssl;
+    // Connections not configured by a connector have nothing to verify the chain against.
+    let verification = ssl.ex_data(Ssl::cached_ex_index::<PlatformVerification>());
+
+    let result = match (verification, ssl.peer_cert_chain()) {
+        (Some(verification), Some(chain)) => {
+            platform_verifier::verify(chain, verification.host.as_deref()).and_then(|()| {
+                match &verification.checks {
+                    Some(checks) => checks.check_chain(chain, ssl),
+                    None => Ok(()),
+                }
+            })
+        }
+        _ => Err(X509VerifyError::UNSPECIFIED),
+    };
+
+    result.map_err(|e| {
+        let alert = match e {
+            X509VerifyError::CERT_UNTRUSTED => SslAlert::UNKNOWN_CA,
+            X509VerifyError::CERT_HAS_EXPIRED => SslAlert::CERTIFICATE_EXPIRED,
+            X509VerifyError::CERT_REVOKED => SslAlert::CERTIFICATE_REVOKED,
+            X509VerifyError::MUST_STAPLE_NOT_MET => SslAlert::BAD_CERTIFICATE_STATUS_RESPONSE,
+            _ => SslAlert::BAD_CERTIFICATE,
+        };
+        SslVerifyError::Invalid(alert)
+    })
+}
+
+/// A source of revocation information for the certificates of servers, such as a CRLite or
+/// CRLSet snapshot, a denylist or an OCSP cache.
+///
+/// See [`SslConnectorBuilder::set_revocation_checker`].
+pub trait RevocationChecker: Send + Sync + 'static {
+    /// Checks whether a certificate of `chain`, leaf first, is revoked.
+    ///
+    /// `chain` is the chain built by BoringSSL up to a trusted root, or the chain sent by the
+    /// server when the platform verifier is used. `ocsp_response` is the OCSP response stapled
+    /// by the server, if any.
+    ///
+    /// Returns [`X509VerifyError::CERT_REVOKED`] for a revoked certificate. Whether a
+    /// certificate of unknown status is accepted is up to the checker.
+    fn check(&self, chain: &StackRef<X509>, ocsp_response: Option<&[u8]>) -> X509VerifyResult;
+}
+
+struct LeafChecks {
+    spki_pins: Vec<[u8; 32]>,
+    must_staple: bool,
+    revocation: Option<Arc<dyn RevocationChecker>>,
+    #[cfg(feature = "ct")]
+    ct: Option<Arc<CtVerifier>>,
+}
+
+impl LeafChecks {
+    /// Returns the checks of connections made with `ctx`, if there are any.
+    fn new(spki_pins: Vec<[u8; 32]>, ctx: &SslContextRef) -> Option<Arc<LeafChecks>> {
+        let checks = LeafChecks {
+            spki_pins,
+            must_staple: ctx.ex_data(*MUST_STAPLE_INDEX).copied().unwrap_or(false),
+            revocation: ctx.ex_data(*REVOCATION_CHECKER_INDEX).cloned(),
+            #[cfg(feature = "ct")]
+            ct: ctx.ex_data(*CT_VERIFIER_INDEX).cloned(),
+        };
+
+        (!checks.is_empty()).then(|| Arc::new(checks))
+    }
+
+    fn is_empty(&self) -> bool {
+        #[cfg(feature = "ct")]
+        if self.ct.is_some() {
+            return false;
+        }
+
+        self.spki_pins.is_empty() && !self.must_staple && self.revocation.is_none()
+    }
+
+    fn check(&self, ctx: &X509StoreContextRef) -> X509VerifyResult {
+        let chain = ctx.chain().ok_or(X509VerifyError::UNSPECIFIED)?;
+        let ssl = X509StoreContext::ssl_idx()
+            .ok()
+            .and_then(|idx| ctx.ex_data(idx))
+            .ok_or(X509VerifyError::UNSPECIFIED)?;
+
+        self.check_chain(chain, ssl)
+    }
+
+    fn check_chain(&self, chain: &StackRef<X509>, ssl: &SslRef) -> X509VerifyResult {
+        if !self.spki_pins.is_empty()
+            && !chain.iter().any(|cert| {
+                cert.spki_sha256()
+                    .is_ok_and(|hash| self.spki_pins.contains(&hash))
+            })
+        {
+            return Err(X509VerifyError::SPKI_PIN_MISMATCH);
+        }
+
+        if self.must_staple {
+            ocsp::check_stapling(chain, ssl.ocsp_status())?;
+        }
+
+        if let Some(revocation) = &self.revocation {
+            revocation.check(chain, ssl.ocsp_status())?;
+        }
+
+        #[cfg(feature = "ct")]
+        if let Some(ct) = &self.ct {
+            ct.verify(chain, ssl.signed_cert_timestamp_list(), ssl.ocsp_status())
+                .map_err(|_| X509VerifyError::CT_POLICY_NOT_MET)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn setup_leaf_checks(ssl: &mut SslRef, checks: Arc<LeafChecks>) {
+    let mode = ssl.verify_mode();
+    // SAFETY: `ssl` is valid. The closures of the verify callbacks set with
+    // `SslContextBuilder::set_verify_callback` and `SslRef::set_verify_callback` are kept in ex
+    // data indexed by their type, so they outlive the callback which replaces them.
+    let previous = unsafe { ffi::SSL_get_verify_callback(ssl.as_ptr()) };
+    ssl.set_verify_callback(mode, move |preverify_ok, ctx| {
+        let preverify_ok = match previous {
+            // SAFETY: `ctx` is the store context BoringSSL passed to this callback.
+            Some(previous) => unsafe { previous(c_int::from(preverify_ok), ctx.as_ptr()) != 0 },
+            None => preverify_ok,
+        };
+
+        // The leaf is checked last, once the rest of the chain has been verified.
+        if !preverify_ok || ctx.error_depth() != 0 {
+            return preverify_ok;
+        }
+
+        match checks.check(ctx) {
+            Ok(()) => true,
+            Err(e) => {
+                ctx.set_error(Err(e));
+                false
+            }
+        }
+    });
+}
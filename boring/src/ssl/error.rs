@@ -48,6 +48,11 @@ impl ErrorCode {
 
     pub const PENDING_TICKET: ErrorCode = ErrorCode(ffi::SSL_ERROR_PENDING_TICKET);
 
+    /// The server rejected the early data sent by the client.
+    ///
+    /// See [`SslRef::reset_early_data_reject`](super::SslRef::reset_early_data_reject).
+    pub const EARLY_DATA_REJECTED: ErrorCode = ErrorCode(ffi::SSL_ERROR_EARLY_DATA_REJECTED);
+
     /// A non-recoverable IO error occurred.
     pub const SYSCALL: ErrorCode = ErrorCode(ffi::SSL_ERROR_SYSCALL);
 
@@ -146,6 +151,29 @@ impl Error {
         }
     }
 
+    /// Returns the [`io::ErrorKind`] best describing this error.
+    ///
+    /// I/O errors keep their kind. A connection closed without a `close_notify` alert is
+    /// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof), a session shut down by the peer is
+    /// [`ConnectionAborted`](io::ErrorKind::ConnectionAborted), and a fatal alert from the peer
+    /// is [`ConnectionReset`](io::ErrorKind::ConnectionReset).
+    #[must_use]
+    pub fn io_error_kind(&self) -> io::ErrorKind {
+        if let Some(e) = self.io_error() {
+            return e.kind();
+        }
+
+        match self.code {
+            ErrorCode::ZERO_RETURN => io::ErrorKind::ConnectionAborted,
+            ErrorCode::SYSCALL => io::ErrorKind::UnexpectedEof,
+            ErrorCode::SSL => self
+                .ssl_error()
+                .map_or(io::ErrorKind::Other, ErrorStack::io_error_kind),
+            _ if self.would_block() => io::ErrorKind::WouldBlock,
+            _ => io::ErrorKind::Other,
+        }
+    }
+
     #[must_use]
     pub fn would_block(&self) -> bool {
         matches!(
@@ -171,6 +199,15 @@ impl From<ErrorStack> for Error {
     }
 }
 
+impl From<Error> for io::Error {
+    fn from(e: Error) -> io::Error {
+        let kind = e.io_error_kind();
+
+        e.into_io_error()
+            .unwrap_or_else(|e| io::Error::new(kind, e))
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         let msg = match self.code {
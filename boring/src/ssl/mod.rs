@@ -76,11 +76,15 @@ use std::ptr::{self, NonNull};
 use std::slice;
 use std::str;
 use std::sync::{Arc, LazyLock, Mutex};
+#[cfg(feature = "unsafe-deterministic-mode")]
+use std::time::SystemTime;
 
+use crate::cbs::{Cbs, CONTEXT_0};
 use crate::dh::DhRef;
 use crate::ec::EcKeyRef;
 use crate::error::ErrorStack;
 use crate::ex_data::Index;
+use crate::hash::{DigestBytes, MessageDigest};
 use crate::hmac::HmacCtxRef;
 use crate::nid::Nid;
 use crate::pkey::{HasPrivate, PKeyRef, Params, Private};
@@ -99,25 +103,46 @@ use crate::{cvt, cvt_0i, cvt_n, cvt_p, init};
 use crate::{ffi, free_data_box};
 
 pub use self::async_callbacks::{
-    AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, AsyncSelectCertError, BoxCustomVerifyFinish,
-    BoxCustomVerifyFuture, BoxGetSessionFinish, BoxGetSessionFuture, BoxPrivateKeyMethodFinish,
-    BoxPrivateKeyMethodFuture, BoxSelectCertFinish, BoxSelectCertFuture, ExDataFuture,
+    AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, AsyncSelectCertError, BoxCertCallbackFinish,
+    BoxCertCallbackFuture, BoxCustomVerifyFinish, BoxCustomVerifyFuture, BoxGetSessionFinish,
+    BoxGetSessionFuture, BoxPrivateKeyMethodFinish, BoxPrivateKeyMethodFuture, BoxSelectCertFinish,
+    BoxSelectCertFuture, ExDataFuture,
 };
+pub use self::connection_info::ConnectionInfo;
 pub use self::connector::{
-    ConnectConfiguration, SslAcceptor, SslAcceptorBuilder, SslConnector, SslConnectorBuilder,
+    ConnectConfiguration, RevocationChecker, SharedConnectConfiguration, SslAcceptor,
+    SslAcceptorBuilder, SslConnector, SslConnectorBuilder,
 };
 pub use self::ech::{SslEchKeys, SslEchKeysRef};
 pub use self::error::{Error, ErrorCode, HandshakeError};
+pub use self::quic::{
+    quic_retry_tag, QuicCryptoSession, QuicEncryptionLevel, QuicEvent, QuicHeaderKey, QuicKeyPair,
+    QuicKeys, QuicPacketKey, QuicSecret, QuicSession, QUIC_HEADER_SAMPLE_LEN, QUIC_RETRY_TAG_LEN,
+};
+pub use self::session_cache::{SessionCacheStats, ShardedSessionCache};
+pub use self::telemetry::{HandshakeCompleted, HandshakeFailed, TelemetryHook};
+pub use self::timings::HandshakeTimings;
 
 mod async_callbacks;
 mod bio;
 mod callbacks;
+mod connection_info;
 mod connector;
 mod ech;
 mod error;
 mod mut_only;
+mod ocsp;
+#[cfg(feature = "platform-verifier")]
+mod platform_verifier;
+mod quic;
+mod session_cache;
+mod telemetry;
 #[cfg(test)]
 mod test;
+mod timings;
+#[cfg(feature = "tracing")]
+mod trace;
+mod transcript;
 
 bitflags! {
     /// Options controlling the behavior of an `SslContext`.
@@ -227,7 +252,7 @@ bitflags! {
 
         /// Release memory buffers when the session does not need them.
         ///
-        /// This saves ~34 KiB of memory for idle streams.
+        /// BoringSSL always releases the buffers of idle streams, so this has no effect.
         const RELEASE_BUFFERS = ffi::SSL_MODE_RELEASE_BUFFERS as _;
 
         /// Sends the fake `TLS_FALLBACK_SCSV` cipher suite in the ClientHello message of a
@@ -531,6 +556,22 @@ impl SelectCertError {
     pub const RETRY: Self = Self(ffi::ssl_select_cert_result_t::ssl_select_cert_retry);
 }
 
+/// An error returned from a certificate callback.
+///
+/// See [`SslContextBuilder::set_cert_callback`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CertCallbackError(c_int);
+
+impl CertCallbackError {
+    /// A fatal error occurred and the handshake should be terminated.
+    pub const ERROR: Self = Self(0);
+
+    /// The operation could not be completed and should be retried later.
+    ///
+    /// The handshake is interrupted with [`ErrorCode::WANT_X509_LOOKUP`].
+    pub const RETRY: Self = Self(-1);
+}
+
 /// Extension types, to be used with `ClientHello::get_extension`.
 ///
 /// **WARNING**: The current implementation of `From` is unsound, as it's possible to create an
@@ -689,6 +730,28 @@ impl SslSignatureAlgorithm {
         SslSignatureAlgorithm(ffi::SSL_SIGN_RSA_PSS_RSAE_SHA512 as _);
 
     pub const ED25519: SslSignatureAlgorithm = SslSignatureAlgorithm(ffi::SSL_SIGN_ED25519 as _);
+
+    /// Returns the digest the message is hashed with before being signed, or `None` if the
+    /// algorithm does not prehash messages, like Ed25519, or is unknown.
+    #[corresponds(SSL_get_signature_algorithm_digest)]
+    #[must_use]
+    pub fn digest(&self) -> Option<MessageDigest> {
+        unsafe {
+            let md = ffi::SSL_get_signature_algorithm_digest(self.0);
+            if md.is_null() {
+                None
+            } else {
+                Some(MessageDigest::from_ptr(md))
+            }
+        }
+    }
+
+    /// Returns `true` if this is an RSA-PSS algorithm.
+    #[corresponds(SSL_is_signature_algorithm_rsa_pss)]
+    #[must_use]
+    pub fn is_rsa_pss(&self) -> bool {
+        unsafe { ffi::SSL_is_signature_algorithm_rsa_pss(self.0) == 1 }
+    }
 }
 
 impl From<u16> for SslSignatureAlgorithm {
@@ -765,6 +828,42 @@ pub fn select_next_proto<'a>(server: &'a [u8], client: &'a [u8]) -> Option<&'a [
     }
 }
 
+/// The content type of a message passed to the callback set with
+/// [`SslContextBuilder::set_msg_callback`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SslContentType(c_int);
+
+impl SslContentType {
+    /// A ChangeCipherSpec message, whose contents are its one-byte body.
+    pub const CHANGE_CIPHER_SPEC: Self = Self(ffi::SSL3_RT_CHANGE_CIPHER_SPEC as _);
+
+    /// An alert, whose contents are its level and description.
+    pub const ALERT: Self = Self(ffi::SSL3_RT_ALERT as _);
+
+    /// A handshake message, including its type and length.
+    pub const HANDSHAKE: Self = Self(ffi::SSL3_RT_HANDSHAKE as _);
+
+    /// A record header. The length it contains is that of the ciphertext if the record is
+    /// encrypted.
+    pub const HEADER: Self = Self(ffi::SSL3_RT_HEADER as _);
+
+    /// A ClientHelloInner encrypted or decrypted with ECH, including its handshake header.
+    pub const CLIENT_HELLO_INNER: Self = Self(ffi::SSL3_RT_CLIENT_HELLO_INNER as _);
+
+    /// Constructs an `SslContentType` from a raw `SSL3_RT_*` value.
+    #[must_use]
+    pub fn from_raw(raw: c_int) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the raw `SSL3_RT_*` value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    #[must_use]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
 /// Options controlling the behavior of the info callback.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub struct SslInfoCallbackMode(i32);
@@ -950,6 +1049,8 @@ impl SslContextBuilder {
         unsafe {
             init();
             let ctx = cvt_p(ffi::SSL_CTX_new(method.as_ptr()))?;
+            #[cfg(feature = "tracing")]
+            ffi::SSL_CTX_set_info_callback(ctx, Some(callbacks::raw_builtin_info_callback));
             Ok(SslContextBuilder::from_ptr(ctx))
         }
     }
@@ -1242,10 +1343,9 @@ impl SslContextBuilder {
 
     /// Controls read ahead behavior.
     ///
-    /// If enabled, OpenSSL will read as much data as is available from the underlying stream,
-    /// instead of a single record at a time.
-    ///
-    /// It has no effect when used with DTLS.
+    /// In OpenSSL, this reads as much data as is available from the underlying stream instead of
+    /// a single record at a time. BoringSSL sizes its read buffer on its own, so this has no
+    /// effect.
     #[corresponds(SSL_CTX_set_read_ahead)]
     pub fn set_read_ahead(&mut self, read_ahead: bool) {
         unsafe {
@@ -1260,6 +1360,27 @@ impl SslContextBuilder {
         SslMode::from_bits_retain(bits)
     }
 
+    /// Sets the maximum length, in bytes, of the records sent, which bounds the size of the
+    /// write buffer of each connection.
+    ///
+    /// Longer handshake messages and application data are split into several records. The
+    /// length is clamped between 512 and 16384, the default.
+    #[corresponds(SSL_CTX_set_max_send_fragment)]
+    pub fn set_max_send_fragment(&mut self, len: usize) {
+        unsafe {
+            ffi::SSL_CTX_set_max_send_fragment(self.as_ptr(), len);
+        }
+    }
+
+    /// Sets the maximum length, in bytes, of the certificate chain accepted from the peer, which
+    /// bounds the memory used during the handshake.
+    ///
+    /// Defaults to 102400.
+    #[corresponds(SSL_CTX_set_max_cert_list)]
+    pub fn set_max_cert_list(&mut self, len: usize) {
+        unsafe { ffi::SSL_CTX_set_max_cert_list(self.as_ptr(), len) }
+    }
+
     /// Sets the parameters to be used during ephemeral Diffie-Hellman key exchange.
     #[corresponds(SSL_CTX_set_tmp_dh)]
     pub fn set_tmp_dh(&mut self, dh: &DhRef<Params>) -> Result<(), ErrorStack> {
@@ -1674,6 +1795,40 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets whether early data (0-RTT) is sent by clients and accepted by servers when resuming
+    /// TLS 1.3 sessions.
+    ///
+    /// Early data can be replayed by an attacker. Servers sharing session tickets across
+    /// several processes should reject replayed ClientHellos with
+    /// [`ClientHello::filter_early_data`].
+    ///
+    /// Defaults to `false`.
+    #[corresponds(SSL_CTX_set_early_data_enabled)]
+    pub fn set_early_data_enabled(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_CTX_set_early_data_enabled(self.as_ptr(), enabled as c_int) }
+    }
+
+    /// Sets a callback that is called to configure the certificate and private key for a
+    /// connection.
+    ///
+    /// Servers call it after the ClientHello has been processed. Clients call it when the server
+    /// requests a certificate, after the CertificateRequest has been received, so the callback
+    /// can pick a client certificate for the server being connected to.
+    #[corresponds(SSL_CTX_set_cert_cb)]
+    pub fn set_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<(), CertCallbackError> + Sync + Send + 'static,
+    {
+        unsafe {
+            self.replace_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_cert_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_cert_cb::<F>),
+                ptr::null_mut(),
+            );
+        }
+    }
+
     /// Registers a certificate compression algorithm.
     ///
     /// [`SSL_CTX_add_cert_compression_alg`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_add_cert_compression_alg
@@ -1932,6 +2087,28 @@ impl SslContextBuilder {
         }
     }
 
+    /// Freezes the clock of connections made with this context at `time`.
+    ///
+    /// The clock dates new sessions and decides when sessions and tickets expire. Times before
+    /// the Unix epoch are clamped to it. If the context of a connection is switched, for instance
+    /// from a servername callback, the clock of the new context is used.
+    ///
+    /// Without this, the deterministic mode of BoringSSL already freezes the clock at a time in
+    /// 1970. That clock is kept for the rotation of session ticket keys, which is not done for a
+    /// particular connection.
+    #[cfg(feature = "unsafe-deterministic-mode")]
+    #[corresponds(SSL_CTX_set_current_time_cb)]
+    pub fn set_frozen_time(&mut self, time: SystemTime) {
+        self.replace_ex_data(
+            SslContext::cached_ex_index::<callbacks::FrozenTime>(),
+            callbacks::FrozenTime::new(time),
+        );
+
+        unsafe {
+            ffi::SSL_CTX_set_current_time_cb(self.as_ptr(), Some(callbacks::raw_frozen_time));
+        }
+    }
+
     /// Sets the session caching mode use for connections made with the context.
     ///
     /// Returns the previous session caching mode.
@@ -1943,6 +2120,35 @@ impl SslContextBuilder {
         }
     }
 
+    /// Stores the sessions of connections accepted with this context in `cache` rather than in
+    /// the internal session cache.
+    ///
+    /// This sets the session cache mode to [`SslSessionCacheMode::SERVER`] and
+    /// [`SslSessionCacheMode::NO_INTERNAL`], and replaces the callbacks set with
+    /// [`Self::set_new_session_callback`] and [`Self::set_get_session_callback`].
+    ///
+    /// As with the internal session cache, only sessions resumed by ID are stored, which requires
+    /// [`SslOptions::NO_TICKET`] and a session ID context.
+    pub fn set_sharded_session_cache(&mut self, cache: ShardedSessionCache) {
+        let cache = Arc::new(cache);
+
+        self.set_session_cache_mode(SslSessionCacheMode::SERVER | SslSessionCacheMode::NO_INTERNAL);
+        self.replace_ex_data(
+            SslContext::cached_ex_index::<Arc<ShardedSessionCache>>(),
+            cache.clone(),
+        );
+
+        let new_cache = cache.clone();
+        self.set_new_session_callback(move |_, session| new_cache.insert(session));
+
+        // SAFETY: `cache` is owned by this context and only holds the sessions created by it.
+        unsafe {
+            self.set_get_session_callback(
+                move |ssl, id| Ok(cache.get(id, session_cache::now(ssl))),
+            );
+        }
+    }
+
     /// Sets the extra data at the specified index.
     ///
     /// This can be used to provide data to callbacks registered with the context. Use the
@@ -2018,6 +2224,22 @@ impl SslContextBuilder {
         unsafe { ffi::SSL_CTX_enable_signed_cert_timestamps(self.as_ptr()) }
     }
 
+    /// Sets the SCT list sent to clients which request one.
+    ///
+    /// `list` is a TLS-encoded `SignedCertificateTimestampList`, including the two leading length
+    /// bytes.
+    #[corresponds(SSL_CTX_set_signed_cert_timestamp_list)]
+    pub fn set_signed_cert_timestamp_list(&mut self, list: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_set_signed_cert_timestamp_list(
+                self.as_ptr(),
+                list.as_ptr(),
+                list.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Enables OCSP stapling on all client SSL handshakes.
     #[corresponds(SSL_CTX_enable_ocsp_stapling)]
     pub fn enable_ocsp_stapling(&mut self) {
@@ -2046,6 +2268,9 @@ impl SslContextBuilder {
     }
 
     /// Sets the context's info callback.
+    ///
+    /// With the `tracing` feature, the context keeps emitting its handshake and alert events
+    /// before calling `callback`.
     #[corresponds(SSL_CTX_set_info_callback)]
     pub fn set_info_callback<F>(&mut self, callback: F)
     where
@@ -2057,6 +2282,93 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets a callback called with each handshake message, alert, ChangeCipherSpec and record
+    /// header sent or received.
+    ///
+    /// The callback is passed whether the message was sent, its content type, and its contents.
+    /// This allows observing post-handshake messages such as KeyUpdates.
+    #[corresponds(SSL_CTX_set_msg_callback)]
+    pub fn set_msg_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, bool, SslContentType, &[u8]) + Send + Sync + 'static,
+    {
+        self.replace_ex_data(
+            SslContext::cached_ex_index::<transcript::UserMsgCallback>(),
+            transcript::UserMsgCallback,
+        );
+
+        unsafe {
+            self.replace_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_msg_callback(self.as_ptr(), Some(callbacks::raw_msg_callback::<F>));
+        }
+    }
+
+    /// Sets a hook receiving the duration, parameters, and outcome of the handshakes of
+    /// connections made with this context.
+    ///
+    /// The hook keeps receiving them if an info callback is set with
+    /// [`Self::set_info_callback`].
+    pub fn set_telemetry_hook(&mut self, hook: impl TelemetryHook) {
+        let hook: Arc<dyn TelemetryHook> = Arc::new(hook);
+        self.replace_ex_data(
+            SslContext::cached_ex_index::<Arc<dyn TelemetryHook>>(),
+            hook,
+        );
+
+        unsafe {
+            if ffi::SSL_CTX_get_info_callback(self.as_ptr()).is_none() {
+                ffi::SSL_CTX_set_info_callback(
+                    self.as_ptr(),
+                    Some(callbacks::raw_builtin_info_callback),
+                );
+            }
+        }
+    }
+
+    /// Records when the milestones of the handshakes of connections made with this context are
+    /// reached, for [`SslRef::handshake_timings`].
+    ///
+    /// Timings keep being recorded if an info callback is set with [`Self::set_info_callback`].
+    pub fn enable_handshake_timings(&mut self) {
+        self.replace_ex_data(
+            SslContext::cached_ex_index::<timings::RecordHandshakeTimings>(),
+            timings::RecordHandshakeTimings,
+        );
+
+        unsafe {
+            if ffi::SSL_CTX_get_info_callback(self.as_ptr()).is_none() {
+                ffi::SSL_CTX_set_info_callback(
+                    self.as_ptr(),
+                    Some(callbacks::raw_builtin_info_callback),
+                );
+            }
+        }
+    }
+
+    /// Records the handshake messages of connections made with this context, for
+    /// [`SslRef::handshake_transcript`] and [`SslRef::handshake_transcript_hash`].
+    ///
+    /// Messages keep being recorded if a msg callback is set with [`Self::set_msg_callback`].
+    pub fn enable_handshake_transcript(&mut self) {
+        self.replace_ex_data(
+            SslContext::cached_ex_index::<transcript::RecordHandshakeTranscript>(),
+            transcript::RecordHandshakeTranscript,
+        );
+
+        if self
+            .ctx
+            .ex_data(SslContext::cached_ex_index::<transcript::UserMsgCallback>())
+            .is_none()
+        {
+            unsafe {
+                ffi::SSL_CTX_set_msg_callback(
+                    self.as_ptr(),
+                    Some(callbacks::raw_builtin_msg_callback),
+                );
+            }
+        }
+    }
+
     /// Registers a list of ECH keys on the context. This list should contain new and old
     /// ECHConfigs to allow stale DNS caches to update. Unlike most `SSL_CTX` APIs, this function
     /// is safe to call even after the `SSL_CTX` has been associated with connections on various
@@ -2305,6 +2617,30 @@ impl SslContextRef {
         unsafe { ffi::SSL_CTX_sess_get_cache_size(self.as_ptr()).into() }
     }
 
+    /// Returns the session cache set with [`SslContextBuilder::set_sharded_session_cache`].
+    #[must_use]
+    pub fn sharded_session_cache(&self) -> Option<&ShardedSessionCache> {
+        self.ex_data(SslContext::cached_ex_index::<Arc<ShardedSessionCache>>())
+            .map(|cache| &**cache)
+    }
+
+    /// Returns the number of sessions in the context's internal session cache.
+    #[corresponds(SSL_CTX_sess_number)]
+    #[must_use]
+    pub fn session_count(&self) -> usize {
+        unsafe { ffi::SSL_CTX_sess_number(self.as_ptr()) }
+    }
+
+    /// Returns the counters of the session cache set with
+    /// [`SslContextBuilder::set_sharded_session_cache`].
+    ///
+    /// BoringSSL does not count the lookups of the internal session cache, so this is `None`
+    /// without a sharded session cache.
+    #[must_use]
+    pub fn session_cache_stats(&self) -> Option<SessionCacheStats> {
+        self.sharded_session_cache().map(ShardedSessionCache::stats)
+    }
+
     /// Returns the verify mode that was set on this context from [`SslContextBuilder::set_verify`].
     ///
     /// [`SslContextBuilder::set_verify`]: struct.SslContextBuilder.html#method.set_verify
@@ -2419,6 +2755,25 @@ impl ClientHello<'_> {
     pub fn ciphers(&self) -> &[u8] {
         unsafe { slice::from_raw_parts(self.0.cipher_suites, self.0.cipher_suites_len) }
     }
+
+    /// Rejects the early data offered with this ClientHello unless `is_fresh` returns `true`.
+    ///
+    /// `is_fresh` is given the client random, which the PSK binder ties to the ClientHello, and
+    /// should record it in a replay cache shared by all servers accepting the session tickets,
+    /// returning `false` if it was already there. It is not called if the client offers no
+    /// early data.
+    ///
+    /// This must be called from the callback set with
+    /// [`SslContextBuilder::set_select_certificate_callback`].
+    pub fn filter_early_data(&mut self, is_fresh: impl FnOnce(&[u8]) -> bool) {
+        if self.get_extension(ExtensionType::EARLY_DATA).is_none() {
+            return;
+        }
+
+        if !is_fresh(self.random()) {
+            self.ssl_mut().set_early_data_enabled(false);
+        }
+    }
 }
 
 /// Information about a cipher.
@@ -2665,6 +3020,16 @@ impl SslSessionRef {
         unsafe { ffi::SSL_SESSION_get_timeout(self.as_ptr()) }
     }
 
+    /// Determines if early data would be sent when resuming the session, if enabled.
+    ///
+    /// BoringSSL servers always accept up to 14336 bytes of early data. BoringSSL does not expose
+    /// the limit a server advertised in the session.
+    #[corresponds(SSL_SESSION_early_data_capable)]
+    #[must_use]
+    pub fn early_data_capable(&self) -> bool {
+        unsafe { ffi::SSL_SESSION_early_data_capable(self.as_ptr()) != 0 }
+    }
+
     /// Returns the session's TLS protocol version.
     #[corresponds(SSL_SESSION_get_protocol_version)]
     #[must_use]
@@ -3553,6 +3918,38 @@ impl SslRef {
         }
     }
 
+    /// Returns the RFC 5929 `tls-server-end-point` channel binding of the connection.
+    ///
+    /// This is the hash of the server's certificate with the digest of its signature algorithm,
+    /// or SHA-256 if that digest is MD5 or SHA-1. For RSASSA-PSS, the digest is the one in the
+    /// signature parameters. Returns `None` if there is no server certificate, or if its
+    /// signature algorithm does not have a single digest, as with Ed25519.
+    pub fn tls_server_end_point(&self) -> Result<Option<DigestBytes>, ErrorStack> {
+        let peer;
+        let cert = if self.is_server() {
+            self.certificate()
+        } else {
+            peer = self.peer_certificate();
+            peer.as_deref()
+        };
+        let Some(cert) = cert else {
+            return Ok(None);
+        };
+
+        let algorithm = cert.signature_algorithm();
+        let digest = match algorithm.object().nid() {
+            Nid::RSASSAPSS => pss_digest(&algorithm.to_der()?),
+            nid => nid.signature_algorithms().map(|algs| algs.digest),
+        }
+        .map(|digest| match digest {
+            Nid::MD5 | Nid::SHA1 => Nid::SHA256,
+            digest => digest,
+        })
+        .and_then(MessageDigest::from_nid);
+
+        digest.map(|digest| cert.digest(digest)).transpose()
+    }
+
     /// Sets the session to be used.
     ///
     /// This should be called before the handshake to attempt to reuse a previously established
@@ -3575,6 +3972,47 @@ impl SslRef {
         unsafe { ffi::SSL_session_reused(self.as_ptr()) != 0 }
     }
 
+    /// Like [`SslContextBuilder::set_early_data_enabled`].
+    #[corresponds(SSL_set_early_data_enabled)]
+    pub fn set_early_data_enabled(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_set_early_data_enabled(self.as_ptr(), enabled as c_int) }
+    }
+
+    /// Determines if early data was accepted by the server.
+    #[corresponds(SSL_early_data_accepted)]
+    #[must_use]
+    pub fn early_data_accepted(&self) -> bool {
+        unsafe { ffi::SSL_early_data_accepted(self.as_ptr()) != 0 }
+    }
+
+    /// Resets a client connection whose early data was rejected, failing with
+    /// [`ErrorCode::EARLY_DATA_REJECTED`], so that the handshake can continue without it.
+    ///
+    /// Any early data written is discarded, and should be written again once the handshake
+    /// completes.
+    #[corresponds(SSL_reset_early_data_reject)]
+    pub fn reset_early_data_reject(&mut self) {
+        unsafe { ffi::SSL_reset_early_data_reject(self.as_ptr()) }
+    }
+
+    /// Queues a TLS 1.3 KeyUpdate message, updating the keys used to send records. If
+    /// `request_peer` is `true`, the peer is asked to update the keys it sends records with too.
+    ///
+    /// The message is sent with the next write. [`SslStream::key_update`] also sends it.
+    ///
+    /// KeyUpdates received from the peer can be observed with
+    /// [`SslContextBuilder::set_msg_callback`].
+    #[corresponds(SSL_key_update)]
+    pub fn key_update(&mut self, request_peer: bool) -> Result<(), ErrorStack> {
+        let request_type = if request_peer {
+            ffi::SSL_KEY_UPDATE_REQUESTED
+        } else {
+            ffi::SSL_KEY_UPDATE_NOT_REQUESTED
+        };
+
+        unsafe { cvt(ffi::SSL_key_update(self.as_ptr(), request_type as c_int)).map(|_| ()) }
+    }
+
     /// Sets the status response a client wishes the server to reply with.
     #[corresponds(SSL_set_tlsext_status_type)]
     pub fn set_status_type(&mut self, type_: StatusType) -> Result<(), ErrorStack> {
@@ -3583,6 +4021,26 @@ impl SslRef {
         }
     }
 
+    /// Returns the SCT list sent by the server, if present.
+    ///
+    /// This is a TLS-encoded `SignedCertificateTimestampList` and is not guaranteed to be well
+    /// formed.
+    #[corresponds(SSL_get0_signed_cert_timestamp_list)]
+    #[must_use]
+    pub fn signed_cert_timestamp_list(&self) -> Option<&[u8]> {
+        unsafe {
+            let mut p = ptr::null();
+            let mut len = 0;
+            ffi::SSL_get0_signed_cert_timestamp_list(self.as_ptr(), &mut p, &mut len);
+
+            if len == 0 {
+                None
+            } else {
+                Some(slice::from_raw_parts(p, len))
+            }
+        }
+    }
+
     /// Returns the server's OCSP response, if present.
     #[corresponds(SSL_get_tlsext_status_ocsp_resp)]
     #[must_use]
@@ -3776,6 +4234,20 @@ impl SslRef {
         SslMode::from_bits_retain(bits)
     }
 
+    /// Like [`SslContextBuilder::set_max_send_fragment`].
+    #[corresponds(SSL_set_max_send_fragment)]
+    pub fn set_max_send_fragment(&mut self, len: usize) {
+        unsafe {
+            ffi::SSL_set_max_send_fragment(self.as_ptr(), len);
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_max_cert_list`].
+    #[corresponds(SSL_set_max_cert_list)]
+    pub fn set_max_cert_list(&mut self, len: usize) {
+        unsafe { ffi::SSL_set_max_cert_list(self.as_ptr(), len) }
+    }
+
     /// Appends `cert` to the chain associated with the current certificate of `SSL`.
     #[corresponds(SSL_add1_chain_cert)]
     pub fn add_chain_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
@@ -3934,6 +4406,7 @@ impl<S> MidHandshakeSslStream<S> {
             Err(if self.error.would_block() {
                 HandshakeError::WouldBlock(self)
             } else {
+                telemetry::handshake_failed(self.ssl(), &self.error);
                 HandshakeError::Failure(self)
             })
         }
@@ -4017,7 +4490,7 @@ impl<S: Read + Write> SslStream<S> {
                 }
                 Err(ref e) if e.code() == ErrorCode::WANT_READ && e.io_error().is_none() => {}
                 Err(e) => {
-                    return Err(e.into_io_error().unwrap_or_else(io::Error::other));
+                    return Err(e.into());
                 }
             }
         }
@@ -4058,6 +4531,39 @@ impl<S: Read + Write> SslStream<S> {
         }
     }
 
+    /// Like `ssl_read`, but leaves the returned data in the session to be read again.
+    #[corresponds(SSL_peek)]
+    pub fn ssl_peek(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        // SAFETY: `ssl_peek_uninit` does not de-initialize the buffer.
+        unsafe {
+            self.ssl_peek_uninit(slice::from_raw_parts_mut(
+                buf.as_mut_ptr().cast::<MaybeUninit<u8>>(),
+                buf.len(),
+            ))
+        }
+    }
+
+    /// Like `ssl_peek`, but takes a possibly-uninitialized slice.
+    ///
+    /// # Safety
+    ///
+    /// No portion of `buf` will be de-initialized by this method. If the method returns `Ok(n)`,
+    /// then the first `n` bytes of `buf` are guaranteed to be initialized.
+    #[corresponds(SSL_peek)]
+    pub fn ssl_peek_uninit(&mut self, buf: &mut [MaybeUninit<u8>]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let len = usize::min(c_int::MAX as usize, buf.len()) as c_int;
+        let ret = unsafe { ffi::SSL_peek(self.ssl().as_ptr(), buf.as_mut_ptr().cast(), len) };
+        if ret > 0 {
+            Ok(ret as usize)
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
     /// Like `write`, but returns an `ssl::Error` rather than an `io::Error`.
     ///
     /// It is particularly useful with a nonblocking socket, where the error value will identify if
@@ -4077,6 +4583,20 @@ impl<S: Read + Write> SslStream<S> {
         }
     }
 
+    /// Like [`SslRef::key_update`], but also sends the KeyUpdate message.
+    #[corresponds(SSL_key_update)]
+    pub fn key_update(&mut self, request_peer: bool) -> Result<(), Error> {
+        self.ssl.key_update(request_peer)?;
+
+        // A zero-length write only flushes the pending KeyUpdate.
+        let ret = unsafe { ffi::SSL_write(self.ssl().as_ptr(), ptr::null(), 0) };
+        if ret >= 0 {
+            Ok(())
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
     /// Shuts down the session.
     ///
     /// The shutdown process consists of two steps. The first step sends a close notify message to
@@ -4242,7 +4762,7 @@ impl<S: Read + Write> Write for SslStream<S> {
                 Ok(n) => return Ok(n),
                 Err(ref e) if e.code() == ErrorCode::WANT_READ && e.io_error().is_none() => {}
                 Err(e) => {
-                    return Err(e.into_io_error().unwrap_or_else(io::Error::other));
+                    return Err(e.into());
                 }
             }
         }
@@ -4353,6 +4873,7 @@ where
             Err(if error.would_block() {
                 HandshakeError::WouldBlock(MidHandshakeSslStream { stream, error })
             } else {
+                telemetry::handshake_failed(stream.ssl(), &error);
                 HandshakeError::Failure(MidHandshakeSslStream { stream, error })
             })
         }
@@ -4531,6 +5052,28 @@ pub trait CertificateCompressor: Send + Sync + 'static {
     }
 }
 
+/// Returns the digest of the RSASSA-PSS parameters in `algorithm`, a DER-encoded
+/// `AlgorithmIdentifier`, as described in [RFC 4055 §3.1].
+///
+/// [RFC 4055 §3.1]: https://www.rfc-editor.org/rfc/rfc4055#section-3.1
+fn pss_digest(algorithm: &[u8]) -> Option<Nid> {
+    let mut algorithm = Cbs::new(algorithm).get_asn1(ffi::CBS_ASN1_SEQUENCE).ok()?;
+    algorithm.skip_any_asn1().ok()?;
+    let mut params = algorithm.get_asn1(ffi::CBS_ASN1_SEQUENCE).ok()?;
+
+    let Some(mut hash) = params.get_optional_asn1(CONTEXT_0).ok()? else {
+        // The digest defaults to SHA-1.
+        return Some(Nid::SHA1);
+    };
+    let digest = hash
+        .get_asn1(ffi::CBS_ASN1_SEQUENCE)
+        .ok()?
+        .get_oid_nid()
+        .ok()?;
+
+    (digest != Nid::UNDEF).then_some(digest)
+}
+
 use crate::ffi::{SSL_CTX_up_ref, SSL_SESSION_get_master_key, SSL_SESSION_up_ref, SSL_is_server};
 
 use crate::ffi::{DTLS_method, TLS_client_method, TLS_method, TLS_server_method};
@@ -0,0 +1,577 @@
+//! Checks of stapled OCSP responses, for certificates requiring them.
+//!
+//! BoringSSL passes stapled responses through without parsing them, so they are checked here as
+//! described in [RFC 6960], and certificates requiring them are recognized by their TLS feature
+//! extension, as described in [RFC 7633].
+//!
+//! [RFC 6960]: https://www.rfc-editor.org/rfc/rfc6960
+//! [RFC 7633]: https://www.rfc-editor.org/rfc/rfc7633
+use foreign_types::ForeignTypeRef;
+use std::str;
+use std::sync::LazyLock;
+
+use crate::asn1::{Asn1Object, Asn1StringRef, Asn1Time};
+use crate::cbs::{Cbs, Malformed, CONTEXT_0, CONTEXT_1};
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::pkey::{Id, PKey, PKeyRef, Public};
+use crate::sign::Verifier;
+use crate::stack::StackRef;
+use crate::x509::{X509Ref, X509VerifyError, X509VerifyResult, X509};
+use crate::{cvt, ffi};
+
+static TLS_FEATURE_OID: LazyLock<Asn1Object> =
+    LazyLock::new(|| Asn1Object::from_str("1.3.6.1.5.5.7.1.24").unwrap());
+
+// The `status_request` TLS extension, as an INTEGER of the TLS feature extension.
+const STATUS_REQUEST: &[u8] = &[5];
+
+// The `successful` OCSP response status.
+const SUCCESSFUL: &[u8] = &[0];
+
+// The tag of the `good` certificate status, an implicitly tagged NULL.
+const GOOD: ffi::CBS_ASN1_TAG = ffi::CBS_ASN1_CONTEXT_SPECIFIC;
+
+// DER encoding of 1.3.6.1.5.5.7.48.1.1, id-pkix-ocsp-basic.
+const OCSP_BASIC_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01];
+
+// DER encodings of the hash algorithms of certificate IDs.
+const SHA1_OID: &[u8] = &[0x2b, 0x0e, 0x03, 0x02, 0x1a];
+const SHA256_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+
+// DER encodings of the signature algorithms of responses, without their last byte.
+const RSA_OID_PREFIX: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01];
+const ECDSA_OID_PREFIX: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03];
+const ED25519_OID: &[u8] = &[0x2b, 0x65, 0x70];
+
+/// Returns `true` if `cert` has a TLS feature extension requiring OCSP stapling.
+///
+/// A malformed extension is assumed to require it.
+fn requires_stapling(cert: &X509Ref) -> bool {
+    let data = unsafe {
+        let loc = ffi::X509_get_ext_by_OBJ(cert.as_ptr(), TLS_FEATURE_OID.as_ptr(), -1);
+        if loc < 0 {
+            return false;
+        }
+        let ext = ffi::X509_get_ext(cert.as_ptr(), loc);
+        Asn1StringRef::from_ptr(ffi::X509_EXTENSION_get_data(ext) as *mut _)
+    };
+
+    lists_status_request(data.as_slice()).unwrap_or(true)
+}
+
+fn lists_status_request(features: &[u8]) -> Result<bool, Malformed> {
+    let mut features = Cbs::new(features).get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    while !features.is_empty() {
+        if features.get_asn1(ffi::CBS_ASN1_INTEGER)?.as_slice() == STATUS_REQUEST {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Checks that `response` is a valid OCSP response for the leaf of the verified `chain`, if the
+/// leaf requires OCSP stapling.
+pub(super) fn check_stapling(chain: &StackRef<X509>, response: Option<&[u8]>) -> X509VerifyResult {
+    let leaf = chain.get(0).ok_or(X509VerifyError::UNSPECIFIED)?;
+    if !requires_stapling(leaf) {
+        return Ok(());
+    }
+
+    let (Some(issuer), Some(response)) = (chain.get(1), response) else {
+        return Err(X509VerifyError::MUST_STAPLE_NOT_MET);
+    };
+
+    match cert_status(response, leaf, issuer) {
+        Ok(CertStatus::Good) => Ok(()),
+        Ok(CertStatus::Revoked) => Err(X509VerifyError::CERT_REVOKED),
+        Err(Invalid) => Err(X509VerifyError::MUST_STAPLE_NOT_MET),
+    }
+}
+
+enum CertStatus {
+    Good,
+    Revoked,
+}
+
+/// A response that is malformed, unsupported, not signed by the issuer, or not current.
+struct Invalid;
+
+impl From<Malformed> for Invalid {
+    fn from(_: Malformed) -> Invalid {
+        Invalid
+    }
+}
+
+impl From<ErrorStack> for Invalid {
+    fn from(_: ErrorStack) -> Invalid {
+        Invalid
+    }
+}
+
+/// Returns the status of `leaf` in the DER-encoded OCSP `response`.
+fn cert_status(response: &[u8], leaf: &X509Ref, issuer: &X509Ref) -> Result<CertStatus, Invalid> {
+    let mut response = Cbs::new(response).get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    if response.get_asn1(ffi::CBS_ASN1_ENUMERATED)?.as_slice() != SUCCESSFUL {
+        return Err(Invalid);
+    }
+    let mut bytes = response
+        .get_asn1(CONTEXT_0)?
+        .get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    if bytes.get_asn1(ffi::CBS_ASN1_OBJECT)?.as_slice() != OCSP_BASIC_OID {
+        return Err(Invalid);
+    }
+    let mut basic = bytes
+        .get_asn1(ffi::CBS_ASN1_OCTETSTRING)?
+        .get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    let tbs = basic.get_asn1_element(ffi::CBS_ASN1_SEQUENCE)?.as_slice();
+    let algorithm = basic
+        .get_asn1(ffi::CBS_ASN1_SEQUENCE)?
+        .get_asn1(ffi::CBS_ASN1_OBJECT)?
+        .as_slice();
+    let [0, signature @ ..] = basic.get_asn1(ffi::CBS_ASN1_BITSTRING)?.as_slice() else {
+        return Err(Invalid);
+    };
+    let certs = basic.get_optional_asn1(CONTEXT_0)?;
+
+    let now = Asn1Time::days_from_now(0)?;
+
+    let issuer_key = issuer.public_key()?;
+    let mut keys = vec![issuer_key.clone()];
+    if let Some(mut certs) = certs {
+        let mut certs = certs.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+        while !certs.is_empty() {
+            let cert = X509::from_der(certs.get_asn1_element(ffi::CBS_ASN1_SEQUENCE)?.as_slice())?;
+            if is_delegated_responder(&cert, issuer, &issuer_key, &now) {
+                keys.push(cert.public_key()?);
+            }
+        }
+    }
+
+    if !keys
+        .iter()
+        .any(|key| verify_signature(algorithm, key, tbs, signature))
+    {
+        return Err(Invalid);
+    }
+
+    let mut data = Cbs::new(tbs).get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+    data.get_optional_asn1(CONTEXT_0)?;
+    data.skip_any_asn1()?;
+    data.get_asn1(ffi::CBS_ASN1_GENERALIZEDTIME)?;
+    let mut responses = data.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+
+    let serial = leaf.serial_number().to_bn()?.to_vec();
+
+    while !responses.is_empty() {
+        let mut single = responses.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+        let cert_id = single.get_asn1(ffi::CBS_ASN1_SEQUENCE)?;
+        let (status, _) = single.get_any_asn1()?;
+        let this_update = time(single.get_asn1(ffi::CBS_ASN1_GENERALIZEDTIME)?.as_slice())?;
+        let next_update = match single.get_optional_asn1(CONTEXT_0)? {
+            Some(mut next_update) => Some(time(
+                next_update
+                    .get_asn1(ffi::CBS_ASN1_GENERALIZEDTIME)?
+                    .as_slice(),
+            )?),
+            None => None,
+        };
+
+        if !identifies(cert_id, &serial, issuer)? {
+            continue;
+        }
+
+        if this_update > now || next_update.is_some_and(|next_update| next_update < now) {
+            return Err(Invalid);
+        }
+
+        return match status {
+            GOOD => Ok(CertStatus::Good),
+            CONTEXT_1 => Ok(CertStatus::Revoked),
+            _ => Err(Invalid),
+        };
+    }
+
+    Err(Invalid)
+}
+
+/// Returns `true` if the certificate ID `cert_id` refers to the certificate with serial number
+/// `serial` issued by `issuer`.
+fn identifies(mut cert_id: Cbs<'_>, serial: &[u8], issuer: &X509Ref) -> Result<bool, Invalid> {
+    let digest = match cert_id
+        .get_asn1(ffi::CBS_ASN1_SEQUENCE)?
+        .get_asn1(ffi::CBS_ASN1_OBJECT)?
+        .as_slice()
+    {
+        SHA1_OID => MessageDigest::sha1(),
+        SHA256_OID => MessageDigest::sha256(),
+        _ => return Ok(false),
+    };
+    let name_hash = cert_id.get_asn1(ffi::CBS_ASN1_OCTETSTRING)?.as_slice();
+    let key_hash = cert_id.get_asn1(ffi::CBS_ASN1_OCTETSTRING)?.as_slice();
+    let id_serial = cert_id.get_asn1(ffi::CBS_ASN1_INTEGER)?.as_slice();
+    let id_serial = &id_serial[id_serial.iter().take_while(|&&b| b == 0).count()..];
+
+    Ok(id_serial == serial
+        && name_hash == name_digest(issuer, digest)?
+        && key_hash == key_digest(issuer, digest)?)
+}
+
+/// Returns `true` if `cert` is allowed to sign OCSP responses on behalf of `issuer`.
+fn is_delegated_responder(
+    cert: &X509Ref,
+    issuer: &X509Ref,
+    issuer_key: &PKeyRef<Public>,
+    now: &Asn1Time,
+) -> bool {
+    // Without an extended key usage extension, the usage reports every purpose.
+    let flags = unsafe { ffi::X509_get_extension_flags(cert.as_ptr()) };
+    let usage = unsafe { ffi::X509_get_extended_key_usage(cert.as_ptr()) };
+
+    issuer.issued(cert).is_ok()
+        && cert.verify(issuer_key).unwrap_or(false)
+        && flags & ffi::EXFLAG_XKUSAGE as u32 != 0
+        && usage & ffi::XKU_OCSP_SIGN as u32 != 0
+        && cert.not_before() <= *now
+        && cert.not_after() >= *now
+}
+
+fn verify_signature(algorithm: &[u8], key: &PKey<Public>, tbs: &[u8], signature: &[u8]) -> bool {
+    let Some((id, digest)) = signature_algorithm(algorithm) else {
+        return false;
+    };
+    if key.id() != id {
+        return false;
+    }
+
+    let verifier = match digest {
+        Some(digest) => Verifier::new(digest, key),
+        None => Verifier::new_without_digest(key),
+    };
+
+    verifier
+        .and_then(|mut verifier| verifier.verify_oneshot(signature, tbs))
+        .unwrap_or(false)
+}
+
+/// Returns the key type and digest of the signature algorithm with the DER-encoded OID `oid`.
+fn signature_algorithm(oid: &[u8]) -> Option<(Id, Option<MessageDigest>)> {
+    if oid == ED25519_OID {
+        return Some((Id::ED25519, None));
+    }
+
+    let (&last, prefix) = oid.split_last()?;
+    let (id, digest) = match (prefix, last) {
+        (RSA_OID_PREFIX, 0x05) => (Id::RSA, MessageDigest::sha1()),
+        (RSA_OID_PREFIX, 0x0b) => (Id::RSA, MessageDigest::sha256()),
+        (RSA_OID_PREFIX, 0x0c) => (Id::RSA, MessageDigest::sha384()),
+        (RSA_OID_PREFIX, 0x0d) => (Id::RSA, MessageDigest::sha512()),
+        (ECDSA_OID_PREFIX, 0x02) => (Id::EC, MessageDigest::sha256()),
+        (ECDSA_OID_PREFIX, 0x03) => (Id::EC, MessageDigest::sha384()),
+        (ECDSA_OID_PREFIX, 0x04) => (Id::EC, MessageDigest::sha512()),
+        _ => return None,
+    };
+
+    Some((id, Some(digest)))
+}
+
+fn time(time: &[u8]) -> Result<Asn1Time, Invalid> {
+    let time = str::from_utf8(time).map_err(|_| Invalid)?;
+    Ok(Asn1Time::from_str(time)?)
+}
+
+fn name_digest(cert: &X509Ref, digest: MessageDigest) -> Result<Vec<u8>, ErrorStack> {
+    let mut buf = [0; ffi::EVP_MAX_MD_SIZE as usize];
+    let mut len = 0;
+    unsafe {
+        cvt(ffi::X509_NAME_digest(
+            cert.subject_name().as_ptr(),
+            digest.as_ptr(),
+            buf.as_mut_ptr(),
+            &mut len,
+        ))?;
+    }
+
+    Ok(buf[..len as usize].to_vec())
+}
+
+fn key_digest(cert: &X509Ref, digest: MessageDigest) -> Result<Vec<u8>, ErrorStack> {
+    let mut buf = [0; ffi::EVP_MAX_MD_SIZE as usize];
+    let mut len = 0;
+    unsafe {
+        cvt(ffi::X509_pubkey_digest(
+            cert.as_ptr(),
+            digest.as_ptr(),
+            buf.as_mut_ptr(),
+            &mut len,
+        ))?;
+    }
+
+    Ok(buf[..len as usize].to_vec())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::bn::BigNum;
+    use crate::cbs::test::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::pkey::Private;
+    use crate::sign::Signer;
+    use crate::stack::Stack;
+    use crate::x509::extension::{ExtendedKeyUsage, SubjectAlternativeName};
+    use crate::x509::{X509Builder, X509Extension, X509NameBuilder};
+
+    const TIME: &[u8] = b"20240101000000Z";
+
+    const DER_GOOD: u8 = 0x80;
+
+    fn der(tag: u8, contents: &[&[u8]]) -> Vec<u8> {
+        let contents = contents.concat();
+        let mut der = vec![tag];
+        if contents.len() < 0x80 {
+            der.push(contents.len() as u8);
+        } else {
+            let len = (contents.len() as u32).to_be_bytes();
+            let len = &len[len.iter().take_while(|&&b| b == 0).count()..];
+            der.push(0x80 | len.len() as u8);
+            der.extend_from_slice(len);
+        }
+        der.extend_from_slice(&contents);
+        der
+    }
+
+    /// Returns a must-staple certificate for `foobar.com` issued by `issuer`, and its key.
+    pub(crate) fn must_staple_cert(
+        issuer: &X509Ref,
+        issuer_key: &PKeyRef<Private>,
+    ) -> (X509, PKey<Private>) {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "foobar.com").unwrap();
+        let name = name.build();
+
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_version(2).unwrap();
+        // The high bit is set, so the DER encoding has a leading zero.
+        let serial = BigNum::from_u32(0x8001).unwrap();
+        builder
+            .set_serial_number(&serial.to_asn1_integer().unwrap())
+            .unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(issuer.subject_name()).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(30).unwrap())
+            .unwrap();
+        let san = SubjectAlternativeName::new()
+            .dns("foobar.com")
+            .build(&builder.x509v3_context(Some(issuer), None))
+            .unwrap();
+        builder.append_extension(&san).unwrap();
+        let must_staple =
+            X509Extension::new(None, None, "1.3.6.1.5.5.7.1.24", "DER:30:03:02:01:05").unwrap();
+        builder.append_extension(&must_staple).unwrap();
+        builder.sign(issuer_key, MessageDigest::sha256()).unwrap();
+
+        (builder.build(), key)
+    }
+
+    /// Returns a certificate issued by `issuer` for signing OCSP responses on its behalf, and its
+    /// key.
+    pub(crate) fn responder_cert(
+        issuer: &X509Ref,
+        issuer_key: &PKeyRef<Private>,
+    ) -> (X509, PKey<Private>) {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "OCSP responder").unwrap();
+        let name = name.build();
+
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_version(2).unwrap();
+        let serial = BigNum::from_u32(0x8002).unwrap();
+        builder
+            .set_serial_number(&serial.to_asn1_integer().unwrap())
+            .unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(issuer.subject_name()).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(30).unwrap())
+            .unwrap();
+        let usage = ExtendedKeyUsage::new()
+            .other("OCSPSigning")
+            .build()
+            .unwrap();
+        builder.append_extension(&usage).unwrap();
+        builder.sign(issuer_key, MessageDigest::sha256()).unwrap();
+
+        (builder.build(), key)
+    }
+
+    /// Returns an OCSP response signed with `key`, reporting `leaf` issued by `issuer` as good or
+    /// revoked.
+    pub(crate) fn response(
+        leaf: &X509Ref,
+        issuer: &X509Ref,
+        key: &PKeyRef<Private>,
+        revoked: bool,
+    ) -> Vec<u8> {
+        response_with_certs(leaf, issuer, key, revoked, &[])
+    }
+
+    /// Like [`response`], but includes `certs` for finding the key of a delegated responder.
+    pub(crate) fn response_with_certs(
+        leaf: &X509Ref,
+        issuer: &X509Ref,
+        key: &PKeyRef<Private>,
+        revoked: bool,
+        certs: &[&X509Ref],
+    ) -> Vec<u8> {
+        let mut serial = leaf.serial_number().to_bn().unwrap().to_vec();
+        if serial.first().is_some_and(|&b| b & 0x80 != 0) {
+            serial.insert(0, 0);
+        }
+        let key_hash = key_digest(issuer, MessageDigest::sha1()).unwrap();
+
+        let cert_id = der(
+            DER_SEQUENCE,
+            &[
+                &der(DER_SEQUENCE, &[&der(DER_OID, &[SHA1_OID]), &[0x05, 0x00]]),
+                &der(
+                    DER_OCTET_STRING,
+                    &[&name_digest(issuer, MessageDigest::sha1()).unwrap()],
+                ),
+                &der(DER_OCTET_STRING, &[&key_hash]),
+                &der(DER_INTEGER, &[&serial]),
+            ],
+        );
+        let this_update = der(DER_GENERALIZED_TIME, &[TIME]);
+        let status = if revoked {
+            der(DER_CONTEXT_1, &[&this_update])
+        } else {
+            vec![DER_GOOD, 0]
+        };
+        let single = der(DER_SEQUENCE, &[&cert_id, &status, &this_update]);
+        let responder_id = der(0xa2, &[&der(DER_OCTET_STRING, &[&key_hash])]);
+        let tbs = der(
+            DER_SEQUENCE,
+            &[&responder_id, &this_update, &der(DER_SEQUENCE, &[&single])],
+        );
+
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        let signature = signer.sign_oneshot_to_vec(&tbs).unwrap();
+        let algorithm = match key.id() {
+            Id::RSA => [RSA_OID_PREFIX, &[0x0b]].concat(),
+            _ => [ECDSA_OID_PREFIX, &[0x02]].concat(),
+        };
+
+        let certs = certs
+            .iter()
+            .map(|cert| cert.to_der().unwrap())
+            .collect::<Vec<_>>();
+        let certs = certs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let certs = if certs.is_empty() {
+            vec![]
+        } else {
+            der(DER_CONTEXT_0, &[&der(DER_SEQUENCE, &certs)])
+        };
+
+        let basic = der(
+            DER_SEQUENCE,
+            &[
+                &tbs,
+                &der(DER_SEQUENCE, &[&der(DER_OID, &[&algorithm])]),
+                &der(DER_BIT_STRING, &[&[0], &signature]),
+                &certs,
+            ],
+        );
+        let bytes = der(
+            DER_SEQUENCE,
+            &[
+                &der(DER_OID, &[OCSP_BASIC_OID]),
+                &der(DER_OCTET_STRING, &[&basic]),
+            ],
+        );
+
+        der(
+            DER_SEQUENCE,
+            &[
+                &der(DER_ENUMERATED, &[SUCCESSFUL]),
+                &der(DER_CONTEXT_0, &[&bytes]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_check_stapling() {
+        let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+        let root_key =
+            PKey::private_key_from_pem(include_bytes!("../../test/root-ca.key")).unwrap();
+        let (leaf, leaf_key) = must_staple_cert(&root, &root_key);
+        assert!(requires_stapling(&leaf));
+
+        let mut chain = Stack::new().unwrap();
+        chain.push(leaf.clone()).unwrap();
+        chain.push(root.clone()).unwrap();
+
+        let good = response(&leaf, &root, &root_key, false);
+        assert_eq!(check_stapling(&chain, Some(&good)), Ok(()));
+        assert_eq!(
+            check_stapling(&chain, None),
+            Err(X509VerifyError::MUST_STAPLE_NOT_MET)
+        );
+
+        let revoked = response(&leaf, &root, &root_key, true);
+        assert_eq!(
+            check_stapling(&chain, Some(&revoked)),
+            Err(X509VerifyError::CERT_REVOKED)
+        );
+
+        let forged = response(&leaf, &root, &leaf_key, false);
+        assert_eq!(
+            check_stapling(&chain, Some(&forged)),
+            Err(X509VerifyError::MUST_STAPLE_NOT_MET)
+        );
+
+        let (responder, responder_key) = responder_cert(&root, &root_key);
+        let delegated = response_with_certs(&leaf, &root, &responder_key, false, &[&responder]);
+        assert_eq!(check_stapling(&chain, Some(&delegated)), Ok(()));
+
+        // A sibling certificate without an extended key usage does not sign for the issuer.
+        let (sibling, sibling_key) = must_staple_cert(&root, &root_key);
+        let forged = response_with_certs(&leaf, &root, &sibling_key, false, &[&sibling]);
+        assert_eq!(
+            check_stapling(&chain, Some(&forged)),
+            Err(X509VerifyError::MUST_STAPLE_NOT_MET)
+        );
+
+        let mut tampered = good;
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            check_stapling(&chain, Some(&tampered)),
+            Err(X509VerifyError::MUST_STAPLE_NOT_MET)
+        );
+
+        let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+        assert!(!requires_stapling(&cert));
+
+        let mut chain = Stack::new().unwrap();
+        chain.push(cert).unwrap();
+        chain.push(root).unwrap();
+        assert_eq!(check_stapling(&chain, None), Ok(()));
+    }
+}
@@ -0,0 +1,194 @@
+//! Certificate chain verification by the operating system.
+//!
+//! See [`SslConnectorBuilder::set_platform_verifier`](super::SslConnectorBuilder::set_platform_verifier).
+use crate::stack::StackRef;
+use crate::x509::{X509VerifyError, X509};
+
+/// Whether the platform has a verifier replacing BoringSSL's.
+pub(super) const IS_NATIVE: bool = cfg!(any(target_vendor = "apple", windows));
+
+/// Verifies `chain`, leaf first, for a server named `host`, or any server if `host` is `None`.
+pub(super) fn verify(chain: &StackRef<X509>, host: Option<&str>) -> Result<(), X509VerifyError> {
+    let chain = chain
+        .iter()
+        .map(|cert| cert.to_der())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| X509VerifyError::UNSPECIFIED)?;
+
+    imp::verify(&chain, host)
+}
+
+#[cfg(target_vendor = "apple")]
+mod imp {
+    use crate::x509::X509VerifyError;
+    use security_framework::certificate::SecCertificate;
+    use security_framework::policy::SecPolicy;
+    use security_framework::secure_transport::SslProtocolSide;
+    use security_framework::trust::SecTrust;
+
+    pub(super) fn verify(chain: &[Vec<u8>], host: Option<&str>) -> Result<(), X509VerifyError> {
+        let certs = chain
+            .iter()
+            .map(|der| SecCertificate::from_der(der))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| X509VerifyError::UNSPECIFIED)?;
+        let policy = SecPolicy::create_ssl(SslProtocolSide::SERVER, host);
+
+        let trust = SecTrust::create_with_certificates(&certs, &[policy])
+            .map_err(|_| X509VerifyError::UNSPECIFIED)?;
+
+        trust
+            .evaluate_with_error()
+            .map_err(|_| X509VerifyError::CERT_UNTRUSTED)
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use crate::x509::X509VerifyError;
+    use std::ffi::c_void;
+    use std::{mem, ptr};
+    use windows_sys::Win32::Foundation::{CERT_E_CN_NO_MATCH, CERT_E_EXPIRED, CRYPT_E_REVOKED};
+    use windows_sys::Win32::Security::Cryptography::{
+        szOID_PKIX_KP_SERVER_AUTH, CertAddEncodedCertificateToStore, CertCloseStore,
+        CertFreeCertificateChain, CertFreeCertificateContext, CertGetCertificateChain,
+        CertOpenStore, CertVerifyCertificateChainPolicy, AUTHTYPE_SERVER, CERT_CHAIN_CONTEXT,
+        CERT_CHAIN_PARA, CERT_CHAIN_POLICY_IGNORE_ALL_REV_UNKNOWN_FLAGS, CERT_CHAIN_POLICY_PARA,
+        CERT_CHAIN_POLICY_SSL, CERT_CHAIN_POLICY_STATUS, CERT_CHAIN_REVOCATION_CHECK_END_CERT,
+        CERT_CONTEXT, CERT_STORE_ADD_ALWAYS, CERT_STORE_PROV_MEMORY,
+        SSL_EXTRA_CERT_CHAIN_POLICY_PARA, USAGE_MATCH_TYPE_AND, X509_ASN_ENCODING,
+    };
+
+    /// Skips the name check when no hostname is verified.
+    const SECURITY_FLAG_IGNORE_CERT_CN_INVALID: u32 = 0x1000;
+
+    pub(super) fn verify(chain: &[Vec<u8>], host: Option<&str>) -> Result<(), X509VerifyError> {
+        let (leaf, intermediates) = chain.split_first().ok_or(X509VerifyError::UNSPECIFIED)?;
+
+        unsafe {
+            let store = CertOpenStore(CERT_STORE_PROV_MEMORY, 0, 0, 0, ptr::null());
+            if store.is_null() {
+                return Err(X509VerifyError::UNSPECIFIED);
+            }
+
+            let mut leaf_ctx: *mut CERT_CONTEXT = ptr::null_mut();
+            let mut chain_ctx: *mut CERT_CHAIN_CONTEXT = ptr::null_mut();
+
+            let result = add_certificate(store, leaf, &mut leaf_ctx)
+                .and_then(|()| {
+                    intermediates
+                        .iter()
+                        .try_for_each(|der| add_certificate(store, der, ptr::null_mut()))
+                })
+                .and_then(|()| build_chain(store, leaf_ctx, &mut chain_ctx))
+                .and_then(|()| check_policy(chain_ctx, host));
+
+            if !chain_ctx.is_null() {
+                CertFreeCertificateChain(chain_ctx);
+            }
+            if !leaf_ctx.is_null() {
+                CertFreeCertificateContext(leaf_ctx);
+            }
+            CertCloseStore(store, 0);
+
+            result
+        }
+    }
+
+    unsafe fn add_certificate(
+        store: *mut c_void,
+        der: &[u8],
+        ctx: *mut *mut CERT_CONTEXT,
+    ) -> Result<(), X509VerifyError> {
+        let len = u32::try_from(der.len()).map_err(|_| X509VerifyError::UNSPECIFIED)?;
+        let ok = CertAddEncodedCertificateToStore(
+            store,
+            X509_ASN_ENCODING,
+            der.as_ptr(),
+            len,
+            CERT_STORE_ADD_ALWAYS,
+            ctx,
+        );
+
+        if ok == 0 {
+            return Err(X509VerifyError::UNSPECIFIED);
+        }
+
+        Ok(())
+    }
+
+    unsafe fn build_chain(
+        store: *mut c_void,
+        leaf: *mut CERT_CONTEXT,
+        chain: &mut *mut CERT_CHAIN_CONTEXT,
+    ) -> Result<(), X509VerifyError> {
+        let mut server_auth = szOID_PKIX_KP_SERVER_AUTH.cast_mut();
+
+        let mut para: CERT_CHAIN_PARA = mem::zeroed();
+        para.cbSize = mem::size_of::<CERT_CHAIN_PARA>() as u32;
+        para.RequestedUsage.dwType = USAGE_MATCH_TYPE_AND;
+        para.RequestedUsage.Usage.cUsageIdentifier = 1;
+        para.RequestedUsage.Usage.rgpszUsageIdentifier = &mut server_auth;
+
+        let ok = CertGetCertificateChain(
+            ptr::null_mut(),
+            leaf,
+            ptr::null(),
+            store,
+            &para,
+            CERT_CHAIN_REVOCATION_CHECK_END_CERT,
+            ptr::null(),
+            chain,
+        );
+
+        if ok == 0 {
+            return Err(X509VerifyError::UNSPECIFIED);
+        }
+
+        Ok(())
+    }
+
+    unsafe fn check_policy(
+        chain: *mut CERT_CHAIN_CONTEXT,
+        host: Option<&str>,
+    ) -> Result<(), X509VerifyError> {
+        let mut host = host.map(|host| host.encode_utf16().chain([0]).collect::<Vec<u16>>());
+
+        let mut extra: SSL_EXTRA_CERT_CHAIN_POLICY_PARA = mem::zeroed();
+        extra.Anonymous.cbSize = mem::size_of::<SSL_EXTRA_CERT_CHAIN_POLICY_PARA>() as u32;
+        extra.dwAuthType = AUTHTYPE_SERVER;
+        match &mut host {
+            Some(host) => extra.pwszServerName = host.as_mut_ptr(),
+            None => extra.fdwChecks = SECURITY_FLAG_IGNORE_CERT_CN_INVALID,
+        }
+
+        let mut para: CERT_CHAIN_POLICY_PARA = mem::zeroed();
+        para.cbSize = mem::size_of::<CERT_CHAIN_POLICY_PARA>() as u32;
+        para.dwFlags = CERT_CHAIN_POLICY_IGNORE_ALL_REV_UNKNOWN_FLAGS;
+        para.pvExtraPolicyPara = (&mut extra as *mut SSL_EXTRA_CERT_CHAIN_POLICY_PARA).cast();
+
+        let mut status: CERT_CHAIN_POLICY_STATUS = mem::zeroed();
+        status.cbSize = mem::size_of::<CERT_CHAIN_POLICY_STATUS>() as u32;
+
+        if CertVerifyCertificateChainPolicy(CERT_CHAIN_POLICY_SSL, chain, &para, &mut status) == 0 {
+            return Err(X509VerifyError::UNSPECIFIED);
+        }
+
+        match status.dwError as i32 {
+            0 => Ok(()),
+            CERT_E_CN_NO_MATCH => Err(X509VerifyError::HOSTNAME_MISMATCH),
+            CERT_E_EXPIRED => Err(X509VerifyError::CERT_HAS_EXPIRED),
+            CRYPT_E_REVOKED => Err(X509VerifyError::CERT_REVOKED),
+            _ => Err(X509VerifyError::CERT_UNTRUSTED),
+        }
+    }
+}
+
+#[cfg(not(any(target_vendor = "apple", windows)))]
+mod imp {
+    use crate::x509::X509VerifyError;
+
+    pub(super) fn verify(_: &[Vec<u8>], _: Option<&str>) -> Result<(), X509VerifyError> {
+        unreachable!("BoringSSL verifies chains on platforms without a native verifier")
+    }
+}
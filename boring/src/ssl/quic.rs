@@ -0,0 +1,1004 @@
+//! QUIC support, as described in [RFC 9001].
+//!
+//! In QUIC, TLS does not run over a byte stream. Instead, handshake messages are exchanged in
+//! CRYPTO frames at a given encryption level, and the traffic secrets derived by the handshake are
+//! handed to the QUIC transport, which does its own packet protection. [`QuicSession`] drives a
+//! handshake in this mode and reports everything the transport needs to act on as [`QuicEvent`]s.
+//!
+//! [`QuicKeys`] derives the packet and header protection keys from those secrets, and
+//! [`QuicKeyPair::initial`] and [`quic_retry_tag`] cover the keys QUIC derives without TLS.
+//! QUIC stacks are written against the [`QuicCryptoSession`] trait, which [`QuicSession`]
+//! implements, in the same way as quinn-proto's `crypto::Session`.
+//!
+//! [RFC 9001]: https://www.rfc-editor.org/rfc/rfc9001
+use super::error::InnerError;
+use super::{Error, ErrorCode, Ssl, SslCipher, SslCipherRef, SslRef};
+use crate::aead::{Aead, AeadCtx};
+use crate::aes::AesKey;
+use crate::chacha;
+use crate::error::ErrorStack;
+use crate::ex_data::Index;
+use crate::hash::MessageDigest;
+use crate::{cvt, ffi, kdf, memcmp};
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::c_int;
+use openssl_macros::corresponds;
+use std::collections::VecDeque;
+use std::fmt;
+use std::ptr;
+use std::slice;
+use std::sync::LazyLock;
+
+static QUIC_STATE_INDEX: LazyLock<Index<Ssl, VecDeque<QuicEvent>>> =
+    LazyLock::new(|| Ssl::new_ex_index().unwrap());
+
+static QUIC_METHOD: ffi::SSL_QUIC_METHOD = ffi::SSL_QUIC_METHOD {
+    set_read_secret: Some(set_read_secret),
+    set_write_secret: Some(set_write_secret),
+    add_handshake_data: Some(add_handshake_data),
+    flush_flight: Some(flush_flight),
+    send_alert: Some(send_alert),
+};
+
+/// A QUIC encryption level.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum QuicEncryptionLevel {
+    /// Initial packets, protected with keys derived from the destination connection ID.
+    Initial,
+    /// 0-RTT packets.
+    EarlyData,
+    /// Handshake packets.
+    Handshake,
+    /// 1-RTT packets.
+    Application,
+}
+
+impl QuicEncryptionLevel {
+    fn from_raw(level: ffi::ssl_encryption_level_t) -> QuicEncryptionLevel {
+        match level {
+            ffi::ssl_encryption_level_t::ssl_encryption_initial => QuicEncryptionLevel::Initial,
+            ffi::ssl_encryption_level_t::ssl_encryption_early_data => {
+                QuicEncryptionLevel::EarlyData
+            }
+            ffi::ssl_encryption_level_t::ssl_encryption_handshake => QuicEncryptionLevel::Handshake,
+            _ => QuicEncryptionLevel::Application,
+        }
+    }
+
+    fn as_raw(self) -> ffi::ssl_encryption_level_t {
+        match self {
+            QuicEncryptionLevel::Initial => ffi::ssl_encryption_level_t::ssl_encryption_initial,
+            QuicEncryptionLevel::EarlyData => {
+                ffi::ssl_encryption_level_t::ssl_encryption_early_data
+            }
+            QuicEncryptionLevel::Handshake => ffi::ssl_encryption_level_t::ssl_encryption_handshake,
+            QuicEncryptionLevel::Application => {
+                ffi::ssl_encryption_level_t::ssl_encryption_application
+            }
+        }
+    }
+}
+
+/// A traffic secret installed by the handshake.
+///
+/// The secret is wiped from memory when this value is dropped.
+pub struct QuicSecret {
+    level: QuicEncryptionLevel,
+    cipher: SslCipher,
+    secret: Vec<u8>,
+}
+
+impl QuicSecret {
+    /// Returns the encryption level this secret protects.
+    #[must_use]
+    pub fn level(&self) -> QuicEncryptionLevel {
+        self.level
+    }
+
+    /// Returns the cipher suite the packet protection keys must be derived for.
+    #[must_use]
+    pub fn cipher(&self) -> SslCipher {
+        self.cipher
+    }
+
+    /// Returns the raw traffic secret.
+    #[must_use]
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Derives the secret of the next 1-RTT key phase, as described in [RFC 9001 §6].
+    ///
+    /// [RFC 9001 §6]: https://www.rfc-editor.org/rfc/rfc9001#section-6
+    pub fn update(&self) -> Result<QuicSecret, ErrorStack> {
+        let suite = Suite::new(&self.cipher)?;
+        let mut secret = vec![0; self.secret.len()];
+        expand_label(suite.digest(), &self.secret, b"quic ku", &mut secret)?;
+
+        Ok(QuicSecret {
+            level: self.level,
+            cipher: self.cipher,
+            secret,
+        })
+    }
+}
+
+impl Drop for QuicSecret {
+    fn drop(&mut self) {
+        unsafe { ffi::OPENSSL_cleanse(self.secret.as_mut_ptr().cast(), self.secret.len()) }
+    }
+}
+
+impl fmt::Debug for QuicSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("QuicSecret")
+            .field("level", &self.level)
+            .field("cipher", &self.cipher.name())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The salt Initial secrets are extracted with, from [RFC 9001 §5.2].
+///
+/// [RFC 9001 §5.2]: https://www.rfc-editor.org/rfc/rfc9001#section-5.2
+const INITIAL_SALT: [u8; 20] = [
+    0x38, 0x76, 0x2c, 0xf7, 0xf5, 0x59, 0x34, 0xb3, 0x4d, 0x17, 0x9a, 0xe6, 0xa4, 0xc8, 0x0c, 0xad,
+    0xcc, 0xbb, 0x7f, 0x0a,
+];
+
+/// The key and nonce Retry integrity tags are computed with, from [RFC 9001 §5.8].
+///
+/// [RFC 9001 §5.8]: https://www.rfc-editor.org/rfc/rfc9001#section-5.8
+const RETRY_KEY: [u8; 16] = [
+    0xbe, 0x0c, 0x69, 0x0b, 0x9f, 0x66, 0x57, 0x5a, 0x1d, 0x76, 0x6b, 0x54, 0xe3, 0x68, 0xc8, 0x4e,
+];
+const RETRY_NONCE: [u8; 12] = [
+    0x46, 0x15, 0x99, 0xd3, 0x5d, 0x63, 0x2b, 0xf2, 0x23, 0x98, 0x25, 0xbb,
+];
+
+/// The length of a Retry integrity tag, in bytes.
+pub const QUIC_RETRY_TAG_LEN: usize = 16;
+
+/// The length of the ciphertext sample header protection masks are computed from, in bytes.
+pub const QUIC_HEADER_SAMPLE_LEN: usize = 16;
+
+/// The TLS 1.3 cipher suites QUIC packet protection is defined for.
+#[derive(Copy, Clone)]
+enum Suite {
+    Aes128Gcm,
+    Aes256Gcm,
+    ChaCha20Poly1305,
+}
+
+impl Suite {
+    fn new(cipher: &SslCipherRef) -> Result<Suite, ErrorStack> {
+        match cipher.protocol_id() {
+            0x1301 => Ok(Suite::Aes128Gcm),
+            0x1302 => Ok(Suite::Aes256Gcm),
+            0x1303 => Ok(Suite::ChaCha20Poly1305),
+            _ => Err(ErrorStack::internal_error_str(
+                "cipher suite is not supported by QUIC",
+            )),
+        }
+    }
+
+    fn digest(self) -> MessageDigest {
+        match self {
+            Suite::Aes256Gcm => MessageDigest::sha384(),
+            Suite::Aes128Gcm | Suite::ChaCha20Poly1305 => MessageDigest::sha256(),
+        }
+    }
+
+    fn aead(self) -> Aead {
+        match self {
+            Suite::Aes128Gcm => Aead::aes_128_gcm(),
+            Suite::Aes256Gcm => Aead::aes_256_gcm(),
+            Suite::ChaCha20Poly1305 => Aead::chacha20_poly1305(),
+        }
+    }
+
+    fn key_len(self) -> usize {
+        match self {
+            Suite::Aes128Gcm => 16,
+            Suite::Aes256Gcm | Suite::ChaCha20Poly1305 => 32,
+        }
+    }
+}
+
+/// Implements `HKDF-Expand-Label` from [RFC 8446 §7.1], with an empty context.
+///
+/// [RFC 8446 §7.1]: https://www.rfc-editor.org/rfc/rfc8446#section-7.1
+fn expand_label(
+    digest: MessageDigest,
+    secret: &[u8],
+    label: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    const PREFIX: &[u8] = b"tls13 ";
+
+    let mut info = Vec::with_capacity(4 + PREFIX.len() + label.len());
+    info.extend_from_slice(&(out.len() as u16).to_be_bytes());
+    info.push((PREFIX.len() + label.len()) as u8);
+    info.extend_from_slice(PREFIX);
+    info.extend_from_slice(label);
+    info.push(0);
+
+    kdf::hkdf_expand(digest, secret, &info, out)
+}
+
+/// The key protecting the payload of QUIC packets, as described in [RFC 9001 §5.3].
+///
+/// [RFC 9001 §5.3]: https://www.rfc-editor.org/rfc/rfc9001#section-5.3
+pub struct QuicPacketKey {
+    suite: Suite,
+    ctx: AeadCtx,
+    iv: [u8; 12],
+}
+
+impl QuicPacketKey {
+    fn new(suite: Suite, secret: &[u8]) -> Result<QuicPacketKey, ErrorStack> {
+        let mut key = [0; 32];
+        let key = &mut key[..suite.key_len()];
+        let mut iv = [0; 12];
+
+        let ctx = expand_label(suite.digest(), secret, b"quic key", key)
+            .and_then(|()| expand_label(suite.digest(), secret, b"quic iv", &mut iv))
+            .and_then(|()| AeadCtx::new(suite.aead(), key));
+        memcmp::cleanse(key);
+
+        Ok(QuicPacketKey {
+            suite,
+            ctx: ctx?,
+            iv,
+        })
+    }
+
+    fn nonce(&self, packet_number: u64) -> [u8; 12] {
+        let mut nonce = self.iv;
+        for (nonce, pn) in nonce[4..].iter_mut().zip(packet_number.to_be_bytes()) {
+            *nonce ^= pn;
+        }
+        nonce
+    }
+
+    /// Encrypts `payload` in place and appends the tag to it.
+    ///
+    /// `header` is the unprotected header of the packet, which is authenticated but not encrypted.
+    pub fn encrypt(
+        &self,
+        packet_number: u64,
+        header: &[u8],
+        payload: &mut Vec<u8>,
+    ) -> Result<(), ErrorStack> {
+        self.ctx
+            .seal_in_place_append_tag(&self.nonce(packet_number), payload, header)
+    }
+
+    /// Decrypts `payload`, which ends with the tag, in place.
+    ///
+    /// On success, `payload` is truncated to the plaintext.
+    pub fn decrypt(
+        &self,
+        packet_number: u64,
+        header: &[u8],
+        payload: &mut Vec<u8>,
+    ) -> Result<(), ErrorStack> {
+        self.ctx
+            .open_in_place(&self.nonce(packet_number), payload, header)
+    }
+
+    /// Returns the length of the tag appended to each payload, in bytes.
+    #[must_use]
+    pub fn tag_len(&self) -> usize {
+        self.suite.aead().max_overhead()
+    }
+
+    /// Returns how many packets may be encrypted with this key before it must be updated, as
+    /// described in [RFC 9001 §6.6].
+    ///
+    /// [RFC 9001 §6.6]: https://www.rfc-editor.org/rfc/rfc9001#section-6.6
+    #[must_use]
+    pub fn confidentiality_limit(&self) -> u64 {
+        match self.suite {
+            Suite::Aes128Gcm | Suite::Aes256Gcm => 1 << 23,
+            // The limit is larger than the number of possible packets.
+            Suite::ChaCha20Poly1305 => u64::MAX,
+        }
+    }
+
+    /// Returns how many packets may fail to decrypt with this key before the connection must be
+    /// closed, as described in [RFC 9001 §6.6].
+    ///
+    /// [RFC 9001 §6.6]: https://www.rfc-editor.org/rfc/rfc9001#section-6.6
+    #[must_use]
+    pub fn integrity_limit(&self) -> u64 {
+        match self.suite {
+            Suite::Aes128Gcm | Suite::Aes256Gcm => 1 << 52,
+            Suite::ChaCha20Poly1305 => 1 << 36,
+        }
+    }
+}
+
+impl fmt::Debug for QuicPacketKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("QuicPacketKey").finish_non_exhaustive()
+    }
+}
+
+enum HeaderCipher {
+    Aes(AesKey),
+    ChaCha20([u8; chacha::KEY_LEN]),
+}
+
+/// The key protecting the header of QUIC packets, as described in [RFC 9001 §5.4].
+///
+/// [RFC 9001 §5.4]: https://www.rfc-editor.org/rfc/rfc9001#section-5.4
+pub struct QuicHeaderKey(HeaderCipher);
+
+impl QuicHeaderKey {
+    fn new(suite: Suite, secret: &[u8]) -> Result<QuicHeaderKey, ErrorStack> {
+        let mut key = [0; 32];
+        let key_len = suite.key_len();
+
+        let cipher =
+            expand_label(suite.digest(), secret, b"quic hp", &mut key[..key_len]).map(|()| {
+                match suite {
+                    Suite::ChaCha20Poly1305 => HeaderCipher::ChaCha20(key),
+                    // The key length is always valid for AES.
+                    Suite::Aes128Gcm | Suite::Aes256Gcm => {
+                        HeaderCipher::Aes(AesKey::new_encrypt(&key[..key_len]).unwrap())
+                    }
+                }
+            });
+        memcmp::cleanse(&mut key);
+
+        Ok(QuicHeaderKey(cipher?))
+    }
+
+    /// Returns the mask computed from `sample`.
+    ///
+    /// The first byte masks the flags of the first byte of the header, and the others mask the
+    /// packet number.
+    #[must_use]
+    pub fn mask(&self, sample: &[u8; QUIC_HEADER_SAMPLE_LEN]) -> [u8; 5] {
+        let mut mask = [0; 5];
+        match &self.0 {
+            HeaderCipher::Aes(key) => {
+                let mut block = [0; 16];
+                unsafe { ffi::AES_encrypt(sample.as_ptr(), block.as_mut_ptr(), key.as_ptr()) }
+                mask.copy_from_slice(&block[..5]);
+            }
+            HeaderCipher::ChaCha20(key) => {
+                let (counter, nonce) = sample.split_at(4);
+                let counter = u32::from_le_bytes(counter.try_into().unwrap());
+                chacha::keystream(key, nonce.try_into().unwrap(), counter, &mut mask);
+            }
+        }
+        mask
+    }
+
+    /// Protects the header of `packet`, whose packet number starts at `pn_offset`.
+    ///
+    /// The payload of `packet` must already be encrypted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `packet` is too short to hold the sample that follows the packet number.
+    pub fn encrypt(&self, pn_offset: usize, packet: &mut [u8]) {
+        self.apply(pn_offset, packet, true);
+    }
+
+    /// Removes the protection of the header of `packet`, whose packet number starts at
+    /// `pn_offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `packet` is too short to hold the sample that follows the packet number.
+    pub fn decrypt(&self, pn_offset: usize, packet: &mut [u8]) {
+        self.apply(pn_offset, packet, false);
+    }
+
+    fn apply(&self, pn_offset: usize, packet: &mut [u8], protect: bool) {
+        // The sample is taken as if the packet number were 4 bytes long.
+        let sample_offset = pn_offset + 4;
+        let sample = packet[sample_offset..sample_offset + QUIC_HEADER_SAMPLE_LEN]
+            .try_into()
+            .unwrap();
+        let mask = self.mask(&sample);
+
+        let flags = if packet[0] & 0x80 != 0 { 0x0f } else { 0x1f };
+        let mut pn_len = usize::from(packet[0] & 0x03) + 1;
+        packet[0] ^= mask[0] & flags;
+        if !protect {
+            pn_len = usize::from(packet[0] & 0x03) + 1;
+        }
+
+        for (byte, mask) in packet[pn_offset..pn_offset + pn_len]
+            .iter_mut()
+            .zip(&mask[1..])
+        {
+            *byte ^= mask;
+        }
+    }
+}
+
+impl Drop for QuicHeaderKey {
+    fn drop(&mut self) {
+        if let HeaderCipher::ChaCha20(key) = &mut self.0 {
+            memcmp::cleanse(key);
+        }
+    }
+}
+
+impl fmt::Debug for QuicHeaderKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("QuicHeaderKey").finish_non_exhaustive()
+    }
+}
+
+/// The packet and header protection keys derived from a traffic secret.
+#[derive(Debug)]
+pub struct QuicKeys {
+    /// The key protecting packet payloads.
+    pub packet: QuicPacketKey,
+    /// The key protecting packet headers.
+    pub header: QuicHeaderKey,
+}
+
+impl QuicKeys {
+    /// Derives the keys for a traffic secret installed by the handshake.
+    ///
+    /// This fails if the secret's cipher suite is not one of the three TLS 1.3 suites QUIC
+    /// defines packet protection for.
+    pub fn new(secret: &QuicSecret) -> Result<QuicKeys, ErrorStack> {
+        QuicKeys::derive(Suite::new(&secret.cipher)?, &secret.secret)
+    }
+
+    fn derive(suite: Suite, secret: &[u8]) -> Result<QuicKeys, ErrorStack> {
+        Ok(QuicKeys {
+            packet: QuicPacketKey::new(suite, secret)?,
+            header: QuicHeaderKey::new(suite, secret)?,
+        })
+    }
+}
+
+/// The keys of both directions at one encryption level.
+#[derive(Debug)]
+pub struct QuicKeyPair {
+    /// The keys protecting the packets this endpoint sends.
+    pub local: QuicKeys,
+    /// The keys protecting the packets the peer sends.
+    pub remote: QuicKeys,
+}
+
+impl QuicKeyPair {
+    /// Derives the Initial keys of a connection, as described in [RFC 9001 §5.2].
+    ///
+    /// `dst_cid` is the Destination Connection ID of the first Initial packet the client sent.
+    ///
+    /// [RFC 9001 §5.2]: https://www.rfc-editor.org/rfc/rfc9001#section-5.2
+    pub fn initial(dst_cid: &[u8], is_server: bool) -> Result<QuicKeyPair, ErrorStack> {
+        let suite = Suite::Aes128Gcm;
+        let digest = suite.digest();
+
+        let mut initial_secret = kdf::hkdf_extract(digest, dst_cid, &INITIAL_SALT)?;
+        let mut client_secret = [0; 32];
+        let mut server_secret = [0; 32];
+        let keys = expand_label(digest, &initial_secret, b"client in", &mut client_secret)
+            .and_then(|()| expand_label(digest, &initial_secret, b"server in", &mut server_secret))
+            .and_then(|()| {
+                Ok((
+                    QuicKeys::derive(suite, &client_secret)?,
+                    QuicKeys::derive(suite, &server_secret)?,
+                ))
+            });
+        memcmp::cleanse(&mut initial_secret);
+        memcmp::cleanse(&mut client_secret);
+        memcmp::cleanse(&mut server_secret);
+
+        let (client, server) = keys?;
+        Ok(if is_server {
+            QuicKeyPair {
+                local: server,
+                remote: client,
+            }
+        } else {
+            QuicKeyPair {
+                local: client,
+                remote: server,
+            }
+        })
+    }
+}
+
+/// Computes the integrity tag of a Retry packet, as described in [RFC 9001 §5.8].
+///
+/// `orig_dst_cid` is the Destination Connection ID of the client's first Initial packet, and
+/// `packet` is the Retry packet up to, but not including, the tag.
+///
+/// [RFC 9001 §5.8]: https://www.rfc-editor.org/rfc/rfc9001#section-5.8
+pub fn quic_retry_tag(
+    orig_dst_cid: &[u8],
+    packet: &[u8],
+) -> Result<[u8; QUIC_RETRY_TAG_LEN], ErrorStack> {
+    let Ok(cid_len) = u8::try_from(orig_dst_cid.len()) else {
+        return Err(ErrorStack::internal_error_str("connection ID is too long"));
+    };
+
+    let mut pseudo_packet = Vec::with_capacity(1 + orig_dst_cid.len() + packet.len());
+    pseudo_packet.push(cid_len);
+    pseudo_packet.extend_from_slice(orig_dst_cid);
+    pseudo_packet.extend_from_slice(packet);
+
+    let ctx = AeadCtx::new(Aead::aes_128_gcm(), &RETRY_KEY)?;
+    let mut tag = [0; QUIC_RETRY_TAG_LEN];
+    ctx.seal_in_place_detached(&RETRY_NONCE, &mut [], &mut tag, &pseudo_packet)?;
+    Ok(tag)
+}
+
+/// Something the QUIC transport must act upon, reported by [`QuicSession::poll_event`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum QuicEvent {
+    /// Packets at the secret's level may now be decrypted with it.
+    ReadSecret(QuicSecret),
+    /// Packets at the secret's level must now be encrypted with it.
+    WriteSecret(QuicSecret),
+    /// Handshake data to be sent to the peer in CRYPTO frames at `level`.
+    HandshakeData {
+        level: QuicEncryptionLevel,
+        data: Vec<u8>,
+    },
+    /// All handshake data queued so far should be sent to the peer.
+    FlushFlight,
+    /// The handshake failed, and the connection should be closed with the given TLS alert.
+    SendAlert {
+        level: QuicEncryptionLevel,
+        alert: u8,
+    },
+}
+
+unsafe fn push_event(ssl: *mut ffi::SSL, event: QuicEvent) -> c_int {
+    let ssl = SslRef::from_ptr_mut(ssl);
+    match ssl.ex_data_mut(*QUIC_STATE_INDEX) {
+        Some(events) => {
+            events.push_back(event);
+            1
+        }
+        None => 0,
+    }
+}
+
+unsafe fn secret(
+    level: ffi::ssl_encryption_level_t,
+    cipher: *const ffi::SSL_CIPHER,
+    secret: *const u8,
+    secret_len: usize,
+) -> QuicSecret {
+    QuicSecret {
+        level: QuicEncryptionLevel::from_raw(level),
+        cipher: SslCipher::from_ptr(cipher as *mut _),
+        secret: slice::from_raw_parts(secret, secret_len).to_vec(),
+    }
+}
+
+unsafe extern "C" fn set_read_secret(
+    ssl: *mut ffi::SSL,
+    level: ffi::ssl_encryption_level_t,
+    cipher: *const ffi::SSL_CIPHER,
+    secret_ptr: *const u8,
+    secret_len: usize,
+) -> c_int {
+    let secret = secret(level, cipher, secret_ptr, secret_len);
+    push_event(ssl, QuicEvent::ReadSecret(secret))
+}
+
+unsafe extern "C" fn set_write_secret(
+    ssl: *mut ffi::SSL,
+    level: ffi::ssl_encryption_level_t,
+    cipher: *const ffi::SSL_CIPHER,
+    secret_ptr: *const u8,
+    secret_len: usize,
+) -> c_int {
+    let secret = secret(level, cipher, secret_ptr, secret_len);
+    push_event(ssl, QuicEvent::WriteSecret(secret))
+}
+
+unsafe extern "C" fn add_handshake_data(
+    ssl: *mut ffi::SSL,
+    level: ffi::ssl_encryption_level_t,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    let event = QuicEvent::HandshakeData {
+        level: QuicEncryptionLevel::from_raw(level),
+        data: slice::from_raw_parts(data, len).to_vec(),
+    };
+    push_event(ssl, event)
+}
+
+unsafe extern "C" fn flush_flight(ssl: *mut ffi::SSL) -> c_int {
+    push_event(ssl, QuicEvent::FlushFlight)
+}
+
+unsafe extern "C" fn send_alert(
+    ssl: *mut ffi::SSL,
+    level: ffi::ssl_encryption_level_t,
+    alert: u8,
+) -> c_int {
+    let event = QuicEvent::SendAlert {
+        level: QuicEncryptionLevel::from_raw(level),
+        alert,
+    };
+    push_event(ssl, event)
+}
+
+impl SslRef {
+    /// Sets the QUIC transport parameters sent to the peer.
+    ///
+    /// The parameters are an opaque, already serialized byte string. This may only be called on a
+    /// connection configured for QUIC.
+    #[corresponds(SSL_set_quic_transport_params)]
+    pub fn set_quic_transport_params(&mut self, params: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_set_quic_transport_params(
+                self.as_ptr(),
+                params.as_ptr(),
+                params.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the QUIC transport parameters received from the peer, if any.
+    #[corresponds(SSL_get_peer_quic_transport_params)]
+    #[must_use]
+    pub fn peer_quic_transport_params(&self) -> Option<&[u8]> {
+        let mut ptr = ptr::null();
+        let mut len = 0;
+        unsafe {
+            ffi::SSL_get_peer_quic_transport_params(self.as_ptr(), &mut ptr, &mut len);
+            if ptr.is_null() || len == 0 {
+                None
+            } else {
+                Some(slice::from_raw_parts(ptr, len))
+            }
+        }
+    }
+
+    /// Sets the context that must match for the server to accept 0-RTT on a resumed connection.
+    ///
+    /// Servers must set a non-empty context covering the transport parameters and any application
+    /// state to support 0-RTT in QUIC.
+    #[corresponds(SSL_set_quic_early_data_context)]
+    pub fn set_quic_early_data_context(&mut self, context: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_set_quic_early_data_context(
+                self.as_ptr(),
+                context.as_ptr(),
+                context.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sends the transport parameters under the codepoint used by pre-RFC drafts of QUIC.
+    #[corresponds(SSL_set_quic_use_legacy_codepoint)]
+    pub fn set_quic_use_legacy_codepoint(&mut self, use_legacy: bool) {
+        unsafe { ffi::SSL_set_quic_use_legacy_codepoint(self.as_ptr(), use_legacy as c_int) }
+    }
+
+    /// Returns the encryption level handshake data is currently expected at.
+    #[corresponds(SSL_quic_read_level)]
+    #[must_use]
+    pub fn quic_read_level(&self) -> QuicEncryptionLevel {
+        unsafe { QuicEncryptionLevel::from_raw(ffi::SSL_quic_read_level(self.as_ptr())) }
+    }
+
+    /// Returns the encryption level handshake data is currently written at.
+    #[corresponds(SSL_quic_write_level)]
+    #[must_use]
+    pub fn quic_write_level(&self) -> QuicEncryptionLevel {
+        unsafe { QuicEncryptionLevel::from_raw(ffi::SSL_quic_write_level(self.as_ptr())) }
+    }
+
+    /// Returns the largest amount of handshake data the peer may send at `level` in one flight.
+    ///
+    /// Transports may use this to bound how much unprocessed CRYPTO data they buffer.
+    #[corresponds(SSL_quic_max_handshake_flight_len)]
+    #[must_use]
+    pub fn quic_max_handshake_flight_len(&self, level: QuicEncryptionLevel) -> usize {
+        unsafe { ffi::SSL_quic_max_handshake_flight_len(self.as_ptr(), level.as_raw()) }
+    }
+}
+
+/// The TLS side of a QUIC connection, as seen by the QUIC transport.
+///
+/// This is modelled on quinn-proto's `crypto::Session`. The handshake is driven through the
+/// required methods, and the provided methods derive packet protection keys as specified by
+/// [RFC 9001], which implementors rarely need to override.
+///
+/// [RFC 9001]: https://www.rfc-editor.org/rfc/rfc9001
+pub trait QuicCryptoSession {
+    /// Returns `true` if this is the server side of the connection.
+    fn is_server(&self) -> bool;
+
+    /// Returns `true` while the handshake has not completed.
+    fn is_handshaking(&self) -> bool;
+
+    /// Passes handshake data received from the peer at `level` and advances the handshake.
+    ///
+    /// Returns `true` once the handshake is complete.
+    fn read_handshake(&mut self, level: QuicEncryptionLevel, data: &[u8]) -> Result<bool, Error>;
+
+    /// Returns the oldest event the transport has not yet acted upon.
+    fn poll_event(&mut self) -> Option<QuicEvent>;
+
+    /// Returns the QUIC transport parameters received from the peer, if any.
+    fn peer_transport_params(&self) -> Option<&[u8]>;
+
+    /// Derives the Initial keys of a connection whose client first sent to `dst_cid`.
+    fn initial_keys(&self, dst_cid: &[u8]) -> Result<QuicKeyPair, ErrorStack> {
+        QuicKeyPair::initial(dst_cid, self.is_server())
+    }
+
+    /// Derives the packet and header protection keys for a secret reported by
+    /// [`poll_event`](Self::poll_event).
+    fn keys(&self, secret: &QuicSecret) -> Result<QuicKeys, ErrorStack> {
+        QuicKeys::new(secret)
+    }
+
+    /// Derives the secret of the next 1-RTT key phase.
+    fn next_1rtt_secret(&self, secret: &QuicSecret) -> Result<QuicSecret, ErrorStack> {
+        secret.update()
+    }
+
+    /// Computes the integrity tag of a Retry packet sent in reply to `orig_dst_cid`.
+    fn retry_tag(
+        &self,
+        orig_dst_cid: &[u8],
+        packet: &[u8],
+    ) -> Result<[u8; QUIC_RETRY_TAG_LEN], ErrorStack> {
+        quic_retry_tag(orig_dst_cid, packet)
+    }
+
+    /// Returns `true` if `packet`, a Retry packet ending with its integrity tag, was sent in
+    /// reply to `orig_dst_cid`.
+    fn is_valid_retry(&self, orig_dst_cid: &[u8], packet: &[u8]) -> bool {
+        let Some(tag_offset) = packet.len().checked_sub(QUIC_RETRY_TAG_LEN) else {
+            return false;
+        };
+        let (packet, tag) = packet.split_at(tag_offset);
+
+        self.retry_tag(orig_dst_cid, packet)
+            .is_ok_and(|expected| memcmp::eq(&expected, tag))
+    }
+}
+
+/// A TLS handshake carried over QUIC.
+///
+/// Feed CRYPTO frame contents received from the peer to [`provide_data`](Self::provide_data),
+/// call [`handshake`](Self::handshake), then drain [`poll_event`](Self::poll_event) and act on
+/// each event. The connection must only allow TLS 1.3 and negotiate ALPN.
+pub struct QuicSession {
+    ssl: Ssl,
+}
+
+impl QuicSession {
+    /// Configures `ssl` for QUIC, advertising `transport_params` to the peer.
+    #[corresponds(SSL_set_quic_method)]
+    pub fn new(mut ssl: Ssl, transport_params: &[u8]) -> Result<QuicSession, ErrorStack> {
+        ssl.set_ex_data(*QUIC_STATE_INDEX, VecDeque::new());
+        unsafe {
+            cvt(ffi::SSL_set_quic_method(ssl.as_ptr(), &QUIC_METHOD))?;
+        }
+        ssl.set_quic_transport_params(transport_params)?;
+
+        Ok(QuicSession { ssl })
+    }
+
+    /// Advances the handshake.
+    ///
+    /// Returns `true` once the handshake is complete and `false` if more data is needed from the
+    /// peer. Other retriable conditions, such as a pending asynchronous callback, are reported as
+    /// errors with the corresponding [`ErrorCode`].
+    #[corresponds(SSL_do_handshake)]
+    pub fn handshake(&mut self) -> Result<bool, Error> {
+        let ret = unsafe { ffi::SSL_do_handshake(self.ssl.as_ptr()) };
+        if ret > 0 {
+            return Ok(true);
+        }
+
+        let code = self.ssl.error_code(ret);
+        if code == ErrorCode::WANT_READ {
+            return Ok(false);
+        }
+
+        let errs = ErrorStack::get();
+        let cause = if errs.errors().is_empty() {
+            None
+        } else {
+            Some(InnerError::Ssl(errs))
+        };
+
+        Err(Error { code, cause })
+    }
+
+    /// Passes handshake data received from the peer at `level` to the handshake.
+    ///
+    /// Before the handshake completes, the data is consumed by the next call to
+    /// [`handshake`](Self::handshake). Afterwards, it is processed immediately, which is how
+    /// session tickets are received.
+    #[corresponds(SSL_provide_quic_data)]
+    pub fn provide_data(
+        &mut self,
+        level: QuicEncryptionLevel,
+        data: &[u8],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_provide_quic_data(
+                self.ssl.as_ptr(),
+                level.as_raw(),
+                data.as_ptr(),
+                data.len(),
+            ))?;
+
+            if self.ssl.is_init_finished() {
+                cvt(ffi::SSL_process_quic_post_handshake(self.ssl.as_ptr()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the oldest event the transport has not yet acted upon.
+    pub fn poll_event(&mut self) -> Option<QuicEvent> {
+        self.ssl.ex_data_mut(*QUIC_STATE_INDEX)?.pop_front()
+    }
+
+    /// Returns `true` while the handshake has not completed.
+    #[must_use]
+    pub fn is_handshaking(&self) -> bool {
+        !self.ssl.is_init_finished()
+    }
+
+    /// Returns the QUIC transport parameters received from the peer, if any.
+    #[must_use]
+    pub fn peer_transport_params(&self) -> Option<&[u8]> {
+        self.ssl.peer_quic_transport_params()
+    }
+
+    /// Returns a shared reference to the underlying [`Ssl`].
+    #[must_use]
+    pub fn ssl(&self) -> &SslRef {
+        &self.ssl
+    }
+
+    /// Returns a mutable reference to the underlying [`Ssl`].
+    pub fn ssl_mut(&mut self) -> &mut SslRef {
+        &mut self.ssl
+    }
+}
+
+impl QuicCryptoSession for QuicSession {
+    fn is_server(&self) -> bool {
+        self.ssl.is_server()
+    }
+
+    fn is_handshaking(&self) -> bool {
+        QuicSession::is_handshaking(self)
+    }
+
+    fn read_handshake(&mut self, level: QuicEncryptionLevel, data: &[u8]) -> Result<bool, Error> {
+        self.provide_data(level, data)?;
+        self.handshake()
+    }
+
+    fn poll_event(&mut self) -> Option<QuicEvent> {
+        QuicSession::poll_event(self)
+    }
+
+    fn peer_transport_params(&self) -> Option<&[u8]> {
+        QuicSession::peer_transport_params(self)
+    }
+}
+
+impl fmt::Debug for QuicSession {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("QuicSession")
+            .field("ssl", &self.ssl)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex::FromHex;
+
+    // The test vectors of RFC 9001, appendix A.
+    const DST_CID: &str = "8394c8f03e515708";
+
+    fn sample(hex: &str) -> [u8; QUIC_HEADER_SAMPLE_LEN] {
+        <[u8; QUIC_HEADER_SAMPLE_LEN]>::from_hex(hex).unwrap()
+    }
+
+    #[test]
+    fn initial_keys() {
+        let dst_cid = Vec::from_hex(DST_CID).unwrap();
+        let client = QuicKeyPair::initial(&dst_cid, false).unwrap();
+        let server = QuicKeyPair::initial(&dst_cid, true).unwrap();
+
+        let client_sample = sample("d1b1c98dd7689fb8ec11d242b123dc9b");
+        let client_mask = <[u8; 5]>::from_hex("437b9aec36").unwrap();
+        assert_eq!(client.local.header.mask(&client_sample), client_mask);
+        assert_eq!(server.remote.header.mask(&client_sample), client_mask);
+
+        let server_sample = sample("2cd0991cd25b0aac406a5816b6394100");
+        let server_mask = <[u8; 5]>::from_hex("2ec0d8356a").unwrap();
+        assert_eq!(server.local.header.mask(&server_sample), server_mask);
+        assert_eq!(client.remote.header.mask(&server_sample), server_mask);
+
+        let mut payload = b"CRYPTO".to_vec();
+        client
+            .local
+            .packet
+            .encrypt(2, b"header", &mut payload)
+            .unwrap();
+        assert_eq!(payload.len(), 6 + client.local.packet.tag_len());
+        server
+            .remote
+            .packet
+            .decrypt(2, b"header", &mut payload)
+            .unwrap();
+        assert_eq!(payload, b"CRYPTO");
+    }
+
+    #[test]
+    fn chacha20_short_header() {
+        let secret =
+            Vec::from_hex("9ac312a7f877468ebe69422748ad00a15443f18203a07d6060f688f30f21632b")
+                .unwrap();
+        let keys = QuicKeys::derive(Suite::ChaCha20Poly1305, &secret).unwrap();
+
+        let header = Vec::from_hex("4200bff4").unwrap();
+        let mut payload = vec![0x01];
+        keys.packet
+            .encrypt(654360564, &header, &mut payload)
+            .unwrap();
+        let mut packet = [&header[..], &payload].concat();
+        keys.header.encrypt(1, &mut packet);
+        assert_eq!(
+            packet,
+            Vec::from_hex("4cfe4189655e5cd55c41f69080575d7999c25a5bfb").unwrap()
+        );
+
+        keys.header.decrypt(1, &mut packet);
+        let mut payload = packet.split_off(4);
+        assert_eq!(packet, header);
+        keys.packet
+            .decrypt(654360564, &packet, &mut payload)
+            .unwrap();
+        assert_eq!(payload, [0x01]);
+
+        let mut next = [0; 32];
+        expand_label(MessageDigest::sha256(), &secret, b"quic ku", &mut next).unwrap();
+        assert_eq!(
+            next.to_vec(),
+            Vec::from_hex("1223504755036d556342ee9361d253421a826c9ecdf3c7148684b36b714881f9")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn retry_tag() {
+        let dst_cid = Vec::from_hex(DST_CID).unwrap();
+        let packet = Vec::from_hex("ff000000010008f067a5502a4262b5746f6b656e").unwrap();
+
+        let tag = quic_retry_tag(&dst_cid, &packet).unwrap();
+        assert_eq!(
+            tag,
+            <[u8; QUIC_RETRY_TAG_LEN]>::from_hex("04a265ba2eff4d829058fb3f0f2496ba").unwrap()
+        );
+    }
+}
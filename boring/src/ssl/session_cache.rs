@@ -0,0 +1,172 @@
+use crate::ssl::{SslRef, SslSession};
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+#[cfg(not(feature = "unsafe-deterministic-mode"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A server session cache split into independently locked shards.
+///
+/// The internal session cache of a context is guarded by a single lock, which every handshake
+/// looking up or storing a session takes. Handshakes using this cache instead only contend with
+/// the ones whose sessions hash to the same shard.
+///
+/// When a shard is full, the session it stored first is evicted. Expired sessions are evicted
+/// when a client proposes them.
+///
+/// The cache counts its lookups and evictions, see [`ShardedSessionCache::stats`].
+///
+/// See [`SslContextBuilder::set_sharded_session_cache`].
+///
+/// [`SslContextBuilder::set_sharded_session_cache`]: super::SslContextBuilder::set_sharded_session_cache
+pub struct ShardedSessionCache {
+    shards: Box<[Mutex<Shard>]>,
+    shard_capacity: usize,
+    hasher: RandomState,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    timeouts: AtomicU64,
+    cache_full: AtomicU64,
+}
+
+/// The counters of a [`ShardedSessionCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionCacheStats {
+    /// The number of sessions proposed by clients that were found in the cache.
+    pub hits: u64,
+    /// The number of sessions proposed by clients that were not in the cache.
+    pub misses: u64,
+    /// The number of sessions proposed by clients that were in the cache but had expired.
+    pub timeouts: u64,
+    /// The number of sessions evicted because their shard was full.
+    pub cache_full: u64,
+}
+
+#[derive(Default)]
+struct Shard {
+    sessions: HashMap<Box<[u8]>, SslSession>,
+    order: VecDeque<Box<[u8]>>,
+}
+
+impl ShardedSessionCache {
+    /// Creates a cache of `shards` shards holding up to `capacity` sessions in total.
+    ///
+    /// The cache has at least one shard, and each shard holds at least one session.
+    #[must_use]
+    pub fn new(shards: usize, capacity: usize) -> ShardedSessionCache {
+        let shards = shards.max(1);
+
+        ShardedSessionCache {
+            shards: (0..shards).map(|_| Mutex::default()).collect(),
+            shard_capacity: capacity.div_ceil(shards).max(1),
+            hasher: RandomState::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            cache_full: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the counters of the cache.
+    #[must_use]
+    pub fn stats(&self) -> SessionCacheStats {
+        SessionCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            cache_full: self.cache_full.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the number of sessions in the cache.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().sessions.len())
+            .sum()
+    }
+
+    /// Returns `true` if the cache holds no sessions.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn shard(&self, id: &[u8]) -> &Mutex<Shard> {
+        let i = self.hasher.hash_one(id) as usize % self.shards.len();
+        &self.shards[i]
+    }
+
+    pub(super) fn insert(&self, session: SslSession) {
+        let id: Box<[u8]> = session.id().into();
+        if id.is_empty() {
+            return;
+        }
+
+        let mut shard = self.shard(&id).lock().unwrap();
+
+        if shard.sessions.insert(id.clone(), session).is_some() {
+            return;
+        }
+        shard.order.push_back(id);
+
+        while shard.order.len() > self.shard_capacity {
+            if let Some(evicted) = shard.order.pop_front() {
+                shard.sessions.remove(&evicted);
+                self.cache_full.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Returns the session with ID `id`, if it has not expired at `now`, in seconds since the
+    /// Unix epoch.
+    pub(super) fn get(&self, id: &[u8], now: u64) -> Option<SslSession> {
+        let mut shard = self.shard(id).lock().unwrap();
+
+        let Some(session) = shard.sessions.get(id) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+
+        // As in BoringSSL, sessions dated in the future have not expired.
+        if now.saturating_sub(session.time()) >= u64::from(session.timeout()) {
+            shard.sessions.remove(id);
+            shard.order.retain(|order| **order != *id);
+            self.timeouts.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(session.clone())
+    }
+}
+
+/// Returns the time of the clock BoringSSL uses for `ssl`, in seconds since the Unix epoch.
+pub(super) fn now(ssl: &SslRef) -> u64 {
+    #[cfg(feature = "unsafe-deterministic-mode")]
+    {
+        super::callbacks::frozen_time_micros(ssl) / 1_000_000
+    }
+
+    #[cfg(not(feature = "unsafe-deterministic-mode"))]
+    {
+        let _ = ssl;
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs())
+    }
+}
+
+impl fmt::Debug for ShardedSessionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedSessionCache")
+            .field("shards", &self.shards.len())
+            .field("shard_capacity", &self.shard_capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
@@ -0,0 +1,163 @@
+use crate::ffi;
+use crate::ssl::{Error, Ssl, SslCipherRef, SslContext, SslContextRef, SslRef, SslVersion};
+use crate::x509::X509VerifyResult;
+use libc::c_int;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Receives measurements of the handshakes of every connection made with a context.
+///
+/// See [`SslContextBuilder::set_telemetry_hook`].
+///
+/// [`SslContextBuilder::set_telemetry_hook`]: super::SslContextBuilder::set_telemetry_hook
+pub trait TelemetryHook: Send + Sync + 'static {
+    /// Called when a handshake completes.
+    fn handshake_completed(&self, handshake: &HandshakeCompleted<'_>) {
+        let _ = handshake;
+    }
+
+    /// Called when a handshake fails.
+    ///
+    /// Only failures of handshakes driven through [`SslStream`](super::SslStream) and
+    /// [`MidHandshakeSslStream`](super::MidHandshakeSslStream) are reported.
+    fn handshake_failed(&self, handshake: &HandshakeFailed<'_>) {
+        let _ = handshake;
+    }
+}
+
+/// A completed handshake, reported to [`TelemetryHook::handshake_completed`].
+pub struct HandshakeCompleted<'a> {
+    ssl: &'a SslRef,
+    duration: Duration,
+}
+
+impl HandshakeCompleted<'_> {
+    /// Returns the connection.
+    #[must_use]
+    pub fn ssl(&self) -> &SslRef {
+        self.ssl
+    }
+
+    /// Returns the time elapsed since the handshake started.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the negotiated protocol version.
+    #[must_use]
+    pub fn version(&self) -> Option<SslVersion> {
+        self.ssl.version2()
+    }
+
+    /// Returns the negotiated cipher.
+    #[must_use]
+    pub fn cipher(&self) -> Option<&SslCipherRef> {
+        self.ssl.current_cipher()
+    }
+
+    /// Returns `true` if a session was resumed rather than a full handshake performed.
+    #[must_use]
+    pub fn resumed(&self) -> bool {
+        self.ssl.session_reused()
+    }
+}
+
+impl fmt::Debug for HandshakeCompleted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandshakeCompleted")
+            .field("duration", &self.duration)
+            .field("version", &self.version())
+            .field("cipher", &self.cipher().map(|cipher| cipher.name()))
+            .field("resumed", &self.resumed())
+            .finish()
+    }
+}
+
+/// A failed handshake, reported to [`TelemetryHook::handshake_failed`].
+pub struct HandshakeFailed<'a> {
+    ssl: &'a SslRef,
+    duration: Duration,
+    error: &'a Error,
+}
+
+impl HandshakeFailed<'_> {
+    /// Returns the connection.
+    #[must_use]
+    pub fn ssl(&self) -> &SslRef {
+        self.ssl
+    }
+
+    /// Returns the time elapsed since the handshake started.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the error the handshake failed with.
+    #[must_use]
+    pub fn error(&self) -> &Error {
+        self.error
+    }
+
+    /// Returns the result of the verification of the peer's certificate.
+    #[must_use]
+    pub fn verify_result(&self) -> X509VerifyResult {
+        self.ssl.verify_result()
+    }
+}
+
+impl fmt::Debug for HandshakeFailed<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandshakeFailed")
+            .field("duration", &self.duration)
+            .field("error", &self.error)
+            .field("verify_result", &self.verify_result())
+            .finish()
+    }
+}
+
+struct HandshakeStart(Instant);
+
+pub(super) fn hook(ctx: &SslContextRef) -> Option<&Arc<dyn TelemetryHook>> {
+    ctx.ex_data(SslContext::cached_ex_index::<Arc<dyn TelemetryHook>>())
+}
+
+fn duration(ssl: &SslRef) -> Duration {
+    ssl.ex_data(Ssl::cached_ex_index::<HandshakeStart>())
+        .map_or(Duration::ZERO, |start| start.0.elapsed())
+}
+
+pub(super) fn info(ssl: &mut SslRef, mode: c_int) {
+    let Some(hook) = hook(ssl.ssl_context()).cloned() else {
+        return;
+    };
+
+    match mode {
+        ffi::SSL_CB_HANDSHAKE_START => {
+            ssl.replace_ex_data(
+                Ssl::cached_ex_index::<HandshakeStart>(),
+                HandshakeStart(Instant::now()),
+            );
+        }
+        ffi::SSL_CB_HANDSHAKE_DONE => hook.handshake_completed(&HandshakeCompleted {
+            ssl,
+            duration: duration(ssl),
+        }),
+        _ => {}
+    }
+}
+
+pub(super) fn handshake_failed(ssl: &SslRef, error: &Error) {
+    #[cfg(feature = "tracing")]
+    super::trace::handshake_failed(ssl, error);
+
+    if let Some(hook) = hook(ssl.ssl_context()) {
+        hook.handshake_failed(&HandshakeFailed {
+            ssl,
+            duration: duration(ssl),
+            error,
+        });
+    }
+}
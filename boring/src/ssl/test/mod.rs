@@ -9,17 +9,18 @@ use std::thread;
 
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
-use crate::pkey::PKey;
+use crate::pkey::{PKey, Private};
 use crate::srtp::SrtpProfileId;
 use crate::ssl::test::server::Server;
 use crate::ssl::SslVersion;
 use crate::ssl::{
-    self, ExtensionType, ShutdownResult, ShutdownState, Ssl, SslAcceptor, SslAcceptorBuilder,
-    SslConnector, SslContext, SslFiletype, SslMethod, SslOptions, SslStream, SslVerifyMode,
+    self, ExtensionType, HandshakeError, RevocationChecker, ShutdownResult, ShutdownState, Ssl,
+    SslAcceptor, SslAcceptorBuilder, SslConnector, SslContentType, SslContext, SslFiletype,
+    SslMethod, SslOptions, SslRef, SslStream, SslVerifyMode,
 };
 use crate::x509::store::X509StoreBuilder;
 use crate::x509::verify::X509CheckFlags;
-use crate::x509::{X509Name, X509};
+use crate::x509::{X509Name, X509VerifyError, X509};
 
 use super::CompliancePolicy;
 
@@ -28,6 +29,7 @@ mod cert_verify;
 mod custom_verify;
 mod ech;
 mod private_key_method;
+mod quic;
 mod server;
 mod session;
 mod session_resumption;
@@ -80,6 +82,38 @@ fn peer_certificate() {
     );
 }
 
+#[test]
+fn tls_server_end_point() {
+    const BINDING: &str = "4712b939fbcb42a6b5101b42139a25b14f81b418facabd378746f12f85cc6544";
+
+    let mut server = Server::builder();
+    server.io_cb(|s| {
+        let binding = s.ssl().tls_server_end_point().unwrap().unwrap();
+        assert_eq!(hex::encode(binding), BINDING);
+    });
+    let server = server.build();
+
+    let s = server.client().connect();
+    let binding = s.ssl().tls_server_end_point().unwrap().unwrap();
+    assert_eq!(hex::encode(binding), BINDING);
+}
+
+#[test]
+fn tls_server_end_point_pss() {
+    // The certificate is signed with RSASSA-PSS and SHA-384.
+    const BINDING: &str = "a2d303985e26c1a9239458cb0fe70526e80ce69756b7185bc121faf7f88ac4fd\
+                           cf4a2aced31f9fac2e14e751f089b526";
+
+    let cert = X509::from_pem(include_bytes!("../../../test/cert-pss.pem")).unwrap();
+    let mut server = Server::builder();
+    server.ctx().set_certificate(&cert).unwrap();
+    let server = server.build();
+
+    let s = server.client().connect();
+    let binding = s.ssl().tls_server_end_point().unwrap().unwrap();
+    assert_eq!(hex::encode(binding), BINDING);
+}
+
 #[test]
 fn pending() {
     let mut server = Server::builder();
@@ -93,6 +127,98 @@ fn pending() {
     assert_eq!(s.read(&mut [0; 10]).unwrap(), 9);
 }
 
+#[test]
+fn max_send_fragment() {
+    let mut server = Server::builder();
+    server.ctx().set_max_send_fragment(512);
+    server.io_cb(|mut s| s.write_all(&[0; 2000]).unwrap());
+    let server = server.build();
+
+    let mut s = server.client().connect();
+    s.read_exact(&mut [0]).unwrap();
+
+    assert_eq!(s.ssl().pending(), 511);
+}
+
+#[test]
+fn max_cert_list() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_max_cert_list(100);
+    client.connect_err();
+}
+
+#[test]
+fn key_update() {
+    use std::sync::atomic::AtomicUsize;
+
+    static SERVER_RECEIVED: AtomicUsize = AtomicUsize::new(0);
+    static CLIENT_RECEIVED: AtomicUsize = AtomicUsize::new(0);
+
+    fn count_key_updates(
+        received: &'static AtomicUsize,
+    ) -> impl Fn(&SslRef, bool, SslContentType, &[u8]) + Send + Sync + 'static {
+        move |_, sent, content_type, msg| {
+            if !sent && content_type == SslContentType::HANDSHAKE && msg[0] == 24 {
+                received.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_msg_callback(count_key_updates(&SERVER_RECEIVED));
+    server.io_cb(|mut s| {
+        s.key_update(true).unwrap();
+        s.write_all(&[1]).unwrap();
+        s.read_exact(&mut [0]).unwrap();
+        assert_eq!(SERVER_RECEIVED.load(Ordering::SeqCst), 1);
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_msg_callback(count_key_updates(&CLIENT_RECEIVED));
+    let mut s = client.connect();
+    s.read_exact(&mut [0]).unwrap();
+    assert_eq!(CLIENT_RECEIVED.load(Ordering::SeqCst), 1);
+    s.write_all(&[2]).unwrap();
+}
+
+#[test]
+fn handshake_transcript() {
+    let mut server = Server::builder();
+    server.ctx().enable_handshake_transcript();
+    server.io_cb(|mut s| {
+        let hash = s.ssl().handshake_transcript_hash().unwrap().unwrap();
+        s.write_all(&hash).unwrap();
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_msg_callback(|_, _, _, _| {});
+    client.ctx().enable_handshake_transcript();
+    let mut s = client.connect();
+
+    // The transcript starts with the ClientHello.
+    assert_eq!(s.handshake_transcript().unwrap()[0], 1);
+
+    let hash = s.ssl().handshake_transcript_hash().unwrap().unwrap();
+    let mut server_hash = vec![0; hash.len()];
+    s.read_exact(&mut server_hash).unwrap();
+    assert_eq!(*hash, *server_hash);
+
+    let server = Server::builder().build();
+    let s = server.client().connect();
+    assert!(s.handshake_transcript().is_none());
+    assert!(s.ssl().handshake_transcript_hash().unwrap().is_none());
+}
+
 #[test]
 fn state() {
     let server = Server::builder().build();
@@ -264,6 +390,29 @@ fn test_alpn_server_select_none_fatal() {
     client.connect_err();
 }
 
+#[test]
+fn test_fatal_alert_io_error_kind() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_alpn_select_callback(|_, _| Err(ssl::AlpnError::ALERT_FATAL));
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_alpn_protos(b"\x06http/2").unwrap();
+    let error = match client.connect_err() {
+        HandshakeError::Failure(mid) => mid.into_error(),
+        e => panic!("unexpected error {e}"),
+    };
+
+    assert_eq!(error.io_error_kind(), io::ErrorKind::ConnectionReset);
+    assert_eq!(
+        io::Error::from(error).kind(),
+        io::ErrorKind::ConnectionReset
+    );
+}
+
 #[test]
 fn test_alpn_server_select_none() {
     let mut server = Server::builder();
@@ -278,6 +427,61 @@ fn test_alpn_server_select_none() {
     assert_eq!(None, s.ssl().selected_alpn_protocol());
 }
 
+#[test]
+fn test_alpn_server_preference() {
+    fn negotiate(client_protos: &'static [u8], no_overlap: ssl::AlpnError) -> Option<Vec<u8>> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let t = thread::spawn(move || {
+            let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+            acceptor
+                .set_private_key(&PKey::private_key_from_pem(KEY).unwrap())
+                .unwrap();
+            acceptor
+                .set_certificate(&X509::from_pem(CERT).unwrap())
+                .unwrap();
+            acceptor.set_alpn_server_preference(b"\x02h2\x08http/1.1", no_overlap);
+            let acceptor = acceptor.build();
+
+            let stream = listener.accept().unwrap().0;
+            if let Ok(mut stream) = acceptor.accept(stream) {
+                stream.write_all(&[0]).unwrap();
+            }
+        });
+
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_alpn_protos(client_protos).unwrap();
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let result = Ssl::new(&ctx.build()).unwrap().connect(stream);
+        t.join().unwrap();
+
+        let mut stream = result.ok()?;
+        stream.read_exact(&mut [0]).unwrap();
+        Some(
+            stream
+                .ssl()
+                .selected_alpn_protocol()
+                .unwrap_or_default()
+                .to_vec(),
+        )
+    }
+
+    assert_eq!(
+        negotiate(b"\x08http/1.1\x02h2", ssl::AlpnError::NOACK),
+        Some(b"h2".to_vec())
+    );
+    assert_eq!(
+        negotiate(b"\x06spdy/1\x08http/1.1", ssl::AlpnError::NOACK),
+        Some(b"http/1.1".to_vec())
+    );
+    assert_eq!(
+        negotiate(b"\x06spdy/1", ssl::AlpnError::NOACK),
+        Some(vec![])
+    );
+    assert_eq!(negotiate(b"\x06spdy/1", ssl::AlpnError::ALERT_FATAL), None);
+}
+
 #[test]
 fn test_empty_alpn() {
     assert_eq!(ssl::select_next_proto(b"", b""), None);
@@ -720,6 +924,42 @@ fn connector_no_hostname_still_verifies() {
         .is_err());
 }
 
+#[test]
+fn connector_partial_chain() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector
+        .cert_store_mut()
+        .add_cert(X509::from_pem(CERT).unwrap())
+        .unwrap();
+    let connector = connector.build();
+
+    let mut config = connector.configure().unwrap();
+    config
+        .param_mut()
+        .set_flags(crate::x509::verify::X509VerifyFlags::PARTIAL_CHAIN);
+    config.set_verify_depth(0);
+
+    let s = server.connect_tcp();
+    let mut s = config.connect("foobar.com", s).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[cfg(feature = "platform-verifier")]
+#[test]
+fn connector_platform_verifier_rejects_untrusted_root() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_platform_verifier().unwrap();
+
+    let s = server.connect_tcp();
+    connector.build().connect("foobar.com", s).unwrap_err();
+}
+
 #[test]
 fn connector_no_hostname_can_disable_verify() {
     let server = Server::builder().build();
@@ -738,6 +978,347 @@ fn connector_no_hostname_can_disable_verify() {
     s.read_exact(&mut [0]).unwrap();
 }
 
+#[test]
+fn connector_param_mut_overrides_hostname() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let mut config = connector
+        .build()
+        .configure()
+        .unwrap()
+        .verify_hostname(false);
+    config.param_mut().set_host("bogus.com").unwrap();
+
+    let s = server.connect_tcp();
+    config.connect("foobar.com", s).unwrap_err();
+}
+
+#[test]
+fn connector_spki_pin() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+    let mut config = connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[[0; 32], root.spki_sha256().unwrap()]);
+    config.param_mut().set_time(1577836800);
+
+    let s = server.connect_tcp();
+    let mut s = config.connect("foobar.com", s).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+fn connector_spki_pin_mismatch() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let mut config = connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[[0; 32]]);
+    config.param_mut().set_time(1577836800);
+
+    let s = server.connect_tcp();
+    match config.connect("foobar.com", s) {
+        Err(HandshakeError::Failure(s)) => assert_eq!(
+            s.ssl().verify_result(),
+            Err(X509VerifyError::SPKI_PIN_MISMATCH)
+        ),
+        _ => panic!("expected a pin mismatch"),
+    }
+}
+
+#[test]
+fn connector_spki_pin_keeps_verify_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_verify_callback(SslVerifyMode::PEER, |_, ctx| {
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        // Reject the leaf, which the pin alone would accept.
+        ctx.error_depth() != 0
+    });
+
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+    let mut config = connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[root.spki_sha256().unwrap()]);
+    config.param_mut().set_time(1577836800);
+
+    let s = server.connect_tcp();
+    config.connect("foobar.com", s).unwrap_err();
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn connector_shared_configuration() {
+    let mut server = Server::builder();
+    server.expected_connections_count(2);
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    let connector = connector.build();
+
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+    let config = connector
+        .shared_configuration()
+        .pin_spki_sha256(&[root.spki_sha256().unwrap()]);
+
+    for _ in 0..2 {
+        let s = server.connect_tcp();
+        let mut s = config.clone().connect("foobar.com", s).unwrap();
+        s.read_exact(&mut [0]).unwrap();
+    }
+
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let s = server.connect_tcp();
+    config.connect("bogus.com", s).unwrap_err();
+}
+
+#[cfg(feature = "ct")]
+fn connect_with_ct_policy(
+    policy: crate::ct::CtPolicy,
+    should_error: bool,
+) -> crate::x509::X509VerifyResult {
+    connect_with_ct_policy_and_callback(policy, should_error, |ok, _| ok)
+}
+
+#[cfg(feature = "ct")]
+fn connect_with_ct_policy_and_callback(
+    policy: crate::ct::CtPolicy,
+    should_error: bool,
+    verify: impl Fn(bool, &mut crate::x509::X509StoreContextRef) -> bool + Send + Sync + 'static,
+) -> crate::x509::X509VerifyResult {
+    use crate::ct::test::{log_key, log_list, sct_list, sign_sct};
+    use crate::ct::CtVerifier;
+
+    let google = log_key();
+    let other = log_key();
+    let logs = log_list(&[("Google", &google), ("Other", &other)]);
+    let cert = X509::from_pem(CERT).unwrap();
+    let list = sct_list(&[sign_sct(&google, &cert), sign_sct(&other, &cert)]);
+
+    let mut server = Server::builder();
+    server.ctx().set_signed_cert_timestamp_list(&list).unwrap();
+    if should_error {
+        server.should_error();
+    }
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_ct_verifier(CtVerifier::new(logs, policy));
+    connector.set_verify_callback(SslVerifyMode::PEER, verify);
+
+    let mut config = connector.build().configure().unwrap();
+    config.param_mut().set_time(1577836800);
+
+    let s = server.connect_tcp();
+    match config.connect("foobar.com", s) {
+        Ok(mut s) => {
+            s.read_exact(&mut [0]).unwrap();
+            s.ssl().verify_result()
+        }
+        Err(HandshakeError::Failure(s)) => s.ssl().verify_result(),
+        Err(e) => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+#[cfg(feature = "ct")]
+fn connector_ct_policy() {
+    use crate::ct::CtPolicy;
+
+    assert_eq!(connect_with_ct_policy(CtPolicy::new(), false), Ok(()));
+    assert_eq!(
+        connect_with_ct_policy(CtPolicy::new().min_scts(3), true),
+        Err(X509VerifyError::CT_POLICY_NOT_MET)
+    );
+}
+
+#[test]
+#[cfg(feature = "ct")]
+fn connector_ct_policy_keeps_verify_callback() {
+    use crate::ct::CtPolicy;
+
+    let result = connect_with_ct_policy_and_callback(CtPolicy::new(), true, |ok, ctx| {
+        if ctx.error_depth() == 0 {
+            ctx.set_error(Err(X509VerifyError::CERT_REJECTED));
+            return false;
+        }
+        ok
+    });
+    assert_eq!(result, Err(X509VerifyError::CERT_REJECTED));
+}
+
+fn connect_with_must_staple(stapled: Option<bool>) -> crate::x509::X509VerifyResult {
+    use crate::ssl::ocsp::test::response;
+
+    connect_with_staple(|cert, root, root_key| {
+        stapled.map(|revoked| (response(cert, root, root_key, revoked), !revoked))
+    })
+}
+
+/// Connects to a server with a must-staple certificate, stapling the response returned by
+/// `staple` with whether the handshake should succeed.
+fn connect_with_staple(
+    staple: impl FnOnce(&X509, &X509, &PKey<Private>) -> Option<(Vec<u8>, bool)>,
+) -> crate::x509::X509VerifyResult {
+    use crate::ssl::ocsp::test::must_staple_cert;
+
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+    let root_key = PKey::private_key_from_pem(include_bytes!("../../../test/root-ca.key")).unwrap();
+    let (cert, key) = must_staple_cert(&root, &root_key);
+
+    let mut server = Server::builder();
+    server.ctx().set_certificate(&cert).unwrap();
+    server.ctx().set_private_key(&key).unwrap();
+    match staple(&cert, &root, &root_key) {
+        Some((ocsp, ok)) => {
+            server.ssl_cb(move |ssl| ssl.set_ocsp_status(&ocsp).unwrap());
+            if !ok {
+                server.should_error();
+            }
+        }
+        None => server.should_error(),
+    }
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_enforce_must_staple(true);
+
+    let s = server.connect_tcp();
+    match connector.build().connect("foobar.com", s) {
+        Ok(mut s) => {
+            s.read_exact(&mut [0]).unwrap();
+            s.ssl().verify_result()
+        }
+        Err(HandshakeError::Failure(s)) => s.ssl().verify_result(),
+        Err(e) => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn connector_must_staple() {
+    assert_eq!(connect_with_must_staple(Some(false)), Ok(()));
+    assert_eq!(
+        connect_with_must_staple(None),
+        Err(X509VerifyError::MUST_STAPLE_NOT_MET)
+    );
+    assert_eq!(
+        connect_with_must_staple(Some(true)),
+        Err(X509VerifyError::CERT_REVOKED)
+    );
+}
+
+#[test]
+fn connector_must_staple_delegated_responder() {
+    use crate::ssl::ocsp::test::{must_staple_cert, responder_cert, response_with_certs};
+
+    let result = connect_with_staple(|cert, root, root_key| {
+        let (responder, responder_key) = responder_cert(root, root_key);
+        let ocsp = response_with_certs(cert, root, &responder_key, false, &[&responder]);
+        Some((ocsp, true))
+    });
+    assert_eq!(result, Ok(()));
+
+    // A sibling certificate has no extended key usage, so it cannot sign for the issuer.
+    let result = connect_with_staple(|cert, root, root_key| {
+        let (sibling, sibling_key) = must_staple_cert(root, root_key);
+        let ocsp = response_with_certs(cert, root, &sibling_key, false, &[&sibling]);
+        Some((ocsp, false))
+    });
+    assert_eq!(result, Err(X509VerifyError::MUST_STAPLE_NOT_MET));
+}
+
+#[test]
+fn connector_must_staple_not_required() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_enforce_must_staple(true);
+
+    let s = server.connect_tcp();
+    let mut s = connector.build().connect("foobar.com", s).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+fn connector_revocation_checker() {
+    struct Denylist(Vec<u8>);
+
+    impl RevocationChecker for Denylist {
+        fn check(
+            &self,
+            chain: &crate::stack::StackRef<X509>,
+            ocsp_response: Option<&[u8]>,
+        ) -> crate::x509::X509VerifyResult {
+            assert_eq!(chain.len(), 2);
+            assert!(ocsp_response.is_none());
+            if chain[0].to_der().unwrap() == self.0 {
+                return Err(X509VerifyError::CERT_REVOKED);
+            }
+            Ok(())
+        }
+    }
+
+    let revoked = X509::from_pem(CERT).unwrap().to_der().unwrap();
+
+    for (denied, ok) in [(Vec::new(), true), (revoked, false)] {
+        let mut server = Server::builder();
+        if !ok {
+            server.should_error();
+        }
+        let server = server.build();
+
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        connector.set_ca_file("test/root-ca.pem").unwrap();
+        connector.set_revocation_checker(Denylist(denied));
+
+        let s = server.connect_tcp();
+        match connector.build().connect("foobar.com", s) {
+            Ok(mut s) => {
+                assert!(ok);
+                s.read_exact(&mut [0]).unwrap();
+            }
+            Err(HandshakeError::Failure(s)) => {
+                assert!(!ok);
+                assert_eq!(s.ssl().verify_result(), Err(X509VerifyError::CERT_REVOKED));
+            }
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+}
+
 fn test_mozilla_server(new: fn(SslMethod) -> Result<SslAcceptorBuilder, ErrorStack>) {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let port = listener.local_addr().unwrap().port();
@@ -1071,6 +1652,103 @@ fn test_info_callback() {
     assert!(CALLED_BACK.load(Ordering::Relaxed));
 }
 
+#[test]
+fn test_connection_info() {
+    let mut server = Server::builder();
+    server.ctx().set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x02h2", client).ok_or(ssl::AlpnError::NOACK)
+    });
+    let server = server.build();
+
+    let mut client = server.client_with_root_ca();
+    client.ctx().set_alpn_protos(b"\x02h2").unwrap();
+    let mut client = client.build().builder();
+    client.ssl().set_hostname("foobar.com").unwrap();
+    let stream = client.connect();
+
+    let info = stream.ssl().connection_info();
+    assert_eq!(info.protocol(), "TLSv1.3");
+    assert!(info.cipher().is_some());
+    assert!(info.group().is_some());
+    assert_eq!(info.alpn_protocol(), Some(&b"h2"[..]));
+    assert_eq!(info.server_name(), Some("foobar.com"));
+    assert!(!info.resumed());
+
+    let cert = X509::from_pem(CERT).unwrap();
+    let digest = cert.digest(MessageDigest::sha256()).unwrap();
+    assert_eq!(&info.peer_certificate_sha256().unwrap()[..], &*digest);
+}
+
+#[test]
+fn test_telemetry_hook() {
+    use crate::ssl::{HandshakeCompleted, HandshakeFailed, TelemetryHook};
+    use crate::x509::X509VerifyResult;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Hook {
+        completed: Arc<AtomicUsize>,
+        failed: Arc<Mutex<Option<X509VerifyResult>>>,
+    }
+
+    impl TelemetryHook for Hook {
+        fn handshake_completed(&self, handshake: &HandshakeCompleted<'_>) {
+            assert!(handshake.version().is_some());
+            assert!(handshake.cipher().is_some());
+            assert!(!handshake.resumed());
+            self.completed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn handshake_failed(&self, handshake: &HandshakeFailed<'_>) {
+            *self.failed.lock().unwrap() = Some(handshake.verify_result());
+        }
+    }
+
+    let hook = Hook::default();
+
+    let server = Server::builder().build();
+    let mut client = server.client_with_root_ca();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    client.ctx().set_telemetry_hook(hook.clone());
+    client.connect();
+    assert_eq!(hook.completed.load(Ordering::Relaxed), 1);
+
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+    let mut client = server.client();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    client.ctx().set_telemetry_hook(hook.clone());
+    client.connect_err();
+    assert_eq!(hook.completed.load(Ordering::Relaxed), 1);
+    assert_eq!(
+        hook.failed.lock().unwrap().unwrap(),
+        Err(X509VerifyError::UNABLE_TO_GET_ISSUER_CERT_LOCALLY)
+    );
+}
+
+#[test]
+fn test_handshake_timings() {
+    let server = Server::builder().build();
+    let client = server.client_with_root_ca();
+    let s = client.connect();
+    assert_eq!(s.handshake_timings(), None);
+
+    let server = Server::builder().build();
+    let mut client = server.client_with_root_ca();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    client.ctx().enable_handshake_timings();
+    let s = client.connect();
+
+    let timings = s.handshake_timings().unwrap();
+    let client_hello = timings.client_hello().unwrap();
+    let certificate_verified = timings.certificate_verified().unwrap();
+    let finished = timings.finished().unwrap();
+    assert!(client_hello <= certificate_verified);
+    assert!(certificate_verified <= finished);
+}
+
 #[test]
 fn test_ssl_set_compliance() {
     let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
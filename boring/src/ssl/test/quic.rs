@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+
+use super::{CERT, KEY};
+use crate::pkey::PKey;
+use crate::ssl::{
+    self, QuicCryptoSession, QuicEncryptionLevel, QuicEvent, QuicSecret, QuicSession, Ssl,
+    SslContext, SslMethod, SslVersion,
+};
+use crate::x509::X509;
+
+#[derive(Default)]
+struct Endpoint {
+    inbox: VecDeque<(QuicEncryptionLevel, Vec<u8>)>,
+    read_secrets: Vec<QuicSecret>,
+    write_secrets: Vec<QuicSecret>,
+}
+
+fn session(server: bool, transport_params: &[u8]) -> QuicSession {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_min_proto_version(Some(SslVersion::TLS1_3)).unwrap();
+
+    if server {
+        ctx.set_certificate(&X509::from_pem(CERT).unwrap()).unwrap();
+        ctx.set_private_key(&PKey::private_key_from_pem(KEY).unwrap())
+            .unwrap();
+        ctx.set_alpn_select_callback(|_, client| {
+            ssl::select_next_proto(b"\x02h3", client).ok_or(ssl::AlpnError::NOACK)
+        });
+    } else {
+        ctx.set_alpn_protos(b"\x02h3").unwrap();
+    }
+
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+    if server {
+        ssl.set_accept_state();
+    } else {
+        ssl.set_connect_state();
+    }
+
+    QuicSession::new(ssl, transport_params).unwrap()
+}
+
+// Feeds `endpoint` everything it can consume at its current read level and advances the
+// handshake, queueing outgoing handshake data in the peer's inbox.
+fn step(session: &mut QuicSession, endpoint: &mut Endpoint, peer: &mut Endpoint) -> bool {
+    loop {
+        while endpoint
+            .inbox
+            .front()
+            .is_some_and(|(level, _)| *level == session.ssl().quic_read_level())
+        {
+            let (level, data) = endpoint.inbox.pop_front().unwrap();
+            session.provide_data(level, &data).unwrap();
+        }
+
+        let done = session.handshake().unwrap();
+
+        while let Some(event) = session.poll_event() {
+            match event {
+                QuicEvent::ReadSecret(secret) => endpoint.read_secrets.push(secret),
+                QuicEvent::WriteSecret(secret) => endpoint.write_secrets.push(secret),
+                QuicEvent::HandshakeData { level, data } => peer.inbox.push_back((level, data)),
+                QuicEvent::FlushFlight => {}
+                event => panic!("unexpected event {event:?}"),
+            }
+        }
+
+        let read_level = session.ssl().quic_read_level();
+        if !endpoint
+            .inbox
+            .front()
+            .is_some_and(|(level, _)| *level == read_level)
+        {
+            return done;
+        }
+    }
+}
+
+fn secret(secrets: &[QuicSecret], level: QuicEncryptionLevel) -> &QuicSecret {
+    secrets.iter().find(|s| s.level() == level).unwrap()
+}
+
+#[test]
+fn quic_handshake() {
+    let mut client = session(false, b"client params");
+    let mut server = session(true, b"server params");
+    let mut client_endpoint = Endpoint::default();
+    let mut server_endpoint = Endpoint::default();
+
+    let mut finished = false;
+    for _ in 0..10 {
+        let client_done = step(&mut client, &mut client_endpoint, &mut server_endpoint);
+        let server_done = step(&mut server, &mut server_endpoint, &mut client_endpoint);
+        if client_done
+            && server_done
+            && client_endpoint.inbox.is_empty()
+            && server_endpoint.inbox.is_empty()
+        {
+            finished = true;
+            break;
+        }
+    }
+    assert!(finished);
+    assert!(!client.is_handshaking());
+    assert!(!server.is_handshaking());
+
+    assert_eq!(client.peer_transport_params(), Some(&b"server params"[..]));
+    assert_eq!(server.peer_transport_params(), Some(&b"client params"[..]));
+    assert_eq!(client.ssl().selected_alpn_protocol(), Some(&b"h3"[..]));
+
+    for level in [
+        QuicEncryptionLevel::Handshake,
+        QuicEncryptionLevel::Application,
+    ] {
+        let client_write = secret(&client_endpoint.write_secrets, level);
+        let server_read = secret(&server_endpoint.read_secrets, level);
+        assert_eq!(client_write.secret(), server_read.secret());
+        assert_eq!(client_write.cipher().name(), server_read.cipher().name());
+
+        let server_write = secret(&server_endpoint.write_secrets, level);
+        let client_read = secret(&client_endpoint.read_secrets, level);
+        assert_eq!(server_write.secret(), client_read.secret());
+        assert_ne!(server_write.secret(), client_write.secret());
+    }
+}
+
+#[test]
+fn quic_packet_protection() {
+    let mut client = session(false, b"client params");
+    let mut server = session(true, b"server params");
+    let mut client_endpoint = Endpoint::default();
+    let mut server_endpoint = Endpoint::default();
+
+    for _ in 0..10 {
+        step(&mut client, &mut client_endpoint, &mut server_endpoint);
+        step(&mut server, &mut server_endpoint, &mut client_endpoint);
+    }
+    assert!(!QuicCryptoSession::is_handshaking(&client));
+    assert!(!QuicCryptoSession::is_handshaking(&server));
+
+    let level = QuicEncryptionLevel::Application;
+    let mut client_write = client
+        .keys(secret(&client_endpoint.write_secrets, level))
+        .unwrap();
+    let mut server_read = server
+        .keys(secret(&server_endpoint.read_secrets, level))
+        .unwrap();
+
+    for pn in 0..2 {
+        // A short header with a one byte packet number.
+        let header = [0x40, pn as u8];
+        let mut payload = b"some 1-RTT payload".to_vec();
+        client_write
+            .packet
+            .encrypt(pn, &header, &mut payload)
+            .unwrap();
+        let mut packet = [&header[..], &payload].concat();
+        client_write.header.encrypt(1, &mut packet);
+
+        server_read.header.decrypt(1, &mut packet);
+        let mut payload = packet.split_off(2);
+        assert_eq!(packet, header);
+        server_read
+            .packet
+            .decrypt(pn, &packet, &mut payload)
+            .unwrap();
+        assert_eq!(payload, b"some 1-RTT payload");
+
+        let client_next = client
+            .next_1rtt_secret(secret(&client_endpoint.write_secrets, level))
+            .unwrap();
+        let server_next = server
+            .next_1rtt_secret(secret(&server_endpoint.read_secrets, level))
+            .unwrap();
+        assert_eq!(client_next.secret(), server_next.secret());
+        client_write = client.keys(&client_next).unwrap();
+        server_read = server.keys(&server_next).unwrap();
+    }
+
+    let odcid = b"original";
+    let mut retry = b"retry packet".to_vec();
+    retry.extend_from_slice(&server.retry_tag(odcid, &retry).unwrap());
+    assert!(client.is_valid_retry(odcid, &retry));
+    assert!(!client.is_valid_retry(b"other", &retry));
+
+    let client_initial = client.initial_keys(b"dst cid").unwrap();
+    let server_initial = server.initial_keys(b"dst cid").unwrap();
+    let mut payload = b"CRYPTO".to_vec();
+    server_initial
+        .local
+        .packet
+        .encrypt(0, b"", &mut payload)
+        .unwrap();
+    client_initial
+        .remote
+        .packet
+        .decrypt(0, b"", &mut payload)
+        .unwrap();
+    assert_eq!(payload, b"CRYPTO");
+}
@@ -1,11 +1,12 @@
-use std::io::Write;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::OnceLock;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex, OnceLock};
 
 use crate::ssl::test::server::Server;
 use crate::ssl::{
-    ErrorCode, GetSessionPendingError, HandshakeError, Ssl, SslContext, SslContextBuilder,
-    SslMethod, SslOptions, SslSession, SslSessionCacheMode, SslVersion,
+    ErrorCode, GetSessionPendingError, HandshakeError, SessionCacheStats, ShardedSessionCache, Ssl,
+    SslContext, SslContextBuilder, SslMethod, SslOptions, SslSession, SslSessionCacheMode,
+    SslVersion,
 };
 
 #[test]
@@ -184,3 +185,193 @@ fn session_cache_size() {
     let ctx = ctx.build();
     assert_eq!(ctx.session_cache_size(), 1234);
 }
+
+#[test]
+fn session_count() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    server.ctx().set_options(SslOptions::NO_TICKET);
+    server.ctx().set_session_id_context(b"foo").unwrap();
+
+    let server = server.build();
+
+    let s = server.client().connect();
+    let ctx = s.ssl().ssl_context();
+    assert_eq!(ctx.session_count(), 0);
+
+    let session = s.ssl().session().unwrap();
+    assert!(unsafe { ctx.add_session(session) });
+    assert_eq!(ctx.session_count(), 1);
+}
+
+#[test]
+fn sharded_session_cache() {
+    let (stats_tx, stats_rx) = mpsc::channel();
+    let mut server = Server::builder();
+
+    server.expected_connections_count(2);
+    server
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    server.ctx().set_options(SslOptions::NO_TICKET);
+    server.ctx().set_session_id_context(b"foo").unwrap();
+    server
+        .ctx()
+        .set_sharded_session_cache(ShardedSessionCache::new(4, 16));
+    server.io_cb(move |s| {
+        let stats = s.ssl().ssl_context().session_cache_stats().unwrap();
+        stats_tx.send(stats).unwrap();
+    });
+
+    let server = server.build();
+
+    let client = server.client().build();
+
+    let s = client.builder().connect();
+    assert!(!s.ssl().session_reused());
+    let session = s.ssl().session().unwrap().to_owned();
+    assert_eq!(stats_rx.recv().unwrap(), SessionCacheStats::default());
+
+    let mut ssl_builder = client.builder();
+    unsafe {
+        ssl_builder.ssl().set_session(&session).unwrap();
+    }
+
+    let s = ssl_builder.connect();
+    assert!(s.ssl().session_reused());
+    assert_eq!(
+        stats_rx.recv().unwrap(),
+        SessionCacheStats {
+            hits: 1,
+            ..SessionCacheStats::default()
+        }
+    );
+}
+
+#[test]
+fn sharded_session_cache_eviction() {
+    fn session() -> SslSession {
+        let mut server = Server::builder();
+        server
+            .ctx()
+            .set_max_proto_version(Some(SslVersion::TLS1_2))
+            .unwrap();
+        server.ctx().set_options(SslOptions::NO_TICKET);
+
+        let server = server.build();
+        let s = server.client().connect();
+        s.ssl().session().unwrap().to_owned()
+    }
+
+    let cache = ShardedSessionCache::new(1, 1);
+    assert!(cache.is_empty());
+
+    let first = session();
+    let now = first.time();
+    cache.insert(first.clone());
+    assert_eq!(cache.len(), 1);
+    assert!(cache.get(first.id(), now).is_some());
+
+    let second = session();
+    cache.insert(second.clone());
+    assert_eq!(cache.len(), 1);
+    assert!(cache.get(first.id(), now).is_none());
+    assert!(cache.get(second.id(), now).is_some());
+
+    let expiry = second.time() + u64::from(second.timeout());
+    assert!(cache.get(second.id(), expiry).is_none());
+    assert!(cache.is_empty());
+
+    assert_eq!(
+        cache.stats(),
+        SessionCacheStats {
+            hits: 2,
+            misses: 1,
+            timeouts: 1,
+            cache_full: 1,
+        }
+    );
+}
+
+#[test]
+fn early_data_filter() {
+    static FILTERED: AtomicUsize = AtomicUsize::new(0);
+    static SESSION: Mutex<Option<SslSession>> = Mutex::new(None);
+
+    let mut server = Server::builder();
+    server.expected_connections_count(3);
+    server.ctx().set_early_data_enabled(true);
+    server.ctx().set_select_certificate_callback(|mut hello| {
+        // Only the first resumption is fresh.
+        hello.filter_early_data(|random| {
+            assert_eq!(random.len(), 32);
+            FILTERED.fetch_add(1, Ordering::SeqCst) == 0
+        });
+        Ok(())
+    });
+    let server = server.build();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_early_data_enabled(true);
+    ctx.set_session_cache_mode(SslSessionCacheMode::CLIENT);
+    ctx.set_new_session_callback(|_, session| *SESSION.lock().unwrap() = Some(session));
+    let ctx = ctx.build();
+
+    let connect = |resume: bool| {
+        let mut ssl = Ssl::new(&ctx).unwrap();
+        if resume {
+            let session = SESSION.lock().unwrap().clone().unwrap();
+            unsafe { ssl.set_session(&session).unwrap() };
+        }
+        ssl.connect(server.connect_tcp()).unwrap()
+    };
+
+    let mut s = connect(false);
+    s.read_exact(&mut [0]).unwrap();
+    assert!(!s.ssl().early_data_accepted());
+    assert_eq!(FILTERED.load(Ordering::SeqCst), 0);
+    assert!(SESSION
+        .lock()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .early_data_capable());
+
+    let mut s = connect(true);
+    s.read_exact(&mut [0]).unwrap();
+    assert!(s.ssl().early_data_accepted());
+    assert_eq!(FILTERED.load(Ordering::SeqCst), 1);
+
+    let mut s = connect(true);
+    let err = s.ssl_read(&mut [0]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::EARLY_DATA_REJECTED);
+    s.ssl_mut().reset_early_data_reject();
+    s.read_exact(&mut [0]).unwrap();
+    assert!(!s.ssl().early_data_accepted());
+    assert_eq!(FILTERED.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "unsafe-deterministic-mode")]
+#[test]
+fn frozen_time() {
+    let mut server = Server::builder();
+    server.expected_connections_count(2);
+    let server = server.build();
+
+    // Each context keeps its own clock.
+    let clients = [1_700_000_000, 1_800_000_000].map(|secs| {
+        let mut client = server.client();
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        client.ctx().set_frozen_time(time);
+        (client.build(), secs)
+    });
+
+    for (client, secs) in &clients {
+        let s = client.builder().connect();
+        assert_eq!(s.ssl().session().unwrap().time(), *secs);
+    }
+}
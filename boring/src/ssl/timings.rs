@@ -0,0 +1,143 @@
+use crate::ffi;
+use crate::ssl::{Ssl, SslContext, SslRef, SslStream};
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
+use std::mem;
+use std::time::{Duration, Instant};
+
+/// When the milestones of a handshake were reached, relative to its start.
+///
+/// Timings are only recorded for connections made with contexts configured with
+/// [`SslContextBuilder::enable_handshake_timings`]. Comparing the milestones tells apart
+/// time spent waiting on the network from time spent in verification.
+///
+/// [`SslContextBuilder::enable_handshake_timings`]: super::SslContextBuilder::enable_handshake_timings
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HandshakeTimings {
+    client_hello: Option<Duration>,
+    certificate_verified: Option<Duration>,
+    finished: Option<Duration>,
+}
+
+impl HandshakeTimings {
+    /// Returns when a client sent its ClientHello, or a server received it.
+    #[must_use]
+    pub fn client_hello(&self) -> Option<Duration> {
+        self.client_hello
+    }
+
+    /// Returns when the peer's certificate was verified.
+    ///
+    /// This is `None` if the peer sent no certificate, such as when a session was resumed.
+    #[must_use]
+    pub fn certificate_verified(&self) -> Option<Duration> {
+        self.certificate_verified
+    }
+
+    /// Returns when the handshake completed.
+    ///
+    /// This is `None` while the handshake is in progress.
+    #[must_use]
+    pub fn finished(&self) -> Option<Duration> {
+        self.finished
+    }
+}
+
+/// Marks contexts recording handshake timings.
+pub(super) struct RecordHandshakeTimings;
+
+struct Recorder {
+    start: Instant,
+    state: &'static str,
+    timings: HandshakeTimings,
+}
+
+/// Returns `true` if leaving `state` means the ClientHello was sent or received.
+fn is_client_hello_state(state: &str) -> bool {
+    state.ends_with(" start_connect") || state.ends_with(" read_client_hello")
+}
+
+/// Returns `true` if leaving `state` means the peer's certificate was verified.
+fn is_verify_state(state: &str) -> bool {
+    [
+        "verify_server_certificate",
+        "verify_client_certificate",
+        "read_server_certificate_verify",
+        "read_client_certificate_verify",
+    ]
+    .iter()
+    .any(|suffix| state.ends_with(suffix))
+}
+
+pub(super) fn info(ssl: &mut SslRef, mode: c_int) {
+    if ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<RecordHandshakeTimings>())
+        .is_none()
+    {
+        return;
+    }
+
+    let now = Instant::now();
+    let state = ssl.state_string_long();
+    // SAFETY: `ssl` is valid.
+    let has_peer_certificate = unsafe { !ffi::SSL_get0_peer_certificates(ssl.as_ptr()).is_null() };
+    let index = Ssl::cached_ex_index::<Recorder>();
+
+    if mode == ffi::SSL_CB_HANDSHAKE_START {
+        ssl.replace_ex_data(
+            index,
+            Recorder {
+                start: now,
+                state,
+                timings: HandshakeTimings::default(),
+            },
+        );
+        return;
+    }
+
+    let Some(recorder) = ssl.ex_data_mut(index) else {
+        return;
+    };
+    let elapsed = now.duration_since(recorder.start);
+
+    match mode {
+        ffi::SSL_CB_CONNECT_LOOP | ffi::SSL_CB_ACCEPT_LOOP => {
+            let previous = mem::replace(&mut recorder.state, state);
+            let timings = &mut recorder.timings;
+
+            if is_client_hello_state(previous) {
+                timings.client_hello.get_or_insert(elapsed);
+            }
+            if has_peer_certificate && is_verify_state(previous) {
+                timings.certificate_verified.get_or_insert(elapsed);
+            }
+        }
+        ffi::SSL_CB_HANDSHAKE_DONE => recorder.timings.finished = Some(elapsed),
+        _ => {}
+    }
+}
+
+impl SslRef {
+    /// Returns the timings of the handshake recorded so far.
+    ///
+    /// This is `None` unless the context of the connection was configured with
+    /// [`SslContextBuilder::enable_handshake_timings`] and the handshake started.
+    ///
+    /// [`SslContextBuilder::enable_handshake_timings`]: super::SslContextBuilder::enable_handshake_timings
+    #[must_use]
+    pub fn handshake_timings(&self) -> Option<HandshakeTimings> {
+        self.ex_data(Ssl::cached_ex_index::<Recorder>())
+            .map(|recorder| recorder.timings)
+    }
+}
+
+impl<S> SslStream<S> {
+    /// Returns the timings of the handshake of the stream.
+    ///
+    /// See [`SslRef::handshake_timings`].
+    #[must_use]
+    pub fn handshake_timings(&self) -> Option<HandshakeTimings> {
+        self.ssl().handshake_timings()
+    }
+}
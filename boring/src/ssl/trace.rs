@@ -0,0 +1,58 @@
+//! `tracing` events for TLS connections.
+//!
+//! Every context reports handshakes and alerts through an info callback installed when it is
+//! created, and callbacks set with [`SslContextBuilder::set_info_callback`] keep reporting them.
+//!
+//! [`SslContextBuilder::set_info_callback`]: super::SslContextBuilder::set_info_callback
+use crate::ffi;
+use crate::ssl::{Error, SslRef};
+use libc::c_int;
+use std::ffi::CStr;
+
+pub(super) fn info(ssl: &SslRef, mode: c_int, value: c_int) {
+    let server = ssl.is_server();
+
+    match mode {
+        ffi::SSL_CB_HANDSHAKE_START => tracing::debug!(server, "TLS handshake started"),
+        ffi::SSL_CB_HANDSHAKE_DONE => tracing::debug!(
+            server,
+            version = ssl.version_str(),
+            cipher = ssl.current_cipher().map(|cipher| cipher.name()),
+            resumed = ssl.session_reused(),
+            "TLS handshake completed",
+        ),
+        ffi::SSL_CB_READ_ALERT | ffi::SSL_CB_WRITE_ALERT => {
+            let direction = if mode == ffi::SSL_CB_READ_ALERT {
+                "received"
+            } else {
+                "sent"
+            };
+            // SAFETY: both functions return static strings.
+            let (level, description) = unsafe {
+                (
+                    CStr::from_ptr(ffi::SSL_alert_type_string_long(value)),
+                    CStr::from_ptr(ffi::SSL_alert_desc_string_long(value)),
+                )
+            };
+            let level = level.to_str().unwrap_or_default();
+            let description = description.to_str().unwrap_or_default();
+
+            if value >> 8 == ffi::SSL3_AL_FATAL {
+                tracing::warn!(server, direction, level, description, "TLS alert");
+            } else {
+                tracing::debug!(server, direction, level, description, "TLS alert");
+            }
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn handshake_failed(ssl: &SslRef, error: &Error) {
+    let server = ssl.is_server();
+
+    if let Err(verify_error) = ssl.verify_result() {
+        tracing::warn!(server, error = %verify_error, "TLS certificate verification failed");
+    }
+
+    tracing::warn!(server, %error, "TLS handshake failed");
+}
@@ -0,0 +1,99 @@
+use crate::error::ErrorStack;
+use crate::ffi;
+use crate::hash::{self, DigestBytes, MessageDigest};
+use crate::nid::Nid;
+use crate::ssl::{Ssl, SslContentType, SslContext, SslRef, SslStream, SslVersion};
+use foreign_types::ForeignTypeRef;
+
+/// Marks contexts recording handshake transcripts.
+pub(super) struct RecordHandshakeTranscript;
+
+/// Marks contexts with a msg callback set with [`SslContextBuilder::set_msg_callback`].
+///
+/// [`SslContextBuilder::set_msg_callback`]: super::SslContextBuilder::set_msg_callback
+pub(super) struct UserMsgCallback;
+
+/// The handshake messages of a connection, in order.
+struct Transcript(Vec<u8>);
+
+const NEW_SESSION_TICKET: u8 = 4;
+
+pub(super) fn msg(ssl: &mut SslRef, content_type: SslContentType, msg: &[u8]) {
+    if content_type != SslContentType::HANDSHAKE
+        || ssl.is_init_finished()
+        || ssl
+            .ssl_context()
+            .ex_data(SslContext::cached_ex_index::<RecordHandshakeTranscript>())
+            .is_none()
+    {
+        return;
+    }
+
+    // TLS 1.3 servers send their session tickets before the handshake is done, but tickets are
+    // not part of the handshake.
+    if msg.first() == Some(&NEW_SESSION_TICKET) && ssl.version2() == Some(SslVersion::TLS1_3) {
+        return;
+    }
+
+    let index = Ssl::cached_ex_index::<Transcript>();
+    match ssl.ex_data_mut(index) {
+        Some(transcript) => transcript.0.extend_from_slice(msg),
+        None => ssl.set_ex_data(index, Transcript(msg.to_vec())),
+    }
+}
+
+impl SslRef {
+    /// Returns the handshake messages sent and received on the connection, in order.
+    ///
+    /// Each message is recorded as sent, with its header. Messages sent after the handshake,
+    /// such as session tickets and KeyUpdates, are not recorded.
+    ///
+    /// This is `None` unless the context of the connection was configured with
+    /// [`SslContextBuilder::enable_handshake_transcript`] and the handshake completed.
+    ///
+    /// [`SslContextBuilder::enable_handshake_transcript`]: super::SslContextBuilder::enable_handshake_transcript
+    #[must_use]
+    pub fn handshake_transcript(&self) -> Option<&[u8]> {
+        if !self.is_init_finished() {
+            return None;
+        }
+
+        self.ex_data(Ssl::cached_ex_index::<Transcript>())
+            .map(|transcript| &*transcript.0)
+    }
+
+    /// Returns the hash of [`Self::handshake_transcript`] with the PRF hash of the negotiated
+    /// cipher.
+    ///
+    /// This is the TLS transcript hash of the handshake, except after a TLS 1.3
+    /// HelloRetryRequest, whose preceding ClientHello is not replaced by its hash, and with
+    /// Encrypted Client Hello, where the outer ClientHello is recorded.
+    pub fn handshake_transcript_hash(&self) -> Result<Option<DigestBytes>, ErrorStack> {
+        let (Some(transcript), Some(cipher)) = (self.handshake_transcript(), self.current_cipher())
+        else {
+            return Ok(None);
+        };
+
+        // SAFETY: `cipher` is valid.
+        let nid = Nid::from_raw(unsafe { ffi::SSL_CIPHER_get_prf_nid(cipher.as_ptr()) });
+        // Ciphers predating TLS 1.2 use SHA-256 in TLS 1.2.
+        let nid = match nid {
+            Nid::MD5_SHA1 if self.version2() == Some(SslVersion::TLS1_2) => Nid::SHA256,
+            nid => nid,
+        };
+
+        MessageDigest::from_nid(nid)
+            .map(|digest| hash::hash(digest, transcript))
+            .transpose()
+    }
+}
+
+impl<S> SslStream<S> {
+    /// Returns the handshake messages of the stream.
+    ///
+    /// See [`SslRef::handshake_transcript`].
+    #[must_use]
+    pub fn handshake_transcript(&self) -> Option<&[u8]> {
+        self.ssl().handshake_transcript()
+    }
+}
@@ -57,6 +57,7 @@ use foreign_types::ForeignTypeRef;
 use libc::{c_int, c_uint};
 use openssl_macros::corresponds;
 use std::cmp;
+use std::io::{self, Read, Write};
 use std::ptr;
 
 use crate::error::ErrorStack;
@@ -774,6 +775,245 @@ pub fn decrypt_aead(
     Ok(out)
 }
 
+/// The amount of data passed to the `Crypter` at once by the IO adapters.
+const CHUNK_LEN: usize = 16 * 1024;
+
+/// A writer that encrypts data before writing it to an inner writer.
+///
+/// The cipher is finalized, flushing any buffered partial block and padding, by
+/// [`finish`](Self::finish). If the writer is dropped without calling `finish`, it is finalized
+/// on drop and any error is ignored. [`flush`](Write::flush) only flushes the inner writer, since
+/// nothing more can be encrypted after finalization.
+///
+/// Once a write fails, the writer is unusable: the plaintext it consumed may or may not have
+/// reached the inner writer, so later writes and `finish` return an error rather than encrypting
+/// data twice or leaving a gap in the ciphertext. Dropping a failed writer does not finalize it.
+///
+/// # Examples
+///
+/// ```
+/// use boring::symm::{decrypt, Cipher, EncryptingWriter};
+/// use std::io::Write;
+///
+/// let cipher = Cipher::aes_128_cbc();
+/// let key = [0x42; 16];
+/// let iv = [0x24; 16];
+///
+/// let mut writer = EncryptingWriter::new(Vec::new(), cipher, &key, Some(&iv)).unwrap();
+/// writer.write_all(b"Some Stream of").unwrap();
+/// writer.write_all(b" Crypto Text").unwrap();
+/// let ciphertext = writer.finish().unwrap();
+///
+/// let plaintext = decrypt(cipher, &key, Some(&iv), &ciphertext).unwrap();
+/// assert_eq!(plaintext, b"Some Stream of Crypto Text");
+/// ```
+pub struct EncryptingWriter<W: Write> {
+    inner: Option<W>,
+    crypter: Crypter,
+    buf: Vec<u8>,
+    failed: bool,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    /// Wraps `inner`, encrypting with `t` under `key` and `iv`. Padding is enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Crypter::new`].
+    pub fn new(
+        inner: W,
+        t: Cipher,
+        key: &[u8],
+        iv: Option<&[u8]>,
+    ) -> Result<EncryptingWriter<W>, ErrorStack> {
+        let crypter = Crypter::new(t, Mode::Encrypt, key, iv)?;
+        Ok(EncryptingWriter::with_crypter(inner, crypter))
+    }
+
+    /// Wraps `inner`, encrypting with an already configured `crypter`.
+    ///
+    /// `crypter` must have been created with [`Mode::Encrypt`].
+    pub fn with_crypter(inner: W, crypter: Crypter) -> EncryptingWriter<W> {
+        EncryptingWriter {
+            inner: Some(inner),
+            crypter,
+            buf: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// Returns a shared reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.as_mut().unwrap()
+    }
+
+    /// Finalizes the cipher, writes the remaining ciphertext and returns the inner writer.
+    ///
+    /// Returns an error without finalizing if an earlier write failed.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.finalize()?;
+        Ok(self.inner.take().unwrap())
+    }
+
+    fn check_failed(&self) -> io::Result<()> {
+        if self.failed {
+            return Err(io::Error::other(
+                "EncryptingWriter is unusable after an error",
+            ));
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> io::Result<()> {
+        self.check_failed()?;
+        // The cipher can only be finalized once, whether or not this succeeds.
+        self.failed = true;
+
+        self.buf.resize(cmp::max(self.crypter.block_size, 1), 0);
+        let n = self.crypter.finalize(&mut self.buf)?;
+        let inner = self.inner.as_mut().unwrap();
+        inner.write_all(&self.buf[..n])?;
+        inner.flush()
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_failed()?;
+
+        let input = &buf[..cmp::min(buf.len(), CHUNK_LEN)];
+        self.buf.resize(input.len() + self.crypter.block_size, 0);
+        let res = self
+            .crypter
+            .update(input, &mut self.buf)
+            .map_err(io::Error::from)
+            .and_then(|n| self.inner.as_mut().unwrap().write_all(&self.buf[..n]));
+        if res.is_err() {
+            self.failed = true;
+        }
+        res.map(|()| input.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for EncryptingWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() && !self.failed {
+            let _ = self.finalize();
+        }
+    }
+}
+
+/// A reader that decrypts data read from an inner reader.
+///
+/// The cipher is finalized once the inner reader reaches end of file. For padded block ciphers,
+/// this is where invalid padding is detected, so the final read may fail with an error even though
+/// earlier reads succeeded.
+///
+/// # Examples
+///
+/// ```
+/// use boring::symm::{encrypt, Cipher, DecryptingReader};
+/// use std::io::Read;
+///
+/// let cipher = Cipher::aes_128_cbc();
+/// let key = [0x42; 16];
+/// let iv = [0x24; 16];
+/// let ciphertext = encrypt(cipher, &key, Some(&iv), b"Some Crypto Text").unwrap();
+///
+/// let mut reader = DecryptingReader::new(&ciphertext[..], cipher, &key, Some(&iv)).unwrap();
+/// let mut plaintext = vec![];
+/// reader.read_to_end(&mut plaintext).unwrap();
+/// assert_eq!(plaintext, b"Some Crypto Text");
+/// ```
+pub struct DecryptingReader<R> {
+    inner: R,
+    crypter: Crypter,
+    input: Box<[u8]>,
+    output: Vec<u8>,
+    pos: usize,
+    finished: bool,
+}
+
+impl<R: Read> DecryptingReader<R> {
+    /// Wraps `inner`, decrypting with `t` under `key` and `iv`. Padding is enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Crypter::new`].
+    pub fn new(
+        inner: R,
+        t: Cipher,
+        key: &[u8],
+        iv: Option<&[u8]>,
+    ) -> Result<DecryptingReader<R>, ErrorStack> {
+        let crypter = Crypter::new(t, Mode::Decrypt, key, iv)?;
+        Ok(DecryptingReader::with_crypter(inner, crypter))
+    }
+
+    /// Wraps `inner`, decrypting with an already configured `crypter`.
+    ///
+    /// `crypter` must have been created with [`Mode::Decrypt`].
+    pub fn with_crypter(inner: R, crypter: Crypter) -> DecryptingReader<R> {
+        DecryptingReader {
+            inner,
+            crypter,
+            input: vec![0; CHUNK_LEN].into_boxed_slice(),
+            output: Vec::new(),
+            pos: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns a shared reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the inner reader.
+    ///
+    /// Any data that was decrypted but not yet read is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for DecryptingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.output.len() && !self.finished {
+            self.pos = 0;
+            let n = self.inner.read(&mut self.input)?;
+            self.output
+                .resize(n + cmp::max(self.crypter.block_size, 1), 0);
+            let len = if n == 0 {
+                self.finished = true;
+                self.crypter.finalize(&mut self.output)?
+            } else {
+                self.crypter.update(&self.input[..n], &mut self.output)?
+            };
+            self.output.truncate(len);
+        }
+
+        let n = cmp::min(buf.len(), self.output.len() - self.pos);
+        buf[..n].copy_from_slice(&self.output[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
 use crate::ffi::{EVP_CIPHER_block_size, EVP_CIPHER_iv_length, EVP_CIPHER_key_length};
 
 #[cfg(test)]
@@ -1051,4 +1291,122 @@ mod tests {
         .unwrap();
         assert_eq!(pt, hex::encode(out));
     }
+
+    #[test]
+    fn test_io_adapters() {
+        let cipher = Cipher::aes_128_cbc();
+        let key = [0x42; 16];
+        let iv = [0x24; 16];
+        let plaintext = (0..1000u32).map(|i| i as u8).collect::<Vec<_>>();
+        let expected = encrypt(cipher, &key, Some(&iv), &plaintext).unwrap();
+
+        let mut writer = EncryptingWriter::new(Vec::new(), cipher, &key, Some(&iv)).unwrap();
+        for chunk in plaintext.chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), expected);
+
+        let mut reader = DecryptingReader::new(&expected[..], cipher, &key, Some(&iv)).unwrap();
+        let mut decrypted = vec![];
+        let mut buf = [0; 13];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            decrypted.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_encrypting_writer_finalizes_on_drop() {
+        let cipher = Cipher::aes_128_cbc();
+        let key = [0x42; 16];
+        let iv = [0x24; 16];
+
+        let mut out = vec![];
+        {
+            let mut writer = EncryptingWriter::new(&mut out, cipher, &key, Some(&iv)).unwrap();
+            writer.write_all(b"Some Crypto Text").unwrap();
+        }
+        assert_eq!(
+            out,
+            encrypt(cipher, &key, Some(&iv), b"Some Crypto Text").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encrypting_writer_unusable_after_error() {
+        struct FailingWriter<'a> {
+            out: &'a mut Vec<u8>,
+            fail: bool,
+        }
+
+        impl Write for FailingWriter<'_> {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                if self.fail {
+                    return Err(io::Error::other("write failed"));
+                }
+                self.out.write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let cipher = Cipher::aes_128_cbc();
+        let key = [0x42; 16];
+        let iv = [0x24; 16];
+        let first_block = encrypt(cipher, &key, Some(&iv), &[0; 16]).unwrap()[..16].to_vec();
+
+        let mut out = vec![];
+        {
+            let inner = FailingWriter {
+                out: &mut out,
+                fail: false,
+            };
+            let mut writer = EncryptingWriter::new(inner, cipher, &key, Some(&iv)).unwrap();
+            writer.write_all(&[0; 16]).unwrap();
+
+            writer.get_mut().fail = true;
+            writer.write_all(&[0; 16]).unwrap_err();
+
+            writer.get_mut().fail = false;
+            writer.write_all(&[0; 16]).unwrap_err();
+            writer.finish().unwrap_err();
+        }
+        assert_eq!(out, first_block);
+
+        let mut out = vec![];
+        {
+            let inner = FailingWriter {
+                out: &mut out,
+                fail: false,
+            };
+            let mut writer = EncryptingWriter::new(inner, cipher, &key, Some(&iv)).unwrap();
+            writer.write_all(&[0; 16]).unwrap();
+
+            writer.get_mut().fail = true;
+            writer.finish().unwrap_err();
+        }
+        // The writer was not finalized again on drop.
+        assert_eq!(out, first_block);
+    }
+
+    #[test]
+    fn test_decrypting_reader_bad_padding() {
+        let cipher = Cipher::aes_128_cbc();
+        let key = [0x42; 16];
+        let iv = [0x24; 16];
+        let mut ciphertext = encrypt(cipher, &key, Some(&iv), b"Some Crypto Text").unwrap();
+        // Flipping a bit in the first block flips the same bit of the padding in the second.
+        ciphertext[15] ^= 1;
+
+        let mut reader = DecryptingReader::new(&ciphertext[..], cipher, &key, Some(&iv)).unwrap();
+        reader
+            .read_to_end(&mut vec![])
+            .expect_err("padding should be invalid");
+    }
 }
@@ -0,0 +1,665 @@
+//! Trust Tokens and Privacy Pass.
+//!
+//! An issuer hands out blinded tokens to a client, which can later redeem them without the issuer
+//! being able to link the redemption to the issuance. BoringSSL marks this API as unstable, and
+//! the protocol variants follow drafts that may change.
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::trust_token::{
+//!     TrustTokenClient, TrustTokenIssuer, TrustTokenKeyPair, TrustTokenMethod,
+//! };
+//!
+//! let method = TrustTokenMethod::pst_v1_voprf();
+//! let key = TrustTokenKeyPair::generate(method, 1).unwrap();
+//!
+//! let mut issuer = TrustTokenIssuer::new(method, 8).unwrap();
+//! issuer.add_key(key.private_key()).unwrap();
+//! issuer.set_metadata_key(&[0; 32]).unwrap();
+//!
+//! let mut client = TrustTokenClient::new(method, 8).unwrap();
+//! client.add_key(key.public_key()).unwrap();
+//!
+//! let request = client.begin_issuance(2).unwrap();
+//! let (response, _) = issuer.issue(&request, 1, false, 2).unwrap();
+//! let (tokens, _) = client.finish_issuance(&response).unwrap();
+//!
+//! let request = client.begin_redemption(&tokens[0], b"client data", 0).unwrap();
+//! let redemption = issuer.redeem(&request).unwrap();
+//! assert_eq!(redemption.client_data(), b"client data");
+//! ```
+use crate::error::ErrorStack;
+use crate::ffi;
+use crate::memcmp::cleanse;
+use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+use crate::stack::{Stack, Stackable};
+use crate::{cvt, cvt_p};
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl_macros::corresponds;
+use std::ptr;
+use std::slice;
+
+/// A Trust Token protocol variant.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct TrustTokenMethod(*const ffi::TRUST_TOKEN_METHOD);
+
+impl TrustTokenMethod {
+    /// VOPRF over P-384 with up to 6 keys.
+    #[corresponds(TRUST_TOKEN_experiment_v2_voprf)]
+    #[must_use]
+    pub fn experiment_v2_voprf() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_experiment_v2_voprf()) }
+    }
+
+    /// PMBTokens over P-384 with up to 3 keys, supporting a private metadata bit.
+    #[corresponds(TRUST_TOKEN_experiment_v2_pmb)]
+    #[must_use]
+    pub fn experiment_v2_pmb() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_experiment_v2_pmb()) }
+    }
+
+    /// Private State Tokens using a VOPRF over P-384 with up to 6 keys.
+    #[corresponds(TRUST_TOKEN_pst_v1_voprf)]
+    #[must_use]
+    pub fn pst_v1_voprf() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_pst_v1_voprf()) }
+    }
+
+    /// Private State Tokens using PMBTokens over P-384 with up to 3 keys.
+    #[corresponds(TRUST_TOKEN_pst_v1_pmb)]
+    #[must_use]
+    pub fn pst_v1_pmb() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_pst_v1_pmb()) }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    #[must_use]
+    pub fn as_ptr(&self) -> *const ffi::TRUST_TOKEN_METHOD {
+        self.0
+    }
+}
+
+unsafe impl Sync for TrustTokenMethod {}
+unsafe impl Send for TrustTokenMethod {}
+
+/// A serialized issuer key pair.
+///
+/// The private key is configured on a [`TrustTokenIssuer`], and the public key is published to
+/// clients in the issuer's key commitment.
+#[derive(Clone)]
+pub struct TrustTokenKeyPair {
+    private_key: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+impl TrustTokenKeyPair {
+    /// Generates a new key pair labeled with `id`.
+    #[corresponds(TRUST_TOKEN_generate_key)]
+    pub fn generate(method: TrustTokenMethod, id: u32) -> Result<TrustTokenKeyPair, ErrorStack> {
+        let mut private_key = vec![0; ffi::TRUST_TOKEN_MAX_PRIVATE_KEY_SIZE as usize];
+        let mut public_key = vec![0; ffi::TRUST_TOKEN_MAX_PUBLIC_KEY_SIZE as usize];
+        let mut private_key_len = 0;
+        let mut public_key_len = 0;
+        unsafe {
+            ffi::init();
+            cvt(ffi::TRUST_TOKEN_generate_key(
+                method.as_ptr(),
+                private_key.as_mut_ptr(),
+                &mut private_key_len,
+                private_key.len(),
+                public_key.as_mut_ptr(),
+                &mut public_key_len,
+                public_key.len(),
+                id,
+            ))?;
+        }
+        private_key.truncate(private_key_len);
+        public_key.truncate(public_key_len);
+        Ok(TrustTokenKeyPair {
+            private_key,
+            public_key,
+        })
+    }
+
+    /// Deterministically derives a key pair labeled with `id` from `secret`.
+    #[corresponds(TRUST_TOKEN_derive_key_from_secret)]
+    pub fn derive_from_secret(
+        method: TrustTokenMethod,
+        id: u32,
+        secret: &[u8],
+    ) -> Result<TrustTokenKeyPair, ErrorStack> {
+        let mut private_key = vec![0; ffi::TRUST_TOKEN_MAX_PRIVATE_KEY_SIZE as usize];
+        let mut public_key = vec![0; ffi::TRUST_TOKEN_MAX_PUBLIC_KEY_SIZE as usize];
+        let mut private_key_len = 0;
+        let mut public_key_len = 0;
+        unsafe {
+            ffi::init();
+            cvt(ffi::TRUST_TOKEN_derive_key_from_secret(
+                method.as_ptr(),
+                private_key.as_mut_ptr(),
+                &mut private_key_len,
+                private_key.len(),
+                public_key.as_mut_ptr(),
+                &mut public_key_len,
+                public_key.len(),
+                id,
+                secret.as_ptr(),
+                secret.len(),
+            ))?;
+        }
+        private_key.truncate(private_key_len);
+        public_key.truncate(public_key_len);
+        Ok(TrustTokenKeyPair {
+            private_key,
+            public_key,
+        })
+    }
+
+    /// Returns the serialized private key.
+    #[must_use]
+    pub fn private_key(&self) -> &[u8] {
+        &self.private_key
+    }
+
+    /// Returns the serialized public key.
+    #[must_use]
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+impl Drop for TrustTokenKeyPair {
+    fn drop(&mut self) {
+        cleanse(&mut self.private_key);
+    }
+}
+
+/// Zeroizing the key pair only wipes its private key.
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for TrustTokenKeyPair {
+    fn zeroize(&mut self) {
+        cleanse(&mut self.private_key);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for TrustTokenKeyPair {}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TRUST_TOKEN;
+    fn drop = ffi::TRUST_TOKEN_free;
+
+    /// A single-use token.
+    pub struct TrustToken;
+}
+
+impl Stackable for TrustToken {
+    type StackType = ffi::stack_st_TRUST_TOKEN;
+}
+
+impl TrustToken {
+    /// Creates a token from its serialized value, as stored by the client.
+    #[corresponds(TRUST_TOKEN_new)]
+    pub fn new(data: &[u8]) -> Result<TrustToken, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::TRUST_TOKEN_new(data.as_ptr(), data.len())).map(|p| TrustToken::from_ptr(p))
+        }
+    }
+}
+
+impl TrustTokenRef {
+    /// Returns the serialized value of the token.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            let token = &*self.as_ptr();
+            if token.data.is_null() {
+                &[]
+            } else {
+                slice::from_raw_parts(token.data, token.len)
+            }
+        }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TRUST_TOKEN_ISSUER;
+    fn drop = ffi::TRUST_TOKEN_ISSUER_free;
+
+    /// The issuer side of the protocol.
+    ///
+    /// An issuer may be reused across many operations. Issuing and redeeming only need a shared
+    /// reference, so a configured issuer can be shared between threads.
+    pub struct TrustTokenIssuer;
+}
+
+impl TrustTokenIssuer {
+    /// Creates an issuer accepting issuance requests of up to `max_batchsize` tokens.
+    #[corresponds(TRUST_TOKEN_ISSUER_new)]
+    pub fn new(
+        method: TrustTokenMethod,
+        max_batchsize: usize,
+    ) -> Result<TrustTokenIssuer, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::TRUST_TOKEN_ISSUER_new(method.as_ptr(), max_batchsize))
+                .map(|p| TrustTokenIssuer::from_ptr(p))
+        }
+    }
+}
+
+impl TrustTokenIssuerRef {
+    /// Adds a private key produced by [`TrustTokenKeyPair`].
+    #[corresponds(TRUST_TOKEN_ISSUER_add_key)]
+    pub fn add_key(&mut self, private_key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_ISSUER_add_key(
+                self.as_ptr(),
+                private_key.as_ptr(),
+                private_key.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the key used to sign redemption records.
+    #[corresponds(TRUST_TOKEN_ISSUER_set_srr_key)]
+    pub fn set_srr_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_ISSUER_set_srr_key(
+                self.as_ptr(),
+                key.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the key, of at least 32 random bytes, used to encrypt the private metadata bit.
+    #[corresponds(TRUST_TOKEN_ISSUER_set_metadata_key)]
+    pub fn set_metadata_key(&mut self, key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_ISSUER_set_metadata_key(
+                self.as_ptr(),
+                key.as_ptr(),
+                key.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Issues up to `max_issuance` tokens in response to a client's issuance request.
+    ///
+    /// `public_metadata` must be the id of a configured key. Returns the response to send to the
+    /// client and the number of tokens issued.
+    #[corresponds(TRUST_TOKEN_ISSUER_issue)]
+    pub fn issue(
+        &self,
+        request: &[u8],
+        public_metadata: u32,
+        private_metadata: bool,
+        max_issuance: usize,
+    ) -> Result<(Vec<u8>, usize), ErrorStack> {
+        let mut out = ptr::null_mut();
+        let mut out_len = 0;
+        let mut issued = 0;
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_ISSUER_issue(
+                self.as_ptr(),
+                &mut out,
+                &mut out_len,
+                &mut issued,
+                request.as_ptr(),
+                request.len(),
+                public_metadata,
+                private_metadata as u8,
+                max_issuance,
+            ))?;
+            Ok((take_buf(out, out_len), issued))
+        }
+    }
+
+    /// Verifies a client's redemption request.
+    ///
+    /// The caller must remember every redeemed [`TrustTokenRedemption::token`] and reject
+    /// requests that reuse one, or tokens can be spent more than once.
+    #[corresponds(TRUST_TOKEN_ISSUER_redeem)]
+    pub fn redeem(&self, request: &[u8]) -> Result<TrustTokenRedemption, ErrorStack> {
+        let mut public_metadata = 0;
+        let mut private_metadata = 0;
+        let mut token = ptr::null_mut();
+        let mut client_data = ptr::null_mut();
+        let mut client_data_len = 0;
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_ISSUER_redeem(
+                self.as_ptr(),
+                &mut public_metadata,
+                &mut private_metadata,
+                &mut token,
+                &mut client_data,
+                &mut client_data_len,
+                request.as_ptr(),
+                request.len(),
+            ))?;
+            Ok(TrustTokenRedemption {
+                public_metadata,
+                private_metadata: private_metadata != 0,
+                token: TrustToken::from_ptr(token),
+                client_data: take_buf(client_data, client_data_len),
+            })
+        }
+    }
+
+    /// Like [`redeem`](Self::redeem), but also checks that the token was issued over `msg`.
+    #[corresponds(TRUST_TOKEN_ISSUER_redeem_over_message)]
+    pub fn redeem_over_message(
+        &self,
+        request: &[u8],
+        msg: &[u8],
+    ) -> Result<TrustTokenRedemption, ErrorStack> {
+        let mut public_metadata = 0;
+        let mut private_metadata = 0;
+        let mut token = ptr::null_mut();
+        let mut client_data = ptr::null_mut();
+        let mut client_data_len = 0;
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_ISSUER_redeem_over_message(
+                self.as_ptr(),
+                &mut public_metadata,
+                &mut private_metadata,
+                &mut token,
+                &mut client_data,
+                &mut client_data_len,
+                request.as_ptr(),
+                request.len(),
+                msg.as_ptr(),
+                msg.len(),
+            ))?;
+            Ok(TrustTokenRedemption {
+                public_metadata,
+                private_metadata: private_metadata != 0,
+                token: TrustToken::from_ptr(token),
+                client_data: take_buf(client_data, client_data_len),
+            })
+        }
+    }
+}
+
+/// The result of a successful redemption.
+pub struct TrustTokenRedemption {
+    public_metadata: u32,
+    private_metadata: bool,
+    token: TrustToken,
+    client_data: Vec<u8>,
+}
+
+impl TrustTokenRedemption {
+    /// Returns the id of the key the token was issued with.
+    #[must_use]
+    pub fn public_metadata(&self) -> u32 {
+        self.public_metadata
+    }
+
+    /// Returns the private metadata bit the token was issued with.
+    #[must_use]
+    pub fn private_metadata(&self) -> bool {
+        self.private_metadata
+    }
+
+    /// Returns the redeemed token.
+    #[must_use]
+    pub fn token(&self) -> &TrustTokenRef {
+        &self.token
+    }
+
+    /// Returns the data the client bound to the redemption.
+    #[must_use]
+    pub fn client_data(&self) -> &[u8] {
+        &self.client_data
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TRUST_TOKEN_CLIENT;
+    fn drop = ffi::TRUST_TOKEN_CLIENT_free;
+
+    /// The client side of the protocol.
+    ///
+    /// A client performs a single operation at a time: an issuance or redemption must be finished
+    /// before the next one begins.
+    pub struct TrustTokenClient;
+}
+
+impl TrustTokenClient {
+    /// Creates a client requesting batches of up to `max_batchsize` tokens.
+    #[corresponds(TRUST_TOKEN_CLIENT_new)]
+    pub fn new(
+        method: TrustTokenMethod,
+        max_batchsize: usize,
+    ) -> Result<TrustTokenClient, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::TRUST_TOKEN_CLIENT_new(method.as_ptr(), max_batchsize))
+                .map(|p| TrustTokenClient::from_ptr(p))
+        }
+    }
+}
+
+impl TrustTokenClientRef {
+    /// Adds one of the issuer's public keys, returning the index it was assigned.
+    #[corresponds(TRUST_TOKEN_CLIENT_add_key)]
+    pub fn add_key(&mut self, public_key: &[u8]) -> Result<usize, ErrorStack> {
+        let mut index = 0;
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_CLIENT_add_key(
+                self.as_ptr(),
+                &mut index,
+                public_key.as_ptr(),
+                public_key.len(),
+            ))?;
+        }
+        Ok(index)
+    }
+
+    /// Sets the key used to verify redemption records.
+    #[corresponds(TRUST_TOKEN_CLIENT_set_srr_key)]
+    pub fn set_srr_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_CLIENT_set_srr_key(
+                self.as_ptr(),
+                key.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Starts an issuance of `count` tokens, returning the request to send to the issuer.
+    #[corresponds(TRUST_TOKEN_CLIENT_begin_issuance)]
+    pub fn begin_issuance(&mut self, count: usize) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = ptr::null_mut();
+        let mut out_len = 0;
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_CLIENT_begin_issuance(
+                self.as_ptr(),
+                &mut out,
+                &mut out_len,
+                count,
+            ))?;
+            Ok(take_buf(out, out_len))
+        }
+    }
+
+    /// Like [`begin_issuance`](Self::begin_issuance), but derives the tokens from `msg`.
+    #[corresponds(TRUST_TOKEN_CLIENT_begin_issuance_over_message)]
+    pub fn begin_issuance_over_message(
+        &mut self,
+        count: usize,
+        msg: &[u8],
+    ) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = ptr::null_mut();
+        let mut out_len = 0;
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_CLIENT_begin_issuance_over_message(
+                self.as_ptr(),
+                &mut out,
+                &mut out_len,
+                count,
+                msg.as_ptr(),
+                msg.len(),
+            ))?;
+            Ok(take_buf(out, out_len))
+        }
+    }
+
+    /// Processes the issuer's response, returning the issued tokens and the index of the key
+    /// that signed them.
+    #[corresponds(TRUST_TOKEN_CLIENT_finish_issuance)]
+    pub fn finish_issuance(
+        &mut self,
+        response: &[u8],
+    ) -> Result<(Vec<TrustToken>, usize), ErrorStack> {
+        let mut key_index = 0;
+        let tokens = unsafe {
+            let stack = cvt_p(ffi::TRUST_TOKEN_CLIENT_finish_issuance(
+                self.as_ptr(),
+                &mut key_index,
+                response.as_ptr(),
+                response.len(),
+            ))?;
+            Stack::<TrustToken>::from_ptr(stack)
+        };
+        if tokens.is_empty() {
+            return Err(ErrorStack::get());
+        }
+        Ok((tokens.into_iter().collect(), key_index))
+    }
+
+    /// Starts redeeming `token`, binding `data` to the redemption.
+    ///
+    /// `time` is the current time in seconds since the Unix epoch. It is only used by
+    /// [`TrustTokenMethod`]s that sign redemption records.
+    #[corresponds(TRUST_TOKEN_CLIENT_begin_redemption)]
+    pub fn begin_redemption(
+        &mut self,
+        token: &TrustTokenRef,
+        data: &[u8],
+        time: u64,
+    ) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = ptr::null_mut();
+        let mut out_len = 0;
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_CLIENT_begin_redemption(
+                self.as_ptr(),
+                &mut out,
+                &mut out_len,
+                token.as_ptr(),
+                data.as_ptr(),
+                data.len(),
+                time,
+            ))?;
+            Ok(take_buf(out, out_len))
+        }
+    }
+
+    /// Processes the issuer's redemption response, returning the redemption record and its
+    /// signature.
+    ///
+    /// Only methods that sign redemption records verify them here. The others return the raw
+    /// response and an empty signature, leaving validation to the caller.
+    #[corresponds(TRUST_TOKEN_CLIENT_finish_redemption)]
+    pub fn finish_redemption(&mut self, response: &[u8]) -> Result<(Vec<u8>, Vec<u8>), ErrorStack> {
+        let mut rr = ptr::null_mut();
+        let mut rr_len = 0;
+        let mut sig = ptr::null_mut();
+        let mut sig_len = 0;
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_CLIENT_finish_redemption(
+                self.as_ptr(),
+                &mut rr,
+                &mut rr_len,
+                &mut sig,
+                &mut sig_len,
+                response.as_ptr(),
+                response.len(),
+            ))?;
+            Ok((take_buf(rr, rr_len), take_buf(sig, sig_len)))
+        }
+    }
+}
+
+/// Copies a buffer allocated by BoringSSL into a `Vec` and frees it.
+unsafe fn take_buf(buf: *mut u8, len: usize) -> Vec<u8> {
+    if buf.is_null() {
+        return Vec::new();
+    }
+    let out = slice::from_raw_parts(buf, len).to_vec();
+    ffi::OPENSSL_free(buf.cast());
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup(method: TrustTokenMethod) -> (TrustTokenIssuer, TrustTokenClient) {
+        let key = TrustTokenKeyPair::generate(method, 7).unwrap();
+
+        let mut issuer = TrustTokenIssuer::new(method, 4).unwrap();
+        issuer.add_key(key.private_key()).unwrap();
+        issuer.set_metadata_key(&[0x42; 32]).unwrap();
+
+        let mut client = TrustTokenClient::new(method, 4).unwrap();
+        assert_eq!(client.add_key(key.public_key()).unwrap(), 0);
+
+        (issuer, client)
+    }
+
+    #[test]
+    fn test_issue_and_redeem() {
+        for method in [
+            TrustTokenMethod::pst_v1_voprf(),
+            TrustTokenMethod::pst_v1_pmb(),
+        ] {
+            let (issuer, mut client) = setup(method);
+
+            let request = client.begin_issuance(3).unwrap();
+            let (response, issued) = issuer.issue(&request, 7, false, 3).unwrap();
+            assert_eq!(issued, 3);
+            let (tokens, key_index) = client.finish_issuance(&response).unwrap();
+            assert_eq!(tokens.len(), 3);
+            assert_eq!(key_index, 0);
+
+            let request = client
+                .begin_redemption(&tokens[1], b"client data", 0)
+                .unwrap();
+            let redemption = issuer.redeem(&request).unwrap();
+            assert_eq!(redemption.public_metadata(), 7);
+            assert!(!redemption.private_metadata());
+            assert_eq!(redemption.client_data(), b"client data");
+            assert!(!redemption.token().as_bytes().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_unknown_key_id() {
+        let (issuer, mut client) = setup(TrustTokenMethod::pst_v1_voprf());
+        let request = client.begin_issuance(1).unwrap();
+        assert!(issuer.issue(&request, 8, false, 1).is_err());
+    }
+
+    #[test]
+    fn test_derive_from_secret() {
+        let method = TrustTokenMethod::pst_v1_voprf();
+        let a = TrustTokenKeyPair::derive_from_secret(method, 1, b"secret").unwrap();
+        let b = TrustTokenKeyPair::derive_from_secret(method, 1, b"secret").unwrap();
+        assert_eq!(a.private_key(), b.private_key());
+        assert_eq!(a.public_key(), b.public_key());
+    }
+}
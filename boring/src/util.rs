@@ -92,3 +92,83 @@ pub trait ForeignTypeRefExt: ForeignTypeRef {
     }
 }
 impl<FT: ForeignTypeRef> ForeignTypeRefExt for FT {}
+
+/// Serde support for types that round-trip through PEM.
+///
+/// Values are serialized as PEM strings. Deserialization accepts PEM as either a string or bytes.
+#[cfg(feature = "serde")]
+pub(crate) mod pem_serde {
+    use crate::error::ErrorStack;
+    use crate::memcmp;
+    use serde::de::{self, Deserializer, Visitor};
+    use serde::ser::{self, Serializer};
+    use std::fmt;
+    use std::marker::PhantomData;
+    use std::str;
+
+    /// A PEM buffer that is wiped when dropped, as it may hold a private key.
+    struct Cleansed(Vec<u8>);
+
+    impl Drop for Cleansed {
+        fn drop(&mut self) {
+            memcmp::cleanse(&mut self.0);
+        }
+    }
+
+    pub(crate) fn serialize<S>(
+        pem: Result<Vec<u8>, ErrorStack>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let pem = Cleansed(pem.map_err(ser::Error::custom)?);
+        serializer.serialize_str(str::from_utf8(&pem.0).map_err(ser::Error::custom)?)
+    }
+
+    pub(crate) fn deserialize<'de, D, T>(
+        deserializer: D,
+        expecting: &'static str,
+        from_pem: fn(&[u8]) -> Result<T, ErrorStack>,
+    ) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PemVisitor<T> {
+            expecting: &'static str,
+            from_pem: fn(&[u8]) -> Result<T, ErrorStack>,
+            _p: PhantomData<T>,
+        }
+
+        impl<T> Visitor<'_> for PemVisitor<T> {
+            type Value = T;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(self.expecting)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+                self.visit_bytes(v.as_bytes())
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+                (self.from_pem)(v).map_err(E::custom)
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<T, E> {
+                self.visit_byte_buf(v.into_bytes())
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<T, E> {
+                let v = Cleansed(v);
+                self.visit_bytes(&v.0)
+            }
+        }
+
+        deserializer.deserialize_str(PemVisitor {
+            expecting,
+            from_pem,
+            _p: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,220 @@
+//! X25519 Diffie-Hellman key agreement, as described in [RFC 7748].
+//!
+//! Private keys and shared secrets are wiped from memory when dropped.
+//!
+//! BoringSSL does not implement X448.
+//!
+//! [RFC 7748]: https://www.rfc-editor.org/rfc/rfc7748
+//!
+//! # Examples
+//!
+//! ```
+//! use boring::x25519::PrivateKey;
+//!
+//! let alice = PrivateKey::generate();
+//! let bob = PrivateKey::generate();
+//!
+//! let alice_secret = alice.diffie_hellman(&bob.public_key()).unwrap();
+//! let bob_secret = bob.diffie_hellman(&alice.public_key()).unwrap();
+//! assert_eq!(alice_secret.as_bytes(), bob_secret.as_bytes());
+//! ```
+use crate::error::ErrorStack;
+use crate::ffi;
+use crate::memcmp::cleanse;
+use openssl_macros::corresponds;
+use std::fmt;
+
+/// The length of an X25519 private key, in bytes.
+pub const PRIVATE_KEY_LEN: usize = ffi::X25519_PRIVATE_KEY_LEN as usize;
+
+/// The length of an X25519 public key, in bytes.
+pub const PUBLIC_KEY_LEN: usize = ffi::X25519_PUBLIC_VALUE_LEN as usize;
+
+/// The length of an X25519 shared secret, in bytes.
+pub const SHARED_SECRET_LEN: usize = ffi::X25519_SHARED_KEY_LEN as usize;
+
+/// An X25519 private key.
+pub struct PrivateKey([u8; PRIVATE_KEY_LEN]);
+
+impl PrivateKey {
+    /// Generates a new random private key.
+    #[corresponds(X25519_keypair)]
+    #[must_use]
+    pub fn generate() -> PrivateKey {
+        let mut public = [0; PUBLIC_KEY_LEN];
+        let mut private = [0; PRIVATE_KEY_LEN];
+        unsafe {
+            ffi::init();
+            ffi::X25519_keypair(public.as_mut_ptr(), private.as_mut_ptr());
+        }
+        PrivateKey(private)
+    }
+
+    /// Creates a private key from its raw byte representation.
+    #[must_use]
+    pub fn from_bytes(bytes: [u8; PRIVATE_KEY_LEN]) -> PrivateKey {
+        PrivateKey(bytes)
+    }
+
+    /// Returns the raw byte representation of the private key.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; PRIVATE_KEY_LEN] {
+        &self.0
+    }
+
+    /// Returns the public key corresponding to this private key.
+    #[corresponds(X25519_public_from_private)]
+    #[must_use]
+    pub fn public_key(&self) -> PublicKey {
+        let mut public = [0; PUBLIC_KEY_LEN];
+        unsafe {
+            ffi::init();
+            ffi::X25519_public_from_private(public.as_mut_ptr(), self.0.as_ptr());
+        }
+        PublicKey(public)
+    }
+
+    /// Computes the shared secret between this private key and the peer's public key.
+    ///
+    /// Returns an error if the peer's public key is a small-order point, which would result in an
+    /// all-zero shared secret.
+    #[corresponds(X25519)]
+    pub fn diffie_hellman(&self, peer: &PublicKey) -> Result<SharedSecret, ErrorStack> {
+        let mut secret = SharedSecret([0; SHARED_SECRET_LEN]);
+        unsafe {
+            ffi::init();
+            if ffi::X25519(secret.0.as_mut_ptr(), self.0.as_ptr(), peer.0.as_ptr()) != 1 {
+                return Err(ErrorStack::internal_error_str(
+                    "X25519 peer public key has small order",
+                ));
+            }
+        }
+        Ok(secret)
+    }
+}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        cleanse(&mut self.0);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for PrivateKey {
+    fn zeroize(&mut self) {
+        cleanse(&mut self.0);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for PrivateKey {}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PrivateKey")
+    }
+}
+
+/// An X25519 public key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PublicKey([u8; PUBLIC_KEY_LEN]);
+
+impl PublicKey {
+    /// Creates a public key from its raw byte representation.
+    #[must_use]
+    pub fn from_bytes(bytes: [u8; PUBLIC_KEY_LEN]) -> PublicKey {
+        PublicKey(bytes)
+    }
+
+    /// Returns the raw byte representation of the public key.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; PUBLIC_KEY_LEN] {
+        &self.0
+    }
+}
+
+impl From<[u8; PUBLIC_KEY_LEN]> for PublicKey {
+    fn from(bytes: [u8; PUBLIC_KEY_LEN]) -> PublicKey {
+        PublicKey(bytes)
+    }
+}
+
+/// A shared secret computed by [`PrivateKey::diffie_hellman`].
+pub struct SharedSecret([u8; SHARED_SECRET_LEN]);
+
+impl SharedSecret {
+    /// Returns the raw bytes of the shared secret.
+    ///
+    /// The shared secret should be passed through a KDF before being used as a key.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; SHARED_SECRET_LEN] {
+        &self.0
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        cleanse(&mut self.0);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SharedSecret {
+    fn zeroize(&mut self) {
+        cleanse(&mut self.0);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SharedSecret {}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SharedSecret")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex::FromHex;
+
+    #[test]
+    fn test_rfc7748_vector() {
+        let alice = PrivateKey::from_bytes(
+            <[u8; 32]>::from_hex(
+                "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+            )
+            .unwrap(),
+        );
+        let bob = PrivateKey::from_bytes(
+            <[u8; 32]>::from_hex(
+                "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            hex::encode(alice.public_key().as_bytes()),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        assert_eq!(
+            hex::encode(bob.public_key().as_bytes()),
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"
+        );
+
+        let shared = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+        let secret = alice.diffie_hellman(&bob.public_key()).unwrap();
+        assert_eq!(hex::encode(secret.as_bytes()), shared);
+        let secret = bob.diffie_hellman(&alice.public_key()).unwrap();
+        assert_eq!(hex::encode(secret.as_bytes()), shared);
+    }
+
+    #[test]
+    fn test_small_order_point() {
+        let key = PrivateKey::generate();
+        assert!(key
+            .diffie_hellman(&PublicKey::from_bytes([0; PUBLIC_KEY_LEN]))
+            .is_err());
+    }
+}
@@ -37,7 +37,7 @@ use crate::ssl::SslRef;
 use crate::stack::{Stack, StackRef, Stackable};
 use crate::string::OpensslString;
 use crate::util::ForeignTypeRefExt;
-use crate::x509::verify::{X509VerifyParam, X509VerifyParamRef};
+use crate::x509::verify::{X509CheckFlags, X509VerifyParam, X509VerifyParamRef};
 use crate::{cvt, cvt_n, cvt_p};
 use crate::{ffi, free_data_box};
 
@@ -554,6 +554,85 @@ impl X509Ref {
         }
     }
 
+    /// Returns this certificate's authority information access entries, if they exist.
+    ///
+    /// Use [`AccessDescriptionRef::ocsp_uri`] and [`AccessDescriptionRef::ca_issuers_uri`] to
+    /// tell OCSP responders apart from issuer certificate locations.
+    #[corresponds(X509_get_ext_d2i)]
+    #[must_use]
+    pub fn authority_info_access(&self) -> Option<Stack<AccessDescription>> {
+        unsafe {
+            let stack = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_info_access,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            if stack.is_null() {
+                None
+            } else {
+                Some(Stack::from_ptr(stack as *mut _))
+            }
+        }
+    }
+
+    /// Returns this certificate's CRL distribution points, if they exist.
+    #[corresponds(X509_get_ext_d2i)]
+    #[must_use]
+    pub fn crl_distribution_points(&self) -> Option<Stack<DistPoint>> {
+        unsafe {
+            let stack = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_crl_distribution_points,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            if stack.is_null() {
+                None
+            } else {
+                Some(Stack::from_ptr(stack as *mut _))
+            }
+        }
+    }
+
+    /// Returns this certificate's name constraints extension, if it exists.
+    #[corresponds(X509_get_ext_d2i)]
+    #[must_use]
+    pub fn name_constraints(&self) -> Option<NameConstraints> {
+        unsafe {
+            let nc = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_name_constraints,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            if nc.is_null() {
+                None
+            } else {
+                Some(NameConstraints::from_ptr(nc as *mut _))
+            }
+        }
+    }
+
+    /// Returns this certificate's certificate policies extension, if it exists.
+    #[corresponds(X509_get_ext_d2i)]
+    #[must_use]
+    pub fn certificate_policies(&self) -> Option<Stack<PolicyInfo>> {
+        unsafe {
+            let stack = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_certificate_policies,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            if stack.is_null() {
+                None
+            } else {
+                Some(Stack::from_ptr(stack as *mut _))
+            }
+        }
+    }
+
     /// Returns this certificate's issuer name.
     #[corresponds(X509_get_issuer_name)]
     #[must_use]
@@ -637,6 +716,37 @@ impl X509Ref {
         self.digest(hash_type).map(|b| b.to_vec())
     }
 
+    /// Returns the DER-encoded SubjectPublicKeyInfo of the certificate.
+    #[corresponds(i2d_X509_PUBKEY)]
+    pub fn public_key_info_to_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let spki = ffi::X509_get_X509_PUBKEY(self.as_ptr());
+            assert!(!spki.is_null());
+            let len = cvt(ffi::i2d_X509_PUBKEY(spki, ptr::null_mut()))?;
+            let mut buf = vec![0; len as usize];
+            cvt(ffi::i2d_X509_PUBKEY(spki, &mut buf.as_mut_ptr()))?;
+            Ok(buf)
+        }
+    }
+
+    /// Returns the SHA-256 hash of the DER-encoded SubjectPublicKeyInfo of the certificate.
+    ///
+    /// This is the value used for public key pinning, as in [RFC 7469].
+    ///
+    /// [RFC 7469]: https://www.rfc-editor.org/rfc/rfc7469#section-2.4
+    pub fn spki_sha256(&self) -> Result<[u8; 32], ErrorStack> {
+        self.public_key_info_to_der()
+            .map(|der| crate::sha::sha256(&der))
+    }
+
+    /// Returns the base64-encoded SHA-256 hash of the certificate's SubjectPublicKeyInfo.
+    ///
+    /// This is the `pin-sha256` format used by HTTP public key pinning.
+    pub fn spki_sha256_base64(&self) -> Result<String, ErrorStack> {
+        self.spki_sha256()
+            .map(|hash| crate::base64::encode_block(&hash))
+    }
+
     /// Returns the certificate's Not After validity period.
     #[corresponds(X509_getm_notAfter)]
     #[must_use]
@@ -725,23 +835,73 @@ impl X509Ref {
     }
 
     pub fn check_host(&self, host: &str) -> Result<bool, ErrorStack> {
+        self.check_host_with_flags(host, X509CheckFlags::empty())
+    }
+
+    #[corresponds(X509_check_ip_asc)]
+    pub fn check_ip_asc(&self, address: &str) -> Result<bool, ErrorStack> {
+        let c_str = CString::new(address).map_err(ErrorStack::internal_error)?;
+
+        unsafe { cvt_n(ffi::X509_check_ip_asc(self.as_ptr(), c_str.as_ptr(), 0)).map(|n| n == 1) }
+    }
+
+    /// Returns `true` if the certificate is valid for the DNS name `host`.
+    ///
+    /// Unlike [`check_host`](Self::check_host), the matching rules can be adjusted with `flags`.
+    /// BoringSSL only honors [`X509CheckFlags::NO_WILDCARDS`],
+    /// [`X509CheckFlags::NEVER_CHECK_SUBJECT`] and, with the `underscore-wildcards` feature,
+    /// `UNDERSCORE_WILDCARDS`. The other flags are zero and have no effect: partial wildcards
+    /// such as `f*.example.com` never match, as if `NO_PARTIAL_WILDCARDS` was always set, and
+    /// wildcards only ever match a single label.
+    #[corresponds(X509_check_host)]
+    pub fn check_host_with_flags(
+        &self,
+        host: &str,
+        flags: X509CheckFlags,
+    ) -> Result<bool, ErrorStack> {
         unsafe {
             cvt_n(ffi::X509_check_host(
                 self.as_ptr(),
                 host.as_ptr() as _,
                 host.len(),
-                0,
-                std::ptr::null_mut(),
+                flags.bits(),
+                ptr::null_mut(),
             ))
             .map(|n| n == 1)
         }
     }
 
-    #[corresponds(X509_check_ip_asc)]
-    pub fn check_ip_asc(&self, address: &str) -> Result<bool, ErrorStack> {
-        let c_str = CString::new(address).map_err(ErrorStack::internal_error)?;
+    /// Returns `true` if the certificate is valid for the email address `email`.
+    #[corresponds(X509_check_email)]
+    pub fn check_email(&self, email: &str, flags: X509CheckFlags) -> Result<bool, ErrorStack> {
+        unsafe {
+            cvt_n(ffi::X509_check_email(
+                self.as_ptr(),
+                email.as_ptr() as _,
+                email.len(),
+                flags.bits(),
+            ))
+            .map(|n| n == 1)
+        }
+    }
 
-        unsafe { cvt_n(ffi::X509_check_ip_asc(self.as_ptr(), c_str.as_ptr(), 0)).map(|n| n == 1) }
+    /// Returns `true` if the certificate is valid for the IP address `ip`.
+    #[corresponds(X509_check_ip)]
+    pub fn check_ip(&self, ip: IpAddr, flags: X509CheckFlags) -> Result<bool, ErrorStack> {
+        let octets = match ip {
+            IpAddr::V4(ip) => ip.octets().to_vec(),
+            IpAddr::V6(ip) => ip.octets().to_vec(),
+        };
+
+        unsafe {
+            cvt_n(ffi::X509_check_ip(
+                self.as_ptr(),
+                octets.as_ptr(),
+                octets.len(),
+                flags.bits(),
+            ))
+            .map(|n| n == 1)
+        }
     }
 
     to_pem! {
@@ -772,6 +932,71 @@ impl ToOwned for X509Ref {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for X509Ref {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::util::pem_serde::serialize(self.to_pem(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for X509 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        X509Ref::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for X509 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<X509, D::Error> {
+        crate::util::pem_serde::deserialize(
+            deserializer,
+            "a PEM-encoded certificate",
+            X509::from_pem,
+        )
+    }
+}
+
+#[cfg(feature = "rustls-pki-types")]
+impl TryFrom<&X509Ref> for rustls_pki_types::CertificateDer<'static> {
+    type Error = ErrorStack;
+
+    fn try_from(cert: &X509Ref) -> Result<Self, ErrorStack> {
+        Ok(cert.to_der()?.into())
+    }
+}
+
+#[cfg(feature = "rustls-pki-types")]
+impl TryFrom<&rustls_pki_types::CertificateDer<'_>> for X509 {
+    type Error = ErrorStack;
+
+    fn try_from(cert: &rustls_pki_types::CertificateDer<'_>) -> Result<Self, ErrorStack> {
+        X509::from_der(cert)
+    }
+}
+
+#[cfg(feature = "x509-cert")]
+impl TryFrom<&X509Ref> for x509_cert::Certificate {
+    type Error = ErrorStack;
+
+    fn try_from(cert: &X509Ref) -> Result<Self, ErrorStack> {
+        use x509_cert::der::Decode;
+
+        x509_cert::Certificate::from_der(&cert.to_der()?).map_err(ErrorStack::internal_error)
+    }
+}
+
+#[cfg(feature = "x509-cert")]
+impl TryFrom<&x509_cert::Certificate> for X509 {
+    type Error = ErrorStack;
+
+    fn try_from(cert: &x509_cert::Certificate) -> Result<Self, ErrorStack> {
+        use x509_cert::der::Encode;
+
+        X509::from_der(&cert.to_der().map_err(ErrorStack::internal_error)?)
+    }
+}
+
 impl X509 {
     /// Returns a new builder.
     pub fn builder() -> Result<X509Builder, ErrorStack> {
@@ -1103,6 +1328,81 @@ impl X509NameBuilder {
         }
     }
 
+    /// Add a field entry by object identifier with a specific type.
+    ///
+    /// This allows attribute types which have no NID or short name known to BoringSSL.
+    #[corresponds(X509_NAME_add_entry_by_OBJ)]
+    pub fn append_entry_by_object(
+        &mut self,
+        field: &Asn1ObjectRef,
+        value: &str,
+        ty: Asn1Type,
+    ) -> Result<(), ErrorStack> {
+        self.add_entry_by_object(field, value, ty, 0)
+    }
+
+    /// Add a field entry by NID with a specific type to the most recently added RDN.
+    ///
+    /// This creates a multi-valued RDN such as `O=Example+OU=Engineering`. If the name is empty, a
+    /// new RDN is started.
+    #[corresponds(X509_NAME_add_entry_by_NID)]
+    pub fn append_entry_to_last_rdn_by_nid(
+        &mut self,
+        field: Nid,
+        value: &str,
+        ty: Asn1Type,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(value.len() <= ValueLen::MAX as usize);
+            cvt(ffi::X509_NAME_add_entry_by_NID(
+                self.0.as_ptr(),
+                field.as_raw(),
+                ty.as_raw(),
+                value.as_ptr() as *mut _,
+                value.len() as ValueLen,
+                -1,
+                -1,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Add a field entry by object identifier with a specific type to the most recently added
+    /// RDN.
+    ///
+    /// See [`append_entry_to_last_rdn_by_nid`](Self::append_entry_to_last_rdn_by_nid).
+    #[corresponds(X509_NAME_add_entry_by_OBJ)]
+    pub fn append_entry_to_last_rdn_by_object(
+        &mut self,
+        field: &Asn1ObjectRef,
+        value: &str,
+        ty: Asn1Type,
+    ) -> Result<(), ErrorStack> {
+        self.add_entry_by_object(field, value, ty, -1)
+    }
+
+    fn add_entry_by_object(
+        &mut self,
+        field: &Asn1ObjectRef,
+        value: &str,
+        ty: Asn1Type,
+        set: c_int,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(value.len() <= ValueLen::MAX as usize);
+            cvt(ffi::X509_NAME_add_entry_by_OBJ(
+                self.0.as_ptr(),
+                field.as_ptr(),
+                ty.as_raw(),
+                value.as_ptr(),
+                value.len() as ValueLen,
+                -1,
+                set,
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Return an `X509Name`.
     #[must_use]
     pub fn build(self) -> X509Name {
@@ -1390,6 +1690,53 @@ foreign_type_and_impl_send_sync! {
     pub struct X509Req;
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for X509ReqRef {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::util::pem_serde::serialize(self.to_pem(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for X509Req {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        X509ReqRef::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for X509Req {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<X509Req, D::Error> {
+        crate::util::pem_serde::deserialize(
+            deserializer,
+            "a PEM-encoded certificate request",
+            X509Req::from_pem,
+        )
+    }
+}
+
+#[cfg(feature = "x509-cert")]
+impl TryFrom<&X509ReqRef> for x509_cert::request::CertReq {
+    type Error = ErrorStack;
+
+    fn try_from(req: &X509ReqRef) -> Result<Self, ErrorStack> {
+        use x509_cert::der::Decode;
+
+        x509_cert::request::CertReq::from_der(&req.to_der()?).map_err(ErrorStack::internal_error)
+    }
+}
+
+#[cfg(feature = "x509-cert")]
+impl TryFrom<&x509_cert::request::CertReq> for X509Req {
+    type Error = ErrorStack;
+
+    fn try_from(req: &x509_cert::request::CertReq) -> Result<Self, ErrorStack> {
+        use x509_cert::der::Encode;
+
+        X509Req::from_der(&req.to_der().map_err(ErrorStack::internal_error)?)
+    }
+}
+
 impl X509Req {
     /// A builder for `X509Req`.
     pub fn builder() -> Result<X509ReqBuilder, ErrorStack> {
@@ -1481,6 +1828,112 @@ impl X509ReqRef {
     }
 }
 
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_CRL;
+    fn drop = ffi::X509_CRL_free;
+
+    /// An `X509` certificate revocation list.
+    pub struct X509Crl;
+}
+
+impl X509Crl {
+    from_pem! {
+        /// Deserializes a PEM-encoded certificate revocation list.
+        ///
+        /// The input should have a header of `-----BEGIN X509 CRL-----`.
+        #[corresponds(PEM_read_bio_X509_CRL)]
+        from_pem,
+        X509Crl,
+        ffi::PEM_read_bio_X509_CRL
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded certificate revocation list.
+        #[corresponds(d2i_X509_CRL)]
+        from_der,
+        X509Crl,
+        ffi::d2i_X509_CRL,
+        ::libc::c_long
+    }
+}
+
+impl X509CrlRef {
+    to_pem! {
+        /// Serializes the certificate revocation list to a PEM-encoded structure.
+        ///
+        /// The output will have a header of `-----BEGIN X509 CRL-----`.
+        #[corresponds(PEM_write_bio_X509_CRL)]
+        to_pem,
+        ffi::PEM_write_bio_X509_CRL
+    }
+
+    to_der! {
+        /// Serializes the certificate revocation list to a DER-encoded structure.
+        #[corresponds(i2d_X509_CRL)]
+        to_der,
+        ffi::i2d_X509_CRL
+    }
+
+    /// Returns the issuer name of the certificate revocation list.
+    #[corresponds(X509_CRL_get_issuer)]
+    #[must_use]
+    pub fn issuer_name(&self) -> &X509NameRef {
+        unsafe {
+            let name = ffi::X509_CRL_get_issuer(self.as_ptr());
+            X509NameRef::from_const_ptr_opt(name).expect("issuer name must not be null")
+        }
+    }
+
+    /// Returns the time at which this revocation list was issued.
+    #[corresponds(X509_CRL_get0_lastUpdate)]
+    #[must_use]
+    pub fn last_update(&self) -> &Asn1TimeRef {
+        unsafe {
+            let date = ffi::X509_CRL_get0_lastUpdate(self.as_ptr());
+            Asn1TimeRef::from_const_ptr_opt(date).expect("last update must not be null")
+        }
+    }
+
+    /// Returns the time by which a newer revocation list will be issued, if present.
+    #[corresponds(X509_CRL_get0_nextUpdate)]
+    #[must_use]
+    pub fn next_update(&self) -> Option<&Asn1TimeRef> {
+        unsafe { Asn1TimeRef::from_const_ptr_opt(ffi::X509_CRL_get0_nextUpdate(self.as_ptr())) }
+    }
+
+    /// Check if the certificate revocation list is signed using the given public key.
+    ///
+    /// Returns `true` if verification succeeds.
+    #[corresponds(X509_CRL_verify)]
+    pub fn verify<T>(&self, key: &PKeyRef<T>) -> Result<bool, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe { cvt_n(ffi::X509_CRL_verify(self.as_ptr(), key.as_ptr())).map(|n| n != 0) }
+    }
+}
+
+impl ToOwned for X509CrlRef {
+    type Owned = X509Crl;
+
+    fn to_owned(&self) -> X509Crl {
+        unsafe {
+            ffi::X509_CRL_up_ref(self.as_ptr());
+            X509Crl::from_ptr(self.as_ptr())
+        }
+    }
+}
+
+impl Clone for X509Crl {
+    fn clone(&self) -> X509Crl {
+        X509CrlRef::to_owned(self)
+    }
+}
+
+impl Stackable for X509Crl {
+    type StackType = ffi::stack_st_X509_CRL;
+}
+
 /// The result of peer certificate verification.
 pub type X509VerifyResult = Result<(), X509VerifyError>;
 
@@ -1533,6 +1986,14 @@ impl X509VerifyError {
     #[allow(clippy::trivially_copy_pass_by_ref)]
     #[must_use]
     pub fn error_string(&self) -> &'static str {
+        if RESERVED_CODES.contains(&self.0) {
+            let index = (self.0 - RESERVED_CODES.start) as usize;
+            return RESERVED_ERROR_STRINGS
+                .get(index)
+                .copied()
+                .unwrap_or("unknown certificate verification error");
+        }
+
         ffi::init();
 
         unsafe {
@@ -1540,6 +2001,70 @@ impl X509VerifyError {
             CStr::from_ptr(s).to_str().unwrap_or_default()
         }
     }
+
+    /// Returns `true` if the certificate was revoked, or if revocation could not be
+    /// checked because of a missing or invalid certificate revocation list.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    #[must_use]
+    pub fn is_crl_error(&self) -> bool {
+        matches!(
+            *self,
+            Self::UNABLE_TO_GET_CRL
+                | Self::UNABLE_TO_DECRYPT_CRL_SIGNATURE
+                | Self::CRL_SIGNATURE_FAILURE
+                | Self::CRL_NOT_YET_VALID
+                | Self::CRL_HAS_EXPIRED
+                | Self::ERROR_IN_CRL_LAST_UPDATE_FIELD
+                | Self::ERROR_IN_CRL_NEXT_UPDATE_FIELD
+                | Self::CERT_REVOKED
+                | Self::UNABLE_TO_GET_CRL_ISSUER
+                | Self::KEYUSAGE_NO_CRL_SIGN
+                | Self::UNHANDLED_CRITICAL_CRL_EXTENSION
+                | Self::DIFFERENT_CRL_SCOPE
+                | Self::CRL_PATH_VALIDATION_ERROR
+        )
+    }
+}
+
+/// The error codes reserved for the errors this crate reports itself.
+///
+/// They travel through BoringSSL as verification results, so they share the number space of the
+/// `X509_V_ERR_*` codes. BoringSSL numbers those from 1 upwards and is far from this range, which
+/// a test checks it does not define.
+const RESERVED_CODES: std::ops::Range<c_int> = 0x1000..0x1100;
+
+/// The descriptions of the reserved codes in use, starting at `RESERVED_CODES.start`.
+const RESERVED_ERROR_STRINGS: [&str; 3] = [
+    "certificate public key did not match any pin",
+    "certificate transparency policy not met",
+    "valid stapled OCSP response required",
+];
+
+impl X509VerifyError {
+    /// No certificate in the verified chain matched a pinned public key.
+    ///
+    /// This is not a BoringSSL error code. It is reported by connections configured with
+    /// [`ConnectConfiguration::set_pin_spki_sha256`](crate::ssl::ConnectConfiguration::set_pin_spki_sha256).
+    pub const SPKI_PIN_MISMATCH: Self = Self(RESERVED_CODES.start);
+
+    /// The certificate did not satisfy the connector's Certificate Transparency policy.
+    ///
+    /// This is not a BoringSSL error code. It is reported by connectors configured with
+    /// [`SslConnectorBuilder::set_ct_verifier`](crate::ssl::SslConnectorBuilder::set_ct_verifier).
+    pub const CT_POLICY_NOT_MET: Self = Self(RESERVED_CODES.start + 1);
+
+    /// The certificate requires OCSP stapling, but the server stapled no valid OCSP response.
+    ///
+    /// This is not a BoringSSL error code. It is reported by connectors configured with
+    /// [`SslConnectorBuilder::set_enforce_must_staple`](crate::ssl::SslConnectorBuilder::set_enforce_must_staple).
+    pub const MUST_STAPLE_NOT_MET: Self = Self(RESERVED_CODES.start + 2);
+
+    /// Returns `true` if this error is reported by this crate rather than by BoringSSL.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    #[must_use]
+    pub fn is_reserved(&self) -> bool {
+        RESERVED_CODES.contains(&self.0)
+    }
 }
 
 #[allow(missing_docs)] // no need to document the constants
@@ -1622,6 +2147,135 @@ impl X509VerifyError {
         Self(ffi::X509_V_ERR_NAME_CONSTRAINTS_WITHOUT_SANS);
 }
 
+/// Verifies `leaf` against the trusted certificates in `store`, outside of a TLS handshake.
+///
+/// `intermediates` are untrusted certificates that may be used to build the chain. If `params`
+/// is given, it is applied on top of the verification parameters of `store`.
+///
+/// On success, the returned [`VerifiedChain`] holds the chain that was built, from `leaf` up
+/// to the trust anchor.
+pub fn verify_chain(
+    leaf: &X509Ref,
+    intermediates: &StackRef<X509>,
+    store: &store::X509StoreRef,
+    params: Option<&X509VerifyParamRef>,
+) -> Result<VerifiedChain, VerifyError> {
+    let mut context = X509StoreContext::new()?;
+
+    context.init(store, leaf, intermediates, |context| {
+        if let Some(params) = params {
+            context.verify_param_mut().copy_from(params)?;
+        }
+
+        if context.verify_cert()? {
+            let mut chain = Stack::new()?;
+            for cert in context.chain().into_iter().flatten() {
+                chain.push(cert.to_owned())?;
+            }
+
+            return Ok(Ok(VerifiedChain { chain }));
+        }
+
+        Ok(Err(VerifyError::Invalid {
+            error: context
+                .verify_result()
+                .err()
+                .unwrap_or(X509VerifyError::UNSPECIFIED),
+            depth: context.error_depth(),
+            cert: context.current_cert().map(ToOwned::to_owned),
+        }))
+    })?
+}
+
+/// A certificate chain that was successfully verified by [`verify_chain`].
+#[derive(Debug)]
+pub struct VerifiedChain {
+    chain: Stack<X509>,
+}
+
+impl VerifiedChain {
+    /// Returns the certificates of the chain, starting with the leaf and ending with the trust
+    /// anchor.
+    #[must_use]
+    pub fn certs(&self) -> &StackRef<X509> {
+        &self.chain
+    }
+
+    /// Returns the leaf certificate.
+    #[must_use]
+    pub fn leaf(&self) -> &X509Ref {
+        &self.chain[0]
+    }
+
+    /// Returns the trust anchor the chain was verified against.
+    #[must_use]
+    pub fn root(&self) -> &X509Ref {
+        &self.chain[self.chain.len() - 1]
+    }
+
+    /// Consumes the chain, returning its certificates.
+    #[must_use]
+    pub fn into_certs(self) -> Stack<X509> {
+        self.chain
+    }
+}
+
+/// An error returned by [`verify_chain`].
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The certificate chain was rejected.
+    Invalid {
+        /// The reason the chain was rejected.
+        error: X509VerifyError,
+        /// The depth in the chain at which the error occurred, where zero is the leaf.
+        depth: u32,
+        /// The certificate which caused the error, if any.
+        cert: Option<X509>,
+    },
+    /// The verification could not be performed.
+    Internal(ErrorStack),
+}
+
+impl VerifyError {
+    /// Returns the verification error, if the chain was rejected.
+    #[must_use]
+    pub fn verify_error(&self) -> Option<X509VerifyError> {
+        match self {
+            Self::Invalid { error, .. } => Some(*error),
+            Self::Internal(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid { error, depth, .. } => {
+                write!(
+                    fmt,
+                    "certificate verification failed at depth {depth}: {error}"
+                )
+            }
+            Self::Internal(e) => write!(fmt, "certificate verification could not run: {e}"),
+        }
+    }
+}
+
+impl Error for VerifyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Invalid { error, .. } => Some(error),
+            Self::Internal(e) => Some(e),
+        }
+    }
+}
+
+impl From<ErrorStack> for VerifyError {
+    fn from(e: ErrorStack) -> VerifyError {
+        VerifyError::Internal(e)
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::GENERAL_NAME;
     fn drop = ffi::GENERAL_NAME_free;
@@ -1727,6 +2381,67 @@ impl GeneralNameRef {
             Some(Asn1BitStringRef::from_ptr((*self.as_ptr()).d.ip).as_slice())
         }
     }
+
+    /// Returns the contents of this `GeneralName` as an address if it is a 4 or 16 byte
+    /// `iPAddress`.
+    #[must_use]
+    pub fn ip_addr(&self) -> Option<IpAddr> {
+        let bytes = self.ipaddress()?;
+        if let Ok(v4) = <[u8; 4]>::try_from(bytes) {
+            Some(IpAddr::from(v4))
+        } else {
+            <[u8; 16]>::try_from(bytes).ok().map(IpAddr::from)
+        }
+    }
+
+    /// Returns the contents of this `GeneralName` if it is a `directoryName`.
+    #[must_use]
+    pub fn directory_name(&self) -> Option<&X509NameRef> {
+        unsafe {
+            if (*self.as_ptr()).type_ != ffi::GEN_DIRNAME {
+                return None;
+            }
+
+            X509NameRef::from_const_ptr_opt((*self.as_ptr()).d.directoryName)
+        }
+    }
+
+    /// Returns a typed view of the contents of this `GeneralName`.
+    #[must_use]
+    pub fn value(&self) -> GeneralNameValue<'_> {
+        if let Some(email) = self.email() {
+            GeneralNameValue::Email(email)
+        } else if let Some(dnsname) = self.dnsname() {
+            GeneralNameValue::Dns(dnsname)
+        } else if let Some(uri) = self.uri() {
+            GeneralNameValue::Uri(uri)
+        } else if let Some(ip) = self.ip_addr() {
+            GeneralNameValue::Ip(ip)
+        } else if let Some(name) = self.directory_name() {
+            GeneralNameValue::DirectoryName(name)
+        } else {
+            GeneralNameValue::Other
+        }
+    }
+}
+
+/// The contents of a [`GeneralName`], as returned by [`GeneralNameRef::value`].
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum GeneralNameValue<'a> {
+    /// An `rfc822Name`.
+    Email(&'a str),
+    /// A `dNSName`.
+    Dns(&'a str),
+    /// A `uniformResourceIdentifier`.
+    Uri(&'a str),
+    /// An `iPAddress` holding an IPv4 or IPv6 address.
+    Ip(IpAddr),
+    /// A `directoryName`.
+    DirectoryName(&'a X509NameRef),
+    /// Any other kind of name, including strings that are not valid ASCII and
+    /// `iPAddress` entries holding a netmask.
+    Other,
 }
 
 impl fmt::Debug for GeneralNameRef {
@@ -1750,6 +2465,250 @@ impl Stackable for GeneralName {
     type StackType = ffi::stack_st_GENERAL_NAME;
 }
 
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::ACCESS_DESCRIPTION;
+    fn drop = ffi::ACCESS_DESCRIPTION_free;
+
+    /// An entry of an authority or subject information access extension.
+    pub struct AccessDescription;
+}
+
+impl AccessDescriptionRef {
+    /// Returns the OID of the access method, such as [`Nid::AD_OCSP`] or
+    /// [`Nid::AD_CA_ISSUERS`].
+    #[must_use]
+    pub fn method(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_ptr((*self.as_ptr()).method) }
+    }
+
+    /// Returns the location the information can be fetched from.
+    #[must_use]
+    pub fn location(&self) -> &GeneralNameRef {
+        unsafe { GeneralNameRef::from_ptr((*self.as_ptr()).location) }
+    }
+
+    /// Returns the location URI if this entry points to an OCSP responder.
+    #[must_use]
+    pub fn ocsp_uri(&self) -> Option<&str> {
+        self.uri_for(Nid::AD_OCSP)
+    }
+
+    /// Returns the location URI if this entry points to the certificate of the issuer.
+    #[must_use]
+    pub fn ca_issuers_uri(&self) -> Option<&str> {
+        self.uri_for(Nid::AD_CA_ISSUERS)
+    }
+
+    fn uri_for(&self, method: Nid) -> Option<&str> {
+        if self.method().nid() == method {
+            self.location().uri()
+        } else {
+            None
+        }
+    }
+}
+
+impl Stackable for AccessDescription {
+    type StackType = ffi::stack_st_ACCESS_DESCRIPTION;
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::DIST_POINT;
+    fn drop = ffi::DIST_POINT_free;
+
+    /// An entry of a CRL distribution points extension.
+    pub struct DistPoint;
+}
+
+impl DistPointRef {
+    /// Returns the name of this distribution point, if present.
+    #[must_use]
+    pub fn distpoint(&self) -> Option<&DistPointNameRef> {
+        unsafe { DistPointNameRef::from_const_ptr_opt((*self.as_ptr()).distpoint) }
+    }
+
+    /// Returns the names of the issuer of the CRL, if it differs from the issuer of the
+    /// certificate.
+    #[must_use]
+    pub fn crl_issuer(&self) -> Option<&StackRef<GeneralName>> {
+        unsafe { StackRef::from_const_ptr_opt((*self.as_ptr()).CRLissuer) }
+    }
+
+    /// Returns an iterator over the URIs the CRL can be fetched from.
+    pub fn uris(&self) -> impl Iterator<Item = &str> {
+        self.distpoint()
+            .and_then(DistPointNameRef::fullname)
+            .into_iter()
+            .flat_map(|names| names.iter().filter_map(GeneralNameRef::uri))
+    }
+}
+
+impl Stackable for DistPoint {
+    type StackType = ffi::stack_st_DIST_POINT;
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::DIST_POINT_NAME;
+    fn drop = ffi::DIST_POINT_NAME_free;
+
+    /// The name of a CRL distribution point.
+    pub struct DistPointName;
+}
+
+impl DistPointNameRef {
+    /// Returns the full names of the distribution point, if it is not specified relative
+    /// to the CRL issuer.
+    #[must_use]
+    pub fn fullname(&self) -> Option<&StackRef<GeneralName>> {
+        unsafe {
+            if (*self.as_ptr()).type_ != 0 {
+                return None;
+            }
+
+            StackRef::from_const_ptr_opt((*self.as_ptr()).name.fullname)
+        }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::NAME_CONSTRAINTS;
+    fn drop = ffi::NAME_CONSTRAINTS_free;
+
+    /// The name constraints extension of a CA certificate.
+    pub struct NameConstraints;
+}
+
+impl NameConstraintsRef {
+    /// Returns the subtrees names in issued certificates must fall within, if any.
+    #[must_use]
+    pub fn permitted_subtrees(&self) -> Option<&StackRef<GeneralSubtree>> {
+        unsafe { StackRef::from_const_ptr_opt((*self.as_ptr()).permittedSubtrees) }
+    }
+
+    /// Returns the subtrees names in issued certificates must not fall within, if any.
+    #[must_use]
+    pub fn excluded_subtrees(&self) -> Option<&StackRef<GeneralSubtree>> {
+        unsafe { StackRef::from_const_ptr_opt((*self.as_ptr()).excludedSubtrees) }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::GENERAL_SUBTREE;
+    fn drop = ffi::GENERAL_SUBTREE_free;
+
+    /// A subtree of names within a name constraints extension.
+    pub struct GeneralSubtree;
+}
+
+impl GeneralSubtreeRef {
+    /// Returns the name at the root of this subtree.
+    ///
+    /// For `iPAddress` constraints, [`GeneralNameRef::ipaddress`] returns the address
+    /// followed by the netmask.
+    #[must_use]
+    pub fn base(&self) -> &GeneralNameRef {
+        unsafe { GeneralNameRef::from_ptr((*self.as_ptr()).base) }
+    }
+
+    /// Returns the minimum distance of this subtree, if present.
+    #[must_use]
+    pub fn minimum(&self) -> Option<&Asn1IntegerRef> {
+        unsafe { Asn1IntegerRef::from_const_ptr_opt((*self.as_ptr()).minimum) }
+    }
+
+    /// Returns the maximum distance of this subtree, if present.
+    #[must_use]
+    pub fn maximum(&self) -> Option<&Asn1IntegerRef> {
+        unsafe { Asn1IntegerRef::from_const_ptr_opt((*self.as_ptr()).maximum) }
+    }
+}
+
+impl Stackable for GeneralSubtree {
+    type StackType = ffi::stack_st_GENERAL_SUBTREE;
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::POLICYINFO;
+    fn drop = ffi::POLICYINFO_free;
+
+    /// A single policy within a certificate policies extension.
+    pub struct PolicyInfo;
+}
+
+impl PolicyInfoRef {
+    /// Returns the OID of this policy.
+    #[must_use]
+    pub fn policy_id(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_ptr((*self.as_ptr()).policyid) }
+    }
+
+    /// Returns the qualifiers attached to this policy, if any.
+    #[must_use]
+    pub fn qualifiers(&self) -> Option<&StackRef<PolicyQualifierInfo>> {
+        unsafe { StackRef::from_const_ptr_opt((*self.as_ptr()).qualifiers) }
+    }
+}
+
+impl Stackable for PolicyInfo {
+    type StackType = ffi::stack_st_POLICYINFO;
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::POLICYQUALINFO;
+    fn drop = ffi::POLICYQUALINFO_free;
+
+    /// A qualifier attached to a certificate policy.
+    pub struct PolicyQualifierInfo;
+}
+
+impl PolicyQualifierInfoRef {
+    /// Returns the OID identifying the kind of this qualifier.
+    #[must_use]
+    pub fn qualifier_id(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_ptr((*self.as_ptr()).pqualid) }
+    }
+
+    /// Returns the CPS pointer URI if this is an `id-qt-cps` qualifier.
+    ///
+    /// The URI is an IA5String, so this is `None` if it is not ASCII.
+    #[must_use]
+    pub fn cps_uri(&self) -> Option<&str> {
+        let uri = unsafe {
+            if self.qualifier_id().nid() != Nid::ID_QT_CPS {
+                return None;
+            }
+
+            Asn1StringRef::from_const_ptr_opt((*self.as_ptr()).d.cpsuri)?
+        };
+
+        let uri = uri.as_slice();
+        if !uri.is_ascii() {
+            return None;
+        }
+        str::from_utf8(uri).ok()
+    }
+
+    /// Returns the explicit notice text if this is an `id-qt-unotice` qualifier that has one.
+    #[must_use]
+    pub fn user_notice_text(&self) -> Option<&Asn1StringRef> {
+        unsafe {
+            if self.qualifier_id().nid() != Nid::ID_QT_UNOTICE {
+                return None;
+            }
+
+            let notice = (*self.as_ptr()).d.usernotice;
+            if notice.is_null() {
+                return None;
+            }
+            Asn1StringRef::from_const_ptr_opt((*notice).exptext)
+        }
+    }
+}
+
+impl Stackable for PolicyQualifierInfo {
+    type StackType = ffi::stack_st_POLICYQUALINFO;
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_ALGOR;
     fn drop = ffi::X509_ALGOR_free;
@@ -1769,6 +2728,13 @@ impl X509AlgorithmRef {
             Asn1ObjectRef::from_ptr(oid as *mut _)
         }
     }
+
+    to_der! {
+        /// Serializes the algorithm and its parameters into a DER-encoded `AlgorithmIdentifier`.
+        #[corresponds(i2d_X509_ALGOR)]
+        to_der,
+        ffi::i2d_X509_ALGOR
+    }
 }
 
 foreign_type_and_impl_send_sync! {
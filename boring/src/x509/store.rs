@@ -44,11 +44,15 @@ use crate::error::ErrorStack;
 use crate::ffi;
 use crate::stack::StackRef;
 use crate::x509::verify::{X509VerifyFlags, X509VerifyParamRef};
-use crate::x509::{X509Object, X509};
+use crate::x509::{X509CrlRef, X509Object, X509};
 use crate::{cvt, cvt_p};
 use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::c_int;
 use openssl_macros::corresponds;
+use std::ffi::CString;
 use std::mem;
+use std::path::Path;
+use std::ptr;
 
 foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_STORE;
@@ -87,6 +91,15 @@ impl X509StoreBuilderRef {
         unsafe { cvt(ffi::X509_STORE_add_cert(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
     }
 
+    /// Adds a certificate revocation list to the certificate store.
+    ///
+    /// Revocation lists are only consulted when [`X509VerifyFlags::CRL_CHECK`] or
+    /// [`X509VerifyFlags::CRL_CHECK_ALL`] is set.
+    #[corresponds(X509_STORE_add_crl)]
+    pub fn add_crl(&mut self, crl: &X509CrlRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_add_crl(self.as_ptr(), crl.as_ptr())).map(|_| ()) }
+    }
+
     /// Load certificates from their default locations.
     ///
     /// These locations are read from the `SSL_CERT_FILE` and `SSL_CERT_DIR`
@@ -97,6 +110,31 @@ impl X509StoreBuilderRef {
         unsafe { cvt(ffi::X509_STORE_set_default_paths(self.as_ptr())).map(|_| ()) }
     }
 
+    /// Adds a lookup that loads certificates and CRLs on demand from a hashed directory.
+    ///
+    /// The directory is expected to be in the format produced by `c_rehash`, where each PEM file
+    /// is named after the hash of its subject (or issuer, for CRLs), such as `9d66eef0.0` or
+    /// `9d66eef0.r0`. Files are only read when verification needs them.
+    #[corresponds(X509_LOOKUP_hash_dir)]
+    pub fn add_lookup_hash_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<(), ErrorStack> {
+        let dir = CString::new(dir.as_ref().as_os_str().as_encoded_bytes())
+            .map_err(ErrorStack::internal_error)?;
+        unsafe {
+            let lookup = cvt_p(ffi::X509_STORE_add_lookup(
+                self.as_ptr(),
+                ffi::X509_LOOKUP_hash_dir(),
+            ))?;
+            cvt(ffi::X509_LOOKUP_ctrl(
+                lookup,
+                ffi::X509_L_ADD_DIR as _,
+                dir.as_ptr(),
+                ffi::X509_FILETYPE_PEM as _,
+                ptr::null_mut(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Sets certificate chain validation related flags.
     #[corresponds(X509_STORE_set_flags)]
     pub fn set_flags(&mut self, flags: X509VerifyFlags) {
@@ -105,6 +143,17 @@ impl X509StoreBuilderRef {
         }
     }
 
+    /// Sets the maximum depth of verified chains, where the leaf is at depth 0, its issuer at
+    /// depth 1, and so on.
+    ///
+    /// Chains which cannot be completed within this depth fail to verify.
+    #[corresponds(X509_STORE_set_depth)]
+    pub fn set_depth(&mut self, depth: u32) {
+        unsafe {
+            cvt(ffi::X509_STORE_set_depth(self.as_ptr(), depth as c_int)).unwrap();
+        }
+    }
+
     /// Returns a mutable reference to the X509 verification configuration.
     #[corresponds(X509_STORE_get0_param)]
     pub fn verify_param_mut(&mut self) -> &mut X509VerifyParamRef {
@@ -1,7 +1,11 @@
 use hex::{self, FromHex};
+use libc::c_long;
+use std::ffi::CStr;
+use std::net::IpAddr;
 
-use crate::asn1::Asn1Time;
+use crate::asn1::{Asn1Object, Asn1Time, Asn1Type};
 use crate::bn::{BigNum, MsbOption};
+use crate::ffi;
 use crate::hash::MessageDigest;
 use crate::nid::Nid;
 use crate::pkey::{PKey, Private};
@@ -12,7 +16,13 @@ use crate::x509::extension::{
     SubjectKeyIdentifier,
 };
 use crate::x509::store::X509StoreBuilder;
-use crate::x509::{X509Extension, X509Name, X509Req, X509StoreContext, X509};
+use crate::x509::verify::{
+    X509CheckFlags, X509PurposeId, X509VerifyFlags, X509VerifyParam, X509VerifyParamRef,
+};
+use crate::x509::{
+    verify_chain, GeneralNameValue, VerifyError, X509Crl, X509Extension, X509Name, X509Req,
+    X509StoreContext, X509VerifyError, RESERVED_CODES, X509,
+};
 
 mod trusted_first;
 
@@ -33,6 +43,22 @@ fn test_cert_loading() {
     assert_eq!(hash_vec, &*fingerprint);
 }
 
+#[test]
+fn test_spki_sha256() {
+    let cert = include_bytes!("../../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+
+    let spki = cert.public_key_info_to_der().unwrap();
+    assert_eq!(
+        spki,
+        cert.public_key().unwrap().public_key_to_der().unwrap()
+    );
+    assert_eq!(
+        cert.spki_sha256_base64().unwrap(),
+        "VHQAbNl67nmkZJNESeTKvTxb5bQmd1maWnMKG/tjcAY="
+    );
+}
+
 #[test]
 fn test_debug() {
     let cert = include_bytes!("../../../test/cert.pem");
@@ -122,6 +148,46 @@ fn test_nameref_iterator() {
     }
 }
 
+#[test]
+fn test_name_builder_multi_valued_rdn() {
+    let custom = Asn1Object::from_str("1.2.3.4").unwrap();
+
+    let mut builder = X509Name::builder().unwrap();
+    builder
+        .append_entry_by_nid_with_type(Nid::COMMONNAME, "test", Asn1Type::UTF8STRING)
+        .unwrap();
+    builder
+        .append_entry_by_object(&custom, "custom", Asn1Type::UTF8STRING)
+        .unwrap();
+    builder
+        .append_entry_to_last_rdn_by_nid(
+            Nid::ORGANIZATIONNAME,
+            "Example Org",
+            Asn1Type::PRINTABLESTRING,
+        )
+        .unwrap();
+    let name = builder.build();
+
+    let mut entries = name.entries();
+    let cn = entries.next().unwrap();
+    assert_eq!(cn.object().nid(), Nid::COMMONNAME);
+    assert_eq!(cn.data().as_slice(), b"test");
+    let entry = entries.next().unwrap();
+    assert_eq!(entry.object().to_string(), "1.2.3.4");
+    assert_eq!(entry.data().as_slice(), b"custom");
+    let org = entries.next().unwrap();
+    assert_eq!(org.object().nid(), Nid::ORGANIZATIONNAME);
+    assert_eq!(org.data().as_slice(), b"Example Org");
+    assert!(entries.next().is_none());
+
+    // CN is its own RDN, the custom attribute and O share a second one.
+    assert_eq!(
+        hex::encode(name.to_der().unwrap()),
+        "3034310d300b06035504030c04746573743123300d06032a03040c06637573746f6d30120603\
+         55040a130b4578616d706c65204f7267"
+    );
+}
+
 #[test]
 fn test_nid_uid_value() {
     let cert = include_bytes!("../../../test/nid_uid_test_cert.pem");
@@ -528,3 +594,494 @@ fn test_check_ip_asc() {
     assert!(cert.check_ip_asc("0:0:0:0:0:0:0:1").unwrap());
     assert!(!cert.check_ip_asc("0:0:0:0:0:0:0:2").unwrap());
 }
+
+#[test]
+fn test_check_with_flags() {
+    let cert = include_bytes!("../../../test/alt_name_cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+
+    assert!(cert
+        .check_host_with_flags("example.com", X509CheckFlags::empty())
+        .unwrap());
+    assert!(!cert
+        .check_host_with_flags("www.example.com", X509CheckFlags::empty())
+        .unwrap());
+
+    assert!(cert
+        .check_email("test@example.com", X509CheckFlags::empty())
+        .unwrap());
+    assert!(!cert
+        .check_email("other@example.com", X509CheckFlags::empty())
+        .unwrap());
+
+    let ip: IpAddr = "127.0.0.1".parse().unwrap();
+    assert!(cert.check_ip(ip, X509CheckFlags::empty()).unwrap());
+    let ip: IpAddr = "::1".parse().unwrap();
+    assert!(cert.check_ip(ip, X509CheckFlags::empty()).unwrap());
+    let ip: IpAddr = "::2".parse().unwrap();
+    assert!(!cert.check_ip(ip, X509CheckFlags::empty()).unwrap());
+
+    // Without a DNS name, the subject's common name is used unless disabled.
+    let cert = include_bytes!("../../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    assert!(cert
+        .check_host_with_flags("foobar.com", X509CheckFlags::empty())
+        .unwrap());
+    assert!(!cert
+        .check_host_with_flags("foobar.com", X509CheckFlags::NEVER_CHECK_SUBJECT)
+        .unwrap());
+
+    let cert = include_bytes!("../../../test/cert-wildcard.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    assert!(cert.check_host("www.foobar.com").unwrap());
+    assert!(!cert
+        .check_host_with_flags("www.foobar.com", X509CheckFlags::NO_WILDCARDS)
+        .unwrap());
+}
+
+fn verify_with_param(
+    configure: impl FnOnce(&mut X509VerifyParamRef),
+) -> Result<(), X509VerifyError> {
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+    let ca = X509::from_pem(include_bytes!("../../../test/root-ca.pem")).unwrap();
+    let chain = Stack::new().unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(ca).unwrap();
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    context
+        .init(&store, &cert, &chain, |c| {
+            // 2020-01-01, while the test certificates are valid.
+            c.verify_param_mut().set_time(1577836800);
+            configure(c.verify_param_mut());
+            c.verify_cert()?;
+            Ok(c.verify_result())
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_verify_param_no_check_time() {
+    // 2100-01-01, long after the test certificates expire.
+    const FUTURE: libc::time_t = 4102444800;
+
+    assert_eq!(
+        verify_with_param(|param| param.set_time(FUTURE)),
+        Err(X509VerifyError::CERT_HAS_EXPIRED)
+    );
+    assert_eq!(
+        verify_with_param(|param| {
+            param.set_time(FUTURE);
+            param.set_flags(X509VerifyFlags::NO_CHECK_TIME);
+        }),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_verify_param_host_and_ip() {
+    assert_eq!(
+        verify_with_param(|param| param.set_host("foobar.com").unwrap()),
+        Ok(())
+    );
+    assert_eq!(
+        verify_with_param(|param| param.set_host("example.com").unwrap()),
+        Err(X509VerifyError::HOSTNAME_MISMATCH)
+    );
+    assert_eq!(
+        verify_with_param(|param| {
+            param.set_host("example.com").unwrap();
+            param.add_host("foobar.com").unwrap();
+        }),
+        Ok(())
+    );
+    assert_eq!(
+        verify_with_param(|param| param.set_ip_asc("127.0.0.1").unwrap()),
+        Err(X509VerifyError::IP_ADDRESS_MISMATCH)
+    );
+}
+
+#[test]
+fn test_verify_param_accessors() {
+    let mut param = X509VerifyParam::new().unwrap();
+    assert_eq!(param.depth(), -1);
+    param.set_depth(5);
+    assert_eq!(param.depth(), 5);
+
+    param.set_purpose(X509PurposeId::SSL_SERVER).unwrap();
+    assert!(param.set_purpose(X509PurposeId::from_raw(-42)).is_err());
+    assert!(param.set_ip_asc("not an ip").is_err());
+
+    assert_eq!(
+        verify_with_param(|param| param.set_purpose(X509PurposeId::SSL_SERVER).unwrap()),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_store_lookup_hash_dir() {
+    let ca = X509::from_pem(include_bytes!("../../../test/root-ca.pem")).unwrap();
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("boring-hash-dir-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join(format!("{:08x}.0", ca.subject_name_hash())),
+        ca.to_pem().unwrap(),
+    )
+    .unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_lookup_hash_dir(&dir).unwrap();
+    store_bldr.verify_param_mut().set_time(1577836800);
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    let verified = context
+        .init(&store, &cert, &Stack::new().unwrap(), |c| c.verify_cert())
+        .unwrap();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(verified);
+}
+
+fn verify_with_crl(crl: Option<&[u8]>, flags: X509VerifyFlags) -> Result<(), X509VerifyError> {
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+    let ca = X509::from_pem(include_bytes!("../../../test/root-ca.pem")).unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(ca).unwrap();
+    if let Some(crl) = crl {
+        store_bldr
+            .add_crl(&X509Crl::from_pem(crl).unwrap())
+            .unwrap();
+    }
+    store_bldr.set_flags(flags);
+    store_bldr.verify_param_mut().set_time(1577836800);
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    context
+        .init(&store, &cert, &Stack::new().unwrap(), |c| {
+            c.verify_cert()?;
+            Ok(c.verify_result())
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_crl_loading() {
+    let crl = X509Crl::from_pem(include_bytes!("../../../test/crl-revoked.pem")).unwrap();
+    let ca = X509::from_pem(include_bytes!("../../../test/root-ca.pem")).unwrap();
+
+    assert!(crl.verify(&ca.public_key().unwrap()).unwrap());
+    assert_eq!(
+        crl.issuer_name().to_der().unwrap(),
+        ca.subject_name().to_der().unwrap()
+    );
+    assert!(crl.last_update() < crl.next_update().unwrap());
+
+    let der = crl.to_der().unwrap();
+    assert_eq!(X509Crl::from_der(&der).unwrap().to_der().unwrap(), der);
+}
+
+#[test]
+fn test_crl_check() {
+    let revoked = &include_bytes!("../../../test/crl-revoked.pem")[..];
+    let empty = &include_bytes!("../../../test/crl-empty.pem")[..];
+
+    assert_eq!(
+        verify_with_crl(Some(revoked), X509VerifyFlags::empty()),
+        Ok(())
+    );
+    assert_eq!(
+        verify_with_crl(Some(empty), X509VerifyFlags::CRL_CHECK),
+        Ok(())
+    );
+
+    let err = verify_with_crl(Some(revoked), X509VerifyFlags::CRL_CHECK).unwrap_err();
+    assert_eq!(err, X509VerifyError::CERT_REVOKED);
+    assert!(err.is_crl_error());
+
+    let err = verify_with_crl(None, X509VerifyFlags::CRL_CHECK).unwrap_err();
+    assert_eq!(err, X509VerifyError::UNABLE_TO_GET_CRL);
+    assert!(err.is_crl_error());
+    assert!(!X509VerifyError::CERT_HAS_EXPIRED.is_crl_error());
+}
+
+#[test]
+fn verify_error_reserved_codes() {
+    let unknown = unsafe { CStr::from_ptr(ffi::X509_verify_cert_error_string(c_long::MAX)) };
+    for code in RESERVED_CODES {
+        let s = unsafe { CStr::from_ptr(ffi::X509_verify_cert_error_string(code.into())) };
+        assert_eq!(s, unknown, "BoringSSL defines the reserved code {code:#x}");
+    }
+
+    for err in [
+        X509VerifyError::SPKI_PIN_MISMATCH,
+        X509VerifyError::CT_POLICY_NOT_MET,
+        X509VerifyError::MUST_STAPLE_NOT_MET,
+    ] {
+        assert!(err.is_reserved());
+        assert_ne!(err.error_string(), unknown.to_str().unwrap());
+    }
+    assert!(!X509VerifyError::CERT_REVOKED.is_reserved());
+}
+
+#[test]
+fn test_name_constraints() {
+    let cert = X509::from_pem(include_bytes!("../../../test/constrained-ca.pem")).unwrap();
+    let nc = cert.name_constraints().unwrap();
+
+    let permitted = nc.permitted_subtrees().unwrap();
+    assert_eq!(permitted.len(), 2);
+    assert_eq!(permitted[0].base().dnsname(), Some(".example.com"));
+    assert_eq!(
+        permitted[1].base().ipaddress(),
+        Some(&[10, 0, 0, 0, 255, 0, 0, 0][..])
+    );
+    assert!(permitted[0].minimum().is_none());
+    assert!(permitted[0].maximum().is_none());
+
+    let excluded = nc.excluded_subtrees().unwrap();
+    assert_eq!(excluded.len(), 2);
+    assert_eq!(excluded[0].base().dnsname(), Some(".bad.example.com"));
+    assert_eq!(excluded[1].base().email(), Some("example.org"));
+
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+    assert!(cert.name_constraints().is_none());
+}
+
+#[test]
+fn test_certificate_policies() {
+    let cert = X509::from_pem(include_bytes!("../../../test/constrained-ca.pem")).unwrap();
+    let policies = cert.certificate_policies().unwrap();
+    assert_eq!(policies.len(), 2);
+
+    assert_eq!(policies[0].policy_id().to_string(), "2.23.140.1.2.1");
+    assert!(policies[0].qualifiers().is_none());
+
+    assert_eq!(
+        policies[1].policy_id().to_string(),
+        "1.3.6.1.4.1.11129.2.5.3"
+    );
+    let qualifiers = policies[1].qualifiers().unwrap();
+    assert_eq!(qualifiers.len(), 2);
+    assert_eq!(qualifiers[0].qualifier_id().nid(), Nid::ID_QT_CPS);
+    assert_eq!(qualifiers[0].cps_uri(), Some("https://example.com/cps"));
+    assert!(qualifiers[0].user_notice_text().is_none());
+    assert_eq!(qualifiers[1].qualifier_id().nid(), Nid::ID_QT_UNOTICE);
+    assert!(qualifiers[1].cps_uri().is_none());
+    assert_eq!(
+        qualifiers[1].user_notice_text().unwrap().as_slice(),
+        b"Test notice"
+    );
+
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+    assert!(cert.certificate_policies().is_none());
+}
+
+#[test]
+fn test_subject_alt_name_values() {
+    let cert = include_bytes!("../../../test/cert-with-extensions.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let sans = cert.subject_alt_names().unwrap();
+    let values = sans.iter().map(|name| name.value()).collect::<Vec<_>>();
+    assert_eq!(values.len(), 5);
+
+    assert!(matches!(values[0], GeneralNameValue::Dns("example.com")));
+    assert!(
+        matches!(values[1], GeneralNameValue::Ip(ip) if ip == "192.0.2.1".parse::<IpAddr>().unwrap())
+    );
+    assert!(
+        matches!(values[2], GeneralNameValue::Ip(ip) if ip == "2001:db8::1".parse::<IpAddr>().unwrap())
+    );
+    assert!(matches!(
+        values[3],
+        GeneralNameValue::Uri("https://example.com/")
+    ));
+    match values[4] {
+        GeneralNameValue::DirectoryName(name) => {
+            let cn = name.entries_by_nid(Nid::COMMONNAME).next().unwrap();
+            assert_eq!(cn.data().as_slice(), b"Directory Name");
+        }
+        other => panic!("unexpected name {other:?}"),
+    }
+}
+
+#[test]
+fn test_authority_info_access() {
+    let cert = include_bytes!("../../../test/cert-with-extensions.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let aia = cert.authority_info_access().unwrap();
+    assert_eq!(aia.len(), 2);
+
+    assert_eq!(aia[0].method().nid(), Nid::AD_OCSP);
+    assert_eq!(aia[0].ocsp_uri(), Some("http://ocsp.example.com"));
+    assert_eq!(aia[0].ca_issuers_uri(), None);
+
+    assert_eq!(aia[1].method().nid(), Nid::AD_CA_ISSUERS);
+    assert_eq!(aia[1].ocsp_uri(), None);
+    assert_eq!(
+        aia[1].ca_issuers_uri(),
+        Some("http://ca.example.com/ca.der")
+    );
+
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+    assert!(cert.authority_info_access().is_none());
+}
+
+#[test]
+fn test_crl_distribution_points() {
+    let cert = include_bytes!("../../../test/cert-with-extensions.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let dps = cert.crl_distribution_points().unwrap();
+    assert_eq!(dps.len(), 2);
+    assert!(dps[0].crl_issuer().is_none());
+
+    let uris = dps.iter().flat_map(|dp| dp.uris()).collect::<Vec<_>>();
+    assert_eq!(
+        uris,
+        ["http://crl.example.com/ca.crl", "ldap://crl.example.com/ca"]
+    );
+
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+    assert!(cert.crl_distribution_points().is_none());
+}
+
+#[test]
+fn test_verify_chain() {
+    let leaf = X509::from_pem(include_bytes!("../../../test/cert-with-intermediate.pem")).unwrap();
+    let intermediate = X509::from_pem(include_bytes!("../../../test/intermediate-ca.pem")).unwrap();
+    let root = X509::from_pem(include_bytes!("../../../test/root-ca.pem")).unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(root.clone()).unwrap();
+    let store = store_bldr.build();
+
+    let mut params = X509VerifyParam::new().unwrap();
+    // 2025-01-01, while the test certificates are valid.
+    params.set_time(1735689600);
+
+    let mut intermediates = Stack::new().unwrap();
+    intermediates.push(intermediate).unwrap();
+
+    let chain = verify_chain(&leaf, &intermediates, &store, Some(&params)).unwrap();
+    assert_eq!(chain.certs().len(), 3);
+    assert_eq!(chain.leaf().to_der().unwrap(), leaf.to_der().unwrap());
+    assert_eq!(chain.root().to_der().unwrap(), root.to_der().unwrap());
+
+    let err = verify_chain(&leaf, &Stack::new().unwrap(), &store, Some(&params)).unwrap_err();
+    assert_eq!(
+        err.verify_error(),
+        Some(X509VerifyError::UNABLE_TO_GET_ISSUER_CERT_LOCALLY)
+    );
+    match err {
+        VerifyError::Invalid { depth, cert, .. } => {
+            assert_eq!(depth, 0);
+            assert_eq!(cert.unwrap().to_der().unwrap(), leaf.to_der().unwrap());
+        }
+        VerifyError::Internal(e) => panic!("unexpected error {e}"),
+    }
+
+    params.set_host("example.com").unwrap();
+    let err = verify_chain(&leaf, &intermediates, &store, Some(&params)).unwrap_err();
+    assert_eq!(err.verify_error(), Some(X509VerifyError::HOSTNAME_MISMATCH));
+}
+
+#[test]
+fn test_verify_partial_chain_and_depth() {
+    let leaf = X509::from_pem(include_bytes!("../../../test/cert-with-intermediate.pem")).unwrap();
+    let intermediate = X509::from_pem(include_bytes!("../../../test/intermediate-ca.pem")).unwrap();
+    let root = X509::from_pem(include_bytes!("../../../test/root-ca.pem")).unwrap();
+
+    let mut params = X509VerifyParam::new().unwrap();
+    // 2025-01-01, while the test certificates are valid.
+    params.set_time(1735689600);
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(intermediate.clone()).unwrap();
+    let store = store_bldr.build();
+    assert!(verify_chain(&leaf, &Stack::new().unwrap(), &store, Some(&params)).is_err());
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(intermediate.clone()).unwrap();
+    store_bldr.set_flags(X509VerifyFlags::PARTIAL_CHAIN);
+    let store = store_bldr.build();
+    let chain = verify_chain(&leaf, &Stack::new().unwrap(), &store, Some(&params)).unwrap();
+    assert_eq!(chain.certs().len(), 2);
+    assert_eq!(
+        chain.root().to_der().unwrap(),
+        intermediate.to_der().unwrap()
+    );
+
+    let mut intermediates = Stack::new().unwrap();
+    intermediates.push(intermediate).unwrap();
+
+    for (depth, ok) in [(1, false), (2, true)] {
+        let mut store_bldr = X509StoreBuilder::new().unwrap();
+        store_bldr.add_cert(root.clone()).unwrap();
+        store_bldr.set_depth(depth);
+        let store = store_bldr.build();
+        let result = verify_chain(&leaf, &intermediates, &store, Some(&params));
+        assert_eq!(result.is_ok(), ok);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+    let json = serde_json::to_string(&cert).unwrap();
+    let decoded: X509 = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.to_der().unwrap(), cert.to_der().unwrap());
+
+    let req = {
+        let pkey = pkey();
+        let mut builder = X509Req::builder().unwrap();
+        builder.set_pubkey(&pkey).unwrap();
+        builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+        builder.build()
+    };
+    let json = serde_json::to_string(&req).unwrap();
+    let decoded: X509Req = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.to_der().unwrap(), req.to_der().unwrap());
+
+    assert!(serde_json::from_str::<X509>("\"not a certificate\"").is_err());
+}
+
+#[cfg(feature = "rustls-pki-types")]
+#[test]
+fn test_rustls_pki_types_certificate() {
+    use rustls_pki_types::CertificateDer;
+
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+    let der = CertificateDer::try_from(&*cert).unwrap();
+    assert_eq!(der.as_ref(), cert.to_der().unwrap());
+
+    let decoded = X509::try_from(&der).unwrap();
+    assert_eq!(decoded.to_der().unwrap(), cert.to_der().unwrap());
+}
+
+#[cfg(feature = "x509-cert")]
+#[test]
+fn test_x509_cert() {
+    let cert = X509::from_pem(include_bytes!("../../../test/cert.pem")).unwrap();
+    let parsed = x509_cert::Certificate::try_from(&*cert).unwrap();
+    assert_eq!(parsed.signature.raw_bytes(), cert.signature().as_slice());
+    let decoded = X509::try_from(&parsed).unwrap();
+    assert_eq!(decoded.to_der().unwrap(), cert.to_der().unwrap());
+
+    let req = {
+        let pkey = pkey();
+        let mut builder = X509Req::builder().unwrap();
+        builder.set_pubkey(&pkey).unwrap();
+        builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+        builder.build()
+    };
+    let parsed = x509_cert::request::CertReq::try_from(&*req).unwrap();
+    let decoded = X509Req::try_from(&parsed).unwrap();
+    assert_eq!(decoded.to_der().unwrap(), req.to_der().unwrap());
+}
@@ -2,9 +2,12 @@ use crate::ffi;
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::{c_int, c_uint, c_ulong, time_t};
 use openssl_macros::corresponds;
+use std::ffi::CString;
 use std::net::IpAddr;
 
+use crate::asn1::Asn1Object;
 use crate::error::ErrorStack;
+use crate::stack::StackRef;
 use crate::{cvt, cvt_p};
 
 bitflags! {
@@ -47,6 +50,64 @@ bitflags! {
         const TRUSTED_FIRST = ffi::X509_V_FLAG_TRUSTED_FIRST as _;
         const PARTIAL_CHAIN = ffi::X509_V_FLAG_PARTIAL_CHAIN as _;
         const NO_ALT_CHAINS = ffi::X509_V_FLAG_NO_ALT_CHAINS as _;
+        const NO_CHECK_TIME = ffi::X509_V_FLAG_NO_CHECK_TIME as _;
+    }
+}
+
+/// The intended purpose of a certificate, checked against its key usage and
+/// extended key usage extensions during verification.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct X509PurposeId(c_int);
+
+impl X509PurposeId {
+    pub const SSL_CLIENT: Self = Self(ffi::X509_PURPOSE_SSL_CLIENT as _);
+    pub const SSL_SERVER: Self = Self(ffi::X509_PURPOSE_SSL_SERVER as _);
+    pub const NS_SSL_SERVER: Self = Self(ffi::X509_PURPOSE_NS_SSL_SERVER as _);
+    pub const SMIME_SIGN: Self = Self(ffi::X509_PURPOSE_SMIME_SIGN as _);
+    pub const SMIME_ENCRYPT: Self = Self(ffi::X509_PURPOSE_SMIME_ENCRYPT as _);
+    pub const CRL_SIGN: Self = Self(ffi::X509_PURPOSE_CRL_SIGN as _);
+    pub const ANY: Self = Self(ffi::X509_PURPOSE_ANY as _);
+    pub const OCSP_HELPER: Self = Self(ffi::X509_PURPOSE_OCSP_HELPER as _);
+    pub const TIMESTAMP_SIGN: Self = Self(ffi::X509_PURPOSE_TIMESTAMP_SIGN as _);
+
+    /// Constructs an `X509PurposeId` from a raw OpenSSL value.
+    #[must_use]
+    pub fn from_raw(id: c_int) -> Self {
+        X509PurposeId(id)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[must_use]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The trust setting checked against the trusted certificate at the root of
+/// the chain.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct X509TrustId(c_int);
+
+impl X509TrustId {
+    pub const COMPAT: Self = Self(ffi::X509_TRUST_COMPAT as _);
+    pub const SSL_CLIENT: Self = Self(ffi::X509_TRUST_SSL_CLIENT as _);
+    pub const SSL_SERVER: Self = Self(ffi::X509_TRUST_SSL_SERVER as _);
+    pub const EMAIL: Self = Self(ffi::X509_TRUST_EMAIL as _);
+    pub const OBJECT_SIGN: Self = Self(ffi::X509_TRUST_OBJECT_SIGN as _);
+    pub const OCSP_SIGN: Self = Self(ffi::X509_TRUST_OCSP_SIGN as _);
+    pub const OCSP_REQUEST: Self = Self(ffi::X509_TRUST_OCSP_REQUEST as _);
+    pub const TSA: Self = Self(ffi::X509_TRUST_TSA as _);
+
+    /// Constructs an `X509TrustId` from a raw OpenSSL value.
+    #[must_use]
+    pub fn from_raw(id: c_int) -> Self {
+        X509TrustId(id)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[must_use]
+    pub fn as_raw(&self) -> c_int {
+        self.0
     }
 }
 
@@ -127,6 +188,23 @@ impl X509VerifyParamRef {
         }
     }
 
+    /// Adds an additional expected DNS hostname.
+    ///
+    /// Verification succeeds if the certificate matches any of the configured hostnames.
+    #[corresponds(X509_VERIFY_PARAM_add1_host)]
+    pub fn add_host(&mut self, host: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            // len == 0 means "run strlen" :(
+            let raw_host = if host.is_empty() { "\0" } else { host };
+            cvt(ffi::X509_VERIFY_PARAM_add1_host(
+                self.as_ptr(),
+                raw_host.as_ptr() as *const _,
+                host.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Set the expected email address.
     #[corresponds(X509_VERIFY_PARAM_set1_email)]
     pub fn set_email(&mut self, email: &str) -> Result<(), ErrorStack> {
@@ -166,6 +244,19 @@ impl X509VerifyParamRef {
         }
     }
 
+    /// Set the expected IPv4 or IPv6 address from its textual representation.
+    #[corresponds(X509_VERIFY_PARAM_set1_ip_asc)]
+    pub fn set_ip_asc(&mut self, ip: &str) -> Result<(), ErrorStack> {
+        let ip = CString::new(ip).map_err(ErrorStack::internal_error)?;
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set1_ip_asc(
+                self.as_ptr(),
+                ip.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Set the verification time, where time is of type time_t, traditionaly defined as seconds since the epoch
     #[corresponds(X509_VERIFY_PARAM_set_time)]
     pub fn set_time(&mut self, time: time_t) {
@@ -178,6 +269,65 @@ impl X509VerifyParamRef {
         unsafe { ffi::X509_VERIFY_PARAM_set_depth(self.as_ptr(), depth) }
     }
 
+    /// Returns the verification depth, or -1 if it is unset.
+    #[corresponds(X509_VERIFY_PARAM_get_depth)]
+    #[must_use]
+    pub fn depth(&self) -> c_int {
+        unsafe { ffi::X509_VERIFY_PARAM_get_depth(self.as_ptr()) }
+    }
+
+    /// Sets the purpose the certificate chain is checked for.
+    #[corresponds(X509_VERIFY_PARAM_set_purpose)]
+    pub fn set_purpose(&mut self, purpose: X509PurposeId) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set_purpose(
+                self.as_ptr(),
+                purpose.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the trust setting the root of the chain is checked for.
+    #[corresponds(X509_VERIFY_PARAM_set_trust)]
+    pub fn set_trust(&mut self, trust: X509TrustId) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set_trust(
+                self.as_ptr(),
+                trust.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds an acceptable certificate policy.
+    ///
+    /// Policies are only enforced when [`X509VerifyFlags::POLICY_CHECK`] or
+    /// [`X509VerifyFlags::EXPLICIT_POLICY`] is set.
+    #[corresponds(X509_VERIFY_PARAM_add0_policy)]
+    pub fn add_policy(&mut self, policy: Asn1Object) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_add0_policy(
+                self.as_ptr(),
+                policy.as_ptr(),
+            ))?;
+            std::mem::forget(policy);
+            Ok(())
+        }
+    }
+
+    /// Replaces the set of acceptable certificate policies.
+    #[corresponds(X509_VERIFY_PARAM_set1_policies)]
+    pub fn set_policies(&mut self, policies: &StackRef<Asn1Object>) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set1_policies(
+                self.as_ptr(),
+                policies.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Copies parameters from `src`.
     ///
     /// If a parameter is unset in `src`, the existing value in `self`` is preserved.
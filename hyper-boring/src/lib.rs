@@ -5,14 +5,19 @@
 use crate::cache::SessionKey;
 use boring::error::ErrorStack;
 use boring::ex_data::Index;
-use boring::ssl::Ssl;
+use boring::ssl::{NameType, Ssl, SslRef, SslVersion};
+use boring::x509::X509;
 use std::fmt;
 use std::sync::LazyLock;
 use tokio_boring::SslStream;
 
 mod cache;
+mod proxy;
+mod server;
 mod v1;
 
+pub use self::proxy::{ProxyConnector, ProxyStream};
+pub use self::server::{TlsAccept, TlsAcceptLayer, TlsListener};
 pub use self::v1::*;
 
 fn key_index() -> Result<Index<Ssl, SessionKey>, ErrorStack> {
@@ -58,6 +63,120 @@ impl HttpsLayerSettingsBuilder {
     }
 }
 
+/// TLS settings overriding those of an [`HttpsConnector`] for particular URIs.
+///
+/// See [`HttpsConnector::set_tls_overrides`].
+#[derive(Clone, Debug, Default)]
+pub struct TlsOverrides {
+    server_name: Option<String>,
+    alpn_protos: Option<Vec<u8>>,
+    pinned_spki_sha256: Vec<[u8; 32]>,
+    verify_hostname: Option<bool>,
+}
+
+impl TlsOverrides {
+    /// Creates overrides that leave every setting of the connector as is.
+    #[must_use]
+    pub fn new() -> TlsOverrides {
+        TlsOverrides::default()
+    }
+
+    /// Sets the name sent in SNI and checked against the server's certificate, instead of the
+    /// URI's host.
+    pub fn set_server_name(&mut self, server_name: impl Into<String>) {
+        self.server_name = Some(server_name.into());
+    }
+
+    /// Sets the ALPN protocols to offer, in wire format.
+    pub fn set_alpn_protos(&mut self, protos: &[u8]) {
+        self.alpn_protos = Some(protos.to_vec());
+    }
+
+    /// Requires the server's certificate chain to contain a certificate with the given
+    /// SHA-256 digest of its DER-encoded SubjectPublicKeyInfo.
+    ///
+    /// May be called multiple times; the connection is accepted if any pin matches. Pins are
+    /// checked in addition to regular certificate verification.
+    pub fn add_pinned_spki_sha256(&mut self, digest: [u8; 32]) {
+        self.pinned_spki_sha256.push(digest);
+    }
+
+    /// Sets whether the server's certificate must match the server name.
+    pub fn set_verify_hostname(&mut self, verify_hostname: bool) {
+        self.verify_hostname = Some(verify_hostname);
+    }
+}
+
+/// Information about the TLS session of a connection.
+///
+/// Each [`Response`](hyper::Response) received over an HTTPS connection established by
+/// [`HttpsConnector`] carries this in its extensions, and it can be obtained from a stream
+/// directly with [`MaybeHttpsStream::tls_info`]. On the server side, it can be built from
+/// streams accepted with [`TlsAcceptLayer`] or [`TlsListener`].
+#[derive(Clone, Debug)]
+pub struct TlsInfo {
+    server_name: Option<String>,
+    alpn_protocol: Option<Vec<u8>>,
+    version: Option<SslVersion>,
+    cipher: Option<&'static str>,
+    peer_certificates: Vec<X509>,
+}
+
+impl TlsInfo {
+    /// Records information about the TLS session of `ssl`.
+    #[must_use]
+    pub fn new(ssl: &SslRef) -> TlsInfo {
+        // Servers are not given the client's leaf certificate as part of the chain.
+        let leaf = if ssl.is_server() {
+            ssl.peer_certificate()
+        } else {
+            None
+        };
+        let chain = ssl.peer_cert_chain().into_iter().flatten();
+
+        TlsInfo {
+            server_name: ssl.servername(NameType::HOST_NAME).map(ToOwned::to_owned),
+            alpn_protocol: ssl.selected_alpn_protocol().map(<[u8]>::to_vec),
+            version: ssl.version2(),
+            cipher: ssl.current_cipher().map(|cipher| cipher.name()),
+            peer_certificates: leaf
+                .into_iter()
+                .chain(chain.map(ToOwned::to_owned))
+                .collect(),
+        }
+    }
+
+    /// Returns the server name sent by the client in SNI, if any.
+    #[must_use]
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    /// Returns the protocol selected via ALPN, if any.
+    #[must_use]
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Returns the negotiated protocol version.
+    #[must_use]
+    pub fn version(&self) -> Option<SslVersion> {
+        self.version
+    }
+
+    /// Returns the name of the negotiated cipher.
+    #[must_use]
+    pub fn cipher(&self) -> Option<&'static str> {
+        self.cipher
+    }
+
+    /// Returns the certificate chain presented by the peer, starting with its leaf certificate.
+    #[must_use]
+    pub fn peer_certificates(&self) -> &[X509] {
+        &self.peer_certificates
+    }
+}
+
 /// A stream which may be wrapped with TLS.
 pub enum MaybeHttpsStream<T> {
     /// A raw HTTP stream.
@@ -74,3 +193,14 @@ impl<T> fmt::Debug for MaybeHttpsStream<T> {
         }
     }
 }
+
+impl<T> MaybeHttpsStream<T> {
+    /// Returns information about the TLS session, or `None` for a raw HTTP stream.
+    #[must_use]
+    pub fn tls_info(&self) -> Option<TlsInfo> {
+        match self {
+            MaybeHttpsStream::Http(..) => None,
+            MaybeHttpsStream::Https(s) => Some(TlsInfo::new(s.ssl())),
+        }
+    }
+}
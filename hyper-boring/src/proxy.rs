@@ -0,0 +1,269 @@
+use boring::error::ErrorStack;
+use boring::ssl::{SslConnector, SslMethod};
+use http::uri::Scheme;
+use http::{HeaderValue, Uri};
+use hyper_util::client::legacy::connect::{Connected, Connection};
+use hyper_util::rt::TokioIo;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio_boring::SslStream;
+use tower_service::Service;
+
+/// The largest proxy response head accepted when establishing a tunnel.
+const MAX_RESPONSE_HEAD_LEN: usize = 8192;
+
+/// A connector tunneling connections through an HTTP or HTTPS proxy with `CONNECT`.
+///
+/// Wrap it in an [`HttpsConnector`](crate::HttpsConnector) to reach `https` origins through the
+/// proxy. The TLS session with the origin then runs inside the tunnel, with SNI and certificate
+/// verification against the origin's host name, while the proxy only sees the `CONNECT` request.
+/// If the proxy URI has the `https` scheme, the connection to the proxy is itself secured with
+/// TLS.
+#[derive(Clone)]
+pub struct ProxyConnector<S> {
+    http: S,
+    proxy: Uri,
+    tls: Option<SslConnector>,
+    authorization: Option<HeaderValue>,
+}
+
+impl<S> ProxyConnector<S> {
+    /// Creates a connector tunneling through the proxy at `proxy`, reached with `http`.
+    ///
+    /// For `https` proxies, the proxy's certificate is verified with the default settings of
+    /// [`SslConnector`].
+    pub fn new(http: S, proxy: Uri) -> Result<ProxyConnector<S>, ErrorStack> {
+        let tls = if proxy.scheme() == Some(&Scheme::HTTPS) {
+            Some(SslConnector::builder(SslMethod::tls())?.build())
+        } else {
+            None
+        };
+
+        Ok(ProxyConnector {
+            http,
+            proxy,
+            tls,
+            authorization: None,
+        })
+    }
+
+    /// Creates a connector tunneling through the proxy at `proxy` over TLS configured by `ssl`.
+    #[must_use]
+    pub fn with_tls_connector(http: S, proxy: Uri, ssl: SslConnector) -> ProxyConnector<S> {
+        ProxyConnector {
+            http,
+            proxy,
+            tls: Some(ssl),
+            authorization: None,
+        }
+    }
+
+    /// Sets the `Proxy-Authorization` header sent with each `CONNECT` request.
+    pub fn set_authorization(&mut self, authorization: HeaderValue) {
+        self.authorization = Some(authorization);
+    }
+}
+
+impl<S, T> Service<Uri> for ProxyConnector<S>
+where
+    S: Service<Uri, Response = TokioIo<T>> + Send,
+    S::Error: Into<Box<dyn Error + Send + Sync>>,
+    S::Future: Send + 'static,
+    T: AsyncRead + AsyncWrite + Connection + Unpin + fmt::Debug + Sync + Send + 'static,
+{
+    type Response = TokioIo<ProxyStream<T>>;
+    type Error = Box<dyn Error + Sync + Send>;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        // `HttpConnector` only connects to `http` URIs by default, so the proxy is reached through
+        // the `http` URI of its authority, whatever its scheme.
+        let connect = proxy_dst(&self.proxy).map(|dst| self.http.call(dst));
+        let proxy = self.proxy.clone();
+        let tls = self.tls.clone();
+        let authorization = self.authorization.clone();
+
+        let f = async move {
+            let host = uri.host().ok_or("URI missing host")?;
+            let port = match uri.port_u16() {
+                Some(port) => port,
+                None if uri.scheme() == Some(&Scheme::HTTP) => 80,
+                None => 443,
+            };
+
+            let conn = connect?.await.map_err(Into::into)?.into_inner();
+
+            let mut stream = match tls {
+                Some(tls) => {
+                    let proxy_host = proxy.host().ok_or("proxy URI missing host")?;
+                    let config = tls.configure()?;
+
+                    ProxyStream::Tls(tokio_boring::connect(config, proxy_host, conn).await?)
+                }
+                None => ProxyStream::Plain(conn),
+            };
+
+            tunnel(&mut stream, host, port, authorization.as_ref()).await?;
+
+            Ok(TokioIo::new(stream))
+        };
+
+        Box::pin(f)
+    }
+}
+
+/// Returns the `http` URI of the authority of `proxy`, with its port made explicit.
+fn proxy_dst(proxy: &Uri) -> Result<Uri, Box<dyn Error + Sync + Send>> {
+    let host = proxy.host().ok_or("proxy URI missing host")?;
+    let port = match proxy.port_u16() {
+        Some(port) => port,
+        None if proxy.scheme() == Some(&Scheme::HTTPS) => 443,
+        None => 80,
+    };
+
+    let dst = Uri::builder()
+        .scheme(Scheme::HTTP)
+        .authority(format!("{host}:{port}"))
+        .path_and_query("/")
+        .build()?;
+
+    Ok(dst)
+}
+
+async fn tunnel<S>(
+    stream: &mut S,
+    host: &str,
+    port: u16,
+    authorization: Option<&HeaderValue>,
+) -> Result<(), Box<dyn Error + Sync + Send>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut request =
+        format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n").into_bytes();
+    if let Some(authorization) = authorization {
+        request.extend_from_slice(b"Proxy-Authorization: ");
+        request.extend_from_slice(authorization.as_bytes());
+        request.extend_from_slice(b"\r\n");
+    }
+    request.extend_from_slice(b"\r\n");
+
+    stream.write_all(&request).await?;
+    stream.flush().await?;
+
+    // The origin does not speak before the client does, so the response head is all the proxy
+    // sends until the tunnel is used.
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+    while !head.ends_with(b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Err("proxy closed the connection before responding to CONNECT".into());
+        }
+        head.extend_from_slice(&buf[..n]);
+
+        if head.len() > MAX_RESPONSE_HEAD_LEN {
+            return Err("proxy response to CONNECT is too large".into());
+        }
+    }
+
+    let status_line = head.split(|&b| b == b'\n').next().unwrap_or_default();
+    let mut parts = status_line.split(|&b| b == b' ');
+    let version = parts.next().unwrap_or_default();
+    let status = parts.next().unwrap_or_default();
+
+    if !version.starts_with(b"HTTP/1.") || status.len() != 3 {
+        return Err("invalid proxy response to CONNECT".into());
+    }
+    if status[0] != b'2' {
+        let status = String::from_utf8_lossy(status);
+        return Err(format!("proxy refused CONNECT with status {status}").into());
+    }
+
+    Ok(())
+}
+
+/// A connection to a proxy, over which a tunnel to the origin has been established.
+pub enum ProxyStream<T> {
+    /// A plaintext connection to an `http` proxy.
+    Plain(T),
+    /// A TLS connection to an `https` proxy.
+    Tls(SslStream<T>),
+}
+
+impl<T> fmt::Debug for ProxyStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProxyStream::Plain(..) => f.pad("Plain(..)"),
+            ProxyStream::Tls(..) => f.pad("Tls(..)"),
+        }
+    }
+}
+
+impl<T> Connection for ProxyStream<T>
+where
+    T: Connection,
+{
+    fn connected(&self) -> Connected {
+        match self {
+            ProxyStream::Plain(s) => s.connected(),
+            ProxyStream::Tls(s) => s.get_ref().connected(),
+        }
+    }
+}
+
+impl<T> AsyncRead for ProxyStream<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match &mut *self {
+            ProxyStream::Plain(inner) => Pin::new(inner).poll_read(cx, buf),
+            ProxyStream::Tls(inner) => Pin::new(inner).poll_read(cx, buf),
+        }
+    }
+}
+
+impl<T> AsyncWrite for ProxyStream<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match &mut *self {
+            ProxyStream::Plain(inner) => Pin::new(inner).poll_write(cx, buf),
+            ProxyStream::Tls(inner) => Pin::new(inner).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut *self {
+            ProxyStream::Plain(inner) => Pin::new(inner).poll_flush(cx),
+            ProxyStream::Tls(inner) => Pin::new(inner).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut *self {
+            ProxyStream::Plain(inner) => Pin::new(inner).poll_shutdown(cx),
+            ProxyStream::Tls(inner) => Pin::new(inner).poll_shutdown(cx),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use boring::ssl::SslAcceptor;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_boring::SslStream;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A layer accepting TLS connections with an [`SslAcceptor`].
+///
+/// The wrapped service is called with the [`SslStream`] of each connection once its handshake
+/// completes. The SNI, ALPN protocol, and peer certificates negotiated for the connection can
+/// be read from [`SslStream::ssl`], or collected with [`TlsInfo::new`](crate::TlsInfo::new).
+#[derive(Clone)]
+pub struct TlsAcceptLayer {
+    acceptor: SslAcceptor,
+}
+
+impl TlsAcceptLayer {
+    /// Creates a layer accepting connections with `acceptor`.
+    #[must_use]
+    pub fn new(acceptor: SslAcceptor) -> TlsAcceptLayer {
+        TlsAcceptLayer { acceptor }
+    }
+}
+
+impl<S> Layer<S> for TlsAcceptLayer {
+    type Service = TlsAccept<S>;
+
+    fn layer(&self, inner: S) -> TlsAccept<S> {
+        TlsAccept {
+            inner,
+            acceptor: self.acceptor.clone(),
+        }
+    }
+}
+
+/// A service performing the server side of the TLS handshake on streams before passing them to
+/// the wrapped service.
+///
+/// See [`TlsAcceptLayer`].
+#[derive(Clone)]
+pub struct TlsAccept<S> {
+    inner: S,
+    acceptor: SslAcceptor,
+}
+
+impl<S, T> Service<T> for TlsAccept<S>
+where
+    S: Service<SslStream<T>> + Clone + Send + 'static,
+    S::Error: Into<Box<dyn Error + Send + Sync>>,
+    S::Future: Send,
+    T: AsyncRead + AsyncWrite + Unpin + fmt::Debug + Sync + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Box<dyn Error + Sync + Send>;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, stream: T) -> Self::Future {
+        // Take the service that was polled ready, leaving a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let acceptor = self.acceptor.clone();
+
+        let f = async move {
+            let stream = tokio_boring::accept(&acceptor, stream).await?;
+
+            inner.call(stream).await.map_err(Into::into)
+        };
+
+        Box::pin(f)
+    }
+}
+
+/// A TCP listener accepting TLS connections with an [`SslAcceptor`].
+pub struct TlsListener {
+    listener: TcpListener,
+    acceptor: SslAcceptor,
+}
+
+impl TlsListener {
+    /// Creates a listener accepting TLS connections on `listener` with `acceptor`.
+    #[must_use]
+    pub fn new(listener: TcpListener, acceptor: SslAcceptor) -> TlsListener {
+        TlsListener { listener, acceptor }
+    }
+
+    /// Accepts a connection and performs the TLS handshake on it.
+    ///
+    /// The handshake is not raced against other connections; servers accepting many clients
+    /// should spawn the handshakes instead, for instance with [`TlsAcceptLayer`]. A failed
+    /// handshake only affects its own connection, and the listener can keep accepting.
+    pub async fn accept(
+        &self,
+    ) -> Result<(SslStream<TcpStream>, SocketAddr), Box<dyn Error + Sync + Send>> {
+        let (stream, addr) = self.listener.accept().await?;
+        let stream = tokio_boring::accept(&self.acceptor, stream).await?;
+
+        Ok((stream, addr))
+    }
+
+    /// Returns the local address the listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Returns the acceptor used to establish connections.
+    #[must_use]
+    pub fn acceptor(&self) -> &SslAcceptor {
+        &self.acceptor
+    }
+
+    /// Returns the underlying TCP listener.
+    #[must_use]
+    pub fn get_ref(&self) -> &TcpListener {
+        &self.listener
+    }
+}
@@ -1,7 +1,8 @@
 use crate::cache::{SessionCache, SessionKey};
-use crate::{key_index, HttpsLayerSettings, MaybeHttpsStream};
+use crate::{key_index, HttpsLayerSettings, MaybeHttpsStream, TlsInfo, TlsOverrides};
 use antidote::Mutex;
 use boring::error::ErrorStack;
+use boring::sha::sha256;
 use boring::ssl::{
     ConnectConfiguration, Ssl, SslConnector, SslConnectorBuilder, SslMethod, SslRef,
     SslSessionCacheMode,
@@ -78,6 +79,17 @@ where
     {
         self.inner.ssl_callback = Some(Arc::new(callback));
     }
+
+    /// Registers a callback which returns the [`TlsOverrides`] for the connection to a URI.
+    ///
+    /// This lets one connector, and the pool of the client using it, talk to backends with
+    /// different TLS requirements. Overrides are applied after the other callbacks.
+    pub fn set_tls_overrides<F>(&mut self, callback: F)
+    where
+        F: Fn(&Uri) -> Option<TlsOverrides> + 'static + Sync + Send,
+    {
+        self.inner.overrides = Some(Arc::new(callback));
+    }
 }
 
 /// A layer which wraps services in an `HttpsConnector`.
@@ -91,11 +103,13 @@ struct Inner {
     cache: Arc<Mutex<SessionCache>>,
     callback: Option<Callback>,
     ssl_callback: Option<SslCallback>,
+    overrides: Option<OverridesCallback>,
 }
 
 type Callback =
     Arc<dyn Fn(&mut ConnectConfiguration, &Uri) -> Result<(), ErrorStack> + Sync + Send>;
 type SslCallback = Arc<dyn Fn(&mut SslRef, &Uri) -> Result<(), ErrorStack> + Sync + Send>;
+type OverridesCallback = Arc<dyn Fn(&Uri) -> Option<TlsOverrides> + Sync + Send>;
 
 impl HttpsLayer {
     /// Creates a new `HttpsLayer` with default settings.
@@ -142,6 +156,7 @@ impl HttpsLayer {
                 cache,
                 callback: None,
                 ssl_callback: None,
+                overrides: None,
             },
         })
     }
@@ -165,6 +180,17 @@ impl HttpsLayer {
     {
         self.inner.ssl_callback = Some(Arc::new(callback));
     }
+
+    /// Registers a callback which returns the [`TlsOverrides`] for the connection to a URI.
+    ///
+    /// This lets one connector, and the pool of the client using it, talk to backends with
+    /// different TLS requirements. Overrides are applied after the other callbacks.
+    pub fn set_tls_overrides<F>(&mut self, callback: F)
+    where
+        F: Fn(&Uri) -> Option<TlsOverrides> + 'static + Sync + Send,
+    {
+        self.inner.overrides = Some(Arc::new(callback));
+    }
 }
 
 impl<S> Layer<S> for HttpsLayer {
@@ -179,13 +205,24 @@ impl<S> Layer<S> for HttpsLayer {
 }
 
 impl Inner {
-    fn setup_ssl(&self, uri: &Uri, host: &str) -> Result<Ssl, ErrorStack> {
+    fn setup_ssl(
+        &self,
+        uri: &Uri,
+        host: &str,
+        overrides: &TlsOverrides,
+    ) -> Result<Ssl, ErrorStack> {
         let mut conf = self.ssl.configure()?;
 
         if let Some(ref callback) = self.callback {
             callback(&mut conf, uri)?;
         }
 
+        if let Some(verify_hostname) = overrides.verify_hostname {
+            conf.set_verify_hostname(verify_hostname);
+        }
+
+        let host = overrides.server_name.as_deref().unwrap_or(host);
+
         let key = SessionKey {
             host: host.to_string(),
             port: uri.port_u16().unwrap_or(443),
@@ -206,10 +243,34 @@ impl Inner {
             ssl_callback(&mut ssl, uri)?;
         }
 
+        if let Some(ref protos) = overrides.alpn_protos {
+            ssl.set_alpn_protos(protos)?;
+        }
+
         Ok(ssl)
     }
 }
 
+impl TlsOverrides {
+    fn check_pins(&self, ssl: &SslRef) -> Result<(), Box<dyn Error + Sync + Send>> {
+        if self.pinned_spki_sha256.is_empty() {
+            return Ok(());
+        }
+
+        let leaf = ssl.peer_certificate();
+        let chain = ssl.peer_cert_chain().into_iter().flatten();
+
+        for cert in leaf.as_deref().into_iter().chain(chain) {
+            let spki = cert.public_key()?.public_key_to_der()?;
+            if self.pinned_spki_sha256.contains(&sha256(&spki)) {
+                return Ok(());
+            }
+        }
+
+        Err("server certificate does not match any pinned public key".into())
+    }
+}
+
 impl<T, S> Service<Uri> for HttpsConnector<S>
 where
     S: Service<Uri, Response = TokioIo<T>> + Send,
@@ -264,11 +325,19 @@ where
                 }
             }
 
-            let ssl = inner.setup_ssl(&uri, host)?;
+            let overrides = inner
+                .overrides
+                .as_ref()
+                .and_then(|overrides| overrides(&uri))
+                .unwrap_or_default();
+
+            let ssl = inner.setup_ssl(&uri, host, &overrides)?;
             let stream = tokio_boring::SslStreamBuilder::new(ssl, conn)
                 .connect()
                 .await?;
 
+            overrides.check_pins(stream.ssl())?;
+
             Ok(MaybeHttpsStream::Https(stream))
         };
 
@@ -290,6 +359,8 @@ where
                     connected = connected.negotiated_h2();
                 }
 
+                connected = connected.extra(TlsInfo::new(s.ssl()));
+
                 connected
             }
         }
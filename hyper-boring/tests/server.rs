@@ -0,0 +1,87 @@
+use boring::ssl::{self, AlpnError, SslAcceptor, SslConnector, SslFiletype, SslMethod};
+use hyper_boring::{TlsAcceptLayer, TlsInfo, TlsListener};
+use std::convert::Infallible;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_boring::SslStream;
+use tower::{Layer, Service, ServiceExt};
+
+fn acceptor() -> SslAcceptor {
+    let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+    acceptor
+        .set_private_key_file("tests/test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    acceptor
+        .set_certificate_chain_file("tests/test/cert.pem")
+        .unwrap();
+    acceptor.set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x02h2", client).ok_or(AlpnError::NOACK)
+    });
+    acceptor.build()
+}
+
+async fn connect(addr: std::net::SocketAddr) -> SslStream<TcpStream> {
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("tests/test/root-ca.pem").unwrap();
+    connector.set_alpn_protos(b"\x02h2").unwrap();
+    let config = connector.build().configure().unwrap();
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    tokio_boring::connect(config, "foobar.com", stream)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn tls_listener() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let listener = TlsListener::new(listener, acceptor());
+    let addr = listener.local_addr().unwrap();
+
+    let client = tokio::spawn(async move {
+        let mut stream = connect(addr).await;
+        stream.write_all(b"hello").await.unwrap();
+        stream.shutdown().await.unwrap();
+    });
+
+    let (mut stream, _) = listener.accept().await.unwrap();
+
+    let info = TlsInfo::new(stream.ssl());
+    assert_eq!(info.server_name(), Some("foobar.com"));
+    assert_eq!(info.alpn_protocol(), Some(&b"h2"[..]));
+
+    let mut buf = vec![];
+    stream.read_to_end(&mut buf).await.unwrap();
+    assert_eq!(buf, b"hello");
+
+    client.await.unwrap();
+}
+
+#[tokio::test]
+async fn tls_accept_layer() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let client = tokio::spawn(async move {
+        let mut stream = connect(addr).await;
+        let mut buf = vec![];
+        stream.read_to_end(&mut buf).await.unwrap();
+        buf
+    });
+
+    let service = tower::service_fn(|mut stream: SslStream<TcpStream>| async move {
+        let info = TlsInfo::new(stream.ssl());
+        stream
+            .write_all(info.server_name().unwrap().as_bytes())
+            .await
+            .unwrap();
+        stream.shutdown().await.unwrap();
+        Ok::<_, Infallible>(())
+    });
+    let mut service = TlsAcceptLayer::new(acceptor()).layer(service);
+
+    let stream = listener.accept().await.unwrap().0;
+    service.ready().await.unwrap().call(stream).await.unwrap();
+
+    assert_eq!(client.await.unwrap(), b"foobar.com");
+}
@@ -1,9 +1,11 @@
-use boring::ssl::{SslAcceptor, SslConnector, SslFiletype, SslMethod};
+use boring::sha::sha256;
+use boring::ssl::{SslAcceptor, SslConnector, SslFiletype, SslMethod, SslVersion};
+use boring::x509::X509;
 use bytes::Bytes;
 use futures::StreamExt;
 use http_body_util::{BodyStream, Empty};
 use hyper::{service, Response};
-use hyper_boring::HttpsConnector;
+use hyper_boring::{HttpsConnector, TlsInfo, TlsOverrides};
 use hyper_util::client::legacy::connect::HttpConnector;
 use hyper_util::client::legacy::Client;
 use hyper_util::rt::{TokioExecutor, TokioIo};
@@ -148,6 +150,276 @@ async fn alpn_h2() {
 
     let client = Client::builder(TokioExecutor::new()).build::<_, Empty<Bytes>>(ssl);
 
+    let resp = client
+        .get(format!("https://foobar.com:{}", port).parse().unwrap())
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "{}", resp.status());
+
+    let info = resp.extensions().get::<TlsInfo>().unwrap();
+    assert_eq!(info.alpn_protocol(), Some(&b"h2"[..]));
+    assert_eq!(info.version(), Some(SslVersion::TLS1_2));
+    assert!(info.cipher().is_some());
+    assert!(!info.peer_certificates().is_empty());
+
+    let mut body = BodyStream::new(resp.into_body());
+    while body.next().await.transpose().unwrap().is_some() {}
+}
+
+#[tokio::test]
+async fn tls_overrides() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let port = addr.port();
+
+    let server = async move {
+        let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        acceptor
+            .set_private_key_file("tests/test/key.pem", SslFiletype::PEM)
+            .unwrap();
+        acceptor
+            .set_certificate_chain_file("tests/test/cert.pem")
+            .unwrap();
+        let acceptor = acceptor.build();
+
+        loop {
+            let stream = listener.accept().await.unwrap().0;
+            let Ok(stream) = tokio_boring::accept(&acceptor, stream).await else {
+                continue;
+            };
+
+            let service = service::service_fn(|_| async {
+                Ok::<_, io::Error>(Response::new(<Empty<Bytes>>::new()))
+            });
+
+            hyper::server::conn::http1::Builder::new()
+                .keep_alive(false)
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+                .unwrap();
+        }
+    };
+    tokio::spawn(server);
+
+    let resolver =
+        tower::service_fn(move |_name| async move { Ok::<_, Infallible>(iter::once(addr)) });
+
+    let mut connector = HttpConnector::new_with_resolver(resolver);
+
+    connector.enforce_http(false);
+
+    let mut ssl = SslConnector::builder(SslMethod::tls()).unwrap();
+
+    ssl.set_ca_file("tests/test/root-ca.pem").unwrap();
+
+    let cert = X509::from_pem(&std::fs::read("tests/test/cert.pem").unwrap()).unwrap();
+    let pin = sha256(&cert.public_key().unwrap().public_key_to_der().unwrap());
+
+    let mut ssl = HttpsConnector::with_connector(connector, ssl).unwrap();
+
+    ssl.set_tls_overrides(move |uri| {
+        let mut overrides = TlsOverrides::new();
+        // The backends are addressed by an internal name, but present a certificate for
+        // foobar.com.
+        overrides.set_server_name("foobar.com");
+        match uri.host() {
+            Some("pinned.internal") => overrides.add_pinned_spki_sha256(pin),
+            Some("mispinned.internal") => overrides.add_pinned_spki_sha256([0; 32]),
+            _ => {}
+        }
+        Some(overrides)
+    });
+
+    let client = Client::builder(TokioExecutor::new()).build::<_, Empty<Bytes>>(ssl);
+
+    for host in ["backend.internal", "pinned.internal"] {
+        let resp = client
+            .get(format!("https://{host}:{port}").parse().unwrap())
+            .await
+            .unwrap();
+        assert!(resp.status().is_success(), "{}", resp.status());
+        let mut body = BodyStream::new(resp.into_body());
+        while body.next().await.transpose().unwrap().is_some() {}
+    }
+
+    let uri = format!("https://mispinned.internal:{port}")
+        .parse()
+        .unwrap();
+    client.get(uri).await.unwrap_err();
+}
+
+#[tokio::test]
+async fn proxy_connect() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let port = addr.port();
+
+    let server = async move {
+        let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        acceptor
+            .set_private_key_file("tests/test/key.pem", SslFiletype::PEM)
+            .unwrap();
+        acceptor
+            .set_certificate_chain_file("tests/test/cert.pem")
+            .unwrap();
+        let acceptor = acceptor.build();
+
+        let stream = listener.accept().await.unwrap().0;
+        let stream = tokio_boring::accept(&acceptor, stream).await.unwrap();
+
+        let service = service::service_fn(|_| async {
+            Ok::<_, io::Error>(Response::new(<Empty<Bytes>>::new()))
+        });
+
+        hyper::server::conn::http1::Builder::new()
+            .keep_alive(false)
+            .serve_connection(TokioIo::new(stream), service)
+            .await
+            .unwrap();
+    };
+    tokio::spawn(server);
+
+    let proxy_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_addr = proxy_listener.local_addr().unwrap();
+
+    let proxy = async move {
+        let mut client = proxy_listener.accept().await.unwrap().0;
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0];
+            client.read_exact(&mut byte).await.unwrap();
+            head.push(byte[0]);
+        }
+
+        let mut origin = TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .await
+            .unwrap();
+        let _ = tokio::io::copy_bidirectional(&mut client, &mut origin).await;
+
+        String::from_utf8(head).unwrap()
+    };
+    let proxy = tokio::spawn(proxy);
+
+    let proxy_connector = hyper_boring::ProxyConnector::new(
+        HttpConnector::new(),
+        format!("http://{proxy_addr}").parse().unwrap(),
+    )
+    .unwrap();
+
+    let mut ssl = SslConnector::builder(SslMethod::tls()).unwrap();
+    ssl.set_ca_file("tests/test/root-ca.pem").unwrap();
+
+    let ssl = HttpsConnector::with_connector(proxy_connector, ssl).unwrap();
+    let client = Client::builder(TokioExecutor::new())
+        .pool_max_idle_per_host(0)
+        .build::<_, Empty<Bytes>>(ssl);
+
+    let resp = client
+        .get(format!("https://foobar.com:{}", port).parse().unwrap())
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "{}", resp.status());
+    let mut body = BodyStream::new(resp.into_body());
+    while body.next().await.transpose().unwrap().is_some() {}
+    drop(client);
+
+    let head = proxy.await.unwrap();
+    assert!(
+        head.starts_with(&format!("CONNECT foobar.com:{port} HTTP/1.1\r\n")),
+        "{head}"
+    );
+}
+
+#[tokio::test]
+async fn proxy_connect_tls() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    fn acceptor() -> SslAcceptor {
+        let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        acceptor
+            .set_private_key_file("tests/test/key.pem", SslFiletype::PEM)
+            .unwrap();
+        acceptor
+            .set_certificate_chain_file("tests/test/cert.pem")
+            .unwrap();
+        acceptor.build()
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let port = addr.port();
+
+    let server = async move {
+        let stream = listener.accept().await.unwrap().0;
+        let stream = tokio_boring::accept(&acceptor(), stream).await.unwrap();
+
+        let service = service::service_fn(|_| async {
+            Ok::<_, io::Error>(Response::new(<Empty<Bytes>>::new()))
+        });
+
+        hyper::server::conn::http1::Builder::new()
+            .keep_alive(false)
+            .serve_connection(TokioIo::new(stream), service)
+            .await
+            .unwrap();
+    };
+    tokio::spawn(server);
+
+    let proxy_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_addr = proxy_listener.local_addr().unwrap();
+
+    let proxy = async move {
+        let client = proxy_listener.accept().await.unwrap().0;
+        let mut client = tokio_boring::accept(&acceptor(), client).await.unwrap();
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0];
+            client.read_exact(&mut byte).await.unwrap();
+            head.push(byte[0]);
+        }
+
+        let mut origin = TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .await
+            .unwrap();
+        let _ = tokio::io::copy_bidirectional(&mut client, &mut origin).await;
+
+        String::from_utf8(head).unwrap()
+    };
+    let proxy = tokio::spawn(proxy);
+
+    // The proxy is named foobar.com too, to match its certificate.
+    let resolver =
+        tower::service_fn(move |_name| async move { Ok::<_, Infallible>(iter::once(proxy_addr)) });
+
+    let mut proxy_ssl = SslConnector::builder(SslMethod::tls()).unwrap();
+    proxy_ssl.set_ca_file("tests/test/root-ca.pem").unwrap();
+
+    let proxy_connector = hyper_boring::ProxyConnector::with_tls_connector(
+        HttpConnector::new_with_resolver(resolver),
+        format!("https://foobar.com:{}", proxy_addr.port())
+            .parse()
+            .unwrap(),
+        proxy_ssl.build(),
+    );
+
+    let mut ssl = SslConnector::builder(SslMethod::tls()).unwrap();
+    ssl.set_ca_file("tests/test/root-ca.pem").unwrap();
+
+    let ssl = HttpsConnector::with_connector(proxy_connector, ssl).unwrap();
+    let client = Client::builder(TokioExecutor::new())
+        .pool_max_idle_per_host(0)
+        .build::<_, Empty<Bytes>>(ssl);
+
     let resp = client
         .get(format!("https://foobar.com:{}", port).parse().unwrap())
         .await
@@ -155,4 +427,11 @@ async fn alpn_h2() {
     assert!(resp.status().is_success(), "{}", resp.status());
     let mut body = BodyStream::new(resp.into_body());
     while body.next().await.transpose().unwrap().is_some() {}
+    drop(client);
+
+    let head = proxy.await.unwrap();
+    assert!(
+        head.starts_with(&format!("CONNECT foobar.com:{port} HTTP/1.1\r\n")),
+        "{head}"
+    );
 }
@@ -1,11 +1,13 @@
+use crate::SessionStore;
 use boring::ssl::{
-    AsyncPrivateKeyMethod, AsyncSelectCertError, BoxGetSessionFuture, BoxSelectCertFuture,
-    ClientHello, SslContextBuilder, SslRef,
+    AsyncPrivateKeyMethod, AsyncSelectCertError, BoxCertCallbackFuture, BoxGetSessionFuture,
+    BoxSelectCertFuture, ClientHello, SslContextBuilder, SslRef,
 };
+use std::sync::Arc;
 
 /// Extensions to [`SslContextBuilder`].
 ///
-/// This trait provides additional methods to use async callbacks with boring.
+/// This trait provides additional methods to use async callbacks and session stores with boring.
 pub trait SslContextBuilderExt: private::Sealed {
     /// Sets a callback that is called before most [`ClientHello`] processing
     /// and before the decision whether to resume a session is made. The
@@ -24,6 +26,24 @@ pub trait SslContextBuilderExt: private::Sealed {
             + Sync
             + 'static;
 
+    /// Sets a callback that is called to configure the certificate and private key for a
+    /// connection. On clients, it is called once the server has requested a certificate, which
+    /// lets the client certificate for mutual TLS be chosen, and fetched asynchronously, per
+    /// connection.
+    ///
+    /// This method uses a function that returns a future whose output is
+    /// itself a closure that will be passed the [`SslRef`] to install
+    /// the certificate and key with.
+    ///
+    /// See [`SslContextBuilder::set_cert_callback`] for the sync setter
+    /// of this callback.
+    fn set_async_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<BoxCertCallbackFuture, AsyncSelectCertError>
+            + Send
+            + Sync
+            + 'static;
+
     /// Configures a custom private key method on the context.
     ///
     /// See [`AsyncPrivateKeyMethod`] for more details.
@@ -45,6 +65,19 @@ pub trait SslContextBuilderExt: private::Sealed {
     unsafe fn set_async_get_session_callback<F>(&mut self, callback: F)
     where
         F: Fn(&mut SslRef, &[u8]) -> Option<BoxGetSessionFuture> + Send + Sync + 'static;
+
+    /// Sets the store client sessions are kept in for resumption.
+    ///
+    /// Connections made with [`connect_with_port`](crate::connect_with_port) offer a stored
+    /// session for their server and store the sessions the server issues. This overwrites the
+    /// context's session cache mode and new session callback.
+    ///
+    /// # Safety
+    ///
+    /// The sessions returned by `store` must not be associated with a different [`SslContext`].
+    ///
+    /// [`SslContext`]: boring::ssl::SslContext
+    unsafe fn set_session_store(&mut self, store: impl SessionStore);
 }
 
 impl SslContextBuilderExt for SslContextBuilder {
@@ -58,6 +91,16 @@ impl SslContextBuilderExt for SslContextBuilder {
         self.set_async_select_certificate_callback(callback);
     }
 
+    fn set_async_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<BoxCertCallbackFuture, AsyncSelectCertError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.set_async_cert_callback(callback);
+    }
+
     fn set_async_private_key_method(&mut self, method: impl AsyncPrivateKeyMethod) {
         self.set_async_private_key_method(method);
     }
@@ -68,6 +111,10 @@ impl SslContextBuilderExt for SslContextBuilder {
     {
         self.set_async_get_session_callback(callback);
     }
+
+    unsafe fn set_session_store(&mut self, store: impl SessionStore) {
+        crate::session_store::set_session_store(self, Arc::new(store));
+    }
 }
 
 mod private {
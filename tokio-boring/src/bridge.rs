@@ -8,6 +8,10 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 pub(crate) struct AsyncStreamBridge<S> {
     pub(crate) stream: S,
     waker: Option<Waker>,
+    /// Whether the stream hit EOF before the peer's close_notify.
+    pub(crate) truncated: bool,
+    /// Plaintext handed out by `AsyncBufRead::poll_fill_buf`, still pending in the session.
+    pub(crate) peek_buf: Vec<u8>,
 }
 
 impl<S> AsyncStreamBridge<S> {
@@ -18,6 +22,8 @@ impl<S> AsyncStreamBridge<S> {
         Self {
             stream,
             waker: None,
+            truncated: false,
+            peek_buf: Vec::new(),
         }
     }
 
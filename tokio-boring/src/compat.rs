@@ -0,0 +1,141 @@
+//! TLS over [`futures-io`](futures_io) streams.
+//!
+//! The handshake and stream machinery of this crate only needs the tokio I/O traits, not the
+//! tokio runtime. [`Compat`] adapts any [`futures_io::AsyncRead`] + [`futures_io::AsyncWrite`]
+//! stream, such as those of async-std or smol, to those traits, and [`SslStream`] implements the
+//! `futures-io` traits in turn, so the whole connection can be driven by any executor.
+use crate::{cvt, read_uninit, HandshakeError, SslStream};
+use boring::ssl::{ConnectConfiguration, SslAcceptor};
+use std::io::{self, Write};
+use std::mem::MaybeUninit;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Asynchronously performs a client-side TLS handshake over a `futures-io` stream.
+///
+/// See [`crate::connect`].
+pub async fn connect<S>(
+    config: ConnectConfiguration,
+    domain: &str,
+    stream: S,
+) -> Result<SslStream<Compat<S>>, HandshakeError<Compat<S>>>
+where
+    S: futures_io::AsyncRead + futures_io::AsyncWrite + Unpin,
+{
+    crate::connect(config, domain, Compat::new(stream)).await
+}
+
+/// Asynchronously performs a server-side TLS handshake over a `futures-io` stream.
+///
+/// See [`crate::accept`].
+pub async fn accept<S>(
+    acceptor: &SslAcceptor,
+    stream: S,
+) -> Result<SslStream<Compat<S>>, HandshakeError<Compat<S>>>
+where
+    S: futures_io::AsyncRead + futures_io::AsyncWrite + Unpin,
+{
+    crate::accept(acceptor, Compat::new(stream)).await
+}
+
+/// Adapts a `futures-io` stream to the tokio I/O traits.
+#[derive(Debug)]
+pub struct Compat<S>(S);
+
+impl<S> Compat<S> {
+    /// Wraps `stream`.
+    pub fn new(stream: S) -> Self {
+        Self(stream)
+    }
+
+    /// Returns a shared reference to the wrapped stream.
+    #[must_use]
+    pub fn get_ref(&self) -> &S {
+        &self.0
+    }
+
+    /// Returns a mutable reference to the wrapped stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.0
+    }
+
+    /// Returns the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.0
+    }
+}
+
+impl<S> AsyncRead for Compat<S>
+where
+    S: futures_io::AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let nread =
+            ready!(Pin::new(&mut self.get_mut().0).poll_read(ctx, buf.initialize_unfilled()))?;
+        buf.advance(nread);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> AsyncWrite for Compat<S>
+where
+    S: futures_io::AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(ctx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(ctx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_close(ctx)
+    }
+}
+
+impl<S> futures_io::AsyncRead for SslStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        // SAFETY: read_uninit does not de-initialize the buffer.
+        let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+        self.run_in_context(ctx, |s| cvt(read_uninit(s, buf)))
+    }
+}
+
+impl<S> futures_io::AsyncWrite for SslStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.run_in_context(ctx, |s| cvt(s.write(buf)))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.run_in_context(ctx, |s| cvt(s.flush()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(self, ctx)
+    }
+}
@@ -14,28 +14,36 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 use boring::ssl::{
-    self, ConnectConfiguration, ErrorCode, MidHandshakeSslStream, ShutdownResult, SslAcceptor,
-    SslRef,
+    self, ConnectConfiguration, ErrorCode, HandshakeTimings, MidHandshakeSslStream, ShutdownResult,
+    SslAcceptor, SslRef,
 };
 use boring_sys as ffi;
 use std::error::Error;
 use std::fmt;
-use std::future::Future;
+use std::future::{self, Future};
 use std::io::{self, Write};
+use std::mem::{self, MaybeUninit};
 use std::pin::Pin;
-use std::task::{Context, Poll};
-use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncWrite, ReadBuf};
 
 mod async_callbacks;
 mod bridge;
+#[cfg(feature = "futures-io")]
+pub mod compat;
+pub mod offload;
+pub mod owned;
+mod session_store;
 
 use self::bridge::AsyncStreamBridge;
 
 pub use crate::async_callbacks::SslContextBuilderExt;
+pub use crate::session_store::{LruSessionStore, SessionKey, SessionStore};
 pub use boring::ssl::{
-    AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, AsyncSelectCertError, BoxGetSessionFinish,
-    BoxGetSessionFuture, BoxPrivateKeyMethodFinish, BoxPrivateKeyMethodFuture, BoxSelectCertFinish,
-    BoxSelectCertFuture, ExDataFuture,
+    AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, AsyncSelectCertError, BoxCertCallbackFinish,
+    BoxCertCallbackFuture, BoxGetSessionFinish, BoxGetSessionFuture, BoxPrivateKeyMethodFinish,
+    BoxPrivateKeyMethodFuture, BoxSelectCertFinish, BoxSelectCertFuture, ExDataFuture,
 };
 
 /// Asynchronously performs a client-side TLS handshake over the provided stream.
@@ -52,11 +60,31 @@ where
 {
     let mid_handshake = config
         .setup_connect(domain, AsyncStreamBridge::new(stream))
-        .map_err(|err| HandshakeError(ssl::HandshakeError::SetupFailure(err)))?;
+        .map_err(|err| HandshakeError(ssl::HandshakeError::SetupFailure(err), None))?;
 
     HandshakeFuture(Some(mid_handshake)).await
 }
 
+/// Like [`connect`], but also identifies the server by `port` so sessions can be resumed.
+///
+/// If a [`SessionStore`] was registered on the context with
+/// [`SslContextBuilderExt::set_session_store`], a session stored for `domain` and `port` is offered
+/// to the server, and sessions issued by the server are stored under the same key.
+pub async fn connect_with_port<S>(
+    mut config: ConnectConfiguration,
+    domain: &str,
+    port: u16,
+    stream: S,
+) -> Result<SslStream<S>, HandshakeError<S>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    session_store::prepare(&mut config, domain, port)
+        .map_err(|err| HandshakeError(ssl::HandshakeError::SetupFailure(err), None))?;
+
+    connect(config, domain, stream).await
+}
+
 /// Asynchronously performs a server-side TLS handshake over the provided stream.
 ///
 /// This function automatically sets the task waker on the `Ssl` from `config` to
@@ -67,11 +95,91 @@ where
 {
     let mid_handshake = acceptor
         .setup_accept(AsyncStreamBridge::new(stream))
-        .map_err(|err| HandshakeError(ssl::HandshakeError::SetupFailure(err)))?;
+        .map_err(|err| HandshakeError(ssl::HandshakeError::SetupFailure(err), None))?;
 
     HandshakeFuture(Some(mid_handshake)).await
 }
 
+/// Like [`connect`], but gives up if the handshake does not complete within `timeout`.
+///
+/// On timeout, the returned error reports [`HandshakeError::is_timeout`] and converts to an
+/// [`io::Error`] of kind [`io::ErrorKind::TimedOut`]. The half-open connection is released
+/// when the error is dropped unless it is recovered with [`HandshakeError::into_source_stream`].
+pub async fn connect_with_timeout<S>(
+    config: ConnectConfiguration,
+    domain: &str,
+    stream: S,
+    timeout: Duration,
+) -> Result<SslStream<S>, HandshakeError<S>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mid_handshake = config
+        .setup_connect(domain, AsyncStreamBridge::new(stream))
+        .map_err(|err| HandshakeError(ssl::HandshakeError::SetupFailure(err), None))?;
+
+    HandshakeFuture(Some(mid_handshake))
+        .with_timeout(timeout)
+        .await
+}
+
+/// Like [`accept`], but gives up if the handshake does not complete within `timeout`.
+///
+/// See [`connect_with_timeout`].
+pub async fn accept_with_timeout<S>(
+    acceptor: &SslAcceptor,
+    stream: S,
+    timeout: Duration,
+) -> Result<SslStream<S>, HandshakeError<S>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mid_handshake = acceptor
+        .setup_accept(AsyncStreamBridge::new(stream))
+        .map_err(|err| HandshakeError(ssl::HandshakeError::SetupFailure(err), None))?;
+
+    HandshakeFuture(Some(mid_handshake))
+        .with_timeout(timeout)
+        .await
+}
+
+/// The largest amount of plaintext a single TLS record can carry.
+const MAX_PLAINTEXT_LEN: usize = 16384;
+
+/// Like `ssl::SslStream::read_uninit`, but records whether EOF was reached without a
+/// close_notify.
+fn read_uninit<S>(
+    stream: &mut ssl::SslStream<AsyncStreamBridge<S>>,
+    buf: &mut [MaybeUninit<u8>],
+) -> io::Result<usize>
+where
+    S: AsyncRead + Unpin,
+{
+    read_with(stream, |s| s.ssl_read_uninit(buf))
+}
+
+fn read_with<S, F>(stream: &mut ssl::SslStream<AsyncStreamBridge<S>>, mut f: F) -> io::Result<usize>
+where
+    S: AsyncRead + Unpin,
+    F: FnMut(&mut ssl::SslStream<AsyncStreamBridge<S>>) -> Result<usize, ssl::Error>,
+{
+    loop {
+        match f(stream) {
+            Ok(n) => return Ok(n),
+            Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => return Ok(0),
+            Err(ref e) if e.code() == ErrorCode::SYSCALL && e.io_error().is_none() => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("TLS connection closed without close_notify");
+
+                stream.get_mut().truncated = true;
+                return Ok(0);
+            }
+            Err(ref e) if e.code() == ErrorCode::WANT_READ && e.io_error().is_none() => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
 fn cvt<T>(r: io::Result<T>) -> Poll<io::Result<T>> {
     match r {
         Ok(v) => Poll::Ready(Ok(v)),
@@ -157,6 +265,23 @@ impl<S> SslStream<S> {
         &mut self.0.get_mut().stream
     }
 
+    /// Returns `true` if the underlying stream reached EOF before the peer sent close_notify.
+    ///
+    /// Reads return `Ok(0)` both on a clean close and on truncation; protocols that do not frame
+    /// their own messages should check this afterwards to detect truncation attacks.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.0.get_ref().truncated
+    }
+
+    /// Returns the timings of the handshake of the stream.
+    ///
+    /// See [`SslRef::handshake_timings`].
+    #[must_use]
+    pub fn handshake_timings(&self) -> Option<HandshakeTimings> {
+        self.0.handshake_timings()
+    }
+
     fn run_in_context<F, R>(&mut self, ctx: &mut Context<'_>, f: F) -> R
     where
         F: FnOnce(&mut ssl::SslStream<AsyncStreamBridge<S>>) -> R,
@@ -192,6 +317,62 @@ where
             AsyncStreamBridge::new(stream),
         ))
     }
+
+    /// Reads data into `buf` without removing it from the stream.
+    ///
+    /// The same data is returned by the next read. Like a read, this returns `Ok(0)` at EOF.
+    pub async fn peek(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        future::poll_fn(|ctx| self.poll_peek(ctx, buf)).await
+    }
+
+    /// Attempts to read data into `buf` without removing it from the stream.
+    ///
+    /// See [`peek`](Self::peek).
+    pub fn poll_peek(&mut self, ctx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.run_in_context(ctx, |s| cvt(read_with(s, |s| s.ssl_peek(buf))))
+    }
+
+    /// Shuts down the TLS session, leaving the underlying stream open.
+    ///
+    /// This sends a close_notify alert to the peer. If `wait_for_peer` is set, it then waits up to
+    /// that long for the peer's own close_notify, failing with [`io::ErrorKind::TimedOut`] if it
+    /// does not arrive in time. Application data received while waiting is an error.
+    pub async fn graceful_shutdown(
+        &mut self,
+        wait_for_peer: Option<Duration>,
+    ) -> io::Result<ShutdownResult> {
+        let result = future::poll_fn(|ctx| self.poll_ssl_shutdown(ctx)).await?;
+        future::poll_fn(|ctx| self.run_in_context(ctx, |s| cvt(s.flush()))).await?;
+
+        let Some(timeout) = wait_for_peer else {
+            return Ok(result);
+        };
+        if result == ShutdownResult::Received {
+            return Ok(result);
+        }
+
+        tokio::time::timeout(timeout, future::poll_fn(|ctx| self.poll_ssl_shutdown(ctx)))
+            .await
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "timed out waiting for the peer's close_notify",
+                )
+            })?
+    }
+
+    fn poll_ssl_shutdown(&mut self, ctx: &mut Context<'_>) -> Poll<io::Result<ShutdownResult>> {
+        match self.run_in_context(ctx, |s| s.shutdown()) {
+            Ok(result) => Poll::Ready(Ok(result)),
+            Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => {
+                Poll::Ready(Ok(ShutdownResult::Received))
+            }
+            Err(ref e) if e.code() == ErrorCode::WANT_READ || e.code() == ErrorCode::WANT_WRITE => {
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e.into())),
+        }
+    }
 }
 
 impl<S> AsyncRead for SslStream<S>
@@ -205,7 +386,7 @@ where
     ) -> Poll<io::Result<()>> {
         self.run_in_context(ctx, |s| {
             // SAFETY: read_uninit does not de-initialize the buffer.
-            match cvt(s.read_uninit(unsafe { buf.unfilled_mut() }))? {
+            match cvt(read_uninit(s, unsafe { buf.unfilled_mut() }))? {
                 Poll::Ready(nread) => {
                     unsafe {
                         buf.assume_init(nread);
@@ -219,6 +400,52 @@ where
     }
 }
 
+impl<S> AsyncBufRead for SslStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+
+        let mut buf = mem::take(&mut this.0.get_mut().peek_buf);
+        buf.clear();
+        buf.reserve(MAX_PLAINTEXT_LEN);
+        let result = this.run_in_context(ctx, |s| {
+            cvt(read_with(s, |s| {
+                s.ssl_peek_uninit(buf.spare_capacity_mut())
+            }))
+        });
+        if let Poll::Ready(Ok(n)) = result {
+            // SAFETY: `ssl_peek_uninit` initialized the first `n` bytes of the spare capacity.
+            unsafe { buf.set_len(n) };
+        }
+        this.0.get_mut().peek_buf = buf;
+
+        match result {
+            Poll::Ready(Ok(_)) => Poll::Ready(Ok(&this.0.get_ref().peek_buf)),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+
+        let mut buf = mem::take(&mut this.0.get_mut().peek_buf);
+        assert!(amt <= buf.len(), "consumed more than was filled");
+
+        // The data is already decrypted and pending in the session, so reading it back does not
+        // touch the underlying stream.
+        if amt > 0 {
+            let read = this.0.ssl_read(&mut buf[..amt]);
+            debug_assert_eq!(read.ok(), Some(amt));
+        }
+
+        buf.clear();
+        this.0.get_mut().peek_buf = buf;
+    }
+}
+
 impl<S> AsyncWrite for SslStream<S>
 where
     S: AsyncRead + AsyncWrite + Unpin,
@@ -236,30 +463,24 @@ where
     }
 
     fn poll_shutdown(mut self: Pin<&mut Self>, ctx: &mut Context) -> Poll<io::Result<()>> {
-        match self.run_in_context(ctx, |s| s.shutdown()) {
-            Ok(ShutdownResult::Sent | ShutdownResult::Received) => {}
-            Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => {}
-            Err(ref e) if e.code() == ErrorCode::WANT_READ || e.code() == ErrorCode::WANT_WRITE => {
-                return Poll::Pending;
-            }
-            Err(e) => {
-                return Poll::Ready(Err(e.into_io_error().unwrap_or_else(io::Error::other)));
-            }
-        }
+        ready!(self.poll_ssl_shutdown(ctx))?;
 
         Pin::new(&mut self.0.get_mut().stream).poll_shutdown(ctx)
     }
 }
 
 /// The error type returned after a failed handshake.
-pub struct HandshakeError<S>(ssl::HandshakeError<AsyncStreamBridge<S>>);
+///
+/// A handshake abandoned by a timeout keeps its mid-handshake stream as
+/// `ssl::HandshakeError::WouldBlock`, alongside the `TimedOut` error reported for it.
+pub struct HandshakeError<S>(ssl::HandshakeError<AsyncStreamBridge<S>>, Option<io::Error>);
 
 impl<S> HandshakeError<S> {
     /// Returns a shared reference to the `Ssl` object associated with this error.
     #[must_use]
     pub fn ssl(&self) -> Option<&SslRef> {
         match &self.0 {
-            ssl::HandshakeError::Failure(s) => Some(s.ssl()),
+            ssl::HandshakeError::Failure(s) | ssl::HandshakeError::WouldBlock(s) => Some(s.ssl()),
             _ => None,
         }
     }
@@ -268,7 +489,9 @@ impl<S> HandshakeError<S> {
     #[must_use]
     pub fn into_source_stream(self) -> Option<S> {
         match self.0 {
-            ssl::HandshakeError::Failure(s) => Some(s.into_source_stream().stream),
+            ssl::HandshakeError::Failure(s) | ssl::HandshakeError::WouldBlock(s) => {
+                Some(s.into_source_stream().stream)
+            }
             _ => None,
         }
     }
@@ -277,11 +500,21 @@ impl<S> HandshakeError<S> {
     #[must_use]
     pub fn as_source_stream(&self) -> Option<&S> {
         match &self.0 {
-            ssl::HandshakeError::Failure(s) => Some(&s.get_ref().stream),
+            ssl::HandshakeError::Failure(s) | ssl::HandshakeError::WouldBlock(s) => {
+                Some(&s.get_ref().stream)
+            }
             _ => None,
         }
     }
 
+    /// Returns `true` if the handshake was abandoned because it did not complete in time.
+    ///
+    /// See [`connect_with_timeout`] and [`accept_with_timeout`].
+    #[must_use]
+    pub fn is_timeout(&self) -> bool {
+        self.1.is_some()
+    }
+
     /// Returns the error code, if any.
     #[must_use]
     pub fn code(&self) -> Option<ErrorCode> {
@@ -292,8 +525,14 @@ impl<S> HandshakeError<S> {
     }
 
     /// Returns a reference to the inner I/O error, if any.
+    ///
+    /// This is an error of kind [`io::ErrorKind::TimedOut`] if [`Self::is_timeout`].
     #[must_use]
     pub fn as_io_error(&self) -> Option<&io::Error> {
+        if let Some(err) = &self.1 {
+            return Some(err);
+        }
+
         match &self.0 {
             ssl::HandshakeError::Failure(s) => s.error().io_error(),
             _ => None,
@@ -306,12 +545,21 @@ where
     S: fmt::Debug,
 {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&self.0, fmt)
+        match (&self.0, &self.1) {
+            (ssl::HandshakeError::WouldBlock(s), Some(_)) => {
+                fmt.debug_tuple("TimedOut").field(s).finish()
+            }
+            _ => fmt::Debug::fmt(&self.0, fmt),
+        }
     }
 }
 
 impl<S> fmt::Display for HandshakeError<S> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(err) = &self.1 {
+            return fmt::Display::fmt(err, fmt);
+        }
+
         fmt::Display::fmt(&self.0, fmt)
     }
 }
@@ -321,15 +569,60 @@ where
     S: fmt::Debug,
 {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
+        if let Some(err) = &self.1 {
+            return Some(err);
+        }
+
         self.0.source()
     }
 }
 
+impl<S> From<HandshakeError<S>> for io::Error
+where
+    S: fmt::Debug + Send + Sync + 'static,
+{
+    fn from(e: HandshakeError<S>) -> io::Error {
+        let kind = match (&e.0, &e.1) {
+            (_, Some(err)) => err.kind(),
+            (ssl::HandshakeError::SetupFailure(_), None) => io::ErrorKind::Other,
+            (ssl::HandshakeError::Failure(s), None) => s.error().io_error_kind(),
+            (ssl::HandshakeError::WouldBlock(_), None) => io::ErrorKind::TimedOut,
+        };
+
+        io::Error::new(kind, e)
+    }
+}
+
 /// Future for an ongoing TLS handshake.
 ///
 /// See [`connect`] and [`accept`].
 pub struct HandshakeFuture<S>(Option<MidHandshakeSslStream<AsyncStreamBridge<S>>>);
 
+impl<S> HandshakeFuture<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    async fn with_timeout(mut self, timeout: Duration) -> Result<SslStream<S>, HandshakeError<S>> {
+        match tokio::time::timeout(timeout, &mut self).await {
+            Ok(result) => result,
+            Err(_) => {
+                let mid_handshake = self.0.take().expect("handshake timed out after completion");
+
+                #[cfg(feature = "tracing")]
+                tracing::warn!(?timeout, "TLS handshake timed out");
+
+                Err(HandshakeError(
+                    ssl::HandshakeError::WouldBlock(mid_handshake),
+                    Some(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "TLS handshake timed out",
+                    )),
+                ))
+            }
+        }
+    }
+}
+
 impl<S> Future for HandshakeFuture<S>
 where
     S: AsyncRead + AsyncWrite + Unpin,
@@ -339,6 +632,10 @@ where
     fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
         let mut mid_handshake = self.0.take().expect("future polled after completion");
 
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("tls_handshake", server = mid_handshake.ssl().is_server())
+            .entered();
+
         mid_handshake.get_mut().set_waker(Some(ctx));
         mid_handshake
             .ssl_mut()
@@ -362,12 +659,13 @@ where
             Err(ssl::HandshakeError::Failure(mut mid_handshake)) => {
                 mid_handshake.get_mut().set_waker(None);
 
-                Poll::Ready(Err(HandshakeError(ssl::HandshakeError::Failure(
-                    mid_handshake,
-                ))))
+                Poll::Ready(Err(HandshakeError(
+                    ssl::HandshakeError::Failure(mid_handshake),
+                    None,
+                )))
             }
             Err(err @ ssl::HandshakeError::SetupFailure(_)) => {
-                Poll::Ready(Err(HandshakeError(err)))
+                Poll::Ready(Err(HandshakeError(err, None)))
             }
         }
     }
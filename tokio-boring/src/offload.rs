@@ -0,0 +1,272 @@
+//! Private key operations on a dedicated thread pool.
+//!
+//! Signing with an RSA key takes long enough that a server accepting connections at a high rate
+//! stalls the other tasks of its reactor threads. [`OffloadedPrivateKey`] is an
+//! [`AsyncPrivateKeyMethod`] running these operations on the threads of an [`OffloadPool`]
+//! instead, while the handshakes driven by [`accept`](crate::accept) wait for them.
+//! [`OffloadAcceptor`] wraps an acceptor set up this way.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use boring::pkey::PKey;
+//! use boring::ssl::{SslAcceptor, SslMethod};
+//! use tokio::net::TcpListener;
+//! use tokio_boring::offload::{OffloadAcceptor, OffloadPool};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let pool = OffloadPool::new(4)?;
+//! let key = PKey::private_key_from_pem(&std::fs::read("key.pem")?)?;
+//!
+//! let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+//! acceptor.set_certificate_chain_file("cert.pem")?;
+//! let acceptor = OffloadAcceptor::new(acceptor, pool, key);
+//!
+//! let listener = TcpListener::bind("0.0.0.0:443").await?;
+//! loop {
+//!     let (stream, _) = listener.accept().await?;
+//!     let acceptor = acceptor.clone();
+//!     tokio::spawn(async move {
+//!         let stream = acceptor.accept(stream).await;
+//!         // ...
+//!     });
+//! }
+//! # }
+//! ```
+use boring::error::ErrorStack;
+use boring::pkey::{PKey, PKeyRef, Private};
+use boring::rsa::Padding;
+use boring::sign::{RsaPssSaltlen, Signer};
+use boring::ssl::{
+    AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, BoxPrivateKeyMethodFinish,
+    BoxPrivateKeyMethodFuture, SslAcceptor, SslAcceptorBuilder, SslRef, SslSignatureAlgorithm,
+};
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::oneshot;
+
+use crate::{HandshakeError, SslStream};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A pool of threads running private key operations.
+///
+/// Cloning a pool returns a handle to the same threads, which exit once every handle is dropped.
+#[derive(Debug, Clone)]
+pub struct OffloadPool {
+    jobs: mpsc::Sender<Job>,
+    completed: Arc<AtomicU64>,
+}
+
+impl OffloadPool {
+    /// Spawns a pool of `threads` threads, or one if `threads` is 0.
+    pub fn new(threads: usize) -> io::Result<OffloadPool> {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for i in 0..threads.max(1) {
+            let receiver = receiver.clone();
+
+            thread::Builder::new()
+                .name(format!("tls-offload-{i}"))
+                .spawn(move || loop {
+                    let job = receiver.lock().unwrap().recv();
+                    let Ok(job) = job else {
+                        return;
+                    };
+
+                    job();
+                })?;
+        }
+
+        Ok(OffloadPool {
+            jobs,
+            completed: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Returns the number of operations the threads of the pool have completed without
+    /// panicking.
+    #[must_use]
+    pub fn completed_operations(&self) -> u64 {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    /// Runs `f` on the pool, returning a receiver for its result.
+    ///
+    /// The receiver fails if `f` panics.
+    fn run<T: Send + 'static>(
+        &self,
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> oneshot::Receiver<T> {
+        let (tx, rx) = oneshot::channel();
+        let completed = self.completed.clone();
+
+        // The threads only exit once every sender is dropped, so this cannot fail.
+        let _ = self.jobs.send(Box::new(move || {
+            if let Ok(res) = panic::catch_unwind(AssertUnwindSafe(f)) {
+                completed.fetch_add(1, Ordering::Relaxed);
+                let _ = tx.send(res);
+            }
+        }));
+
+        rx
+    }
+}
+
+/// An [`AsyncPrivateKeyMethod`] signing and decrypting with a private key on an
+/// [`OffloadPool`].
+///
+/// The certificate matching the key must be set on the context as usual, but not the key.
+pub struct OffloadedPrivateKey {
+    pool: OffloadPool,
+    key: PKey<Private>,
+}
+
+impl OffloadedPrivateKey {
+    /// Creates a private key method running the operations of `key` on `pool`.
+    #[must_use]
+    pub fn new(pool: OffloadPool, key: PKey<Private>) -> OffloadedPrivateKey {
+        OffloadedPrivateKey { pool, key }
+    }
+}
+
+impl AsyncPrivateKeyMethod for OffloadedPrivateKey {
+    fn sign(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        let key = self.key.clone();
+        let input = input.to_vec();
+
+        let signature = self
+            .pool
+            .run(move || sign(&key, &input, signature_algorithm));
+
+        Ok(finish(signature))
+    }
+
+    fn decrypt(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        let rsa = self.key.rsa().map_err(|_| AsyncPrivateKeyMethodError)?;
+        let input = input.to_vec();
+
+        let plaintext = self.pool.run(move || {
+            let mut output = vec![0; rsa.size() as usize];
+            let len = rsa.private_decrypt(&input, &mut output, Padding::NONE)?;
+            output.truncate(len);
+
+            Ok(output)
+        });
+
+        Ok(finish(plaintext))
+    }
+}
+
+/// An [`SslAcceptor`] running the private key operations of its handshakes on an
+/// [`OffloadPool`].
+///
+/// Cloning an acceptor returns a handle to the same context and pool.
+#[derive(Clone)]
+pub struct OffloadAcceptor {
+    acceptor: SslAcceptor,
+}
+
+impl OffloadAcceptor {
+    /// Builds an acceptor from `builder`, running the operations of `key` on `pool`.
+    ///
+    /// The certificate matching the key must be set on `builder`, but not the key.
+    #[must_use]
+    pub fn new(
+        mut builder: SslAcceptorBuilder,
+        pool: OffloadPool,
+        key: PKey<Private>,
+    ) -> OffloadAcceptor {
+        builder.set_async_private_key_method(OffloadedPrivateKey::new(pool, key));
+
+        OffloadAcceptor {
+            acceptor: builder.build(),
+        }
+    }
+
+    /// Returns the underlying acceptor.
+    #[must_use]
+    pub fn acceptor(&self) -> &SslAcceptor {
+        &self.acceptor
+    }
+
+    /// Asynchronously performs a server-side TLS handshake over the provided stream.
+    ///
+    /// See [`accept`](crate::accept).
+    pub async fn accept<S>(&self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        crate::accept(&self.acceptor, stream).await
+    }
+
+    /// Like [`Self::accept`], but gives up if the handshake does not complete within `timeout`.
+    ///
+    /// See [`accept_with_timeout`](crate::accept_with_timeout).
+    pub async fn accept_with_timeout<S>(
+        &self,
+        stream: S,
+        timeout: Duration,
+    ) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        crate::accept_with_timeout(&self.acceptor, stream, timeout).await
+    }
+}
+
+fn sign(
+    key: &PKeyRef<Private>,
+    input: &[u8],
+    signature_algorithm: SslSignatureAlgorithm,
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut signer = match signature_algorithm.digest() {
+        Some(digest) => Signer::new(digest, key)?,
+        None => Signer::new_without_digest(key)?,
+    };
+
+    if signature_algorithm.is_rsa_pss() {
+        signer.set_rsa_padding(Padding::PKCS1_PSS)?;
+        signer.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+    }
+
+    signer.sign_oneshot_to_vec(input)
+}
+
+fn finish(result: oneshot::Receiver<Result<Vec<u8>, ErrorStack>>) -> BoxPrivateKeyMethodFuture {
+    Box::pin(async move {
+        let result = result
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .ok_or(AsyncPrivateKeyMethodError)?;
+
+        let finish: BoxPrivateKeyMethodFinish = Box::new(move |_, output| {
+            output
+                .get_mut(..result.len())
+                .ok_or(AsyncPrivateKeyMethodError)?
+                .copy_from_slice(&result);
+
+            Ok(result.len())
+        });
+
+        Ok(finish)
+    })
+}
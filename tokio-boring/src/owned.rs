@@ -0,0 +1,370 @@
+//! TLS over completion-based I/O.
+//!
+//! Completion-based runtimes such as tokio-uring and monoio take ownership of the buffers passed
+//! to reads and writes for as long as the operation runs, so they cannot implement the
+//! poll-based tokio I/O traits. [`OwnedSslStream`] instead keeps the TLS records of a connection
+//! in memory and moves them through an [`OwnedIo`] transport with owned buffers. Buffers are
+//! handed back and forth between the transport and the TLS session rather than copied, and can be
+//! recycled across connections with a [`BufferPool`].
+use boring::ssl::{self, ConnectConfiguration, ErrorCode, Ssl, SslAcceptor, SslRef};
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::mem::{self, ManuallyDrop};
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+/// The capacity of the buffers lent to [`OwnedIo::read`].
+const READ_BUF_CAPACITY: usize = 16 * 1024;
+
+/// A transport reading and writing with owned buffers.
+pub trait OwnedIo {
+    /// Reads from the transport into the spare capacity of `buf`, which is empty.
+    ///
+    /// Implementations return the number of bytes read, `0` meaning EOF, along with the buffer,
+    /// whose length must be set to the number of bytes read.
+    fn read(&mut self, buf: Vec<u8>) -> impl Future<Output = (io::Result<usize>, Vec<u8>)>;
+
+    /// Writes the contents of `buf` to the transport.
+    ///
+    /// Implementations return the number of bytes written, which may be less than the length of
+    /// the buffer, along with the buffer.
+    fn write(&mut self, buf: Vec<u8>) -> impl Future<Output = (io::Result<usize>, Vec<u8>)>;
+
+    /// Returns an empty buffer for a stream over the transport to hold records in.
+    ///
+    /// Streams take three buffers when they are created. Implementations can take them from a
+    /// [`BufferPool`] instead of allocating new ones.
+    fn alloc_buffer(&mut self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Takes back a buffer of a stream over the transport that is dropped.
+    ///
+    /// Implementations can put it back in a [`BufferPool`] instead of freeing it.
+    fn release_buffer(&mut self, buf: Vec<u8>) {
+        drop(buf);
+    }
+}
+
+/// A pool of buffers shared by the streams of many connections.
+///
+/// Cloning a pool returns a handle to the same buffers.
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+    max_buffers: usize,
+}
+
+impl BufferPool {
+    /// Creates a pool keeping up to `max_buffers` buffers around.
+    #[must_use]
+    pub fn new(max_buffers: usize) -> BufferPool {
+        BufferPool {
+            buffers: Arc::default(),
+            max_buffers,
+        }
+    }
+
+    /// Takes an empty buffer from the pool, or allocates one if the pool is empty.
+    #[must_use]
+    pub fn get(&self) -> Vec<u8> {
+        self.buffers.lock().unwrap().pop().unwrap_or_default()
+    }
+
+    /// Puts a buffer back in the pool, unless the pool is full.
+    pub fn put(&self, mut buf: Vec<u8>) {
+        if buf.capacity() == 0 {
+            return;
+        }
+        buf.clear();
+
+        let mut buffers = self.buffers.lock().unwrap();
+        if buffers.len() < self.max_buffers {
+            buffers.push(buf);
+        }
+    }
+
+    /// Returns the number of buffers in the pool.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.buffers.lock().unwrap().len()
+    }
+
+    /// Returns `true` if the pool holds no buffers.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The in-memory transport of the TLS session.
+#[derive(Debug, Default)]
+struct MemoryBio {
+    incoming: Vec<u8>,
+    pos: usize,
+    outgoing: Vec<u8>,
+    eof: bool,
+}
+
+impl Read for MemoryBio {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = &self.incoming[self.pos..];
+        if available.is_empty() {
+            return if self.eof {
+                Ok(0)
+            } else {
+                Err(io::ErrorKind::WouldBlock.into())
+            };
+        }
+
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.pos += n;
+
+        Ok(n)
+    }
+}
+
+impl Write for MemoryBio {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.outgoing.extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A TLS stream over an [`OwnedIo`] transport.
+///
+/// Its buffers are released to the transport with [`OwnedIo::release_buffer`] when it is dropped.
+///
+/// # Cancel safety
+///
+/// None of the methods of this type are cancel safe. The transport owns the records being read
+/// or written while an operation runs, so cancelling the operation loses them. A stream whose
+/// operation was cancelled while the transport held its records fails every later operation
+/// instead of carrying on with a corrupted TLS session.
+pub struct OwnedSslStream<S: OwnedIo> {
+    inner: ssl::SslStream<MemoryBio>,
+    io: S,
+    read_buf: Vec<u8>,
+    io_pending: bool,
+}
+
+impl<S> OwnedSslStream<S>
+where
+    S: OwnedIo,
+{
+    /// Performs a client-side TLS handshake over `io`.
+    ///
+    /// See [`crate::connect`].
+    pub async fn connect(
+        config: ConnectConfiguration,
+        domain: &str,
+        io: S,
+    ) -> io::Result<OwnedSslStream<S>> {
+        let mut ssl = config.into_ssl(domain).map_err(io::Error::other)?;
+        ssl.set_connect_state();
+
+        OwnedSslStream::handshake(ssl, io).await
+    }
+
+    /// Performs a server-side TLS handshake over `io`.
+    ///
+    /// See [`crate::accept`].
+    pub async fn accept(acceptor: &SslAcceptor, io: S) -> io::Result<OwnedSslStream<S>> {
+        let mut ssl = Ssl::new(acceptor.context()).map_err(io::Error::other)?;
+        ssl.set_accept_state();
+
+        OwnedSslStream::handshake(ssl, io).await
+    }
+
+    /// Performs the TLS handshake of `ssl` over `io`.
+    ///
+    /// The connect or accept state of `ssl` must have been set.
+    pub async fn handshake(ssl: Ssl, mut io: S) -> io::Result<OwnedSslStream<S>> {
+        let bio = MemoryBio {
+            incoming: io.alloc_buffer(),
+            outgoing: io.alloc_buffer(),
+            ..MemoryBio::default()
+        };
+        let inner = ssl::SslStream::new(ssl, bio).map_err(io::Error::other)?;
+
+        let mut stream = OwnedSslStream {
+            inner,
+            read_buf: io.alloc_buffer(),
+            io,
+            io_pending: false,
+        };
+        stream.drive(|s| s.do_handshake()).await?;
+
+        Ok(stream)
+    }
+
+    /// Reads decrypted data into `buf`, returning the number of bytes read.
+    ///
+    /// Returns `0` once the peer has closed the TLS session.
+    pub async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.drive(|s| match s.ssl_read(buf) {
+            Err(e) if e.code() == ErrorCode::ZERO_RETURN => Ok(0),
+            res => res,
+        })
+        .await
+    }
+
+    /// Encrypts and writes data from `buf`, returning the number of bytes written.
+    pub async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.drive(|s| s.ssl_write(buf)).await
+    }
+
+    /// Writes all of `buf`.
+    pub async fn write_all(&mut self, mut buf: &[u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            let n = self.write(buf).await?;
+            buf = &buf[n..];
+        }
+
+        Ok(())
+    }
+
+    /// Sends a `close_notify` alert to the peer.
+    ///
+    /// The transport is not closed.
+    pub async fn shutdown(&mut self) -> io::Result<()> {
+        self.drive(|s| s.shutdown()).await.map(|_| ())
+    }
+
+    /// Runs `f` until it no longer needs more data from the transport, sending the records it
+    /// produces as it goes.
+    async fn drive<T>(
+        &mut self,
+        mut f: impl FnMut(&mut ssl::SslStream<MemoryBio>) -> Result<T, ssl::Error>,
+    ) -> io::Result<T> {
+        if self.io_pending {
+            return Err(io::Error::other(
+                "a previous operation on the stream was cancelled",
+            ));
+        }
+
+        loop {
+            let res = f(&mut self.inner);
+            self.flush_outgoing().await?;
+
+            match res {
+                Ok(value) => return Ok(value),
+                Err(e) if e.code() == ErrorCode::WANT_READ => self.fill_incoming().await?,
+                // Writes to memory always succeed, so `f` only has to be retried.
+                Err(e) if e.code() == ErrorCode::WANT_WRITE => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    async fn flush_outgoing(&mut self) -> io::Result<()> {
+        while !self.inner.get_ref().outgoing.is_empty() {
+            let outgoing = mem::take(&mut self.inner.get_mut().outgoing);
+            // The records are lost if this future is dropped before the write completes.
+            self.io_pending = true;
+            let (res, mut outgoing) = self.io.write(outgoing).await;
+            self.io_pending = false;
+
+            let written = match res {
+                Ok(0) => Err(io::ErrorKind::WriteZero.into()),
+                res => res,
+            };
+            if let Ok(n) = written {
+                outgoing.drain(..n);
+            }
+            self.inner.get_mut().outgoing = outgoing;
+
+            written?;
+        }
+
+        Ok(())
+    }
+
+    async fn fill_incoming(&mut self) -> io::Result<()> {
+        let mut buf = mem::take(&mut self.read_buf);
+        buf.clear();
+        buf.reserve(READ_BUF_CAPACITY);
+
+        // Records read by the transport are lost if this future is dropped before the read
+        // completes.
+        self.io_pending = true;
+        let (res, buf) = self.io.read(buf).await;
+        self.io_pending = false;
+
+        let bio = self.inner.get_mut();
+        if bio.pos == bio.incoming.len() {
+            // Everything received so far has been consumed, so the buffer can be swapped in
+            // whole, and the old one lent to the next read.
+            self.read_buf = mem::replace(&mut bio.incoming, buf);
+            bio.pos = 0;
+        } else {
+            bio.incoming.extend_from_slice(&buf);
+            self.read_buf = buf;
+        }
+
+        if res? == 0 {
+            bio.eof = true;
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: OwnedIo> OwnedSslStream<S> {
+    /// Returns a shared reference to the `Ssl` object associated with this stream.
+    #[must_use]
+    pub fn ssl(&self) -> &SslRef {
+        self.inner.ssl()
+    }
+
+    /// Returns a shared reference to the underlying transport.
+    #[must_use]
+    pub fn get_ref(&self) -> &S {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the underlying transport.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.io
+    }
+
+    /// Returns the underlying transport.
+    ///
+    /// Records that were received but not yet decrypted are lost.
+    pub fn into_inner(self) -> S {
+        let mut this = ManuallyDrop::new(self);
+        this.release_buffers();
+
+        // SAFETY: `this` is never dropped, so `inner` and `io` are dropped or moved out exactly
+        // once. `read_buf` was emptied by `release_buffers` and needs no drop.
+        unsafe {
+            ptr::drop_in_place(&mut this.inner);
+            ptr::read(&this.io)
+        }
+    }
+
+    fn release_buffers(&mut self) {
+        let bio = self.inner.get_mut();
+        let buffers = [
+            mem::take(&mut bio.incoming),
+            mem::take(&mut bio.outgoing),
+            mem::take(&mut self.read_buf),
+        ];
+
+        for buf in buffers {
+            self.io.release_buffer(buf);
+        }
+    }
+}
+
+impl<S: OwnedIo> Drop for OwnedSslStream<S> {
+    fn drop(&mut self) {
+        self.release_buffers();
+    }
+}
@@ -0,0 +1,168 @@
+//! Client-side session storage for TLS resumption.
+use boring::error::ErrorStack;
+use boring::ex_data::Index;
+use boring::ssl::{
+    ConnectConfiguration, Ssl, SslContext, SslContextBuilder, SslSession, SslSessionCacheMode,
+    SslVersion,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock, Mutex};
+
+static STORE_INDEX: LazyLock<Index<SslContext, Arc<dyn SessionStore>>> =
+    LazyLock::new(|| SslContext::new_ex_index().unwrap());
+static KEY_INDEX: LazyLock<Index<Ssl, SessionKey>> = LazyLock::new(|| Ssl::new_ex_index().unwrap());
+
+/// Identifies the server a session was established with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SessionKey {
+    /// The server's host name.
+    pub host: String,
+    /// The server's port.
+    pub port: u16,
+}
+
+/// Storage for sessions that clients can resume.
+///
+/// Register a store with [`SslContextBuilderExt::set_session_store`], and connections made with
+/// [`connect_with_port`](crate::connect_with_port) will offer a stored session for their server
+/// and store the sessions it issues.
+///
+/// [`SslContextBuilderExt::set_session_store`]: crate::SslContextBuilderExt::set_session_store
+pub trait SessionStore: Send + Sync + 'static {
+    /// Returns a session to resume with the server identified by `key`.
+    ///
+    /// TLS 1.3 sessions should only be returned once, see [RFC 8446 appendix C.4].
+    ///
+    /// [RFC 8446 appendix C.4]: https://www.rfc-editor.org/rfc/rfc8446#appendix-C.4
+    fn get(&self, key: &SessionKey) -> Option<SslSession>;
+
+    /// Stores a session issued by the server identified by `key`.
+    fn put(&self, key: SessionKey, session: SslSession);
+}
+
+/// An in-memory [`SessionStore`] keeping the most recent session of a bounded number of servers.
+///
+/// When full, the session of the least recently used server is evicted.
+pub struct LruSessionStore {
+    capacity: usize,
+    inner: Mutex<LruInner>,
+}
+
+struct LruInner {
+    sessions: HashMap<SessionKey, (SslSession, u64)>,
+    tick: u64,
+}
+
+impl LruSessionStore {
+    /// Creates a store holding sessions for up to `capacity` servers.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(LruInner {
+                sessions: HashMap::new(),
+                tick: 0,
+            }),
+        }
+    }
+
+    /// Returns the number of servers with a stored session.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().sessions.len()
+    }
+
+    /// Returns `true` if no sessions are stored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SessionStore for LruSessionStore {
+    fn get(&self, key: &SessionKey) -> Option<SslSession> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+
+        let (session, last_used) = inner.sessions.get_mut(key)?;
+        if session.protocol_version() == SslVersion::TLS1_3 {
+            return inner.sessions.remove(key).map(|(session, _)| session);
+        }
+
+        *last_used = tick;
+        Some(session.clone())
+    }
+
+    fn put(&self, key: SessionKey, session: SslSession) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+
+        if inner.sessions.len() >= self.capacity && !inner.sessions.contains_key(&key) {
+            let oldest = inner
+                .sessions
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                inner.sessions.remove(&oldest);
+            }
+        }
+
+        inner.sessions.insert(key, (session, tick));
+    }
+}
+
+impl fmt::Debug for LruSessionStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LruSessionStore")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+pub(crate) fn set_session_store(builder: &mut SslContextBuilder, store: Arc<dyn SessionStore>) {
+    builder.set_session_cache_mode(SslSessionCacheMode::CLIENT);
+    builder.set_new_session_callback({
+        let store = store.clone();
+        move |ssl, session| {
+            if let Some(key) = ssl.ex_data(*KEY_INDEX) {
+                store.put(key.clone(), session);
+            }
+        }
+    });
+    builder.set_ex_data(*STORE_INDEX, store);
+}
+
+/// Offers a stored session for `host` and `port`, and tags the connection so that new sessions
+/// are stored under the same key.
+pub(crate) fn prepare(
+    config: &mut ConnectConfiguration,
+    host: &str,
+    port: u16,
+) -> Result<(), ErrorStack> {
+    let Some(store) = config.ssl_context().ex_data(*STORE_INDEX).cloned() else {
+        return Ok(());
+    };
+
+    let key = SessionKey {
+        host: host.to_owned(),
+        port,
+    };
+
+    if let Some(session) = store.get(&key) {
+        // SAFETY: `set_session_store` requires the store's sessions to belong to this context.
+        unsafe { config.set_session(&session)? };
+    }
+
+    config.set_ex_data(*KEY_INDEX, key);
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use boring::pkey::PKey;
+use boring::ssl::{SslRef, SslVerifyMode};
+use boring::x509::X509;
+use futures::future;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::task::yield_now;
+use tokio_boring::{AsyncSelectCertError, BoxCertCallbackFinish, SslContextBuilderExt};
+
+mod common;
+
+use self::common::{connect, create_server};
+
+fn require_client_cert(builder: &mut boring::ssl::SslAcceptorBuilder) {
+    builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    builder.set_ca_file("tests/cert.pem").unwrap();
+}
+
+#[tokio::test]
+async fn test_async_cert_callback_client_certificate() {
+    let (stream, addr) = create_server(require_client_cert);
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+        assert!(stream.ssl().peer_certificate().is_some());
+
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"asdf");
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| {
+            builder.set_async_cert_callback(|_| {
+                Ok(Box::pin(async {
+                    yield_now().await;
+
+                    let cert = X509::from_pem(include_bytes!("cert.pem")).unwrap();
+                    let key = PKey::private_key_from_pem(include_bytes!("key.pem")).unwrap();
+
+                    Ok(Box::new(move |ssl: &mut SslRef| {
+                        ssl.set_certificate(&cert).or(Err(AsyncSelectCertError))?;
+                        ssl.set_private_key(&key).or(Err(AsyncSelectCertError))
+                    }) as BoxCertCallbackFinish)
+                }))
+            });
+
+            builder.set_ca_file("tests/cert.pem")
+        })
+        .await
+        .unwrap();
+
+        stream.write_all(b"asdf").await.unwrap();
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn test_async_cert_callback_future_error() {
+    let (stream, addr) = create_server(require_client_cert);
+
+    let server = async {
+        let _err = stream.await.unwrap_err();
+    };
+
+    let client = async {
+        let _err = connect(addr, |builder| {
+            builder.set_async_cert_callback(|_| {
+                Ok(Box::pin(async {
+                    yield_now().await;
+
+                    Err(AsyncSelectCertError)
+                }))
+            });
+
+            builder.set_ca_file("tests/cert.pem")
+        })
+        .await
+        .unwrap_err();
+    };
+
+    future::join(server, client).await;
+}
@@ -1,4 +1,4 @@
-use boring::ssl::ClientHello;
+use boring::ssl::{ClientHello, NameType, SslContext, SslFiletype, SslMethod};
 use futures::future;
 use tokio::task::yield_now;
 use tokio_boring::{AsyncSelectCertError, BoxSelectCertFinish, BoxSelectCertFuture};
@@ -33,6 +33,35 @@ async fn test_async_select_certificate_callback_yield() {
     .await;
 }
 
+#[tokio::test]
+async fn test_async_select_certificate_callback_resolves_context() {
+    with_trivial_client_server_exchange(|builder| {
+        builder.set_async_select_certificate_callback(|hello| {
+            let servername = hello.servername(NameType::HOST_NAME).map(str::to_owned);
+
+            Ok(Box::pin(async move {
+                assert_eq!(servername.as_deref(), Some("localhost"));
+                // Stands in for fetching or issuing the certificate of the server name.
+                yield_now().await;
+
+                let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+                ctx.set_private_key_file("tests/key.pem", SslFiletype::PEM)
+                    .unwrap();
+                ctx.set_certificate_chain_file("tests/cert.pem").unwrap();
+                let ctx = ctx.build();
+
+                Ok(Box::new(move |mut hello: ClientHello<'_>| {
+                    hello
+                        .ssl_mut()
+                        .set_ssl_context(&ctx)
+                        .map_err(|_| AsyncSelectCertError)
+                }) as BoxSelectCertFinish)
+            }))
+        });
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn test_async_select_certificate_callback_return_error() {
     with_async_select_certificate_callback_error(|_| Err(AsyncSelectCertError)).await;
@@ -1,12 +1,16 @@
-use boring::ssl::{SslConnector, SslMethod};
+use boring::ssl::{ShutdownResult, SslConnector, SslMethod};
 use futures::future;
+use std::io;
 use std::net::ToSocketAddrs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 
 mod common;
 
-use self::common::{connect, create_server, with_trivial_client_server_exchange};
+use self::common::{
+    connect, create_connector, create_listener, create_server, with_trivial_client_server_exchange,
+};
 
 #[tokio::test]
 async fn google() {
@@ -57,3 +61,129 @@ async fn handshake_error() {
 
     future::join(server, client).await;
 }
+
+#[tokio::test]
+async fn handshake_timeout() {
+    let (listener, addr) = create_listener();
+
+    let server = async {
+        // Accept the TCP connection but never answer the ClientHello.
+        let (stream, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        drop(stream);
+    };
+
+    let client = async {
+        let config = create_connector(|_| Ok(())).configure().unwrap();
+        let stream = TcpStream::connect(&addr).await.unwrap();
+
+        let err = tokio_boring::connect_with_timeout(
+            config,
+            "localhost",
+            stream,
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.is_timeout());
+        assert_eq!(err.to_string(), "TLS handshake timed out");
+        assert_eq!(
+            err.as_io_error().map(io::Error::kind),
+            Some(io::ErrorKind::TimedOut)
+        );
+        assert!(err.as_source_stream().is_some());
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::TimedOut);
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn graceful_shutdown() {
+    let (stream, addr) = create_server(|_| ());
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+        stream.write_all(b"jkl;").await.unwrap();
+
+        let result = stream
+            .graceful_shutdown(Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert_eq!(result, ShutdownResult::Received);
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+
+        let mut buf = vec![];
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"jkl;");
+        assert!(!stream.is_truncated());
+
+        stream.graceful_shutdown(None).await.unwrap();
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn truncated_read() {
+    let (stream, addr) = create_server(|_| ());
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+        stream.write_all(b"jkl;").await.unwrap();
+
+        // Close the transport without sending close_notify.
+        stream.get_mut().shutdown().await.unwrap();
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+
+        let mut buf = vec![];
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"jkl;");
+        assert!(stream.is_truncated());
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn peek_and_buf_read() {
+    let (stream, addr) = create_server(|_| ());
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+
+        let mut buf = [0; 3];
+        let n = stream.peek(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], &b"GET"[..n]);
+
+        let mut line = String::new();
+        stream.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "GET / HTTP/1.1\r\n");
+
+        let mut rest = [0; 4];
+        stream.read_exact(&mut rest).await.unwrap();
+        assert_eq!(&rest, b"asdf");
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+
+        stream.write_all(b"GET / HTTP/1.1\r\nasdf").await.unwrap();
+        stream.flush().await.unwrap();
+    };
+
+    future::join(server, client).await;
+}
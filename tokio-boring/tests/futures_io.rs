@@ -0,0 +1,50 @@
+#![cfg(feature = "futures-io")]
+
+use futures::io::{AllowStdIo, AsyncReadExt, AsyncWriteExt};
+use std::thread;
+
+mod common;
+
+use self::common::{create_connector, create_server};
+
+#[tokio::test]
+async fn futures_io_client() {
+    let (stream, addr) = create_server(|_| ());
+
+    let client = thread::spawn(move || {
+        let config = create_connector(|builder| builder.set_ca_file("tests/cert.pem"))
+            .configure()
+            .unwrap();
+        let stream = AllowStdIo::new(std::net::TcpStream::connect(addr).unwrap());
+
+        futures::executor::block_on(async {
+            let mut stream = tokio_boring::compat::connect(config, "localhost", stream)
+                .await
+                .unwrap();
+
+            stream.write_all(b"asdf").await.unwrap();
+
+            let mut buf = vec![];
+            stream.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"jkl;");
+        });
+    });
+
+    let mut stream = stream.await.unwrap();
+    let mut buf = [0; 4];
+    tokio::io::AsyncReadExt::read_exact(&mut stream, &mut buf)
+        .await
+        .unwrap();
+    assert_eq!(&buf, b"asdf");
+
+    tokio::io::AsyncWriteExt::write_all(&mut stream, b"jkl;")
+        .await
+        .unwrap();
+    tokio::io::AsyncWriteExt::shutdown(&mut stream)
+        .await
+        .unwrap();
+
+    tokio::task::spawn_blocking(move || client.join().unwrap())
+        .await
+        .unwrap();
+}
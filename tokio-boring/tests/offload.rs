@@ -0,0 +1,60 @@
+use boring::pkey::PKey;
+use boring::ssl::{SslAcceptor, SslMethod};
+use futures::future;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_boring::offload::{OffloadAcceptor, OffloadPool, OffloadedPrivateKey};
+
+mod common;
+
+use self::common::{connect, create_listener, with_trivial_client_server_exchange};
+
+#[tokio::test]
+async fn offloaded_private_key() {
+    let pool = OffloadPool::new(2).unwrap();
+    let key = PKey::private_key_from_pem(include_bytes!("key.pem")).unwrap();
+
+    let method = OffloadedPrivateKey::new(pool.clone(), key);
+    with_trivial_client_server_exchange(|builder| {
+        builder.set_async_private_key_method(method);
+    })
+    .await;
+
+    // The server signed its handshake on the pool.
+    assert_eq!(pool.completed_operations(), 1);
+}
+
+#[tokio::test]
+async fn offload_acceptor() {
+    let pool = OffloadPool::new(2).unwrap();
+    let key = PKey::private_key_from_pem(include_bytes!("key.pem")).unwrap();
+
+    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+    builder
+        .set_certificate_chain_file("tests/cert.pem")
+        .unwrap();
+    let acceptor = OffloadAcceptor::new(builder, pool.clone(), key);
+
+    let (listener, addr) = create_listener();
+
+    let server = async {
+        let stream = listener.accept().await.unwrap().0;
+        let mut stream = acceptor.accept(stream).await.unwrap();
+
+        stream.write_all(b"asdf").await.unwrap();
+        stream.shutdown().await.unwrap();
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+
+        let mut buf = vec![];
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"asdf");
+    };
+
+    future::join(server, client).await;
+
+    assert_eq!(pool.completed_operations(), 1);
+}
@@ -0,0 +1,153 @@
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_boring::owned::{BufferPool, OwnedIo, OwnedSslStream};
+
+mod common;
+
+use self::common::{create_connector, create_server};
+
+// Lends buffers the way completion-based runtimes do, on top of a regular tokio stream.
+struct Owned(TcpStream);
+
+impl OwnedIo for Owned {
+    async fn read(&mut self, mut buf: Vec<u8>) -> (io::Result<usize>, Vec<u8>) {
+        buf.resize(buf.capacity(), 0);
+        let res = self.0.read(&mut buf).await;
+        buf.truncate(*res.as_ref().unwrap_or(&0));
+        (res, buf)
+    }
+
+    async fn write(&mut self, buf: Vec<u8>) -> (io::Result<usize>, Vec<u8>) {
+        let res = self.0.write(&buf).await;
+        (res, buf)
+    }
+}
+
+struct Pooled(Owned, BufferPool);
+
+impl OwnedIo for Pooled {
+    async fn read(&mut self, buf: Vec<u8>) -> (io::Result<usize>, Vec<u8>) {
+        self.0.read(buf).await
+    }
+
+    async fn write(&mut self, buf: Vec<u8>) -> (io::Result<usize>, Vec<u8>) {
+        self.0.write(buf).await
+    }
+
+    fn alloc_buffer(&mut self) -> Vec<u8> {
+        self.1.get()
+    }
+
+    fn release_buffer(&mut self, buf: Vec<u8>) {
+        self.1.put(buf);
+    }
+}
+
+#[tokio::test]
+async fn owned_buffers() {
+    let (stream, addr) = create_server(|_| {});
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"asdf");
+
+        stream.write_all(b"jkl;").await.unwrap();
+        stream.shutdown().await.unwrap();
+    };
+
+    let client = async {
+        let config = create_connector(|builder| builder.set_ca_file("tests/cert.pem"))
+            .configure()
+            .unwrap();
+        let stream = TcpStream::connect(&addr).await.unwrap();
+        let mut stream = OwnedSslStream::connect(config, "localhost", Owned(stream))
+            .await
+            .unwrap();
+
+        stream.write_all(b"asdf").await.unwrap();
+
+        let mut buf = vec![];
+        let mut chunk = [0; 16];
+        loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        assert_eq!(buf, b"jkl;");
+    };
+
+    futures::future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn pooled_buffers() {
+    let pool = BufferPool::new(8);
+
+    for _ in 0..2 {
+        let (stream, addr) = create_server(|_| {});
+
+        let server = async {
+            let mut stream = stream.await.unwrap();
+            stream.write_all(b"asdf").await.unwrap();
+        };
+
+        let client = async {
+            let config = create_connector(|builder| builder.set_ca_file("tests/cert.pem"))
+                .configure()
+                .unwrap();
+            let stream = TcpStream::connect(&addr).await.unwrap();
+            let io = Pooled(Owned(stream), pool.clone());
+            let mut stream = OwnedSslStream::connect(config, "localhost", io)
+                .await
+                .unwrap();
+
+            let mut buf = [0; 4];
+            let n = stream.read(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], &b"asdf"[..n]);
+        };
+
+        futures::future::join(server, client).await;
+
+        // The second connection reuses the buffers of the first.
+        assert!(!pool.is_empty());
+        assert!(pool.len() <= 3);
+    }
+}
+
+#[tokio::test]
+async fn cancelled_read() {
+    let (stream, addr) = create_server(|_| {});
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+        // Waits for the client to go away without sending anything.
+        let _ = stream.read(&mut [0]).await;
+    };
+
+    let client = async {
+        let config = create_connector(|builder| builder.set_ca_file("tests/cert.pem"))
+            .configure()
+            .unwrap();
+        let stream = TcpStream::connect(&addr).await.unwrap();
+        let mut stream = OwnedSslStream::connect(config, "localhost", Owned(stream))
+            .await
+            .unwrap();
+
+        let mut buf = [0; 4];
+        tokio::time::timeout(Duration::from_millis(100), stream.read(&mut buf))
+            .await
+            .unwrap_err();
+
+        // The transport may have read records the cancelled read never got back.
+        stream.read(&mut buf).await.unwrap_err();
+        stream.write_all(b"asdf").await.unwrap_err();
+    };
+
+    futures::future::join(server, client).await;
+}
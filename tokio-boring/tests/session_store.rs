@@ -0,0 +1,65 @@
+use futures::future;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_boring::{LruSessionStore, SessionKey, SessionStore, SslContextBuilderExt};
+
+mod common;
+
+use self::common::{create_acceptor, create_connector, create_listener};
+
+struct SharedStore(Arc<LruSessionStore>);
+
+impl SessionStore for SharedStore {
+    fn get(&self, key: &SessionKey) -> Option<boring::ssl::SslSession> {
+        self.0.get(key)
+    }
+
+    fn put(&self, key: SessionKey, session: boring::ssl::SslSession) {
+        self.0.put(key, session);
+    }
+}
+
+#[tokio::test]
+async fn session_store_resumption() {
+    let (listener, addr) = create_listener();
+    let acceptor = create_acceptor(|_| ());
+
+    let server = async {
+        for _ in 0..2 {
+            let stream = listener.accept().await.unwrap().0;
+            let mut stream = tokio_boring::accept(&acceptor, stream).await.unwrap();
+
+            stream.write_all(b"jkl;").await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    };
+
+    let store = Arc::new(LruSessionStore::new(8));
+    let connector = create_connector(|builder| {
+        unsafe { builder.set_session_store(SharedStore(store.clone())) };
+        builder.set_ca_file("tests/cert.pem")
+    });
+
+    let client = async {
+        for reused in [false, true] {
+            let config = connector.configure().unwrap();
+            let stream = TcpStream::connect(&addr).await.unwrap();
+            let mut stream =
+                tokio_boring::connect_with_port(config, "localhost", addr.port(), stream)
+                    .await
+                    .unwrap();
+
+            assert_eq!(stream.ssl().session_reused(), reused);
+
+            let mut buf = vec![];
+            stream.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"jkl;");
+        }
+    };
+
+    future::join(server, client).await;
+
+    // TLS 1.3 sessions are single-use, so only the ticket from the second connection is left.
+    assert_eq!(store.len(), 1);
+}